@@ -338,17 +338,19 @@ export class Client {
     }
 
     /**
-     * Store a mnemonic in the Stronghold vault
+     * Store a mnemonic in the Stronghold vault, with an optional BIP-39 passphrase
      */
     async storeMnemonic(
         secretManager: SecretManager,
         mnemonic: string,
+        passphrase?: string,
     ): Promise<void> {
         const response = await this.messageHandler.sendMessage({
             name: 'storeMnemonic',
             data: {
                 secretManager,
                 mnemonic,
+                passphrase,
             },
         });
 
@@ -4,7 +4,9 @@
 use std::sync::Arc;
 
 use iota_client::{
+    api_types::response::{OutputMetadataResponse, OutputWithMetadataResponse},
     block::{
+        output::dto::OutputDto,
         payload::milestone::{dto::MilestonePayloadDto, option::dto::ReceiptMilestoneOptionDto},
         BlockDto,
     },
@@ -79,6 +81,15 @@ impl MessageHandler {
                 MqttPayload::Receipt(receipt) => {
                     serde_json::to_string(&ReceiptMilestoneOptionDto::from(receipt)).unwrap()
                 }
+                MqttPayload::BlockMetadata(block_metadata) => serde_json::to_string(&block_metadata).unwrap(),
+                MqttPayload::MilestoneInfo(milestone_info) => serde_json::to_string(&milestone_info).unwrap(),
+                MqttPayload::OutputWithMetadata(output_with_metadata) => {
+                    serde_json::to_string(&OutputWithMetadataResponse {
+                        metadata: OutputMetadataResponse::from(&output_with_metadata.metadata),
+                        output: OutputDto::from(&output_with_metadata.output),
+                    })
+                    .unwrap()
+                }
             };
             let response = MqttResponse {
                 topic: event.topic,
@@ -164,6 +164,7 @@ export interface __StoreMnemonicMessage__ {
     data: {
         secretManager: SecretManager;
         mnemonic: string;
+        passphrase?: string;
     };
 }
 
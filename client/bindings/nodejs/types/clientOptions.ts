@@ -14,6 +14,8 @@ export interface IClientOptions {
     ignoreNodeHealth?: boolean;
     /** Interval in which nodes will be checked for their sync status and the NetworkInfo gets updated */
     nodeSyncInterval?: IDuration;
+    /** Duration for which a node is skipped after failed requests, as long as there are other nodes */
+    nodeCoolDown?: IDuration;
     /** If node quorum is enabled. Will compare the responses from multiple nodes and only returns the
      * response if quorum_threshold of the nodes return the same one
      */
@@ -177,12 +177,13 @@ class IotaClient(NodeCoreAPI, NodeIndexerAPI, HighLevelAPI, Utils):
             'preparedTransactionData': prepared_transaction_data
         })
 
-    def store_mnemonic(self, secret_manager, mnemonic):
-        """Store a mnemonic in the Stronghold vault.
+    def store_mnemonic(self, secret_manager, mnemonic, passphrase=None):
+        """Store a mnemonic in the Stronghold vault, with an optional BIP-39 passphrase.
         """
         return self.send_message('storeMnemonic', {
             'secretManager': secret_manager,
-            'mnemonic': mnemonic
+            'mnemonic': mnemonic,
+            'passphrase': passphrase
         })
 
     def submit_payload(self, payload_dto):
@@ -33,12 +33,9 @@ async fn main() -> Result<()> {
     client
         .subscribe(
             vec![
-                Topic::try_from("milestone-info/latest".to_string())?,
-                Topic::try_from("blocks".to_string())?,
-                Topic::try_from(
-                    "outputs/unlock/address/atoi1qzt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupx3y7x0r"
-                        .to_string(),
-                )?,
+                Topic::latest_milestone_info(),
+                Topic::blocks(),
+                Topic::address_outputs("atoi1qzt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupx3y7x0r")?,
             ],
             move |event| {
                 println!("Topic: {}", event.topic);
@@ -47,6 +44,9 @@ async fn main() -> Result<()> {
                     MqttPayload::Block(block) => println!("{block:?}"),
                     MqttPayload::MilestonePayload(ms) => println!("{ms:?}"),
                     MqttPayload::Receipt(receipt) => println!("{receipt:?}"),
+                    MqttPayload::BlockMetadata(block_metadata) => println!("{block_metadata:?}"),
+                    MqttPayload::MilestoneInfo(milestone_info) => println!("{milestone_info:?}"),
+                    MqttPayload::OutputWithMetadata(output_with_metadata) => println!("{output_with_metadata:?}"),
                 }
                 tx.lock().unwrap().send(()).unwrap();
             },
@@ -57,7 +57,7 @@ async fn main() -> Result<()> {
         rx.recv().unwrap();
         if i == 7 {
             // unsubscribe from topic "blocks", will continue to receive events for "milestones/latest"
-            client.unsubscribe(vec![Topic::try_from("blocks".to_string())?]).await?;
+            client.unsubscribe(vec![Topic::blocks()]).await?;
         }
     }
 
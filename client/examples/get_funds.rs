@@ -4,9 +4,9 @@
 //! cargo run --example get_funds --release
 
 use iota_client::{
+    devnet::{DevnetHarness, DevnetPreset},
     secret::{mnemonic::MnemonicSecretManager, SecretManager},
-    utils::request_funds_from_faucet,
-    Client, Result,
+    Result,
 };
 
 #[tokio::main]
@@ -14,28 +14,16 @@ async fn main() -> Result<()> {
     // This example uses dotenv, which is not safe for use in production
     dotenv::dotenv().ok();
 
-    let node_url = std::env::var("NODE_URL").unwrap();
-    let faucet_url = std::env::var("FAUCET_URL").unwrap();
-
-    // Create a client instance
-    let client = Client::builder()
-        .with_node(&node_url)? // Insert the node here
-        .finish()?;
+    // Connect to the node and faucet set in NODE_URL and FAUCET_URL
+    let harness = DevnetHarness::new(DevnetPreset::from_env())?;
 
     let secret_manager = SecretManager::Mnemonic(MnemonicSecretManager::try_from_mnemonic(
         &std::env::var("NON_SECURE_USE_OF_DEVELOPMENT_MNEMONIC_1").unwrap(),
     )?);
 
-    let addresses = client
-        .get_addresses(&secret_manager)
-        .with_account_index(0)
-        .with_range(0..1)
-        .finish()
-        .await?;
-    println!("{}", addresses[0]);
-
-    let faucet_response = request_funds_from_faucet(&faucet_url, &addresses[0]).await?;
+    // Request funds for the first address and wait until they arrived
+    let funded = harness.fund(secret_manager).await?;
 
-    println!("{faucet_response}");
+    println!("{} has a balance of {}", funded.address, funded.balance);
     Ok(())
 }
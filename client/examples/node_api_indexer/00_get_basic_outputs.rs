@@ -4,7 +4,7 @@
 //! Calls `GET api/indexer/v1/outputs/basic`.
 //! Run: `cargo run --example node_api_indexer_get_basic_outputs --release -- [NODE URL] [ADDRESS]`.
 
-use iota_client::{node_api::indexer::query_parameters::QueryParameter, Client, Result};
+use iota_client::{node_api::indexer::query_parameters::BasicOutputsQuery, Client, Result};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -28,12 +28,13 @@ async fn main() -> Result<()> {
 
     // Get output IDs of basic outputs that can be controlled by this address without further unlock constraints.
     let output_ids = client
-        .basic_output_ids(vec![
-            QueryParameter::Address(address),
-            QueryParameter::HasExpiration(false),
-            QueryParameter::HasTimelock(false),
-            QueryParameter::HasStorageDepositReturn(false),
-        ])
+        .basic_output_ids(
+            BasicOutputsQuery::new()
+                .address(address)
+                .has_expiration(false)
+                .has_timelock(false)
+                .has_storage_deposit_return(false),
+        )
         .await?;
 
     println!("Address output IDs {output_ids:#?}");
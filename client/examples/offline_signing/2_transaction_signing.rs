@@ -12,9 +12,11 @@ use std::{
 };
 
 use iota_client::{
-    api::{PreparedTransactionData, PreparedTransactionDataDto, SignedTransactionData, SignedTransactionDataDto},
-    block::payload::transaction::TransactionPayload,
-    secret::{mnemonic::MnemonicSecretManager, SecretManageExt, SecretManager},
+    api::{
+        sign_prepared_transaction, PreparedTransactionData, PreparedTransactionDataDto, SignedTransactionData,
+        SignedTransactionDataDto,
+    },
+    secret::{mnemonic::MnemonicSecretManager, SecretManager},
     Result,
 };
 
@@ -32,15 +34,7 @@ async fn main() -> Result<()> {
     let prepared_transaction_data = read_prepared_transaction_from_file(PREPARED_TRANSACTION_FILE_NAME)?;
 
     // Signs the prepared transaction offline.
-    let unlocks = secret_manager
-        .sign_transaction_essence(&prepared_transaction_data)
-        .await?;
-    let signed_transaction = TransactionPayload::new(prepared_transaction_data.essence.clone(), unlocks)?;
-
-    let signed_transaction_data = SignedTransactionData {
-        transaction_payload: signed_transaction,
-        inputs_data: prepared_transaction_data.inputs_data,
-    };
+    let signed_transaction_data = sign_prepared_transaction(&secret_manager, prepared_transaction_data).await?;
 
     println!("Signed transaction.");
 
@@ -7,9 +7,8 @@
 use std::{fs::File, io::prelude::*, path::Path};
 
 use iota_client::{
-    api::{verify_semantic, SignedTransactionData, SignedTransactionDataDto},
-    block::{payload::Payload, semantic::ConflictReason},
-    Client, Error, Result,
+    api::{SignedTransactionData, SignedTransactionDataDto},
+    Client, Result,
 };
 
 const SIGNED_TRANSACTION_FILE_NAME: &str = "examples/offline_signing/signed_transaction.json";
@@ -28,25 +27,8 @@ async fn main() -> Result<()> {
 
     let signed_transaction_payload = read_signed_transaction_from_file(SIGNED_TRANSACTION_FILE_NAME)?;
 
-    let current_time = online_client.get_time_checked().await?;
-
-    let conflict = verify_semantic(
-        &signed_transaction_payload.inputs_data,
-        &signed_transaction_payload.transaction_payload,
-        current_time,
-    )?;
-
-    if conflict != ConflictReason::None {
-        return Err(Error::TransactionSemantic(conflict));
-    }
-
-    // Sends the offline signed transaction online.
-    let block = online_client
-        .block()
-        .finish_block(Some(Payload::Transaction(Box::new(
-            signed_transaction_payload.transaction_payload,
-        ))))
-        .await?;
+    // Verifies and sends the offline signed transaction online.
+    let block = online_client.submit_signed(signed_transaction_payload).await?;
 
     println!(
         "Transaction sent: {}/block/{}",
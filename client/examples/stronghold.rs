@@ -13,7 +13,7 @@ use iota_client::{
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let mut stronghold_secret_manager = StrongholdSecretManager::builder()
+    let stronghold_secret_manager = StrongholdSecretManager::builder()
         .password("some_hopefully_secure_password")
         .build("test.stronghold")?;
 
@@ -0,0 +1,401 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parsing of user entered amounts, e.g. "1.5 Mi", "1,5 SMR" or "1500 glow", into base units.
+//!
+//! An amount is a number followed by a [`Unit`], optionally separated by whitespace. The decimal separator is a dot
+//! or a comma, see [`DecimalSeparator`]; the other one can be used to group the digits of the integer part, by three.
+//! Errors point at the position, in characters, of the offending part of the input.
+
+use core::{fmt, str::FromStr};
+
+/// A unit an amount can be entered in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Unit {
+    /// The base unit of Shimmer.
+    Glow,
+    /// 1 SMR = 1_000_000 glow.
+    #[serde(rename = "SMR")]
+    Smr,
+    /// The base unit of IOTA.
+    Micro,
+    /// 1 IOTA = 1_000_000 micro.
+    #[serde(rename = "IOTA")]
+    Iota,
+    /// The base unit, with the legacy IOTA notation.
+    #[serde(rename = "i")]
+    I,
+    /// 1 Ki = 1_000 i.
+    Ki,
+    /// 1 Mi = 1_000_000 i.
+    Mi,
+    /// 1 Gi = 1_000_000_000 i.
+    Gi,
+    /// 1 Ti = 1_000_000_000_000 i.
+    Ti,
+    /// 1 Pi = 1_000_000_000_000_000 i.
+    Pi,
+}
+
+impl Unit {
+    /// The number of decimals of the unit, i.e. the power of ten of base units it's worth.
+    pub const fn decimals(&self) -> u32 {
+        match self {
+            Self::Glow | Self::Micro | Self::I => 0,
+            Self::Ki => 3,
+            Self::Smr | Self::Iota | Self::Mi => 6,
+            Self::Gi => 9,
+            Self::Ti => 12,
+            Self::Pi => 15,
+        }
+    }
+
+    /// The symbol of the unit.
+    pub const fn symbol(&self) -> &'static str {
+        match self {
+            Self::Glow => "glow",
+            Self::Smr => "SMR",
+            Self::Micro => "micro",
+            Self::Iota => "IOTA",
+            Self::I => "i",
+            Self::Ki => "Ki",
+            Self::Mi => "Mi",
+            Self::Gi => "Gi",
+            Self::Ti => "Ti",
+            Self::Pi => "Pi",
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for Unit {
+    type Err = AmountParseError;
+
+    /// Units are matched case-insensitively, "glows" and "micros" are accepted as well.
+    fn from_str(unit: &str) -> Result<Self, Self::Err> {
+        Ok(match unit.to_ascii_lowercase().as_str() {
+            "glow" | "glows" => Self::Glow,
+            "smr" => Self::Smr,
+            "micro" | "micros" => Self::Micro,
+            "iota" => Self::Iota,
+            "i" => Self::I,
+            "ki" => Self::Ki,
+            "mi" => Self::Mi,
+            "gi" => Self::Gi,
+            "ti" => Self::Ti,
+            "pi" => Self::Pi,
+            _ => {
+                return Err(AmountParseError::UnknownUnit {
+                    position: 0,
+                    unit: unit.to_string(),
+                });
+            }
+        })
+    }
+}
+
+/// The decimal separator of an amount, depending on the locale of the user.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DecimalSeparator {
+    /// "1,000.5", e.g. in English.
+    Dot,
+    /// "1.000,5", e.g. in German or French.
+    Comma,
+    /// The last separator of the input is the decimal separator, unless the same separator appears multiple times.
+    /// A single separator followed by exactly three digits, e.g. "1,500", could be either and is rejected with
+    /// [`AmountParseError::AmbiguousSeparator`]; use an explicit separator to parse it.
+    #[default]
+    Auto,
+}
+
+/// Errors of the parsing of an amount.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AmountParseError {
+    /// The input doesn't contain an amount.
+    #[error("the amount is empty")]
+    Empty,
+    /// The input contains a character that isn't expected at its position.
+    #[error("invalid character {character:?} at position {position}")]
+    InvalidCharacter {
+        /// The position of the character.
+        position: usize,
+        /// The invalid character.
+        character: char,
+    },
+    /// The amount isn't followed by a unit.
+    #[error("missing unit at position {position}")]
+    MissingUnit {
+        /// The position the unit is expected at.
+        position: usize,
+    },
+    /// The unit isn't known.
+    #[error("unknown unit {unit:?} at position {position}")]
+    UnknownUnit {
+        /// The position of the unit.
+        position: usize,
+        /// The unknown unit.
+        unit: String,
+    },
+    /// The separator could be the decimal or the group separator, e.g. in "1,500", see [`DecimalSeparator::Auto`].
+    #[error("ambiguous separator at position {position}, it could be a decimal or a group separator")]
+    AmbiguousSeparator {
+        /// The position of the separator.
+        position: usize,
+    },
+    /// The amount has more decimals than the unit allows, i.e. it isn't a whole number of base units.
+    #[error("too many decimals for {unit}, the digit at position {position} is below the base unit")]
+    TooManyDecimals {
+        /// The position of the first digit below the base unit.
+        position: usize,
+        /// The unit of the amount.
+        unit: Unit,
+    },
+    /// The amount doesn't fit in a `u64` of base units.
+    #[error("the amount overflows")]
+    Overflow,
+}
+
+/// Parse a user entered amount, e.g. "1.5 Mi", "1,5 SMR" or "1500 glow", into base units.
+pub fn parse_amount(input: &str, decimal_separator: DecimalSeparator) -> Result<u64, AmountParseError> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let number_start = chars
+        .iter()
+        .position(|c| !c.is_whitespace())
+        .ok_or(AmountParseError::Empty)?;
+    let number_end = chars[number_start..]
+        .iter()
+        .position(|c| c.is_whitespace() || c.is_alphabetic())
+        .map_or(chars.len(), |end| number_start + end);
+    let unit_start = chars[number_end..]
+        .iter()
+        .position(|c| !c.is_whitespace())
+        .map_or(chars.len(), |start| number_end + start);
+    let unit_end = chars[unit_start..]
+        .iter()
+        .position(|c| c.is_whitespace())
+        .map_or(chars.len(), |end| unit_start + end);
+
+    if let Some(position) = (unit_end..chars.len()).find(|position| !chars[*position].is_whitespace()) {
+        return Err(AmountParseError::InvalidCharacter {
+            position,
+            character: chars[position],
+        });
+    }
+    if unit_start == unit_end {
+        return Err(AmountParseError::MissingUnit { position: unit_start });
+    }
+    if let Some(position) = (unit_start..unit_end).find(|position| !chars[*position].is_alphabetic()) {
+        return Err(AmountParseError::InvalidCharacter {
+            position,
+            character: chars[position],
+        });
+    }
+
+    let unit = chars[unit_start..unit_end]
+        .iter()
+        .collect::<String>()
+        .parse::<Unit>()
+        .map_err(|_| AmountParseError::UnknownUnit {
+            position: unit_start,
+            unit: chars[unit_start..unit_end].iter().collect(),
+        })?;
+
+    let decimal_separator = match decimal_separator {
+        DecimalSeparator::Dot => '.',
+        DecimalSeparator::Comma => ',',
+        DecimalSeparator::Auto => detect_decimal_separator(&chars[number_start..number_end])
+            .map_err(|index| AmountParseError::AmbiguousSeparator {
+                position: number_start + index,
+            })?,
+    };
+
+    parse_number(&chars, number_start, number_end, decimal_separator, unit)
+}
+
+/// The last separator is the decimal one, unless it appears more than once. A single separator followed by exactly
+/// three digits after a non-zero integer part could also group the digits, its index is then returned as an error.
+fn detect_decimal_separator(number: &[char]) -> Result<char, usize> {
+    let is_separator = |c: &char| *c == '.' || *c == ',';
+
+    match number.iter().rposition(is_separator) {
+        Some(index) if number.iter().filter(|c| is_separator(c)).count() == 1 => {
+            let integer = &number[..index];
+            let fraction = &number[index + 1..];
+
+            if fraction.len() == 3 && integer.iter().any(|c| *c != '0') {
+                Err(index)
+            } else {
+                Ok(number[index])
+            }
+        }
+        Some(index) if number.iter().filter(|c| **c == number[index]).count() == 1 => Ok(number[index]),
+        Some(index) if number[index] == '.' => Ok(','),
+        _ => Ok('.'),
+    }
+}
+
+fn parse_number(
+    chars: &[char],
+    start: usize,
+    end: usize,
+    decimal_separator: char,
+    unit: Unit,
+) -> Result<u64, AmountParseError> {
+    let group_separator = if decimal_separator == '.' { ',' } else { '.' };
+    let invalid_character = |position: usize| AmountParseError::InvalidCharacter {
+        position,
+        character: chars[position],
+    };
+
+    let separator = (start..end).find(|position| chars[*position] == decimal_separator);
+    let integer_end = separator.unwrap_or(end);
+    let fraction_start = separator.map_or(end, |separator| separator + 1);
+
+    // The integer part: digits, optionally grouped by three.
+    let mut integer_digits = Vec::new();
+    let mut last_group_separator = None;
+    for position in start..integer_end {
+        match chars[position] {
+            c if c.is_ascii_digit() => integer_digits.push(c),
+            c if c == group_separator => {
+                let grouped_digits = position - last_group_separator.map_or(start, |last| last + 1);
+                if integer_digits.is_empty() || (last_group_separator.is_some() && grouped_digits != 3) {
+                    return Err(invalid_character(position));
+                }
+                last_group_separator = Some(position);
+            }
+            _ => return Err(invalid_character(position)),
+        }
+    }
+    if let Some(last) = last_group_separator {
+        if integer_end - last - 1 != 3 {
+            return Err(invalid_character(last));
+        }
+    }
+
+    // The fractional part: digits only.
+    if let Some(position) = (fraction_start..end).find(|position| !chars[*position].is_ascii_digit()) {
+        return Err(invalid_character(position));
+    }
+    let fraction_digits = &chars[fraction_start..end];
+
+    if integer_digits.is_empty() && fraction_digits.is_empty() {
+        return Err(if start == end {
+            AmountParseError::Empty
+        } else {
+            invalid_character(start)
+        });
+    }
+
+    let decimals = unit.decimals() as usize;
+    if let Some(index) = fraction_digits.iter().skip(decimals).position(|c| *c != '0') {
+        return Err(AmountParseError::TooManyDecimals {
+            position: fraction_start + decimals + index,
+            unit,
+        });
+    }
+
+    integer_digits
+        .into_iter()
+        .chain(
+            fraction_digits
+                .iter()
+                .copied()
+                .chain(core::iter::repeat('0'))
+                .take(decimals),
+        )
+        .try_fold(0u64, |amount, digit| {
+            amount
+                .checked_mul(10)?
+                .checked_add(u64::from(digit.to_digit(10).expect("checked digit")))
+        })
+        .ok_or(AmountParseError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_amount("1.5 Mi", DecimalSeparator::Auto), Ok(1_500_000));
+        assert_eq!(parse_amount("1,5 SMR", DecimalSeparator::Auto), Ok(1_500_000));
+        assert_eq!(parse_amount("1500 glow", DecimalSeparator::Auto), Ok(1500));
+        assert_eq!(parse_amount("  2IOTA ", DecimalSeparator::Auto), Ok(2_000_000));
+        assert_eq!(parse_amount(".5 Ki", DecimalSeparator::Auto), Ok(500));
+        assert_eq!(parse_amount("1,000.25 smr", DecimalSeparator::Auto), Ok(1_000_250_000));
+        assert_eq!(parse_amount("1.000.000 glow", DecimalSeparator::Auto), Ok(1_000_000));
+        assert_eq!(parse_amount("1.000,5 Mi", DecimalSeparator::Comma), Ok(1_000_500_000));
+        assert_eq!(parse_amount("1,500 i", DecimalSeparator::Dot), Ok(1500));
+        assert_eq!(parse_amount("1.50 Ki", DecimalSeparator::Dot), Ok(1500));
+        assert_eq!(parse_amount("1.0 glow", DecimalSeparator::Dot), Ok(1));
+        assert_eq!(parse_amount("1,500 SMR", DecimalSeparator::Dot), Ok(1_500_000_000));
+        assert_eq!(parse_amount("1,500 SMR", DecimalSeparator::Comma), Ok(1_500_000));
+        assert_eq!(parse_amount("1.500 SMR", DecimalSeparator::Comma), Ok(1_500_000_000));
+        assert_eq!(parse_amount("0,500 SMR", DecimalSeparator::Auto), Ok(500_000));
+        assert_eq!(parse_amount("1,5000 SMR", DecimalSeparator::Auto), Ok(1_500_000));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse_amount(" ", DecimalSeparator::Auto), Err(AmountParseError::Empty));
+        assert_eq!(
+            parse_amount("1.5 Mx", DecimalSeparator::Auto),
+            Err(AmountParseError::UnknownUnit {
+                position: 4,
+                unit: "Mx".to_string()
+            })
+        );
+        assert_eq!(
+            parse_amount("1-5 Mi", DecimalSeparator::Auto),
+            Err(AmountParseError::InvalidCharacter {
+                position: 1,
+                character: '-'
+            })
+        );
+        assert_eq!(
+            parse_amount("1.5", DecimalSeparator::Auto),
+            Err(AmountParseError::MissingUnit { position: 3 })
+        );
+        assert_eq!(
+            parse_amount("1.5 Mi 2", DecimalSeparator::Auto),
+            Err(AmountParseError::InvalidCharacter {
+                position: 7,
+                character: '2'
+            })
+        );
+        assert_eq!(
+            parse_amount("1,500 SMR", DecimalSeparator::Auto),
+            Err(AmountParseError::AmbiguousSeparator { position: 1 })
+        );
+        assert_eq!(
+            parse_amount(" 1.500 SMR", DecimalSeparator::Auto),
+            Err(AmountParseError::AmbiguousSeparator { position: 2 })
+        );
+        assert_eq!(
+            parse_amount("1,50 i", DecimalSeparator::Dot),
+            Err(AmountParseError::InvalidCharacter {
+                position: 1,
+                character: ','
+            })
+        );
+        assert_eq!(
+            parse_amount("1.0000005 SMR", DecimalSeparator::Auto),
+            Err(AmountParseError::TooManyDecimals {
+                position: 8,
+                unit: Unit::Smr
+            })
+        );
+        assert_eq!(
+            parse_amount("18446744073709551616 glow", DecimalSeparator::Auto),
+            Err(AmountParseError::Overflow)
+        );
+    }
+}
@@ -3,16 +3,50 @@
 
 use std::ops::Range;
 
+use futures::{stream, StreamExt, TryStreamExt};
 use iota_types::block::address::Address;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     api::types::{Bech32Addresses, RawAddresses},
     constants::{SHIMMER_COIN_TYPE, SHIMMER_TESTNET_BECH32_HRP},
-    secret::{GenerateAddressOptions, SecretManage, SecretManager},
+    secret::{types::AccountAddress, DerivationPath, GenerateAddressOptions, SecretManage, SecretManager},
     Client, Result,
 };
 
+/// The BIP-44 chains to take into account when generating or scanning addresses.
+///
+/// Change is sent to internal addresses, so scanning only the public chain misses funds; [`AddressChains::Both`] is
+/// the default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AddressChains {
+    /// Only public (external) addresses.
+    Public,
+    /// Only internal (change) addresses.
+    Internal,
+    /// Both public and internal addresses.
+    #[default]
+    Both,
+}
+
+impl AddressChains {
+    /// Returns the `internal` flags of the chains.
+    pub fn internal_flags(&self) -> &'static [bool] {
+        match self {
+            Self::Public => &[false],
+            Self::Internal => &[true],
+            Self::Both => &[false, true],
+        }
+    }
+}
+
+/// The number of addresses derived by a single call to the secret manager.
+const ADDRESS_BATCH_SIZE: u32 = 100;
+
+/// The default number of batches of addresses derived concurrently.
+pub const DEFAULT_ADDRESS_CONCURRENCY: usize = 4;
+
 /// Builder of get_addresses API
 #[must_use]
 pub struct GetAddressesBuilder<'a> {
@@ -24,6 +58,7 @@ pub struct GetAddressesBuilder<'a> {
     internal: bool,
     bech32_hrp: Option<String>,
     options: Option<GenerateAddressOptions>,
+    concurrency: usize,
 }
 
 /// Get address builder from string
@@ -42,6 +77,8 @@ pub struct GetAddressesBuilderOptions {
     pub bech32_hrp: Option<String>,
     /// Options
     pub options: Option<GenerateAddressOptions>,
+    /// Number of batches of addresses derived concurrently
+    pub concurrency: Option<usize>,
 }
 
 impl<'a> GetAddressesBuilder<'a> {
@@ -56,6 +93,7 @@ impl<'a> GetAddressesBuilder<'a> {
             internal: false,
             bech32_hrp: None,
             options: None,
+            concurrency: DEFAULT_ADDRESS_CONCURRENCY,
         }
     }
 
@@ -101,6 +139,26 @@ impl<'a> GetAddressesBuilder<'a> {
         self
     }
 
+    /// Display the addresses on the device of the secret manager for their confirmation by the user, see
+    /// [`GenerateAddressOptions::ledger_nano_prompt`]
+    pub fn with_device_prompt(mut self, prompt: bool) -> Self {
+        self.options.get_or_insert_with(Default::default).ledger_nano_prompt = prompt;
+        self
+    }
+
+    /// Set the number of batches of addresses derived concurrently, which speeds up the derivation of large ranges
+    /// with secret managers that can derive batches in parallel, the addresses are returned in order regardless
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the derivation path of the addresses, see [`DerivationPath`]
+    pub fn with_derivation_path(mut self, derivation_path: DerivationPath) -> Self {
+        self.options.get_or_insert_with(Default::default).derivation_path = Some(derivation_path);
+        self
+    }
+
     /// Set multiple options from address builder options type
     /// Useful for bindings
     pub fn set_options(mut self, options: GetAddressesBuilderOptions) -> Result<Self> {
@@ -128,6 +186,10 @@ impl<'a> GetAddressesBuilder<'a> {
             self = self.with_options(options);
         };
 
+        if let Some(concurrency) = options.concurrency {
+            self = self.with_concurrency(concurrency);
+        };
+
         Ok(self)
     }
 
@@ -142,14 +204,7 @@ impl<'a> GetAddressesBuilder<'a> {
         };
 
         let addresses = self
-            .secret_manager
-            .generate_addresses(
-                self.coin_type,
-                self.account_index,
-                self.range,
-                self.internal,
-                self.options.clone(),
-            )
+            .generate_addresses(self.range.clone(), self.internal)
             .await?
             .into_iter()
             .map(|a| a.to_bech32(&bech32_hrp))
@@ -157,17 +212,35 @@ impl<'a> GetAddressesBuilder<'a> {
 
         Ok(addresses)
     }
-    /// Consume the builder and get a vector of public addresses
+    /// Consume the builder and get a vector of public (or internal, see [`Self::with_internal_addresses()`])
+    /// addresses
     pub async fn get_raw(self) -> Result<Vec<Address>> {
-        self.secret_manager
-            .generate_addresses(
-                self.coin_type,
-                self.account_index,
-                self.range,
-                false,
-                self.options.clone(),
-            )
-            .await
+        self.generate_addresses(self.range.clone(), self.internal).await
+    }
+
+    /// Consume the builder and get the addresses of the requested chains, ordered by ascending index with the public
+    /// address first for each index
+    pub async fn get_chains(self, chains: AddressChains) -> Result<Vec<AccountAddress>> {
+        let mut addresses = Vec::new();
+
+        for internal in chains.internal_flags() {
+            let chain_addresses = self.generate_addresses(self.range.clone(), *internal).await?;
+
+            addresses.extend(
+                chain_addresses
+                    .into_iter()
+                    .zip(self.range.clone())
+                    .map(|(address, key_index)| AccountAddress {
+                        address,
+                        key_index,
+                        internal: *internal,
+                    }),
+            );
+        }
+
+        addresses.sort_by_key(|address| (address.key_index, address.internal));
+
+        Ok(addresses)
     }
 
     /// Consume the builder and get the vector of public and internal addresses bech32 encoded
@@ -193,33 +266,39 @@ impl<'a> GetAddressesBuilder<'a> {
 
     /// Consume the builder and get the vector of public and internal addresses
     pub async fn get_all_raw(self) -> Result<RawAddresses> {
-        let public_addresses = self
-            .secret_manager
-            .generate_addresses(
-                self.coin_type,
-                self.account_index,
-                self.range.clone(),
-                false,
-                self.options.clone(),
-            )
-            .await?;
+        let public_addresses = self.generate_addresses(self.range.clone(), false).await?;
 
-        let internal_addresses = self
-            .secret_manager
-            .generate_addresses(
-                self.coin_type,
-                self.account_index,
-                self.range,
-                true,
-                self.options.clone(),
-            )
-            .await?;
+        let internal_addresses = self.generate_addresses(self.range.clone(), true).await?;
 
         Ok(RawAddresses {
             public: public_addresses,
             internal: internal_addresses,
         })
     }
+
+    /// Derive the addresses of `range` in batches, up to `concurrency` of them at once, and return them in order.
+    async fn generate_addresses(&self, range: Range<u32>, internal: bool) -> Result<Vec<Address>> {
+        let batches = range
+            .clone()
+            .step_by(ADDRESS_BATCH_SIZE as usize)
+            .map(|start| start..start.saturating_add(ADDRESS_BATCH_SIZE).min(range.end));
+
+        let addresses = stream::iter(batches)
+            .map(|batch| {
+                self.secret_manager.generate_addresses(
+                    self.coin_type,
+                    self.account_index,
+                    batch,
+                    internal,
+                    self.options.clone(),
+                )
+            })
+            .buffered(self.concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(addresses.into_iter().flatten().collect())
+    }
 }
 
 /// Function to find the index and public (false) or internal (true) type of an Bech32 encoded address
@@ -231,22 +310,165 @@ pub async fn search_address(
     range: Range<u32>,
     address: &Address,
 ) -> Result<(u32, bool)> {
-    let addresses = GetAddressesBuilder::new(secret_manager)
+    let max_range_end = range.end;
+
+    search_address_widening(
+        secret_manager,
+        bech32_hrp,
+        coin_type,
+        account_index,
+        range,
+        address,
+        max_range_end,
+    )
+    .await
+}
+
+/// Function to find the index and public (false) or internal (true) type of an Bech32 encoded address derived with
+/// `derivation_path`, for wallets that used a non-standard path.
+pub async fn search_address_with_derivation_path(
+    secret_manager: &SecretManager,
+    bech32_hrp: &str,
+    coin_type: u32,
+    account_index: u32,
+    range: Range<u32>,
+    address: &Address,
+    derivation_path: &DerivationPath,
+) -> Result<(u32, bool)> {
+    let options = AddressSearchOptions {
+        derivation_path: Some(derivation_path.clone()),
+        ..Default::default()
+    };
+
+    search_address_with_options(
+        secret_manager,
+        bech32_hrp,
+        coin_type,
+        account_index,
+        range,
+        address,
+        options,
+    )
+    .await
+}
+
+/// Function to find the index and public (false) or internal (true) type of an Bech32 encoded address, searching the
+/// following ranges of the same length as `range`, up to `max_range_end`, if it isn't found in `range`.
+pub async fn search_address_widening(
+    secret_manager: &SecretManager,
+    bech32_hrp: &str,
+    coin_type: u32,
+    account_index: u32,
+    range: Range<u32>,
+    address: &Address,
+    max_range_end: u32,
+) -> Result<(u32, bool)> {
+    // The following ranges are searched in chunks of the same length
+    let options = AddressSearchOptions {
+        chunk_size: range.len().max(1) as u32,
+        ..Default::default()
+    };
+
+    search_address_with_options(
+        secret_manager,
+        bech32_hrp,
+        coin_type,
+        account_index,
+        range.start..max_range_end.max(range.end),
+        address,
+        options,
+    )
+    .await
+}
+
+/// Options of [`search_address_with_options()`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressSearchOptions {
+    /// The chains to search.
+    pub chains: AddressChains,
+    /// The number of address indexes of a chunk, derived and compared at once.
+    pub chunk_size: u32,
+    /// The number of chunks searched concurrently.
+    pub concurrency: usize,
+    /// The derivation path of the addresses, for wallets that used a non-standard path, see [`DerivationPath`].
+    pub derivation_path: Option<DerivationPath>,
+}
+
+impl Default for AddressSearchOptions {
+    fn default() -> Self {
+        Self {
+            chains: AddressChains::default(),
+            chunk_size: ADDRESS_BATCH_SIZE,
+            concurrency: DEFAULT_ADDRESS_CONCURRENCY,
+            derivation_path: None,
+        }
+    }
+}
+
+/// Function to find the index and public (false) or internal (true) type of an Bech32 encoded address in `range`.
+///
+/// The range is searched in chunks of `options.chunk_size` indexes, up to `options.concurrency` of them at once, and
+/// the search stops at the first chunk, in order, holding the address.
+pub async fn search_address_with_options(
+    secret_manager: &SecretManager,
+    bech32_hrp: &str,
+    coin_type: u32,
+    account_index: u32,
+    range: Range<u32>,
+    address: &Address,
+    options: AddressSearchOptions,
+) -> Result<(u32, bool)> {
+    let chunk_size = options.chunk_size.max(1);
+    let end = range.end;
+    let chunks = range
+        .clone()
+        .step_by(chunk_size as usize)
+        .map(move |start| start..start.saturating_add(chunk_size).min(end));
+
+    let mut found = stream::iter(chunks)
+        .map(|chunk| search_address_in_chunk(secret_manager, coin_type, account_index, chunk, address, &options))
+        .buffered(options.concurrency.max(1));
+
+    // Dropping the stream cancels the search of the following chunks
+    while let Some(chunk_result) = found.try_next().await? {
+        if let Some(result) = chunk_result {
+            return Ok(result);
+        }
+    }
+
+    Err(crate::error::Error::InputAddressNotFound {
+        address: address.to_bech32(bech32_hrp),
+        account_index,
+        range,
+        internal_addresses_searched: options.chains.internal_flags().contains(&true),
+    })
+}
+
+async fn search_address_in_chunk(
+    secret_manager: &SecretManager,
+    coin_type: u32,
+    account_index: u32,
+    chunk: Range<u32>,
+    address: &Address,
+    options: &AddressSearchOptions,
+) -> Result<Option<(u32, bool)>> {
+    let mut builder = GetAddressesBuilder::new(secret_manager)
         .with_coin_type(coin_type)
         .with_account_index(account_index)
-        .with_range(range.clone())
-        .get_all_raw()
-        .await?;
-    for index in 0..addresses.public.len() {
-        if addresses.public[index] == *address {
-            return Ok((range.start + index as u32, false));
-        }
-        if addresses.internal[index] == *address {
-            return Ok((range.start + index as u32, true));
+        .with_range(chunk.clone())
+        .with_concurrency(1);
+    if let Some(derivation_path) = &options.derivation_path {
+        builder = builder.with_derivation_path(derivation_path.clone());
+    }
+
+    for internal in options.chains.internal_flags() {
+        let addresses = builder.generate_addresses(chunk.clone(), *internal).await?;
+
+        if let Some(index) = addresses.iter().position(|a| a == address) {
+            return Ok(Some((chunk.start + index as u32, *internal)));
         }
     }
-    Err(crate::error::Error::InputAddressNotFound(
-        address.to_bech32(bech32_hrp),
-        format!("{range:?}"),
-    ))
+
+    Ok(None)
 }
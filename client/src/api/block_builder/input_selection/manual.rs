@@ -13,7 +13,7 @@ use iota_types::block::{
 
 use crate::{
     api::{
-        address::search_address, block_builder::input_selection::types::SelectedTransactionData,
+        address::search_address_widening, block_builder::input_selection::types::SelectedTransactionData,
         input_selection::try_select_inputs, ClientBlockBuilder,
     },
     constants::HD_WALLET_TYPE,
@@ -56,13 +56,14 @@ impl<'a> ClientBlockBuilder<'a> {
                         Some(secret_manager) => {
                             match output_address {
                                 Address::Ed25519(_) => Some(
-                                    search_address(
+                                    search_address_widening(
                                         secret_manager,
                                         &bech32_hrp,
                                         self.coin_type,
                                         self.account_index,
                                         self.input_range.clone(),
                                         &output_address,
+                                        self.input_search_max_end(),
                                     )
                                     .await?,
                                 ),
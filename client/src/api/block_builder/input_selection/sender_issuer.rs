@@ -12,7 +12,7 @@ use iota_types::block::{
 };
 
 use crate::{
-    api::{address::search_address, ClientBlockBuilder},
+    api::{address::search_address_widening, ClientBlockBuilder},
     constants::HD_WALLET_TYPE,
     secret::types::{InputSigningData, OutputMetadata},
     Error, Result,
@@ -37,13 +37,14 @@ impl<'a> ClientBlockBuilder<'a> {
             match sender_or_issuer_address {
                 Address::Ed25519(_) => {
                     // Check if the address is derived from the seed
-                    let (address_index, internal) = search_address(
+                    let (address_index, internal) = search_address_widening(
                         self.secret_manager.ok_or(Error::MissingParameter("secret manager"))?,
                         &bech32_hrp,
                         self.coin_type,
                         self.account_index,
                         self.input_range.clone(),
                         &sender_or_issuer_address,
+                        self.input_search_max_end(),
                     )
                     .await?;
                     let address_outputs = self
@@ -106,13 +107,14 @@ impl<'a> ClientBlockBuilder<'a> {
                                 Some(secret_manager) => {
                                     match unlock_address {
                                         Address::Ed25519(_) => Some(
-                                            search_address(
+                                            search_address_widening(
                                                 secret_manager,
                                                 &bech32_hrp,
                                                 self.coin_type,
                                                 self.account_index,
                                                 self.input_range.clone(),
                                                 unlock_address,
+                                                self.input_search_max_end(),
                                             )
                                             .await?,
                                         ),
@@ -166,13 +168,14 @@ impl<'a> ClientBlockBuilder<'a> {
                                 Some(secret_manager) => {
                                     match unlock_address {
                                         Address::Ed25519(_) => Some(
-                                            search_address(
+                                            search_address_widening(
                                                 secret_manager,
                                                 &bech32_hrp,
                                                 self.coin_type,
                                                 self.account_index,
                                                 self.input_range.clone(),
                                                 unlock_address,
+                                                self.input_search_max_end(),
                                             )
                                             .await?,
                                         ),
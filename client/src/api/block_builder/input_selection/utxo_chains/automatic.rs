@@ -14,7 +14,7 @@ use iota_types::{
 
 use super::get_alias_and_nft_outputs_recursively;
 use crate::{
-    api::{block_builder::ClientBlockBuilder, search_address},
+    api::{block_builder::ClientBlockBuilder, search_address_widening},
     constants::HD_WALLET_TYPE,
     secret::types::{InputSigningData, OutputMetadata},
     Result,
@@ -95,13 +95,14 @@ impl<'a> ClientBlockBuilder<'a> {
                 Some(secret_manager) => {
                     match unlock_address {
                         Address::Ed25519(_) => Some(
-                            search_address(
+                            search_address_widening(
                                 secret_manager,
                                 &bech32_hrp,
                                 self.coin_type,
                                 self.account_index,
                                 self.input_range.clone(),
                                 &unlock_address,
+                                self.input_search_max_end(),
                             )
                             .await?,
                         ),
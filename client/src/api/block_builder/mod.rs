@@ -2,10 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod input_selection;
+pub mod output_policy;
 pub mod pow;
 pub mod transaction;
 
-use std::{collections::HashSet, ops::Range};
+use std::{collections::HashSet, ops::Range, sync::Arc};
 
 use iota_types::block::{
     address::{Address, Ed25519Address},
@@ -21,8 +22,16 @@ use iota_types::block::{
 };
 use packable::bounded::TryIntoBoundedU16Error;
 
-pub use self::transaction::verify_semantic;
-use crate::{constants::SHIMMER_COIN_TYPE, secret::SecretManager, Client, Error, Result};
+pub use self::{
+    output_policy::{MaxOutputsPerRecipient, MinimumOutputAmount, OutputPolicy, PolicyViolation},
+    transaction::verify_semantic,
+};
+use crate::{
+    constants::SHIMMER_COIN_TYPE,
+    db::{DatabaseProvider, TransactionJournal},
+    secret::SecretManager,
+    Client, Error, Result,
+};
 
 /// Builder of the block API
 #[must_use]
@@ -34,12 +43,15 @@ pub struct ClientBlockBuilder<'a> {
     initial_address_index: u32,
     inputs: Option<Vec<UtxoInput>>,
     input_range: Range<u32>,
+    input_range_max_end: Option<u32>,
     outputs: Vec<Output>,
     custom_remainder_address: Option<Address>,
     tag: Option<Vec<u8>>,
     data: Option<Vec<u8>>,
     parents: Option<Parents>,
     allow_burning: bool,
+    output_policies: Vec<Arc<dyn OutputPolicy>>,
+    journal: Option<&'a TransactionJournal<dyn DatabaseProvider>>,
 }
 
 /// Block output address
@@ -67,6 +79,8 @@ pub struct ClientBlockBuilderOptions {
     pub inputs: Option<Vec<UtxoInputDto>>,
     /// Input range
     pub input_range: Option<Range<u32>>,
+    /// End up to which the input range is widened
+    pub input_range_max_end: Option<u32>,
     /// Bech32 encoded output address and amount
     pub output: Option<ClientBlockBuilderOutputAddress>,
     /// Hex encoded output address and amount
@@ -96,12 +110,15 @@ impl<'a> ClientBlockBuilder<'a> {
             initial_address_index: 0,
             inputs: None,
             input_range: 0..100,
+            input_range_max_end: None,
             outputs: Vec::new(),
             custom_remainder_address: None,
             tag: None,
             data: None,
             parents: None,
             allow_burning: false,
+            output_policies: Vec::new(),
+            journal: None,
         }
     }
 
@@ -111,6 +128,19 @@ impl<'a> ClientBlockBuilder<'a> {
         self
     }
 
+    /// Register a policy validating the outputs of the transaction before it's signed.
+    pub fn with_output_policy(mut self, policy: Arc<dyn OutputPolicy>) -> Self {
+        self.output_policies.push(policy);
+        self
+    }
+
+    /// Record the state transitions of the transaction in `journal` while sending it, so that it can be reconciled
+    /// with [`TransactionJournal::recover()`] after a crash.
+    pub fn with_journal(mut self, journal: &'a TransactionJournal<dyn DatabaseProvider>) -> Self {
+        self.journal.replace(journal);
+        self
+    }
+
     /// Sets the seed.
     pub fn with_secret_manager(mut self, manager: &'a SecretManager) -> Self {
         self.secret_manager.replace(manager);
@@ -157,11 +187,27 @@ impl<'a> ClientBlockBuilder<'a> {
         self
     }
 
+    /// Widen the search for addresses not found in the input range to the following ranges of the same length, up to
+    /// `max_range_end`. Default: no widening
+    pub fn with_input_range_max_end(mut self, max_range_end: u32) -> Self {
+        self.input_range_max_end.replace(max_range_end);
+        self
+    }
+
+    /// The end up to which addresses are searched for custom provided inputs.
+    fn input_search_max_end(&self) -> u32 {
+        self.input_range_max_end
+            .map_or(self.input_range.end, |max_end| max_end.max(self.input_range.end))
+    }
+
     /// Set a transfer to the builder
+    ///
+    /// The address must have the bech32 HRP of the network of the node, so that funds can't be sent by accident to an
+    /// address formatted for another network.
     pub async fn with_output(mut self, address: &str, amount: u64) -> Result<ClientBlockBuilder<'a>> {
         let output = BasicOutputBuilder::new_with_amount(amount)?
             .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(
-                Address::try_from_bech32(address)?.1,
+                self.client.verify_bech32_hrp(address).await?,
             )))
             .finish_output(self.client.get_token_supply().await?)?;
         self.outputs.push(output);
@@ -250,6 +296,10 @@ impl<'a> ClientBlockBuilder<'a> {
             self = self.with_input_range(input_range);
         }
 
+        if let Some(input_range_max_end) = options.input_range_max_end {
+            self = self.with_input_range_max_end(input_range_max_end);
+        }
+
         if let Some(output) = options.output {
             self = self
                 .with_output(
@@ -321,9 +371,14 @@ impl<'a> ClientBlockBuilder<'a> {
                 return Err(Error::MissingParameter("seed"));
             }
             // Send block with transaction
-            let prepared_transaction_data = self.prepare_transaction().await?;
-            let tx_payload = self.sign_transaction(prepared_transaction_data).await?;
-            self.finish_block(Some(tx_payload)).await
+            match self.journal {
+                Some(journal) => self.finish_journaled_transaction(journal).await,
+                None => {
+                    let prepared_transaction_data = self.prepare_transaction().await?;
+                    let tx_payload = self.sign_transaction(prepared_transaction_data).await?;
+                    self.finish_block(Some(tx_payload)).await
+                }
+            }
         } else if self.tag.is_some() {
             // Send block with tagged_data payload
             self.finish_tagged_data().await
@@ -333,6 +388,47 @@ impl<'a> ClientBlockBuilder<'a> {
         }
     }
 
+    /// Prepare, sign and send the transaction, recording each step in `journal` before taking the next one.
+    async fn finish_journaled_transaction(self, journal: &TransactionJournal<dyn DatabaseProvider>) -> Result<Block> {
+        let locked_inputs = self
+            .inputs
+            .iter()
+            .flatten()
+            .map(|input| *input.output_id())
+            .collect();
+        let id = journal.record_intent(locked_inputs).await?;
+
+        // Nothing has been sent before the transaction is signed, so its inputs can be released right away.
+        let tx_payload = match self.prepare_and_sign_transaction(journal, id).await {
+            Ok(tx_payload) => tx_payload,
+            Err(err) => {
+                journal.record_released(id).await?;
+                return Err(err);
+            }
+        };
+
+        let block = self.finish_block(Some(tx_payload)).await?;
+        journal.record_submitted(id, block.id()).await?;
+
+        Ok(block)
+    }
+
+    async fn prepare_and_sign_transaction(
+        &self,
+        journal: &TransactionJournal<dyn DatabaseProvider>,
+        id: u64,
+    ) -> Result<Payload> {
+        let prepared_transaction_data = self.prepare_transaction().await?;
+        journal.record_prepared(id, &prepared_transaction_data).await?;
+
+        let tx_payload = self.sign_transaction(prepared_transaction_data).await?;
+        if let Payload::Transaction(transaction_payload) = &tx_payload {
+            journal.record_signed(id, transaction_payload).await?;
+        }
+
+        Ok(tx_payload)
+    }
+
     /// Get output amount and address from an OutputDto, governance_transition for Alias Outputs so we get the unlock
     /// condition we're interested in
     pub fn get_output_amount_and_address(
@@ -412,7 +508,7 @@ impl<'a> ClientBlockBuilder<'a> {
                     return Ok(block);
                 }
                 #[cfg(not(target_family = "wasm"))]
-                tokio::time::sleep(std::time::Duration::from_millis(time * 50)).await;
+                crate::async_runtime::sleep(std::time::Duration::from_millis(time * 50)).await;
                 #[cfg(target_family = "wasm")]
                 gloo_timers::future::TimeoutFuture::new((time * 50).try_into().unwrap()).await;
             }
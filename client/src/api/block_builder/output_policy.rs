@@ -0,0 +1,167 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Policies validating the outputs of a transaction before it's signed.
+//!
+//! Policies are registered on a [`ClientBlockBuilder`](crate::api::ClientBlockBuilder) with
+//! [`with_output_policy()`](crate::api::ClientBlockBuilder::with_output_policy()) and checked against the final output
+//! set, remainder included, right before signing. The violations of all policies are returned together in
+//! [`Error::OutputPolicyViolations`](crate::Error::OutputPolicyViolations).
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+use instant::Instant;
+use iota_types::block::{address::Address, output::Output, payload::transaction::TransactionEssence};
+
+use crate::api::PreparedTransactionData;
+
+/// A violation of an [`OutputPolicy`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+pub enum PolicyViolation {
+    /// An output has an amount below the minimum.
+    #[serde(rename_all = "camelCase")]
+    AmountBelowMinimum {
+        /// The index of the output in the transaction.
+        output_index: usize,
+        /// The amount of the output.
+        amount: u64,
+        /// The minimum amount.
+        minimum: u64,
+    },
+    /// Too many outputs have been sent to a recipient within the period.
+    #[serde(rename_all = "camelCase")]
+    TooManyOutputsForRecipient {
+        /// The address of the recipient.
+        address: Address,
+        /// The number of outputs sent in the period, including the ones of the transaction.
+        count: usize,
+        /// The maximum number of outputs in the period.
+        maximum: usize,
+    },
+    /// A violation of a policy defined by the integrator.
+    Custom(String),
+}
+
+/// A validation over the outputs of a transaction, run before it's signed.
+pub trait OutputPolicy: Send + Sync {
+    /// Check the outputs of the prepared transaction, returning the violations of the policy.
+    fn check(&self, prepared_transaction_data: &PreparedTransactionData) -> Vec<PolicyViolation>;
+
+    /// Called once the transaction has been signed, to keep track of what has been sent.
+    fn record(&self, _prepared_transaction_data: &PreparedTransactionData) {}
+}
+
+/// A policy rejecting outputs with an amount below a minimum.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MinimumOutputAmount(pub u64);
+
+impl OutputPolicy for MinimumOutputAmount {
+    fn check(&self, prepared_transaction_data: &PreparedTransactionData) -> Vec<PolicyViolation> {
+        outputs(prepared_transaction_data)
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.amount() < self.0)
+            .map(|(output_index, output)| PolicyViolation::AmountBelowMinimum {
+                output_index,
+                amount: output.amount(),
+                minimum: self.0,
+            })
+            .collect()
+    }
+}
+
+/// A policy limiting the number of outputs sent to a recipient within a period, e.g. a day.
+///
+/// The remainder output isn't taken into account. The outputs are counted once signed; the policy has to be shared
+/// between block builders, e.g. in an `Arc`, to count the outputs of all of them.
+#[derive(Debug)]
+pub struct MaxOutputsPerRecipient {
+    maximum: usize,
+    period: Duration,
+    sent: Mutex<HashMap<Address, VecDeque<Instant>>>,
+}
+
+impl MaxOutputsPerRecipient {
+    /// Create a policy allowing at most `maximum` outputs per recipient within `period`.
+    pub fn new(maximum: usize, period: Duration) -> Self {
+        Self {
+            maximum,
+            period,
+            sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn recipients(prepared_transaction_data: &PreparedTransactionData) -> HashMap<Address, usize> {
+        let remainder = prepared_transaction_data
+            .remainder
+            .as_ref()
+            .map(|remainder| &remainder.output);
+        let mut recipients = HashMap::new();
+
+        for output in outputs(prepared_transaction_data) {
+            if Some(output) == remainder {
+                continue;
+            }
+
+            if let Some(address) = output
+                .unlock_conditions()
+                .and_then(|unlock_conditions| unlock_conditions.address())
+            {
+                *recipients.entry(*address.address()).or_insert(0) += 1;
+            }
+        }
+
+        recipients
+    }
+}
+
+impl OutputPolicy for MaxOutputsPerRecipient {
+    fn check(&self, prepared_transaction_data: &PreparedTransactionData) -> Vec<PolicyViolation> {
+        let mut sent = self.sent.lock().expect("failed to lock the sent outputs");
+        let now = Instant::now();
+
+        Self::recipients(prepared_transaction_data)
+            .into_iter()
+            .filter_map(|(address, count)| {
+                let sent_in_period = sent.get_mut(&address).map_or(0, |instants| {
+                    // Forget the outputs sent before the period.
+                    while instants
+                        .front()
+                        .map_or(false, |instant| now.duration_since(*instant) >= self.period)
+                    {
+                        instants.pop_front();
+                    }
+                    instants.len()
+                });
+
+                (sent_in_period + count > self.maximum).then_some(PolicyViolation::TooManyOutputsForRecipient {
+                    address,
+                    count: sent_in_period + count,
+                    maximum: self.maximum,
+                })
+            })
+            .collect()
+    }
+
+    fn record(&self, prepared_transaction_data: &PreparedTransactionData) {
+        let mut sent = self.sent.lock().expect("failed to lock the sent outputs");
+        let now = Instant::now();
+
+        for (address, count) in Self::recipients(prepared_transaction_data) {
+            sent.entry(address)
+                .or_default()
+                .extend(std::iter::repeat(now).take(count));
+        }
+    }
+}
+
+fn outputs(prepared_transaction_data: &PreparedTransactionData) -> &[Output] {
+    match &prepared_transaction_data.essence {
+        TransactionEssence::Regular(essence) => essence.outputs(),
+    }
+}
@@ -4,13 +4,27 @@
 //! PoW functions.
 
 #[cfg(not(target_family = "wasm"))]
-use iota_pow::miner::{Miner, MinerBuilder, MinerCancel};
+use std::time::Duration;
+
 #[cfg(target_family = "wasm")]
 use iota_pow::wasm_miner::{SingleThreadedMiner, SingleThreadedMinerBuilder};
+#[cfg(not(target_family = "wasm"))]
+use iota_pow::{
+    miner::{hash_rate_per_worker, Miner, MinerBuilder, MinerCancel},
+    score::target_zeros,
+};
 use iota_types::block::{parent::Parents, payload::Payload, Block, BlockBuilder};
+#[cfg(not(target_family = "wasm"))]
+use once_cell::sync::OnceCell;
 
+#[cfg(not(target_family = "wasm"))]
+use crate::constants::POW_CALIBRATION_DURATION;
 use crate::{Client, Error, Result};
 
+/// The number of PoW hashes a single worker computes per second on this machine, measured once.
+#[cfg(not(target_family = "wasm"))]
+static HASH_RATE_PER_WORKER: OnceCell<f64> = OnceCell::new();
+
 impl Client {
     /// Finishes the block with local PoW if needed.
     /// Without local PoW, it will finish the block with a 0 nonce.
@@ -33,6 +47,26 @@ impl Client {
         }
     }
 
+    /// Estimates how long local PoW takes on this machine for a block of `block_length` bytes, with the minimum PoW
+    /// score of the network and the configured number of workers, e.g. to warn users before building a large block.
+    ///
+    /// The first call runs a short calibration blocking the current thread, measuring the hash rate of this machine;
+    /// the result is cached for the lifetime of the process. As PoW is a random process, the actual duration can be a
+    /// few times shorter or longer than this expected one.
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn estimate_local_pow_duration(&self, block_length: usize) -> Result<Duration> {
+        let min_pow_score = self.get_min_pow_score().await?;
+        let worker_count = self.pow_worker_count.unwrap_or(1).max(1);
+        let hash_rate = HASH_RATE_PER_WORKER.get_or_init(|| hash_rate_per_worker(POW_CALIBRATION_DURATION));
+
+        // On average, 3^target_zeros hashes are computed until one has enough trailing zeros.
+        let expected_hashes = 3f64.powi(target_zeros(block_length, min_pow_score) as i32);
+
+        Ok(Duration::from_secs_f64(
+            expected_hashes / (hash_rate * worker_count as f64),
+        ))
+    }
+
     /// Calls the appropriate PoW function depending whether the compilation is for wasm or not.
     pub async fn finish_pow(&self, parents: Option<Parents>, payload: Option<Payload>) -> Result<Block> {
         #[cfg(not(target_family = "wasm"))]
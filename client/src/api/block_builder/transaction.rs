@@ -109,6 +109,17 @@ impl<'a> ClientBlockBuilder<'a> {
     pub async fn sign_transaction(&self, prepared_transaction_data: PreparedTransactionData) -> Result<Payload> {
         log::debug!("[sign_transaction] {:?}", prepared_transaction_data);
         let secret_manager = self.secret_manager.ok_or(Error::MissingParameter("secret manager"))?;
+
+        let policy_violations = self
+            .output_policies
+            .iter()
+            .flat_map(|policy| policy.check(&prepared_transaction_data))
+            .collect::<Vec<_>>();
+
+        if !policy_violations.is_empty() {
+            return Err(Error::OutputPolicyViolations(policy_violations));
+        }
+
         let unlocks = secret_manager
             .sign_transaction_essence(&prepared_transaction_data)
             .await?;
@@ -125,6 +136,10 @@ impl<'a> ClientBlockBuilder<'a> {
             return Err(Error::TransactionSemantic(conflict));
         }
 
+        for policy in &self.output_policies {
+            policy.record(&prepared_transaction_data);
+        }
+
         Ok(Payload::from(tx_payload))
     }
 }
@@ -0,0 +1,459 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::future::{try_join4, try_join_all};
+use iota_types::block::{
+    address::{Address, AliasAddress},
+    output::{Output, OutputId},
+};
+
+use crate::{
+    api::{AddressChains, ADDRESS_GAP_RANGE},
+    constants::{MAX_PARALLEL_API_REQUESTS, SHIMMER_COIN_TYPE},
+    node_api::indexer::query_parameters::QueryParameter,
+    secret::{types::AccountAddress, SecretManager},
+    Client, Result,
+};
+
+/// Options for address discovery and balance calculation.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressDiscoveryOptions {
+    /// Coin type
+    pub coin_type: u32,
+    /// Account index
+    pub account_index: u32,
+    /// The chains to scan; internal (change) addresses are scanned by default.
+    pub chains: AddressChains,
+    /// Number of consecutive unused address indexes after which the scanning stops.
+    pub gap_limit: u32,
+}
+
+impl Default for AddressDiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            coin_type: SHIMMER_COIN_TYPE,
+            account_index: 0,
+            chains: AddressChains::default(),
+            gap_limit: ADDRESS_GAP_RANGE,
+        }
+    }
+}
+
+/// An address found during discovery, with the ids of the outputs it owns.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredAddress {
+    /// The address, its key index and chain.
+    pub address: AccountAddress,
+    /// The ids of the basic, NFT and alias outputs owned by the address, and of the foundry outputs of its aliases.
+    pub output_ids: Vec<OutputId>,
+}
+
+/// Options for account discovery.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDiscoveryOptions {
+    /// Coin type
+    pub coin_type: u32,
+    /// The chains to scan; internal (change) addresses are scanned by default.
+    pub chains: AddressChains,
+    /// Number of consecutive unused address indexes after which the scanning of an account stops.
+    pub gap_limit: u32,
+    /// Number of consecutive accounts without any used address after which the scanning stops.
+    pub account_gap_limit: u32,
+}
+
+impl Default for AccountDiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            coin_type: SHIMMER_COIN_TYPE,
+            chains: AddressChains::default(),
+            gap_limit: ADDRESS_GAP_RANGE,
+            // Like BIP-44, accounts are scanned until the first one without used addresses.
+            account_gap_limit: 1,
+        }
+    }
+}
+
+/// An account found during discovery, with its addresses owning outputs.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredAccount {
+    /// The account index.
+    pub account_index: u32,
+    /// The addresses of the account owning outputs.
+    pub addresses: Vec<DiscoveredAddress>,
+}
+
+impl Client {
+    /// Scan the addresses of an account, on the public and/or internal chains, and return the ones owning outputs,
+    /// see [`DiscoveredAddress::output_ids`]. The scanning stops once `gap_limit` consecutive indexes have no outputs
+    /// on any of the scanned chains.
+    pub async fn discover_addresses(
+        &self,
+        secret_manager: &SecretManager,
+        options: AddressDiscoveryOptions,
+    ) -> Result<Vec<DiscoveredAddress>> {
+        let bech32_hrp = self.get_bech32_hrp().await?;
+        let token_supply = self.get_token_supply().await?;
+        let mut discovered = Vec::new();
+        let mut start_index: u32 = 0;
+        let mut empty_indexes = 0;
+
+        while empty_indexes < options.gap_limit {
+            let end_index = start_index
+                .checked_add(options.gap_limit)
+                .ok_or(crate::Error::AddressIndexOverflow {
+                    account_index: options.account_index,
+                    start_index,
+                    range_length: options.gap_limit,
+                })?;
+            let addresses = self
+                .get_addresses(secret_manager)
+                .with_coin_type(options.coin_type)
+                .with_account_index(options.account_index)
+                .with_range(start_index..end_index)
+                .get_chains(options.chains)
+                .await?;
+
+            let mut output_ids = Vec::with_capacity(addresses.len());
+            for addresses_chunk in addresses.chunks(MAX_PARALLEL_API_REQUESTS) {
+                output_ids.extend(
+                    try_join_all(addresses_chunk.iter().map(|address| {
+                        self.owned_output_ids(address.address.to_bech32(&bech32_hrp), &bech32_hrp, token_supply)
+                    }))
+                    .await?,
+                );
+            }
+
+            // Addresses are ordered by index, so all chains of an index are checked before deciding it's empty.
+            let chain_count = options.chains.internal_flags().len();
+            for (index_addresses, index_output_ids) in addresses.chunks(chain_count).zip(output_ids.chunks(chain_count))
+            {
+                let mut index_is_empty = true;
+
+                for (address, output_ids) in index_addresses.iter().zip(index_output_ids) {
+                    if !output_ids.is_empty() {
+                        index_is_empty = false;
+                        discovered.push(DiscoveredAddress {
+                            address: address.clone(),
+                            output_ids: output_ids.clone(),
+                        });
+                    }
+                }
+
+                if index_is_empty {
+                    empty_indexes += 1;
+
+                    if empty_indexes >= options.gap_limit {
+                        break;
+                    }
+                } else {
+                    empty_indexes = 0;
+                }
+            }
+
+            start_index = end_index;
+        }
+
+        Ok(discovered)
+    }
+
+    /// Get the ids of the outputs owned by a bech32 address, see [`DiscoveredAddress::output_ids`], querying the
+    /// indexer for all output kinds concurrently.
+    async fn owned_output_ids(&self, address: String, bech32_hrp: &str, token_supply: u64) -> Result<Vec<OutputId>> {
+        let (basic_output_ids, nft_output_ids, state_controlled_alias_output_ids, governed_alias_output_ids) =
+            try_join4(
+                self.basic_output_ids(vec![QueryParameter::Address(address.clone())]),
+                self.nft_output_ids(vec![QueryParameter::Address(address.clone())]),
+                self.alias_output_ids(vec![QueryParameter::StateController(address.clone())]),
+                self.alias_output_ids(vec![QueryParameter::Governor(address)]),
+            )
+            .await?;
+
+        let mut alias_output_ids = state_controlled_alias_output_ids;
+        for output_id in governed_alias_output_ids {
+            if !alias_output_ids.contains(&output_id) {
+                alias_output_ids.push(output_id);
+            }
+        }
+
+        // Foundries are controlled by aliases, so they are looked up through the aliases of the address.
+        let mut alias_addresses = Vec::new();
+        for output_response in self.get_outputs(alias_output_ids.clone()).await? {
+            if let Output::Alias(alias_output) = Output::try_from_dto(&output_response.output, token_supply)? {
+                let alias_id = alias_output.alias_id_non_null(&output_response.metadata.output_id()?);
+                alias_addresses.push(Address::Alias(AliasAddress::new(alias_id)).to_bech32(bech32_hrp));
+            }
+        }
+        let foundry_output_ids = try_join_all(
+            alias_addresses
+                .into_iter()
+                .map(|alias_address| self.foundry_output_ids(vec![QueryParameter::AliasAddress(alias_address)])),
+        )
+        .await?;
+
+        let mut output_ids = basic_output_ids;
+        output_ids.extend(nft_output_ids);
+        output_ids.extend(alias_output_ids);
+        output_ids.extend(foundry_output_ids.into_iter().flatten());
+
+        Ok(output_ids)
+    }
+
+    /// Scan the accounts of a secret manager, from account index 0, with
+    /// [`discover_addresses()`](Self::discover_addresses) and return the ones owning outputs, e.g. to restore
+    /// a wallet. The scanning stops once `account_gap_limit` consecutive accounts have no addresses owning outputs.
+    pub async fn discover_accounts(
+        &self,
+        secret_manager: &SecretManager,
+        options: AccountDiscoveryOptions,
+    ) -> Result<Vec<DiscoveredAccount>> {
+        let mut discovered = Vec::new();
+        let mut account_index = 0;
+        let mut empty_accounts = 0;
+
+        while empty_accounts < options.account_gap_limit {
+            let addresses = self
+                .discover_addresses(
+                    secret_manager,
+                    AddressDiscoveryOptions {
+                        coin_type: options.coin_type,
+                        account_index,
+                        chains: options.chains,
+                        gap_limit: options.gap_limit,
+                    },
+                )
+                .await?;
+
+            if addresses.is_empty() {
+                empty_accounts += 1;
+            } else {
+                empty_accounts = 0;
+                discovered.push(DiscoveredAccount {
+                    account_index,
+                    addresses,
+                });
+            }
+
+            account_index += 1;
+        }
+
+        Ok(discovered)
+    }
+
+    /// Get the total amount of the outputs owned by the addresses of an account, including the internal
+    /// (change) addresses unless `options.chains` says otherwise.
+    pub async fn get_account_balance(
+        &self,
+        secret_manager: &SecretManager,
+        options: AddressDiscoveryOptions,
+    ) -> Result<u64> {
+        let token_supply = self.get_token_supply().await?;
+        let output_ids = self
+            .discover_addresses(secret_manager, options)
+            .await?
+            .into_iter()
+            .flat_map(|discovered| discovered.output_ids)
+            .collect();
+
+        let mut balance = 0;
+
+        for output_response in self.get_outputs(output_ids).await? {
+            balance += Output::try_from_dto(&output_response.output, token_supply)?.amount();
+        }
+
+        Ok(balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use iota_types::{
+        api::response::{OutputMetadataResponse, OutputWithMetadataResponse},
+        block::{
+            output::{
+                dto::OutputDto,
+                unlock_condition::{
+                    GovernorAddressUnlockCondition, StateControllerAddressUnlockCondition, UnlockCondition,
+                },
+                AliasId, AliasOutputBuilder,
+            },
+            protocol::ProtocolParameters,
+            rand::{
+                address::rand_ed25519_address,
+                block::rand_block_id,
+                output::{rand_foundry_output, rand_nft_output, rand_output_id},
+                transaction::rand_transaction_id,
+            },
+        },
+    };
+
+    use super::*;
+    use crate::{
+        node_api::{
+            indexer::OutputIdsResponse,
+            mock_node::{info_response, mock_node},
+        },
+        secret::mnemonic::MnemonicSecretManager,
+    };
+
+    fn output_ids_response(output_ids: &[OutputId]) -> String {
+        serde_json::to_string(&OutputIdsResponse {
+            ledger_index: 0,
+            cursor: None,
+            items: output_ids.iter().map(ToString::to_string).collect(),
+        })
+        .unwrap()
+    }
+
+    /// An unspent output with a random output id.
+    fn output_response(output: Output) -> OutputWithMetadataResponse {
+        OutputWithMetadataResponse {
+            metadata: OutputMetadataResponse {
+                block_id: rand_block_id().to_string(),
+                transaction_id: rand_transaction_id().to_string(),
+                output_index: 0,
+                is_spent: false,
+                milestone_index_spent: None,
+                milestone_timestamp_spent: None,
+                transaction_id_spent: None,
+                milestone_index_booked: 0,
+                milestone_timestamp_booked: 0,
+                ledger_index: 0,
+            },
+            output: OutputDto::from(&output),
+        }
+    }
+
+    #[tokio::test]
+    async fn discover_addresses() {
+        let token_supply = ProtocolParameters::default().token_supply();
+        let secret_manager = SecretManager::Mnemonic(
+            MnemonicSecretManager::try_from_mnemonic(
+                "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot \
+                 shallow glad autumn author calm heavy hawk abuse rally",
+            )
+            .unwrap(),
+        );
+        let addresses = Client::builder()
+            .finish()
+            .unwrap()
+            .get_addresses(&secret_manager)
+            .with_coin_type(SHIMMER_COIN_TYPE)
+            .with_range(0..5)
+            .with_bech32_hrp("smr")
+            .get_chains(AddressChains::Both)
+            .await
+            .unwrap();
+        let bech32_address = |key_index: u32, internal: bool| {
+            addresses
+                .iter()
+                .find(|address| address.key_index == key_index && address.internal == internal)
+                .unwrap()
+                .address
+                .to_bech32("smr")
+        };
+
+        // The public address 0 only owns an NFT output.
+        let nft_output = output_response(Output::Nft(rand_nft_output(token_supply)));
+        // The internal address 1 only governs an alias, controlling a foundry.
+        let alias_output = output_response(
+            AliasOutputBuilder::new_with_amount(1_000_000, AliasId::null())
+                .unwrap()
+                .add_unlock_condition(UnlockCondition::StateControllerAddress(
+                    StateControllerAddressUnlockCondition::new(Address::Ed25519(rand_ed25519_address())),
+                ))
+                .add_unlock_condition(UnlockCondition::GovernorAddress(GovernorAddressUnlockCondition::new(
+                    addresses[3].address,
+                )))
+                .finish_output(token_supply)
+                .unwrap(),
+        );
+        let alias_output_id = alias_output.metadata.output_id().unwrap();
+        let alias_address = Address::Alias(AliasAddress::new(AliasId::from(&alias_output_id))).to_bech32("smr");
+        let foundry_output = output_response(Output::Foundry(rand_foundry_output(token_supply)));
+        // The public address 4 owns a basic output, beyond the gap limit.
+        let basic_output_id = rand_output_id();
+
+        let output_id = |output_response: &OutputWithMetadataResponse| output_response.metadata.output_id().unwrap();
+        let mut responses = HashMap::from([
+            ("/api/core/v2/info".to_string(), info_response(0)),
+            (
+                format!("/api/indexer/v1/outputs/nft?address={}", bech32_address(0, false)),
+                output_ids_response(&[output_id(&nft_output)]),
+            ),
+            (
+                format!("/api/indexer/v1/outputs/alias?governor={}", bech32_address(1, true)),
+                output_ids_response(&[alias_output_id]),
+            ),
+            (
+                format!("/api/indexer/v1/outputs/foundry?aliasAddress={alias_address}"),
+                output_ids_response(&[output_id(&foundry_output)]),
+            ),
+            (
+                format!("/api/indexer/v1/outputs/basic?address={}", bech32_address(4, false)),
+                output_ids_response(&[basic_output_id]),
+            ),
+        ]);
+        for output_response in [&nft_output, &alias_output, &foundry_output] {
+            responses.insert(
+                format!("/api/core/v2/outputs/{}", output_id(output_response)),
+                serde_json::to_string(output_response).unwrap(),
+            );
+        }
+
+        // The other indexer queries find no output.
+        let node_url = mock_node(move |request| {
+            responses.get(&request.path).cloned().or_else(|| {
+                request
+                    .path
+                    .starts_with("/api/indexer/")
+                    .then(|| output_ids_response(&[]))
+            })
+        });
+        let client = Client::builder()
+            .with_node(&node_url)
+            .unwrap()
+            .with_ignore_node_health()
+            .finish()
+            .unwrap();
+        let options = AddressDiscoveryOptions {
+            gap_limit: 2,
+            ..Default::default()
+        };
+
+        let discovered = client
+            .discover_addresses(&secret_manager, options.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            discovered,
+            vec![
+                DiscoveredAddress {
+                    address: addresses[0].clone(),
+                    output_ids: vec![output_id(&nft_output)],
+                },
+                DiscoveredAddress {
+                    address: addresses[3].clone(),
+                    output_ids: vec![alias_output_id, output_id(&foundry_output)],
+                },
+            ]
+        );
+
+        assert_eq!(
+            client.get_account_balance(&secret_manager, options).await.unwrap(),
+            [&nft_output, &alias_output, &foundry_output]
+                .into_iter()
+                .map(
+                    |output_response| Output::try_from_dto(&output_response.output, token_supply)
+                        .unwrap()
+                        .amount()
+                )
+                .sum::<u64>()
+        );
+    }
+}
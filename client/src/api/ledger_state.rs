@@ -0,0 +1,426 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replay of the ledger state of a set of addresses at a past milestone.
+
+use std::{collections::HashMap, str::FromStr};
+
+use iota_types::{
+    api::response::OutputWithMetadataResponse,
+    block::{
+        address::Address,
+        output::{Output, OutputId},
+    },
+};
+
+use crate::{
+    constants::DEFAULT_LEDGER_STATE_MAX_REPLAY_DEPTH, node_api::indexer::query_parameters::QueryParameter, Client,
+    Error, Result,
+};
+
+/// Builder reconstructing the outputs owned by a set of addresses at a past milestone.
+///
+/// The outputs currently owned by the addresses are taken from the indexer, then the UTXO changes of all milestones
+/// confirmed after the requested one are replayed backwards, to find back the outputs that have been consumed since.
+/// Consumed outputs are fetched from permanodes, if any have been configured, as nodes prune them.
+///
+/// As each milestone confirmed since takes a request, at most
+/// [`DEFAULT_LEDGER_STATE_MAX_REPLAY_DEPTH`] milestones are replayed by default, see
+/// [`with_max_replay_depth()`](Self::with_max_replay_depth()).
+///
+/// Basic and NFT outputs are owned through their address unlock condition, alias outputs through their state
+/// controller or governor address unlock condition. Foundry outputs are owned by aliases, so they're found with the
+/// alias addresses.
+#[derive(Clone)]
+pub struct LedgerStateBuilder<'a> {
+    client: &'a Client,
+    milestone_index: u32,
+    addresses: Vec<String>,
+    max_replay_depth: u32,
+}
+
+/// The outputs owned by a set of addresses at a past milestone, see [`Client::ledger_state()`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerState {
+    /// The milestone index of the state.
+    pub milestone_index: u32,
+    /// The outputs owned by the addresses at the milestone, with their current metadata.
+    pub outputs: Vec<OutputWithMetadataResponse>,
+}
+
+impl LedgerState {
+    /// The total amount of the outputs.
+    pub fn amount(&self, token_supply: u64) -> Result<u64> {
+        let mut amount = 0;
+
+        for output_response in &self.outputs {
+            amount += Output::try_from_dto(&output_response.output, token_supply)?.amount();
+        }
+
+        Ok(amount)
+    }
+}
+
+impl<'a> LedgerStateBuilder<'a> {
+    /// Create a builder for the ledger state at `milestone_index`.
+    pub fn new(client: &'a Client, milestone_index: u32) -> Self {
+        Self {
+            client,
+            milestone_index,
+            addresses: Vec::new(),
+            max_replay_depth: DEFAULT_LEDGER_STATE_MAX_REPLAY_DEPTH,
+        }
+    }
+
+    /// Set the bech32 encoded addresses whose outputs are reconstructed.
+    pub fn with_addresses(mut self, addresses: Vec<String>) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Set the maximal number of milestones confirmed since the milestone to replay; beyond it, [`finish()`] fails
+    /// with [`Error::LedgerStateReplayTooDeep`].
+    ///
+    /// [`finish()`]: Self::finish()
+    pub fn with_max_replay_depth(mut self, max_replay_depth: u32) -> Self {
+        self.max_replay_depth = max_replay_depth;
+        self
+    }
+
+    /// The confirmed milestone index, checking that the milestone is confirmed and within the maximal replay depth.
+    async fn confirmed_milestone_index(&self) -> Result<u32> {
+        let confirmed_milestone_index = self.client.get_info().await?.node_info.status.confirmed_milestone.index;
+
+        if self.milestone_index > confirmed_milestone_index {
+            return Err(Error::MilestoneIndexNotConfirmed {
+                milestone_index: self.milestone_index,
+                confirmed_milestone_index,
+            });
+        }
+
+        let replay_depth = confirmed_milestone_index - self.milestone_index;
+
+        if replay_depth > self.max_replay_depth {
+            return Err(Error::LedgerStateReplayTooDeep {
+                milestone_index: self.milestone_index,
+                replay_depth,
+                max_replay_depth: self.max_replay_depth,
+            });
+        }
+
+        Ok(confirmed_milestone_index)
+    }
+
+    /// Reconstruct the outputs owned by the addresses at the milestone.
+    pub async fn finish(self) -> Result<LedgerState> {
+        let addresses = self
+            .addresses
+            .iter()
+            .map(|address| Ok(Address::try_from_bech32(address)?.1))
+            .collect::<Result<Vec<Address>>>()?;
+        let mut outputs = HashMap::new();
+
+        // Checked first, so that no request is sent for a replay too deep.
+        self.confirmed_milestone_index().await?;
+
+        // The outputs owned now and already booked at the milestone.
+        for address in &self.addresses {
+            let mut output_ids = self
+                .client
+                .basic_output_ids(vec![QueryParameter::Address(address.clone())])
+                .await?;
+            output_ids.extend(
+                self.client
+                    .nft_output_ids(vec![QueryParameter::Address(address.clone())])
+                    .await?,
+            );
+            output_ids.extend(
+                self.client
+                    .alias_output_ids(vec![QueryParameter::StateController(address.clone())])
+                    .await?,
+            );
+            output_ids.extend(
+                self.client
+                    .alias_output_ids(vec![QueryParameter::Governor(address.clone())])
+                    .await?,
+            );
+
+            for output_response in self.client.get_outputs(output_ids).await? {
+                if output_response.metadata.milestone_index_booked <= self.milestone_index {
+                    outputs.insert(output_response.metadata.output_id()?, output_response);
+                }
+            }
+        }
+
+        // Queried again after the indexer, so that no output consumed in between is missed.
+        let confirmed_milestone_index = self.confirmed_milestone_index().await?;
+        let token_supply = self.client.get_token_supply().await?;
+
+        // The outputs consumed since the milestone, which were owned at the milestone.
+        for index in self.milestone_index + 1..=confirmed_milestone_index {
+            let consumed_output_ids = self
+                .client
+                .get_utxo_changes_by_index(index)
+                .await?
+                .consumed_outputs
+                .iter()
+                .map(|output_id| OutputId::from_str(output_id))
+                .collect::<core::result::Result<Vec<OutputId>, _>>()?;
+
+            for output_response in self.client.get_outputs(consumed_output_ids).await? {
+                if output_response.metadata.milestone_index_booked > self.milestone_index {
+                    continue;
+                }
+
+                let output = Output::try_from_dto(&output_response.output, token_supply)?;
+
+                if is_owned(&output, &addresses) {
+                    outputs.insert(output_response.metadata.output_id()?, output_response);
+                }
+            }
+        }
+
+        let mut outputs = outputs.into_iter().collect::<Vec<_>>();
+        outputs.sort_unstable_by_key(|(output_id, _)| *output_id);
+
+        Ok(LedgerState {
+            milestone_index: self.milestone_index,
+            outputs: outputs
+                .into_iter()
+                .map(|(_, output_response)| output_response)
+                .collect(),
+        })
+    }
+}
+
+/// Whether `output` is owned by one of `addresses`, through its address, state controller or governor address unlock
+/// condition.
+fn is_owned(output: &Output, addresses: &[Address]) -> bool {
+    output.unlock_conditions().map_or(false, |unlock_conditions| {
+        [
+            unlock_conditions
+                .address()
+                .map(|unlock_condition| unlock_condition.address()),
+            unlock_conditions
+                .state_controller_address()
+                .map(|unlock_condition| unlock_condition.address()),
+            unlock_conditions
+                .governor_address()
+                .map(|unlock_condition| unlock_condition.address()),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|address| addresses.contains(address))
+    })
+}
+
+impl Client {
+    /// Reconstruct the outputs owned by a set of addresses at a past milestone, e.g. to get a historical balance, see
+    /// [`LedgerStateBuilder`].
+    pub fn ledger_state(&self, milestone_index: u32) -> LedgerStateBuilder<'_> {
+        LedgerStateBuilder::new(self, milestone_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iota_types::{
+        api::response::{OutputMetadataResponse, UtxoChangesResponse},
+        block::{
+            output::{
+                dto::OutputDto,
+                unlock_condition::{
+                    AddressUnlockCondition, GovernorAddressUnlockCondition, StateControllerAddressUnlockCondition,
+                    UnlockCondition,
+                },
+                AliasOutputBuilder, BasicOutputBuilder,
+            },
+            protocol::ProtocolParameters,
+            rand::{
+                address::rand_ed25519_address, block::rand_block_id, output::rand_alias_id,
+                transaction::rand_transaction_id,
+            },
+        },
+    };
+
+    use super::*;
+    use crate::node_api::{
+        indexer::OutputIdsResponse,
+        mock_node::{info_response, mock_node},
+    };
+
+    const MILESTONE_INDEX: u32 = 10;
+    const CONFIRMED_MILESTONE_INDEX: u32 = 12;
+
+    fn output_ids_response(output_ids: &[OutputId]) -> String {
+        serde_json::to_string(&OutputIdsResponse {
+            ledger_index: CONFIRMED_MILESTONE_INDEX,
+            cursor: None,
+            items: output_ids.iter().map(ToString::to_string).collect(),
+        })
+        .unwrap()
+    }
+
+    fn utxo_changes_response(index: u32, consumed_output_ids: &[OutputId]) -> String {
+        serde_json::to_string(&UtxoChangesResponse {
+            index,
+            created_outputs: Vec::new(),
+            consumed_outputs: consumed_output_ids.iter().map(ToString::to_string).collect(),
+        })
+        .unwrap()
+    }
+
+    /// An output booked at `milestone_index_booked`, spent at `milestone_index_spent` if any.
+    fn output_response(
+        output: &Output,
+        milestone_index_booked: u32,
+        milestone_index_spent: Option<u32>,
+    ) -> OutputWithMetadataResponse {
+        OutputWithMetadataResponse {
+            metadata: OutputMetadataResponse {
+                block_id: rand_block_id().to_string(),
+                transaction_id: rand_transaction_id().to_string(),
+                output_index: 0,
+                is_spent: milestone_index_spent.is_some(),
+                milestone_index_spent,
+                milestone_timestamp_spent: milestone_index_spent.map(|_| 0),
+                transaction_id_spent: milestone_index_spent.map(|_| rand_transaction_id().to_string()),
+                milestone_index_booked,
+                milestone_timestamp_booked: 0,
+                ledger_index: CONFIRMED_MILESTONE_INDEX,
+            },
+            output: OutputDto::from(output),
+        }
+    }
+
+    #[tokio::test]
+    async fn ledger_state() {
+        let token_supply = ProtocolParameters::default().token_supply();
+        let address = Address::Ed25519(rand_ed25519_address());
+        let other_address = Address::Ed25519(rand_ed25519_address());
+        let bech32_address = address.to_bech32("smr");
+
+        let basic_output = |address: Address, amount: u64| {
+            BasicOutputBuilder::new_with_amount(amount)
+                .unwrap()
+                .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(address)))
+                .finish_output(token_supply)
+                .unwrap()
+        };
+        let alias_output = |state_controller: Address, governor: Address, amount: u64| {
+            AliasOutputBuilder::new_with_amount(amount, rand_alias_id())
+                .unwrap()
+                .add_unlock_condition(UnlockCondition::StateControllerAddress(
+                    StateControllerAddressUnlockCondition::new(state_controller),
+                ))
+                .add_unlock_condition(UnlockCondition::GovernorAddress(GovernorAddressUnlockCondition::new(
+                    governor,
+                )))
+                .finish_output(token_supply)
+                .unwrap()
+        };
+
+        // Owned now, booked before the milestone.
+        let unspent = output_response(&basic_output(address, 1_000_000), 5, None);
+        // Owned now, booked after the milestone.
+        let unspent_later = output_response(&basic_output(address, 2_000_000), 11, None);
+        // Owned at the milestone, consumed since.
+        let spent = output_response(&basic_output(address, 3_000_000), 8, Some(11));
+        // Controlled at the milestone, consumed since.
+        let spent_alias = output_response(&alias_output(address, other_address, 4_000_000), 9, Some(12));
+        // Governed now, booked before the milestone.
+        let unspent_alias = output_response(&alias_output(other_address, address, 5_000_000), 3, None);
+        // Owned by another address, consumed since.
+        let spent_other = output_response(&basic_output(other_address, 6_000_000), 2, Some(12));
+        // Booked and consumed since the milestone.
+        let spent_later = output_response(&basic_output(address, 7_000_000), 11, Some(12));
+
+        let output_id = |output_response: &OutputWithMetadataResponse| output_response.metadata.output_id().unwrap();
+        let mut responses = HashMap::from([
+            ("/api/core/v2/info".to_string(), info_response(CONFIRMED_MILESTONE_INDEX)),
+            (
+                format!("/api/indexer/v1/outputs/basic?address={bech32_address}"),
+                output_ids_response(&[output_id(&unspent), output_id(&unspent_later)]),
+            ),
+            (
+                format!("/api/indexer/v1/outputs/alias?governor={bech32_address}"),
+                output_ids_response(&[output_id(&unspent_alias)]),
+            ),
+            (
+                "/api/core/v2/milestones/by-index/11/utxo-changes".to_string(),
+                utxo_changes_response(11, &[output_id(&spent)]),
+            ),
+            (
+                "/api/core/v2/milestones/by-index/12/utxo-changes".to_string(),
+                utxo_changes_response(
+                    12,
+                    &[output_id(&spent_alias), output_id(&spent_other), output_id(&spent_later)],
+                ),
+            ),
+        ]);
+        for output_response in [
+            &unspent,
+            &unspent_later,
+            &spent,
+            &spent_alias,
+            &unspent_alias,
+            &spent_other,
+            &spent_later,
+        ] {
+            responses.insert(
+                format!("/api/core/v2/outputs/{}", output_id(output_response)),
+                serde_json::to_string(output_response).unwrap(),
+            );
+        }
+
+        // The other indexer queries find no output.
+        let node_url = mock_node(move |request| {
+            responses
+                .get(&request.path)
+                .cloned()
+                .or_else(|| request.path.starts_with("/api/indexer/").then(|| output_ids_response(&[])))
+        });
+        let client = Client::builder()
+            .with_node(&node_url)
+            .unwrap()
+            .with_ignore_node_health()
+            .finish()
+            .unwrap();
+
+        let ledger_state = client
+            .ledger_state(MILESTONE_INDEX)
+            .with_addresses(vec![bech32_address.clone()])
+            .finish()
+            .await
+            .unwrap();
+
+        let mut expected_output_ids = vec![
+            output_id(&unspent),
+            output_id(&spent),
+            output_id(&spent_alias),
+            output_id(&unspent_alias),
+        ];
+        expected_output_ids.sort_unstable();
+        assert_eq!(
+            ledger_state.outputs.iter().map(output_id).collect::<Vec<_>>(),
+            expected_output_ids
+        );
+        assert_eq!(ledger_state.amount(token_supply).unwrap(), 13_000_000);
+
+        // Replaying the 2 milestones confirmed since is too deep.
+        assert!(matches!(
+            client
+                .ledger_state(MILESTONE_INDEX)
+                .with_addresses(vec![bech32_address])
+                .with_max_replay_depth(1)
+                .finish()
+                .await,
+            Err(Error::LedgerStateReplayTooDeep {
+                milestone_index: MILESTONE_INDEX,
+                replay_depth: 2,
+                max_replay_depth: 1,
+            })
+        ));
+    }
+}
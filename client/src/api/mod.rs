@@ -6,8 +6,15 @@
 mod address;
 mod block_builder;
 mod consolidation;
+mod discovery;
+mod ledger_state;
+mod offline;
+#[cfg(feature = "mqtt")]
+mod payment_request;
 mod types;
 
-pub use self::{address::*, block_builder::*, types::*};
+#[cfg(feature = "mqtt")]
+pub use self::payment_request::*;
+pub use self::{address::*, block_builder::*, discovery::*, ledger_state::*, offline::*, types::*};
 
 const ADDRESS_GAP_RANGE: u32 = 20;
@@ -0,0 +1,530 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline signing of transactions: an online machine prepares a transaction with
+//! [`ClientBlockBuilder::prepare_transaction()`](crate::api::ClientBlockBuilder::prepare_transaction()), an air-gapped
+//! one signs it with [`sign_prepared_transaction()`], and the online one submits it with
+//! [`Client::submit_signed()`].
+//!
+//! Between the machines, the data is exchanged as JSON, through the DTOs, or in a compact binary format with
+//! [`PreparedTransactionData::to_bytes()`] and [`SignedTransactionData::to_bytes()`]. The binary format starts with a
+//! version byte, so that data written by an older version of the library can still be read.
+
+use crypto::keys::slip10::Chain;
+use iota_types::block::{
+    address::Address,
+    output::{Output, OutputId},
+    payload::{
+        transaction::{TransactionEssence, TransactionId},
+        Payload, TransactionPayload,
+    },
+    protocol::ProtocolParameters,
+    semantic::ConflictReason,
+    Block, BlockId,
+};
+use packable::{Packable, PackableExt};
+
+use super::{
+    block_builder::transaction::validate_transaction_payload_length, verify_semantic, PreparedTransactionData,
+    RemainderData, SignedTransactionData,
+};
+use crate::{
+    secret::{
+        types::{InputSigningData, OutputMetadata},
+        SecretManageExt, SecretManager,
+    },
+    Client, Error, Result,
+};
+
+/// The version of the binary format of the offline signing data.
+const OFFLINE_SIGNING_FORMAT_VERSION: u8 = 0;
+
+/// Sign a transaction prepared by an online machine, without needing a node.
+///
+/// The semantic of the transaction is verified when it's submitted, with [`Client::submit_signed()`], as it depends on
+/// the time of the network.
+pub async fn sign_prepared_transaction(
+    secret_manager: &SecretManager,
+    prepared_transaction_data: PreparedTransactionData,
+) -> Result<SignedTransactionData> {
+    let unlocks = secret_manager
+        .sign_transaction_essence(&prepared_transaction_data)
+        .await?;
+    let transaction_payload = TransactionPayload::new(prepared_transaction_data.essence, unlocks)?;
+
+    validate_transaction_payload_length(&transaction_payload)?;
+
+    Ok(SignedTransactionData {
+        transaction_payload,
+        inputs_data: prepared_transaction_data.inputs_data,
+    })
+}
+
+impl Client {
+    /// Verify the semantic of a transaction signed offline, see [`sign_prepared_transaction()`], and submit it in a
+    /// block.
+    pub async fn submit_signed(&self, signed_transaction_data: SignedTransactionData) -> Result<Block> {
+        let current_time = self.get_time_checked().await?;
+
+        let conflict = verify_semantic(
+            &signed_transaction_data.inputs_data,
+            &signed_transaction_data.transaction_payload,
+            current_time,
+        )?;
+
+        if conflict != ConflictReason::None {
+            log::debug!(
+                "[submit_signed] conflict: {conflict:?} for {:#?}",
+                signed_transaction_data.transaction_payload
+            );
+            return Err(Error::TransactionSemantic(conflict));
+        }
+
+        self.block()
+            .finish_block(Some(Payload::from(signed_transaction_data.transaction_payload)))
+            .await
+    }
+}
+
+impl PreparedTransactionData {
+    /// Serialize the prepared transaction in the binary format of the offline signing data.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![OFFLINE_SIGNING_FORMAT_VERSION];
+
+        write_item(&mut bytes, &self.essence.pack_to_vec());
+        write_inputs_data(&mut bytes, &self.inputs_data);
+
+        match &self.remainder {
+            Some(remainder) => {
+                bytes.push(1);
+                write_item(&mut bytes, &remainder.output.pack_to_vec());
+                write_chain(&mut bytes, remainder.chain.as_ref());
+                write_item(&mut bytes, &remainder.address.pack_to_vec());
+            }
+            None => bytes.push(0),
+        }
+
+        bytes
+    }
+
+    /// Deserialize a prepared transaction from the binary format of the offline signing data.
+    pub fn try_from_bytes(bytes: &[u8], protocol_parameters: &ProtocolParameters) -> Result<Self> {
+        let mut reader = Reader::new(bytes)?;
+
+        let essence = unpack::<TransactionEssence>(reader.item()?, protocol_parameters, "essence")?;
+        let inputs_data = reader.inputs_data(protocol_parameters)?;
+        let remainder = match reader.u8()? {
+            0 => None,
+            1 => Some(RemainderData {
+                output: unpack::<Output>(reader.item()?, protocol_parameters, "remainder output")?,
+                chain: reader.chain()?,
+                address: unpack::<Address>(reader.item()?, &(), "remainder address")?,
+            }),
+            _ => return Err(Error::OfflineSigningDataInvalid("remainder")),
+        };
+
+        reader.finish()?;
+
+        Ok(Self {
+            essence,
+            inputs_data,
+            remainder,
+        })
+    }
+}
+
+impl SignedTransactionData {
+    /// Serialize the signed transaction in the binary format of the offline signing data.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![OFFLINE_SIGNING_FORMAT_VERSION];
+
+        write_item(&mut bytes, &self.transaction_payload.pack_to_vec());
+        write_inputs_data(&mut bytes, &self.inputs_data);
+
+        bytes
+    }
+
+    /// Deserialize a signed transaction from the binary format of the offline signing data.
+    pub fn try_from_bytes(bytes: &[u8], protocol_parameters: &ProtocolParameters) -> Result<Self> {
+        let mut reader = Reader::new(bytes)?;
+
+        let transaction_payload =
+            unpack::<TransactionPayload>(reader.item()?, protocol_parameters, "transaction payload")?;
+        let inputs_data = reader.inputs_data(protocol_parameters)?;
+
+        reader.finish()?;
+
+        Ok(Self {
+            transaction_payload,
+            inputs_data,
+        })
+    }
+}
+
+/// Write `item`, prefixed with its length.
+fn write_item(bytes: &mut Vec<u8>, item: &[u8]) {
+    bytes.extend_from_slice(&(item.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(item);
+}
+
+fn write_option_u32(bytes: &mut Vec<u8>, value: Option<u32>) {
+    match value {
+        Some(value) => {
+            bytes.push(1);
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        None => bytes.push(0),
+    }
+}
+
+fn write_chain(bytes: &mut Vec<u8>, chain: Option<&Chain>) {
+    match chain {
+        Some(chain) => {
+            bytes.push(1);
+            bytes.push(chain.segments().len() as u8);
+            for segment in chain.segments() {
+                // The segments are written as is, hardened or not.
+                bytes.extend_from_slice(&segment.bs());
+            }
+        }
+        None => bytes.push(0),
+    }
+}
+
+fn write_inputs_data(bytes: &mut Vec<u8>, inputs_data: &[InputSigningData]) {
+    bytes.extend_from_slice(&(inputs_data.len() as u16).to_le_bytes());
+
+    for input in inputs_data {
+        let metadata = &input.output_metadata;
+
+        write_item(bytes, &input.output.pack_to_vec());
+        bytes.extend_from_slice(metadata.block_id().as_ref());
+        bytes.extend_from_slice(&metadata.output_id().pack_to_vec());
+        bytes.push(metadata.is_spent() as u8);
+        write_option_u32(bytes, metadata.milestone_index_spent());
+        write_option_u32(bytes, metadata.milestone_timestamp_spent());
+        match metadata.transaction_id_spent() {
+            Some(transaction_id) => {
+                bytes.push(1);
+                bytes.extend_from_slice(transaction_id.as_ref());
+            }
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&metadata.milestone_index_booked().to_le_bytes());
+        bytes.extend_from_slice(&metadata.milestone_timestamp_booked().to_le_bytes());
+        bytes.extend_from_slice(&metadata.ledger_index().to_le_bytes());
+        write_chain(bytes, input.chain.as_ref());
+        write_item(bytes, input.bech32_address.as_bytes());
+    }
+}
+
+fn unpack<P: Packable>(bytes: &[u8], visitor: &P::UnpackVisitor, field: &'static str) -> Result<P> {
+    P::unpack_verified(bytes, visitor).map_err(|_| Error::OfflineSigningDataInvalid(field))
+}
+
+/// A reader of the binary format of the offline signing data.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Self> {
+        let mut reader = Self { bytes };
+
+        match reader.u8()? {
+            OFFLINE_SIGNING_FORMAT_VERSION => Ok(reader),
+            version => Err(Error::OfflineSigningDataVersionUnsupported(version)),
+        }
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self
+            .slice(N)?
+            .try_into()
+            .expect("the slice has the length of the array"))
+    }
+
+    fn slice(&mut self, length: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < length {
+            return Err(Error::OfflineSigningDataInvalid("unexpected end of data"));
+        }
+
+        let (slice, bytes) = self.bytes.split_at(length);
+        self.bytes = bytes;
+
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    fn bool(&mut self, field: &'static str) -> Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::OfflineSigningDataInvalid(field)),
+        }
+    }
+
+    fn item(&mut self) -> Result<&'a [u8]> {
+        let length = self.u32()? as usize;
+        self.slice(length)
+    }
+
+    fn option_u32(&mut self, field: &'static str) -> Result<Option<u32>> {
+        Ok(if self.bool(field)? { Some(self.u32()?) } else { None })
+    }
+
+    fn chain(&mut self) -> Result<Option<Chain>> {
+        if !self.bool("chain")? {
+            return Ok(None);
+        }
+
+        let length = self.u8()?;
+        let segments = (0..length)
+            .map(|_| Ok(u32::from_be_bytes(self.bytes()?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Some(Chain::from_u32(segments)))
+    }
+
+    fn inputs_data(&mut self, protocol_parameters: &ProtocolParameters) -> Result<Vec<InputSigningData>> {
+        let count = u16::from_le_bytes(self.bytes()?);
+
+        (0..count)
+            .map(|_| {
+                let output = unpack::<Output>(self.item()?, protocol_parameters, "input output")?;
+                let output_metadata = OutputMetadata::new(
+                    BlockId::new(self.bytes()?),
+                    OutputId::try_from(self.bytes::<{ OutputId::LENGTH }>()?)
+                        .map_err(|_| Error::OfflineSigningDataInvalid("output id"))?,
+                    self.bool("is spent")?,
+                    self.option_u32("milestone index spent")?,
+                    self.option_u32("milestone timestamp spent")?,
+                    if self.bool("transaction id spent")? {
+                        Some(TransactionId::new(self.bytes()?))
+                    } else {
+                        None
+                    },
+                    self.u32()?,
+                    self.u32()?,
+                    self.u32()?,
+                );
+                let chain = self.chain()?;
+                let bech32_address = String::from_utf8(self.item()?.to_vec())
+                    .map_err(|_| Error::OfflineSigningDataInvalid("bech32 address"))?;
+
+                Ok(InputSigningData {
+                    output,
+                    output_metadata,
+                    chain,
+                    bech32_address,
+                })
+            })
+            .collect()
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::OfflineSigningDataInvalid("trailing bytes"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use iota_types::{
+        api::response::{SubmitBlockResponse, TipsResponse},
+        block::{
+            input::{Input, UtxoInput},
+            output::{unlock_condition::AddressUnlockCondition, BasicOutputBuilder, InputsCommitment, UnlockCondition},
+            payload::transaction::RegularTransactionEssence,
+            rand::{address::rand_address, block::rand_block_id, transaction::rand_transaction_id},
+            BlockDto,
+        },
+    };
+
+    use super::*;
+    use crate::{
+        api::SignedTransactionDataDto,
+        constants::{HD_WALLET_TYPE, SHIMMER_COIN_TYPE},
+        node_api::mock_node::{info_response, mock_node},
+        secret::{mnemonic::MnemonicSecretManager, SecretManage},
+    };
+
+    #[test]
+    fn prepared_transaction_data_bytes() {
+        let protocol_parameters = ProtocolParameters::default();
+        let address = rand_address();
+        let output = BasicOutputBuilder::new_with_amount(1_000_000)
+            .unwrap()
+            .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(address)))
+            .finish_output(protocol_parameters.token_supply())
+            .unwrap();
+        let output_id = OutputId::new(rand_transaction_id(), 0).unwrap();
+        let essence = RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new([&output].into_iter()),
+        )
+        .with_inputs(vec![Input::Utxo(
+            UtxoInput::new(*output_id.transaction_id(), output_id.index()).unwrap(),
+        )])
+        .with_outputs(vec![output.clone()])
+        .finish(&protocol_parameters)
+        .unwrap();
+
+        let prepared_transaction_data = PreparedTransactionData {
+            essence: TransactionEssence::Regular(essence),
+            inputs_data: vec![InputSigningData {
+                output: output.clone(),
+                output_metadata: OutputMetadata::new(rand_block_id(), output_id, false, None, None, None, 1, 2, 3),
+                chain: Some(Chain::from_u32_hardened(vec![44, 4218, 0, 0, 1])),
+                bech32_address: address.to_bech32("smr"),
+            }],
+            remainder: Some(RemainderData {
+                output,
+                chain: None,
+                address,
+            }),
+        };
+
+        let bytes = prepared_transaction_data.to_bytes();
+        assert_eq!(bytes[0], OFFLINE_SIGNING_FORMAT_VERSION);
+        assert_eq!(
+            PreparedTransactionData::try_from_bytes(&bytes, &protocol_parameters).unwrap(),
+            prepared_transaction_data
+        );
+
+        assert!(matches!(
+            PreparedTransactionData::try_from_bytes(&bytes[..bytes.len() - 1], &protocol_parameters),
+            Err(Error::OfflineSigningDataInvalid(_))
+        ));
+        assert!(matches!(
+            PreparedTransactionData::try_from_bytes(&[1], &protocol_parameters),
+            Err(Error::OfflineSigningDataVersionUnsupported(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn signed_transaction_data_round_trip() {
+        let protocol_parameters = ProtocolParameters::default();
+        let secret_manager = MnemonicSecretManager::try_from_mnemonic(
+            "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow \
+             glad autumn author calm heavy hawk abuse rally",
+        )
+        .unwrap();
+        let address = secret_manager
+            .generate_addresses(SHIMMER_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap()[0];
+        let secret_manager = SecretManager::Mnemonic(secret_manager);
+
+        let output = BasicOutputBuilder::new_with_amount(1_000_000)
+            .unwrap()
+            .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(address)))
+            .finish_output(protocol_parameters.token_supply())
+            .unwrap();
+        let output_id = OutputId::new(rand_transaction_id(), 0).unwrap();
+        let essence = RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new([&output].into_iter()),
+        )
+        .with_inputs(vec![Input::Utxo(
+            UtxoInput::new(*output_id.transaction_id(), output_id.index()).unwrap(),
+        )])
+        .with_outputs(vec![output.clone()])
+        .finish(&protocol_parameters)
+        .unwrap();
+        let prepared_transaction_data = PreparedTransactionData {
+            essence: TransactionEssence::Regular(essence),
+            inputs_data: vec![InputSigningData {
+                output,
+                output_metadata: OutputMetadata::new(rand_block_id(), output_id, false, None, None, None, 1, 0, 1),
+                chain: Some(Chain::from_u32_hardened(vec![
+                    HD_WALLET_TYPE,
+                    SHIMMER_COIN_TYPE,
+                    0,
+                    0,
+                    0,
+                ])),
+                bech32_address: address.to_bech32("smr"),
+            }],
+            remainder: None,
+        };
+
+        // Signed on the air-gapped machine, and exported in both formats.
+        let signed_transaction_data = sign_prepared_transaction(&secret_manager, prepared_transaction_data)
+            .await
+            .unwrap();
+        let bytes = signed_transaction_data.to_bytes();
+        let json = serde_json::to_string(&SignedTransactionDataDto::from(&signed_transaction_data)).unwrap();
+
+        // Imported on the online machine.
+        let imported = SignedTransactionData::try_from_bytes(&bytes, &protocol_parameters).unwrap();
+        assert_eq!(imported, signed_transaction_data);
+        assert_eq!(
+            SignedTransactionData::try_from_dto(&serde_json::from_str(&json).unwrap(), &protocol_parameters).unwrap(),
+            signed_transaction_data
+        );
+        assert!(matches!(
+            SignedTransactionData::try_from_bytes(&bytes[..bytes.len() - 1], &protocol_parameters),
+            Err(Error::OfflineSigningDataInvalid(_))
+        ));
+
+        // The blocks posted to the node.
+        let posted_blocks = Arc::new(Mutex::new(Vec::new()));
+        let posted_blocks_ = posted_blocks.clone();
+        let node_url = mock_node(move |request| {
+            let mut posted_blocks = posted_blocks_.lock().unwrap();
+
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/api/core/v2/info") => Some(info_response(1)),
+                ("GET", "/api/core/v2/tips") => Some(
+                    serde_json::to_string(&TipsResponse {
+                        tips: vec![rand_block_id().to_string()],
+                    })
+                    .unwrap(),
+                ),
+                ("POST", "/api/core/v2/blocks") => {
+                    let block = Block::unpack_verified(&request.body, &ProtocolParameters::default()).unwrap();
+                    let block_id = block.id();
+                    posted_blocks.push(block);
+
+                    Some(
+                        serde_json::to_string(&SubmitBlockResponse {
+                            block_id: block_id.to_string(),
+                        })
+                        .unwrap(),
+                    )
+                }
+                ("GET", path) => posted_blocks
+                    .iter()
+                    .find(|block| path == format!("/api/core/v2/blocks/{}", block.id()))
+                    .map(|block| serde_json::to_string(&BlockDto::from(block)).unwrap()),
+                _ => None,
+            }
+        });
+        let client = Client::builder()
+            .with_node(&node_url)
+            .unwrap()
+            .with_ignore_node_health()
+            .with_local_pow(false)
+            .finish()
+            .unwrap();
+
+        let block = client.submit_signed(imported).await.unwrap();
+
+        let payload = Payload::from(signed_transaction_data.transaction_payload);
+        assert_eq!(block.payload(), Some(&payload));
+        assert_eq!(posted_blocks.lock().unwrap()[0].payload(), Some(&payload));
+    }
+}
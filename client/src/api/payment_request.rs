@@ -0,0 +1,394 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Payment requests, resolved from the outputs received by an address over MQTT.
+//!
+//! A merchant registers the payments it expects on a [`PaymentRequestListener`], and receives a [`PaymentEvent`] once
+//! each of them is resolved or expired. A payment can be made of several outputs; only unspent outputs without storage
+//! deposit return, timelock or expiration unlock conditions are counted, as their amount can't be used freely
+//! otherwise. Each output is counted at most once, also when it's published again, e.g. after a reconnection.
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use iota_types::block::{address::Address, output::OutputId};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::{
+    node_api::mqtt::{MqttPayload, Topic, TopicEvent},
+    Client, Error, Result,
+};
+
+/// The topic of the confirmed milestones, whose timestamps expire the payment requests.
+const CONFIRMED_MILESTONE_TOPIC: &str = "milestone-info/confirmed";
+
+/// The prefix of the topics of the outputs unlockable by an address.
+const ADDRESS_OUTPUTS_TOPIC_PREFIX: &str = "outputs/unlock/address/";
+
+/// The accepted deviations of the received amount from the requested one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PaymentTolerance {
+    /// The amount that can be missing for a payment to be resolved.
+    pub shortfall: u64,
+    /// The amount that can be received in excess, if limited; larger payments are reported as overpaid.
+    pub excess: Option<u64>,
+}
+
+/// An expected payment to an address.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentRequest {
+    /// The bech32 encoded address receiving the payment.
+    pub address: String,
+    /// The requested amount.
+    pub amount: u64,
+    /// The tag the outputs of the payment must have, if any, e.g. an invoice number.
+    pub tag: Option<Vec<u8>>,
+    /// The milestone timestamp from which the payment expires, if any.
+    pub expiry: Option<u32>,
+    /// The accepted deviations of the received amount.
+    pub tolerance: PaymentTolerance,
+}
+
+/// The state of a payment request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentStatus {
+    /// The payment request.
+    pub request: PaymentRequest,
+    /// The amount received so far.
+    pub received: u64,
+    /// The ids of the outputs received so far.
+    pub output_ids: Vec<OutputId>,
+}
+
+/// The outcome of a payment request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PaymentEvent {
+    /// The requested amount has been received, within the tolerance.
+    Resolved(PaymentStatus),
+    /// More than the requested amount and the tolerated excess has been received.
+    Overpaid(PaymentStatus),
+    /// The payment request expired before the requested amount was received; partial payments are reported.
+    Expired(PaymentStatus),
+}
+
+/// The payment requests of a listener, with the outputs already counted.
+#[derive(Debug, Default)]
+struct PaymentRequests {
+    pending: Vec<PaymentStatus>,
+    // The outputs counted towards any request, including resolved and expired ones.
+    counted_output_ids: HashSet<OutputId>,
+}
+
+/// Listener resolving [`PaymentRequest`]s from the outputs received by their addresses.
+///
+/// The topics subscribed for the requests stay subscribed on the [`Client`] once they are resolved.
+#[derive(Debug)]
+pub struct PaymentRequestListener {
+    requests: Arc<Mutex<PaymentRequests>>,
+    subscribed_topics: Mutex<HashSet<Topic>>,
+    event_sender: UnboundedSender<PaymentEvent>,
+}
+
+impl PaymentRequestListener {
+    /// Create a listener, with the receiver of the events of its payment requests.
+    pub fn new() -> (Self, UnboundedReceiver<PaymentEvent>) {
+        let (event_sender, event_receiver) = unbounded_channel();
+
+        (
+            Self {
+                requests: Arc::new(Mutex::new(PaymentRequests::default())),
+                subscribed_topics: Mutex::new(HashSet::new()),
+                event_sender,
+            },
+            event_receiver,
+        )
+    }
+
+    /// Register a payment request, subscribing to the outputs of its address on the client if needed.
+    ///
+    /// Outputs are counted towards the first pending request, in registration order, with a matching address and tag.
+    pub async fn register(&self, client: &mut Client, request: PaymentRequest) -> Result<()> {
+        Address::try_from_bech32(&request.address)?;
+
+        let topics = {
+            let subscribed_topics = self.subscribed_topics.lock().map_err(|_| Error::PoisonError)?;
+
+            [
+                Topic::confirmed_milestone_info(),
+                Topic::address_outputs(&request.address)?,
+            ]
+            .into_iter()
+            .filter(|topic| !subscribed_topics.contains(topic))
+            .collect::<Vec<_>>()
+        };
+
+        self.requests
+            .lock()
+            .map_err(|_| Error::PoisonError)?
+            .pending
+            .push(PaymentStatus {
+                request,
+                received: 0,
+                output_ids: Vec::new(),
+            });
+
+        if !topics.is_empty() {
+            let requests = self.requests.clone();
+            let event_sender = self.event_sender.clone();
+
+            client
+                .subscribe(topics.clone(), move |event| {
+                    if let Err(err) = handle_event(&requests, &event_sender, event) {
+                        log::warn!("[PaymentRequestListener] failed to handle an event of {}: {err}", event.topic);
+                    }
+                })
+                .await?;
+
+            self.subscribed_topics
+                .lock()
+                .map_err(|_| Error::PoisonError)?
+                .extend(topics);
+        }
+
+        Ok(())
+    }
+
+    /// The payment requests that are neither resolved nor expired yet.
+    pub fn pending(&self) -> Result<Vec<PaymentStatus>> {
+        Ok(self.requests.lock().map_err(|_| Error::PoisonError)?.pending.clone())
+    }
+}
+
+/// Update the pending payment requests with an output received by their address, or expire them with a confirmed
+/// milestone.
+fn handle_event(
+    requests: &Mutex<PaymentRequests>,
+    event_sender: &UnboundedSender<PaymentEvent>,
+    event: &TopicEvent,
+) -> Result<()> {
+    let mut requests = requests.lock().map_err(|_| Error::PoisonError)?;
+    let PaymentRequests {
+        pending,
+        counted_output_ids,
+    } = &mut *requests;
+
+    if let MqttPayload::MilestoneInfo(milestone_info) = &event.payload {
+        if event.topic != CONFIRMED_MILESTONE_TOPIC {
+            return Ok(());
+        }
+
+        let timestamp = u64::from(milestone_info.timestamp);
+
+        pending.retain(|status| {
+            let expired = status
+                .request
+                .expiry
+                .map_or(false, |expiry| u64::from(expiry) <= timestamp);
+
+            if expired {
+                // The receiver may have been dropped, in which case the event doesn't matter.
+                let _ = event_sender.send(PaymentEvent::Expired(status.clone()));
+            }
+
+            !expired
+        });
+    } else if let (MqttPayload::OutputWithMetadata(output_with_metadata), Some(address)) =
+        (&event.payload, event.topic.strip_prefix(ADDRESS_OUTPUTS_TOPIC_PREFIX))
+    {
+        let output = &output_with_metadata.output;
+        let output_id = *output_with_metadata.metadata.output_id();
+
+        // The outputs are published again once spent.
+        if output_with_metadata.metadata.is_spent() {
+            return Ok(());
+        }
+
+        if let Some(unlock_conditions) = output.unlock_conditions() {
+            if unlock_conditions.storage_deposit_return().is_some()
+                || unlock_conditions.timelock().is_some()
+                || unlock_conditions.expiration().is_some()
+            {
+                return Ok(());
+            }
+        }
+
+        // Outputs may be published more than once, e.g. after a reconnection, also once their request is resolved.
+        if counted_output_ids.contains(&output_id) {
+            return Ok(());
+        }
+
+        let tag = output
+            .features()
+            .and_then(|features| features.tag())
+            .map(|tag| tag.tag());
+        let index = match pending.iter().position(|status| {
+            status.request.address == address
+                && status
+                    .request
+                    .tag
+                    .as_deref()
+                    .map_or(true, |requested_tag| Some(requested_tag) == tag)
+        }) {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        let status = &mut pending[index];
+        status.received += output.amount();
+        status.output_ids.push(output_id);
+        counted_output_ids.insert(output_id);
+
+        let request = &status.request;
+
+        if status.received.saturating_add(request.tolerance.shortfall) >= request.amount {
+            let overpaid = request
+                .tolerance
+                .excess
+                .map_or(false, |excess| status.received > request.amount.saturating_add(excess));
+            let status = pending.remove(index);
+
+            let _ = event_sender.send(if overpaid {
+                PaymentEvent::Overpaid(status)
+            } else {
+                PaymentEvent::Resolved(status)
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use iota_types::block::{
+        output::{
+            feature::{Feature, TagFeature},
+            unlock_condition::AddressUnlockCondition,
+            BasicOutput, Output,
+        },
+        payload::{milestone::MilestoneId, transaction::TransactionId},
+        protocol::protocol_parameters,
+        BlockId,
+    };
+
+    use super::*;
+    use crate::{
+        node_api::mqtt::{MilestoneInfo, OutputWithMetadata},
+        secret::types::OutputMetadata,
+    };
+
+    const ADDRESS: &str = "rms1qzev36lk0gzld0k28fd2fauz26qqzh4hd4cwymlqlv96x7phjxcw6v3ea5a";
+    const TRANSACTION_ID: &str = "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+
+    fn output_event(output_index: u16, amount: u64, tag: Option<&str>, is_spent: bool) -> TopicEvent {
+        let token_supply = protocol_parameters().token_supply();
+        let mut builder = BasicOutput::build_with_amount(amount)
+            .unwrap()
+            .add_unlock_condition(AddressUnlockCondition::new(Address::try_from_bech32(ADDRESS).unwrap().1).into());
+
+        if let Some(tag) = tag {
+            builder = builder.add_feature(Feature::Tag(TagFeature::new(tag.as_bytes().to_vec()).unwrap()));
+        }
+
+        let output_id = OutputId::new(TRANSACTION_ID.parse::<TransactionId>().unwrap(), output_index).unwrap();
+
+        TopicEvent {
+            topic: format!("{ADDRESS_OUTPUTS_TOPIC_PREFIX}{ADDRESS}"),
+            payload: MqttPayload::OutputWithMetadata(OutputWithMetadata {
+                output: Output::Basic(builder.finish(token_supply).unwrap()),
+                metadata: OutputMetadata::new(BlockId::null(), output_id, is_spent, None, None, None, 1, 1, 1),
+            }),
+        }
+    }
+
+    fn milestone_event(timestamp: u32) -> TopicEvent {
+        TopicEvent {
+            topic: CONFIRMED_MILESTONE_TOPIC.to_string(),
+            payload: MqttPayload::MilestoneInfo(MilestoneInfo {
+                index: 1,
+                timestamp,
+                milestone_id: MilestoneId::null(),
+            }),
+        }
+    }
+
+    fn request(amount: u64, tag: Option<&str>, tolerance: PaymentTolerance) -> PaymentStatus {
+        PaymentStatus {
+            request: PaymentRequest {
+                address: ADDRESS.to_string(),
+                amount,
+                tag: tag.map(|tag| tag.as_bytes().to_vec()),
+                expiry: Some(100),
+                tolerance,
+            },
+            received: 0,
+            output_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn payment_requests() {
+        let (event_sender, mut event_receiver) = unbounded_channel();
+        let requests = Mutex::new(PaymentRequests {
+            pending: vec![
+                request(
+                    2_000_000,
+                    Some("invoice"),
+                    PaymentTolerance {
+                        shortfall: 100_000,
+                        excess: None,
+                    },
+                ),
+                request(
+                    1_000_000,
+                    None,
+                    PaymentTolerance {
+                        shortfall: 0,
+                        excess: Some(0),
+                    },
+                ),
+                request(1_000_000, None, PaymentTolerance::default()),
+            ],
+            counted_output_ids: HashSet::new(),
+        });
+        let handle = |event| handle_event(&requests, &event_sender, &event).unwrap();
+
+        // A partial payment doesn't resolve the request, and neither a duplicate nor a spent output are counted.
+        handle(output_event(0, 1_000_000, Some("invoice"), false));
+        handle(output_event(0, 1_000_000, Some("invoice"), false));
+        handle(output_event(4, 1_000_000, Some("invoice"), true));
+        assert!(event_receiver.try_recv().is_err());
+        assert_eq!(requests.lock().unwrap().pending[0].received, 1_000_000);
+
+        // Within the tolerated shortfall.
+        handle(output_event(1, 900_000, Some("invoice"), false));
+        assert!(matches!(
+            event_receiver.try_recv(),
+            Ok(PaymentEvent::Resolved(status)) if status.received == 1_900_000 && status.output_ids.len() == 2
+        ));
+
+        // Above the tolerated excess.
+        handle(output_event(2, 1_500_000, None, false));
+        assert!(matches!(
+            event_receiver.try_recv(),
+            Ok(PaymentEvent::Overpaid(status)) if status.received == 1_500_000
+        ));
+
+        // An output of a resolved request published again, e.g. after a reconnection, doesn't pay another one.
+        handle(output_event(2, 1_500_000, None, false));
+        assert!(event_receiver.try_recv().is_err());
+
+        // Expired with a partial payment.
+        handle(output_event(3, 500_000, None, false));
+        handle(milestone_event(99));
+        assert!(event_receiver.try_recv().is_err());
+        handle(milestone_event(100));
+        assert!(matches!(
+            event_receiver.try_recv(),
+            Ok(PaymentEvent::Expired(status)) if status.received == 500_000
+        ));
+        assert!(requests.lock().unwrap().pending.is_empty());
+    }
+}
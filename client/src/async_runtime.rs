@@ -0,0 +1,137 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small abstraction over the async runtime the client spawns its tasks on and sleeps with.
+//!
+//! Tokio is used by default. With the `async_std_runtime` or the `smol_runtime` feature, tasks (e.g. the Stronghold key
+//! clearing task, the MQTT topic handlers or the database expiration task) are spawned on async-std or smol instead, so
+//! that applications built on them don't need to run a Tokio runtime next to their own. The `tokio::sync` primitives
+//! used throughout the client don't depend on the Tokio runtime and work with any of them.
+//!
+//! The MQTT event loop, the node syncing task and the parallel requests keep running on the Tokio runtime of the
+//! client, as `rumqttc` and `reqwest` require it.
+
+use std::{future::Future, time::Duration};
+
+#[cfg(feature = "stronghold")]
+use futures::future::Either;
+use futures::{
+    future::{AbortHandle, Abortable},
+    FutureExt,
+};
+#[cfg(not(any(feature = "async_std_runtime", feature = "smol_runtime")))]
+use {once_cell::sync::Lazy, tokio::runtime::Runtime};
+
+/// The runtime used when spawning outside of a Tokio runtime context.
+#[cfg(not(any(feature = "async_std_runtime", feature = "smol_runtime")))]
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| Runtime::new().expect("failed to create Tokio runtime"));
+
+/// A handle to a spawned task, to abort it.
+#[derive(Debug)]
+pub(crate) struct TaskHandle(AbortHandle);
+
+impl TaskHandle {
+    /// Abort the task; it won't be polled anymore.
+    pub(crate) fn abort(&self) {
+        self.0.abort();
+    }
+}
+
+/// Spawn `future` as a background task.
+pub(crate) fn spawn<F>(future: F) -> TaskHandle
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
+
+    spawn_detached(Abortable::new(future, abort_registration).map(|_| ()));
+
+    TaskHandle(abort_handle)
+}
+
+/// Sleep for `duration` without blocking the runtime.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "async_std_runtime")]
+    async_std::task::sleep(duration).await;
+
+    #[cfg(all(feature = "smol_runtime", not(feature = "async_std_runtime")))]
+    smol::Timer::after(duration).await;
+
+    #[cfg(not(any(feature = "async_std_runtime", feature = "smol_runtime")))]
+    tokio::time::sleep(duration).await;
+}
+
+/// Run the blocking `f` on the blocking thread pool of the runtime, returning a future resolving to its output.
+#[cfg(feature = "stronghold")]
+pub(crate) fn spawn_blocking<F, T>(f: F) -> impl Future<Output = T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    #[cfg(feature = "async_std_runtime")]
+    return async_std::task::spawn_blocking(f);
+
+    #[cfg(all(feature = "smol_runtime", not(feature = "async_std_runtime")))]
+    return smol::unblock(f);
+
+    #[cfg(not(any(feature = "async_std_runtime", feature = "smol_runtime")))]
+    {
+        let handle = match tokio::runtime::Handle::try_current() {
+            Ok(handle) => handle.spawn_blocking(f),
+            Err(_) => RUNTIME.spawn_blocking(f),
+        };
+
+        // Blocking tasks can't be cancelled, so they can only fail by panicking.
+        handle.map(|output| output.unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic())))
+    }
+}
+
+/// Await `future` for at most `duration`, returning `None` if it hasn't completed by then.
+#[cfg(feature = "stronghold")]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    match futures::future::select(Box::pin(future), Box::pin(sleep(duration))).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
+/// Run `future` to completion, blocking the current thread.
+#[cfg(feature = "mqtt")]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    #[cfg(feature = "async_std_runtime")]
+    return async_std::task::block_on(future);
+
+    #[cfg(all(feature = "smol_runtime", not(feature = "async_std_runtime")))]
+    return smol::block_on(future);
+
+    #[cfg(not(any(feature = "async_std_runtime", feature = "smol_runtime")))]
+    RUNTIME.block_on(future)
+}
+
+#[cfg(feature = "async_std_runtime")]
+fn spawn_detached<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    async_std::task::spawn(future);
+}
+
+#[cfg(all(feature = "smol_runtime", not(feature = "async_std_runtime")))]
+fn spawn_detached<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    smol::spawn(future).detach();
+}
+
+#[cfg(not(any(feature = "async_std_runtime", feature = "smol_runtime")))]
+fn spawn_detached<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    // Prefer the runtime of the caller, so that tasks are bound to its lifetime as with `tokio::spawn()`.
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle.spawn(future),
+        Err(_) => RUNTIME.spawn(future),
+    };
+}
@@ -0,0 +1,150 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A blocking (synchronous) facade for the [`Client`](crate::Client), for CLI tools and codebases without an async
+//! runtime.
+//!
+//! The [`Client`] drives its own runtime to run the calls to completion. It must not be used from within an async
+//! runtime, as blocking in it panics; use the async client there.
+//!
+//! ```no_run
+//! # use iota_client::{blocking, Result};
+//! # fn main() -> Result<()> {
+//! let client = iota_client::Client::builder()
+//!     .with_node("http://localhost:14265")?
+//!     .finish()?;
+//! let client = blocking::Client::new(client)?;
+//!
+//! println!("{:#?}", client.get_info()?);
+//! # Ok(())}
+//! ```
+
+use std::{future::Future, ops::Range};
+
+use iota_types::{
+    api::response::{BlockMetadataResponse, OutputWithMetadataResponse},
+    block::{output::OutputId, payload::transaction::TransactionId, Block, BlockId},
+};
+use tokio::runtime::Runtime;
+
+use crate::{
+    node_api::indexer::query_parameters::QueryParameter, secret::SecretManager, NetworkInfo, NodeInfoWrapper, Result,
+};
+
+/// A blocking client, running the calls of an async [`Client`](crate::Client) to completion.
+pub struct Client {
+    client: crate::Client,
+    runtime: Runtime,
+}
+
+impl Client {
+    /// Create a blocking client wrapping `client`.
+    pub fn new(client: crate::Client) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+        Ok(Self { client, runtime })
+    }
+
+    /// The wrapped async client.
+    pub fn inner(&self) -> &crate::Client {
+        &self.client
+    }
+
+    /// Run any future to completion, e.g. one of the calls of the async client that isn't wrapped here.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// See [`Client::get_info()`](crate::Client::get_info()).
+    pub fn get_info(&self) -> Result<NodeInfoWrapper> {
+        self.block_on(self.client.get_info())
+    }
+
+    /// See [`Client::get_network_info()`](crate::Client::get_network_info()).
+    pub fn get_network_info(&self) -> Result<NetworkInfo> {
+        self.block_on(self.client.get_network_info())
+    }
+
+    /// See [`Client::get_bech32_hrp()`](crate::Client::get_bech32_hrp()).
+    pub fn get_bech32_hrp(&self) -> Result<String> {
+        self.block_on(self.client.get_bech32_hrp())
+    }
+
+    /// See [`Client::get_token_supply()`](crate::Client::get_token_supply()).
+    pub fn get_token_supply(&self) -> Result<u64> {
+        self.block_on(self.client.get_token_supply())
+    }
+
+    /// See [`Client::get_tips()`](crate::Client::get_tips()).
+    pub fn get_tips(&self) -> Result<Vec<BlockId>> {
+        self.block_on(self.client.get_tips())
+    }
+
+    /// See [`Client::post_block()`](crate::Client::post_block()).
+    pub fn post_block(&self, block: &Block) -> Result<BlockId> {
+        self.block_on(self.client.post_block(block))
+    }
+
+    /// See [`Client::get_block()`](crate::Client::get_block()).
+    pub fn get_block(&self, block_id: &BlockId) -> Result<Block> {
+        self.block_on(self.client.get_block(block_id))
+    }
+
+    /// See [`Client::get_block_metadata()`](crate::Client::get_block_metadata()).
+    pub fn get_block_metadata(&self, block_id: &BlockId) -> Result<BlockMetadataResponse> {
+        self.block_on(self.client.get_block_metadata(block_id))
+    }
+
+    /// See [`Client::get_included_block()`](crate::Client::get_included_block()).
+    pub fn get_included_block(&self, transaction_id: &TransactionId) -> Result<Block> {
+        self.block_on(self.client.get_included_block(transaction_id))
+    }
+
+    /// See [`Client::retry_until_included()`](crate::Client::retry_until_included()).
+    pub fn retry_until_included(
+        &self,
+        block_id: &BlockId,
+        interval: Option<u64>,
+        max_attempts: Option<u64>,
+    ) -> Result<Vec<(BlockId, Block)>> {
+        self.block_on(self.client.retry_until_included(block_id, interval, max_attempts))
+    }
+
+    /// See [`Client::get_output()`](crate::Client::get_output()).
+    pub fn get_output(&self, output_id: &OutputId) -> Result<OutputWithMetadataResponse> {
+        self.block_on(self.client.get_output(output_id))
+    }
+
+    /// See [`Client::get_outputs()`](crate::Client::get_outputs()).
+    pub fn get_outputs(&self, output_ids: Vec<OutputId>) -> Result<Vec<OutputWithMetadataResponse>> {
+        self.block_on(self.client.get_outputs(output_ids))
+    }
+
+    /// See [`Client::basic_output_ids()`](crate::Client::basic_output_ids()).
+    pub fn basic_output_ids(&self, query_parameters: impl Into<Vec<QueryParameter>>) -> Result<Vec<OutputId>> {
+        self.block_on(self.client.basic_output_ids(query_parameters))
+    }
+
+    /// Get the bech32 encoded public addresses of `account_index` in `range`.
+    ///
+    /// See [`Client::get_addresses()`](crate::Client::get_addresses()).
+    pub fn get_addresses(
+        &self,
+        secret_manager: &SecretManager,
+        account_index: u32,
+        range: Range<u32>,
+    ) -> Result<Vec<String>> {
+        self.block_on(
+            self.client
+                .get_addresses(secret_manager)
+                .with_account_index(account_index)
+                .with_range(range)
+                .finish(),
+        )
+    }
+
+    /// See [`Client::hex_public_key_to_bech32_address()`](crate::Client::hex_public_key_to_bech32_address()).
+    pub fn hex_public_key_to_bech32_address(&self, hex: &str, bech32_hrp: Option<&str>) -> Result<String> {
+        self.block_on(self.client.hex_public_key_to_bech32_address(hex, bech32_hrp))
+    }
+}
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Builder of the Client Instance
+#[cfg(not(target_family = "wasm"))]
+use std::collections::HashSet;
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
@@ -16,7 +18,7 @@ use iota_types::block::{
 use tokio::runtime::Runtime;
 
 #[cfg(feature = "mqtt")]
-use crate::node_api::mqtt::{BrokerOptions, MqttEvent};
+use crate::node_api::mqtt::{BrokerOptions, MqttEvent, MqttTransport};
 use crate::{
     client::Client,
     constants::{DEFAULT_API_TIMEOUT, DEFAULT_REMOTE_POW_API_TIMEOUT, DEFAULT_TIPS_INTERVAL},
@@ -24,6 +26,9 @@ use crate::{
     node_manager::{
         builder::validate_url,
         node::{Node, NodeAuth},
+        node_health::NodeHealthScores,
+        node_pool::NodePool,
+        response_parsing::ResponseParsing,
     },
 };
 
@@ -223,7 +228,10 @@ impl ClientBuilder {
         Ok(self)
     }
 
-    /// Adds a permanode by its URL, with optional jwt and or basic authentication
+    /// Adds a permanode by its URL, with optional jwt and or basic authentication.
+    ///
+    /// Historical data, e.g. old blocks, spent outputs and milestones, is requested from the permanodes when the other
+    /// nodes can't find it, e.g. because they pruned it.
     pub fn with_permanode(mut self, url: &str, auth: Option<NodeAuth>) -> Result<Self> {
         self.node_manager_builder = self.node_manager_builder.with_permanode(url, auth)?;
         Ok(self)
@@ -247,6 +255,12 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the duration for which a node is skipped after failed requests, as long as there are other nodes
+    pub fn with_node_cool_down(mut self, node_cool_down: Duration) -> Self {
+        self.node_manager_builder = self.node_manager_builder.with_node_cool_down(node_cool_down);
+        self
+    }
+
     /// Ignores the node health status.
     /// Every node will be considered healthy and ready to use.
     pub fn with_ignore_node_health(mut self) -> Self {
@@ -280,6 +294,14 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the transport of the MQTT connection to the node at `url`, e.g. WebSocket behind a reverse proxy.
+    #[cfg(feature = "mqtt")]
+    pub fn with_mqtt_node_transport(mut self, url: &str, transport: MqttTransport) -> Result<Self> {
+        let url = validate_url(url::Url::parse(url)?)?;
+        self.broker_options = self.broker_options.node_transport(url, transport);
+        Ok(self)
+    }
+
     /// Sets whether the PoW should be done locally or remotely.
     pub fn with_local_pow(mut self, local: bool) -> Self {
         self.network_info.local_pow = local;
@@ -323,14 +345,40 @@ impl ClientBuilder {
         self
     }
 
+    /// Set how the JSON responses of the nodes are parsed.
+    /// Default is [`ResponseParsing::Strict`]
+    pub fn with_response_parsing(mut self, response_parsing: ResponseParsing) -> Self {
+        self.node_manager_builder = self.node_manager_builder.with_response_parsing(response_parsing);
+        self
+    }
+
     /// Build the Client instance.
     pub fn finish(self) -> Result<Client> {
+        self.finish_with(None)
+    }
+
+    /// Build the Client instance with a warm healthy node pool, e.g. saved before a restart and loaded with
+    /// [`NodePool::load()`].
+    ///
+    /// The nodes of the pool that are still configured are used right away, instead of syncing all nodes before
+    /// returning; the nodes are then synced in the background. If none of them is configured anymore, this is
+    /// equivalent to [`finish()`](Self::finish()).
+    #[cfg(not(target_family = "wasm"))]
+    pub fn finish_with_node_pool(self, node_pool: NodePool) -> Result<Client> {
+        self.finish_with(Some(node_pool))
+    }
+
+    fn finish_with(self, node_pool: Option<NodePool>) -> Result<Client> {
         let network_info = Arc::new(RwLock::new(self.network_info));
         let healthy_nodes = Arc::new(RwLock::new(HashMap::new()));
+        let node_health = NodeHealthScores::new(self.node_manager_builder.node_cool_down);
+
+        #[cfg(target_family = "wasm")]
+        let _ = node_pool;
 
         #[cfg(not(target_family = "wasm"))]
         let (runtime, sync_handle) = {
-            let nodes = self
+            let nodes: HashSet<Node> = self
                 .node_manager_builder
                 .primary_node
                 .iter()
@@ -338,26 +386,51 @@ impl ClientBuilder {
                 .map(|node| node.clone().into())
                 .collect();
 
+            // Only the nodes of the pool that are still configured are used.
+            let warm_nodes = node_pool
+                .map(|node_pool| node_pool.healthy_nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(node, _)| nodes.contains(node))
+                .collect::<HashMap<_, _>>();
+            let warm_pool = !warm_nodes.is_empty();
+
+            if let Some(info) = warm_nodes.values().next() {
+                let mut network_info = network_info.write().map_err(|_| crate::Error::PoisonError)?;
+
+                network_info.latest_milestone_timestamp = info.status.latest_milestone.timestamp;
+                network_info.protocol_parameters = ProtocolParameters::try_from(info.protocol.clone())?;
+            }
+
+            *healthy_nodes.write().map_err(|_| crate::Error::PoisonError)? = warm_nodes;
+
             let healthy_nodes_ = healthy_nodes.clone();
+            let node_health_ = node_health.clone();
             let network_info_ = network_info.clone();
 
             let (runtime, sync_handle) = std::thread::spawn(move || {
                 let runtime = Runtime::new().expect("failed to create Tokio runtime");
-                if let Err(e) = runtime.block_on(Client::sync_nodes(
-                    &healthy_nodes_,
-                    &nodes,
-                    &network_info_,
-                    self.node_manager_builder.ignore_node_health,
-                )) {
-                    panic!("failed to sync nodes: {e:?}");
+                // The warm pool is refreshed in the background by the syncing task rather than before returning.
+                if !warm_pool {
+                    if let Err(e) = runtime.block_on(Client::sync_nodes(
+                        &healthy_nodes_,
+                        &node_health_,
+                        &nodes,
+                        &network_info_,
+                        self.node_manager_builder.ignore_node_health,
+                    )) {
+                        panic!("failed to sync nodes: {e:?}");
+                    }
                 }
                 let sync_handle = Client::start_sync_process(
                     &runtime,
                     healthy_nodes_,
+                    node_health_,
                     nodes,
                     self.node_manager_builder.node_sync_interval,
                     network_info_,
                     self.node_manager_builder.ignore_node_health,
+                    warm_pool,
                 );
                 (runtime, sync_handle)
             })
@@ -366,10 +439,19 @@ impl ClientBuilder {
             (Some(Arc::new(runtime)), Some(sync_handle))
         };
 
+        let node_manager = self.node_manager_builder.build(healthy_nodes, node_health);
+        // The parallel requests are spawned on the runtime of the client.
+        #[cfg(not(target_family = "wasm"))]
+        let node_manager = {
+            let mut node_manager = node_manager;
+            node_manager.runtime = runtime.as_ref().map(|runtime| runtime.handle().clone());
+            node_manager
+        };
+
         #[cfg(feature = "mqtt")]
         let (mqtt_event_tx, mqtt_event_rx) = tokio::sync::watch::channel(MqttEvent::Connected);
         let client = Client {
-            node_manager: self.node_manager_builder.build(healthy_nodes),
+            node_manager,
             #[cfg(not(target_family = "wasm"))]
             runtime,
             #[cfg(not(target_family = "wasm"))]
@@ -383,6 +465,7 @@ impl ClientBuilder {
             #[cfg(feature = "mqtt")]
             mqtt_event_channel: (Arc::new(mqtt_event_tx), mqtt_event_rx),
             network_info,
+            background_components: Default::default(),
             api_timeout: self.api_timeout,
             remote_pow_timeout: self.remote_pow_timeout,
             pow_worker_count: self.pow_worker_count,
@@ -126,7 +126,7 @@ impl Client {
             .await;
 
             #[cfg(not(target_family = "wasm"))]
-            tokio::time::sleep(std::time::Duration::from_secs(
+            crate::async_runtime::sleep(std::time::Duration::from_secs(
                 interval.unwrap_or(DEFAULT_RETRY_UNTIL_INCLUDED_INTERVAL),
             ))
             .await;
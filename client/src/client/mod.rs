@@ -5,12 +5,15 @@
 
 mod builder;
 mod high_level;
+mod network_stats;
+mod shutdown;
 
 use std::{
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
     time::Duration,
 };
 
+use futures::channel::mpsc::UnboundedReceiver;
 use iota_types::block::{output::RentStructure, protocol::ProtocolParameters};
 #[cfg(not(target_family = "wasm"))]
 use tokio::runtime::Runtime;
@@ -21,8 +24,12 @@ use {
     tokio::sync::watch::{Receiver as WatchReceiver, Sender as WatchSender},
 };
 
-pub use self::builder::{ClientBuilder, NetworkInfo, NetworkInfoDto};
-use crate::{constants::DEFAULT_TIPS_INTERVAL, error::Result};
+pub use self::{
+    builder::{ClientBuilder, NetworkInfo, NetworkInfoDto},
+    network_stats::{CongestionLevel, NetworkStats},
+    shutdown::BackgroundComponent,
+};
+use crate::{constants::DEFAULT_TIPS_INTERVAL, error::Result, node_manager::response_parsing::ResponseDeviation};
 
 /// An instance of the client using HORNET or Bee URI
 #[derive(Clone)]
@@ -45,6 +52,8 @@ pub struct Client {
     #[cfg(feature = "mqtt")]
     pub(crate) mqtt_event_channel: (Arc<WatchSender<MqttEvent>>, WatchReceiver<MqttEvent>),
     pub(crate) network_info: Arc<RwLock<NetworkInfo>>,
+    /// The components stopped with the client, see [`Client::register_background_component()`].
+    pub(crate) background_components: Arc<Mutex<Vec<Arc<dyn BackgroundComponent>>>>,
     /// HTTP request timeout.
     pub(crate) api_timeout: Duration,
     /// HTTP request timeout for remote PoW API call.
@@ -102,6 +111,58 @@ impl Client {
         ClientBuilder::new()
     }
 
+    /// Gracefully shutdown the background components of the `Client`: the node syncing task, with the `mqtt` feature
+    /// the MQTT connection with its event loop and topic handlers, and the components registered with
+    /// [`register_background_component()`](Self::register_background_component()), e.g. a Stronghold secret manager,
+    /// whose pending snapshot writes are flushed first.
+    ///
+    /// It returns once the tasks have stopped. Unlike dropping it, this also stops them for the clones of the client,
+    /// which can still send requests to the nodes as last synced. Blocks are only reattached or promoted within the
+    /// futures of the callers, e.g. [`retry_until_included()`](Self::retry_until_included()), so there is no
+    /// reattachment task to stop.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(sync_handle) = self.sync_handle.take() {
+            sync_handle.abort();
+            // The task is aborted on its next await point, on the runtime of the client.
+            while !sync_handle.is_finished() {
+                crate::async_runtime::sleep(Duration::from_millis(10)).await;
+            }
+        }
+
+        #[cfg(feature = "mqtt")]
+        {
+            let connected = self.mqtt_client.is_some();
+            self.subscriber().disconnect().await?;
+
+            // Wait for the event loop to end.
+            if connected {
+                let mut mqtt_event_receiver = self.mqtt_event_receiver();
+                while *mqtt_event_receiver.borrow_and_update() != MqttEvent::Disconnected {
+                    if mqtt_event_receiver.changed().await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.shutdown_background_components().await
+    }
+
+    /// Returns a receiver of the deviations from the API specification tolerated in the responses of the nodes, with
+    /// [`ResponseParsing::Lenient`](crate::node_manager::response_parsing::ResponseParsing::Lenient).
+    pub fn response_deviation_receiver(&self) -> Result<UnboundedReceiver<ResponseDeviation>> {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+
+        self.node_manager
+            .response_deviation_senders
+            .write()
+            .map_err(|_| crate::Error::PoisonError)?
+            .push(sender);
+
+        Ok(receiver)
+    }
+
     /// Gets the network related information such as network_id and min_pow_score
     /// and if it's the default one, sync it first and set the NetworkInfo.
     pub async fn get_network_info(&self) -> Result<NetworkInfo> {
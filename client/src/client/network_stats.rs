@@ -0,0 +1,105 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Confirmation-rate and congestion telemetry.
+
+use super::Client;
+use crate::{
+    constants::{CONGESTION_HIGH_REFERENCED_RATE, CONGESTION_MEDIUM_REFERENCED_RATE},
+    error::Result,
+};
+
+/// The congestion level of the network, derived from the rate of referenced blocks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CongestionLevel {
+    /// Most blocks get referenced by milestones.
+    Low,
+    /// A noticeable share of blocks doesn't get referenced by milestones.
+    Medium,
+    /// Many blocks don't get referenced by milestones; non-urgent sends should be deferred.
+    High,
+}
+
+/// Network telemetry aggregated from the node metrics.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStats {
+    /// Blocks per second received by the node.
+    pub blocks_per_second: f64,
+    /// Blocks per second referenced by milestones.
+    pub referenced_blocks_per_second: f64,
+    /// Percentage of blocks referenced by milestones.
+    pub referenced_rate: f64,
+    /// Seconds between the two latest milestones, if they could be retrieved.
+    pub milestone_interval: Option<u32>,
+    /// Congestion level derived from `referenced_rate`.
+    pub congestion: CongestionLevel,
+}
+
+impl NetworkStats {
+    /// Returns whether non-urgent sends should be deferred until the network is less congested.
+    pub fn should_defer_non_urgent(&self) -> bool {
+        self.congestion == CongestionLevel::High
+    }
+}
+
+impl CongestionLevel {
+    /// Derive the congestion level from a referenced rate in percent.
+    pub fn from_referenced_rate(referenced_rate: f64) -> Self {
+        if referenced_rate < CONGESTION_HIGH_REFERENCED_RATE {
+            Self::High
+        } else if referenced_rate < CONGESTION_MEDIUM_REFERENCED_RATE {
+            Self::Medium
+        } else {
+            Self::Low
+        }
+    }
+}
+
+impl Client {
+    /// Aggregate blocks per second, referenced rate and milestone interval from the node metrics, and derive the
+    /// congestion level of the network from them.
+    pub async fn network_stats(&self) -> Result<NetworkStats> {
+        let info = self.get_info().await?.node_info;
+        let metrics = info.metrics;
+        let latest_milestone = info.status.latest_milestone;
+
+        let milestone_interval = match (latest_milestone.index, latest_milestone.timestamp) {
+            (index, Some(timestamp)) if index > 0 => match self.get_milestone_by_index(index - 1).await {
+                Ok(previous) => Some(timestamp.saturating_sub(previous.essence().timestamp())),
+                Err(err) => {
+                    log::debug!("[network_stats] couldn't get milestone {}: {err}", index - 1);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        Ok(NetworkStats {
+            blocks_per_second: metrics.blocks_per_second,
+            referenced_blocks_per_second: metrics.referenced_blocks_per_second,
+            referenced_rate: metrics.referenced_rate,
+            milestone_interval,
+            congestion: CongestionLevel::from_referenced_rate(metrics.referenced_rate),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn congestion_level() {
+        assert_eq!(CongestionLevel::from_referenced_rate(100.0), CongestionLevel::Low);
+        assert_eq!(
+            CongestionLevel::from_referenced_rate(CONGESTION_MEDIUM_REFERENCED_RATE - 1.0),
+            CongestionLevel::Medium
+        );
+        assert_eq!(
+            CongestionLevel::from_referenced_rate(CONGESTION_HIGH_REFERENCED_RATE - 1.0),
+            CongestionLevel::High
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The background components stopped with [`Client::shutdown()`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::Client;
+use crate::error::Result;
+
+/// A component running tasks in the background or deferring writes, e.g. a Stronghold secret manager with a key
+/// clearing timeout or an `auto_persist_interval`, stopped with the client once registered with
+/// [`Client::register_background_component()`].
+#[async_trait]
+pub trait BackgroundComponent: Send + Sync {
+    /// Stop the background tasks of the component, after writing its pending changes.
+    async fn shutdown(&self) -> Result<()>;
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[async_trait]
+impl<T: BackgroundComponent> BackgroundComponent for tokio::sync::RwLock<T> {
+    async fn shutdown(&self) -> Result<()> {
+        self.read().await.shutdown().await
+    }
+}
+
+impl Client {
+    /// Register a component to stop with [`shutdown()`](Self::shutdown()), for this client and its clones.
+    pub fn register_background_component(&self, component: Arc<dyn BackgroundComponent>) -> Result<()> {
+        self.background_components
+            .lock()
+            .map_err(|_| crate::Error::PoisonError)?
+            .push(component);
+
+        Ok(())
+    }
+
+    /// Stop the registered background components, returning the first error after trying all of them.
+    pub(crate) async fn shutdown_background_components(&self) -> Result<()> {
+        let components = std::mem::take(
+            &mut *self
+                .background_components
+                .lock()
+                .map_err(|_| crate::Error::PoisonError)?,
+        );
+        let mut result = Ok(());
+
+        for component in components {
+            if let Err(err) = component.shutdown().await {
+                log::warn!("[Client] failed to shut down a background component: {err}");
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::db::{DatabaseProvider, ExpirationTask, MemoryDatabaseProvider};
+
+    #[tokio::test]
+    async fn shutdown() {
+        let mut client = Client::builder().finish().unwrap();
+        let sync_handle = client.sync_handle.clone().unwrap();
+
+        let provider = Arc::new(MemoryDatabaseProvider::default());
+        // Kept alive here, as dropping it would stop it too.
+        let expiration_task = Arc::new(ExpirationTask::spawn(provider.clone(), Duration::from_millis(20)));
+        client.register_background_component(expiration_task.clone()).unwrap();
+
+        #[cfg(feature = "stronghold")]
+        let stronghold_path = "test_client_shutdown.stronghold";
+        #[cfg(feature = "stronghold")]
+        let adapter = {
+            let adapter = Arc::new(
+                crate::stronghold::StrongholdAdapter::builder()
+                    .password("drowssap")
+                    .timeout(Duration::from_secs(60))
+                    .auto_persist(true)
+                    .auto_persist_interval(Duration::from_secs(60))
+                    .build(stronghold_path)
+                    .unwrap(),
+            );
+            adapter.insert(b"key", b"value").await.unwrap();
+            client.register_background_component(adapter.clone()).unwrap();
+            adapter
+        };
+
+        // Shutting down a clone stops the components of all clients.
+        client.clone().shutdown().await.unwrap();
+        client.shutdown().await.unwrap();
+
+        assert!(sync_handle.is_finished());
+        assert!(client.background_components.lock().unwrap().is_empty());
+
+        // The expired records aren't deleted anymore.
+        provider
+            .insert_with_ttl(b"session", b"0", Duration::ZERO)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(provider.get(b"session").await.unwrap(), Some(b"0".to_vec()));
+
+        // The key clearing task is stopped, without clearing the key, and the pending change has been written.
+        #[cfg(feature = "stronghold")]
+        {
+            let status = adapter.status().await;
+            assert!(status.unlocked);
+            assert_eq!(status.key_timeout_remaining, None);
+
+            let reader = crate::stronghold::StrongholdAdapter::builder()
+                .password("drowssap")
+                .build(stronghold_path)
+                .unwrap();
+            assert_eq!(reader.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+            std::fs::remove_file(stronghold_path).unwrap();
+        }
+    }
+}
@@ -15,6 +15,8 @@ pub(crate) const DEFAULT_RETRY_UNTIL_INCLUDED_MAX_AMOUNT: u64 = 40;
 pub(crate) const DEFAULT_TIPS_INTERVAL: u64 = 5;
 /// Interval in which the node info will be requested and healthy nodes will be added to the healthy node pool
 pub(crate) const NODE_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// Duration for which a node is skipped after failed requests
+pub(crate) const DEFAULT_NODE_COOL_DOWN: Duration = Duration::from_secs(60);
 pub(crate) const DEFAULT_MIN_QUORUM_SIZE: usize = 3;
 pub(crate) const DEFAULT_QUORUM_THRESHOLD: usize = 66;
 pub(crate) const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
@@ -22,6 +24,21 @@ pub(crate) const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/",
 pub(crate) const MAX_PARALLEL_API_REQUESTS: usize = 100;
 /// Max allowed difference between the local time and latest milestone time, 5 minutes in seconds
 pub(crate) const FIVE_MINUTES_IN_SECONDS: u32 = 300;
+/// Interval in which the balance of an address is polled while waiting for faucet funds
+#[cfg(not(target_family = "wasm"))]
+pub(crate) const DEFAULT_FAUCET_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Number of times the balance of an address is polled while waiting for faucet funds
+#[cfg(not(target_family = "wasm"))]
+pub(crate) const DEFAULT_FAUCET_POLL_MAX_ATTEMPTS: u64 = 30;
+/// Duration of the calibration run measuring the local PoW hash rate
+#[cfg(not(target_family = "wasm"))]
+pub(crate) const POW_CALIBRATION_DURATION: Duration = Duration::from_millis(200);
+/// Maximal number of milestones replayed to reconstruct a past ledger state, a day of milestones every 5 seconds
+pub const DEFAULT_LEDGER_STATE_MAX_REPLAY_DEPTH: u32 = 17_280;
+/// Referenced rate in percent below which the network is considered moderately congested
+pub(crate) const CONGESTION_MEDIUM_REFERENCED_RATE: f64 = 90.0;
+/// Referenced rate in percent below which the network is considered highly congested
+pub(crate) const CONGESTION_HIGH_REFERENCED_RATE: f64 = 60.0;
 
 /// Bech32 hrp for the IOTA mainnet <https://github.com/satoshilabs/slips/blob/master/slip-0173.md>
 pub const IOTA_BECH32_HRP: &str = "iota";
@@ -0,0 +1,101 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Batches of changes, committed atomically to a database provider.
+
+use super::DatabaseProvider;
+use crate::Result;
+
+/// A change of a [`WriteBatch`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BatchOperation {
+    /// Insert `value` under `key`, replacing the record under the same key, if any.
+    Put {
+        /// The key of the record.
+        key: Vec<u8>,
+        /// The value of the record.
+        value: Vec<u8>,
+    },
+    /// Delete the record under `key`, if any.
+    Delete {
+        /// The key of the record.
+        key: Vec<u8>,
+    },
+}
+
+/// A batch of changes to a database provider, committed atomically: after a crash, either all or none of them have
+/// been applied.
+///
+/// ```ignore
+/// let mut batch = provider.batch();
+/// batch.put(b"record", b"value").put(b"index", b"record");
+/// batch.commit().await?;
+/// ```
+#[must_use = "the changes of a batch are applied only when it's committed"]
+pub struct WriteBatch<'a> {
+    provider: &'a dyn DatabaseProvider,
+    operations: Vec<BatchOperation>,
+}
+
+impl<'a> WriteBatch<'a> {
+    /// Start an empty batch of changes to `provider`.
+    pub fn new(provider: &'a dyn DatabaseProvider) -> Self {
+        Self {
+            provider,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Insert `value` under `key`, replacing the record under the same key, if any.
+    pub fn put(&mut self, key: &[u8], value: &[u8]) -> &mut Self {
+        self.operations.push(BatchOperation::Put {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        });
+        self
+    }
+
+    /// Delete the record under `key`, if any.
+    pub fn delete(&mut self, key: &[u8]) -> &mut Self {
+        self.operations.push(BatchOperation::Delete { key: key.to_vec() });
+        self
+    }
+
+    /// The changes of the batch, in the order in which they're applied.
+    pub fn operations(&self) -> &[BatchOperation] {
+        &self.operations
+    }
+
+    /// Apply the changes of the batch atomically.
+    pub async fn commit(self) -> Result<()> {
+        if self.operations.is_empty() {
+            return Ok(());
+        }
+
+        self.provider.apply_batch(self.operations).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDatabaseProvider;
+
+    #[tokio::test]
+    async fn write_batch() {
+        let provider = MemoryDatabaseProvider::default();
+        provider.insert(b"stale", b"0").await.unwrap();
+
+        let mut batch = provider.batch();
+        batch.put(b"record", b"1").put(b"index", b"record").delete(b"stale");
+        assert_eq!(batch.operations().len(), 3);
+
+        // Nothing is applied before the batch is committed.
+        assert_eq!(provider.get(b"record").await.unwrap(), None);
+
+        batch.commit().await.unwrap();
+        assert_eq!(provider.get(b"record").await.unwrap(), Some(b"1".to_vec()));
+        assert_eq!(provider.get(b"index").await.unwrap(), Some(b"record".to_vec()));
+        assert_eq!(provider.get(b"stale").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Typed, namespaced collections of records.
+
+use std::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::DatabaseProvider;
+use crate::Result;
+
+/// A collection of values of type `T` in a database provider, serialized as JSON.
+///
+/// The values are held under their key prefixed with the name of the collection and a `/`, so that collections with
+/// different names don't collide; names should therefore not contain a `/`.
+///
+/// ```ignore
+/// let outputs = provider.collection::<OutputWithMetadataResponse>("outputs");
+/// outputs.insert(&output_id.to_string(), &output).await?;
+/// ```
+pub struct Collection<'a, T> {
+    provider: &'a dyn DatabaseProvider,
+    prefix: String,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: Serialize + DeserializeOwned> Collection<'a, T> {
+    /// The collection `name` of `provider`.
+    pub fn new(provider: &'a dyn DatabaseProvider, name: &str) -> Self {
+        Self {
+            provider,
+            prefix: format!("{name}/"),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the value under `key`.
+    pub async fn get(&self, key: &str) -> Result<Option<T>> {
+        self.provider
+            .get(self.record_key(key).as_bytes())
+            .await?
+            .map(|value| deserialize(&value))
+            .transpose()
+    }
+
+    /// Insert `value` under `key`, returning the value it replaces, if any.
+    pub async fn insert(&self, key: &str, value: &T) -> Result<Option<T>> {
+        self.provider
+            .insert(self.record_key(key).as_bytes(), &serde_json::to_vec(value)?)
+            .await?
+            .map(|value| deserialize(&value))
+            .transpose()
+    }
+
+    /// Delete the value under `key`, returning it, if any.
+    pub async fn delete(&self, key: &str) -> Result<Option<T>> {
+        self.provider
+            .delete(self.record_key(key).as_bytes())
+            .await?
+            .map(|value| deserialize(&value))
+            .transpose()
+    }
+
+    /// Get all the values of the collection with their key, sorted by key.
+    pub async fn iter(&self) -> Result<Vec<(String, T)>> {
+        self.provider
+            .iter_prefix(self.prefix.as_bytes())
+            .await?
+            .into_iter()
+            .map(|(key, value)| {
+                Ok((
+                    String::from_utf8_lossy(&key[self.prefix.len()..]).into_owned(),
+                    deserialize(&value)?,
+                ))
+            })
+            .collect()
+    }
+
+    fn record_key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(value: &[u8]) -> Result<T> {
+    Ok(serde_json::from_slice(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::db::MemoryDatabaseProvider;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Account {
+        index: u32,
+    }
+
+    #[tokio::test]
+    async fn collection() {
+        let provider = MemoryDatabaseProvider::default();
+        let accounts = provider.collection::<Account>("accounts");
+        let aliases = provider.collection::<String>("aliases");
+
+        assert_eq!(accounts.insert("alice", &Account { index: 0 }).await.unwrap(), None);
+        assert_eq!(
+            accounts.insert("alice", &Account { index: 1 }).await.unwrap(),
+            Some(Account { index: 0 })
+        );
+        accounts.insert("bob", &Account { index: 2 }).await.unwrap();
+        aliases.insert("alice", &"0xalice".to_string()).await.unwrap();
+
+        assert_eq!(accounts.get("alice").await.unwrap(), Some(Account { index: 1 }));
+        assert_eq!(aliases.get("alice").await.unwrap(), Some("0xalice".to_string()));
+        assert_eq!(
+            accounts.iter().await.unwrap(),
+            vec![
+                ("alice".to_string(), Account { index: 1 }),
+                ("bob".to_string(), Account { index: 2 })
+            ]
+        );
+
+        assert_eq!(accounts.delete("bob").await.unwrap(), Some(Account { index: 2 }));
+        assert_eq!(accounts.get("bob").await.unwrap(), None);
+        assert_eq!(aliases.iter().await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compression of the values of database providers.
+
+use crate::{Error, Result};
+
+/// The marker of the values stored as is by a compressing database provider.
+#[cfg(feature = "zstd")]
+const UNCOMPRESSED_MARKER: u8 = 0;
+/// The marker of the values compressed with zstd.
+#[cfg(feature = "zstd")]
+const ZSTD_MARKER: u8 = 1;
+
+/// How a database provider compresses the values before encrypting and storing them.
+///
+/// With a compression, each value is prefixed by a marker byte telling whether it has been compressed, as a value
+/// isn't compressed if that doesn't make it smaller, e.g. for small values. Without compression, the values are stored
+/// and read back as is.
+///
+/// The compression is recorded by the database provider apart from the values, the first time it's set. A database
+/// is then read with its recorded compression, and can't be switched to another one once it holds values; databases
+/// without a recorded compression hold uncompressed values.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Compression {
+    /// The values are stored as is.
+    #[default]
+    None,
+    /// The values are compressed with zstd, at its default level.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+// Only used by the database providers behind features.
+#[cfg_attr(
+    not(any(feature = "rocksdb", feature = "sled", feature = "sqlite", feature = "stronghold")),
+    allow(dead_code)
+)]
+impl Compression {
+    /// The name the compression is recorded with.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            #[cfg(feature = "zstd")]
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// The compression recorded as `recorded` by a database provider, [`Compression::None`] if none has been.
+    pub(crate) fn from_recorded(recorded: Option<&[u8]>) -> Result<Self> {
+        match recorded {
+            None | Some(b"none") => Ok(Self::None),
+            #[cfg(feature = "zstd")]
+            Some(b"zstd") => Ok(Self::Zstd),
+            Some(name) => Err(Error::CompressionUnsupported(
+                String::from_utf8_lossy(name).into_owned(),
+            )),
+        }
+    }
+
+    /// Check that a database whose values are stored with `self` can be switched to the compression `configured`,
+    /// which is only the case if it's the same one or if the database doesn't hold values yet.
+    pub(crate) fn check_switch(&self, configured: Self, has_records: bool) -> Result<()> {
+        if *self != configured && has_records {
+            return Err(Error::CompressionMismatch {
+                recorded: self.name().to_string(),
+                configured: configured.name().to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Compress `value`, if that makes it smaller, and mark it accordingly.
+    pub(crate) fn compress(&self, value: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(value.to_vec()),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => {
+                let compressed = zstd::encode_all(value, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+                let (marker, value) = if compressed.len() < value.len() {
+                    (ZSTD_MARKER, compressed.as_slice())
+                } else {
+                    (UNCOMPRESSED_MARKER, value)
+                };
+
+                let mut marked = Vec::with_capacity(value.len() + 1);
+                marked.push(marker);
+                marked.extend_from_slice(value);
+
+                Ok(marked)
+            }
+        }
+    }
+
+    /// Decompress `value`, stored with [`compress()`](Self::compress()), according to its marker.
+    pub(crate) fn decompress(&self, value: Vec<u8>) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(value),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => match value.split_first() {
+                Some((&UNCOMPRESSED_MARKER, value)) => Ok(value.to_vec()),
+                Some((&ZSTD_MARKER, value)) => Ok(zstd::decode_all(value)?),
+                _ => Err(Error::CompressedValueInvalid),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_compression() {
+        assert_eq!(Compression::from_recorded(None).unwrap(), Compression::None);
+        assert_eq!(
+            Compression::from_recorded(Some(Compression::None.name().as_bytes())).unwrap(),
+            Compression::None
+        );
+        assert!(matches!(
+            Compression::from_recorded(Some(b"lz4")),
+            Err(Error::CompressionUnsupported(name)) if name == "lz4"
+        ));
+
+        Compression::None.check_switch(Compression::None, true).unwrap();
+        #[cfg(feature = "zstd")]
+        {
+            assert_eq!(
+                Compression::from_recorded(Some(Compression::Zstd.name().as_bytes())).unwrap(),
+                Compression::Zstd
+            );
+            Compression::None.check_switch(Compression::Zstd, false).unwrap();
+            assert!(matches!(
+                Compression::None.check_switch(Compression::Zstd, true),
+                Err(Error::CompressionMismatch { .. })
+            ));
+            assert!(matches!(
+                Compression::Zstd.check_switch(Compression::None, true),
+                Err(Error::CompressionMismatch { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn no_compression() {
+        // Values looking compressed are read back as is.
+        let value = [0x28, 0xb5, 0x2f, 0xfd, 0x00].to_vec();
+
+        assert_eq!(Compression::None.compress(&value).unwrap(), value);
+        assert_eq!(Compression::None.decompress(value.clone()).unwrap(), value);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_compression() {
+        let value = vec![42; 1024];
+        let compressed = Compression::Zstd.compress(&value).unwrap();
+        assert!(compressed.len() < value.len());
+        assert_eq!(Compression::Zstd.decompress(compressed).unwrap(), value);
+
+        // Small values are stored as is, behind their marker, even if they are zstd frames themselves.
+        let value = zstd::encode_all(&b"value"[..], 0).unwrap();
+        let stored = Compression::Zstd.compress(&value).unwrap();
+        assert_eq!(stored[1..], value);
+        assert_eq!(Compression::Zstd.decompress(stored).unwrap(), value);
+
+        assert!(matches!(
+            Compression::Zstd.decompress(Vec::new()),
+            Err(Error::CompressedValueInvalid)
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Expiration of the records inserted with [`DatabaseProvider::insert_with_ttl()`].
+//!
+//! The expiry time of such a record is held in another record of the same database, under the key of the record
+//! prefixed with [`EXPIRY_KEY_PREFIX`], so that expiration works with every provider. Expired records are deleted by
+//! [`DatabaseProvider::purge_expired()`], e.g. periodically with an [`ExpirationTask`].
+
+#[cfg(not(target_family = "wasm"))]
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(not(target_family = "wasm"))]
+use async_trait::async_trait;
+#[cfg(not(target_family = "wasm"))]
+use log::{debug, error};
+
+use super::BatchOperation;
+#[cfg(not(target_family = "wasm"))]
+use super::DatabaseProvider;
+#[cfg(not(target_family = "wasm"))]
+use crate::{async_runtime, client::BackgroundComponent};
+
+/// The prefix of the keys of the records holding the expiry time of another record.
+pub const EXPIRY_KEY_PREFIX: &[u8] = b"iota-client-record-expiry-";
+
+/// The key of the record holding the expiry time of the record under `key`.
+pub(crate) fn expiry_key(key: &[u8]) -> Vec<u8> {
+    [EXPIRY_KEY_PREFIX, key].concat()
+}
+
+/// The records to insert for a record expiring after `ttl`.
+pub(crate) fn insert_with_ttl_operations(key: &[u8], value: &[u8], ttl: Duration) -> Vec<BatchOperation> {
+    let expires_at = unix_timestamp_millis().saturating_add(ttl.as_millis().try_into().unwrap_or(u64::MAX));
+
+    vec![
+        BatchOperation::Put {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        },
+        BatchOperation::Put {
+            key: expiry_key(key),
+            value: expires_at.to_be_bytes().to_vec(),
+        },
+    ]
+}
+
+/// The deletions of the expired records among the expiry `records`.
+pub(crate) fn purge_operations(records: Vec<(Vec<u8>, Vec<u8>)>) -> Vec<BatchOperation> {
+    let now = unix_timestamp_millis();
+    let mut operations = Vec::new();
+
+    for (key, expires_at) in records {
+        // A malformed expiry time is treated as expired, rather than keeping the record forever.
+        let expired = <[u8; 8]>::try_from(expires_at.as_slice())
+            .map(|expires_at| u64::from_be_bytes(expires_at) <= now)
+            .unwrap_or(true);
+
+        if expired {
+            operations.push(BatchOperation::Delete {
+                key: key[EXPIRY_KEY_PREFIX.len()..].to_vec(),
+            });
+            operations.push(BatchOperation::Delete { key });
+        }
+    }
+
+    operations
+}
+
+fn unix_timestamp_millis() -> u64 {
+    instant::SystemTime::now()
+        .duration_since(instant::SystemTime::UNIX_EPOCH)
+        .expect("time went backwards")
+        .as_millis() as u64
+}
+
+/// A background task deleting the expired records of a database provider periodically, until it's dropped.
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug)]
+pub struct ExpirationTask(async_runtime::TaskHandle);
+
+#[cfg(not(target_family = "wasm"))]
+impl ExpirationTask {
+    /// Spawn a task deleting the expired records of `provider` every `interval`.
+    pub fn spawn(provider: Arc<dyn DatabaseProvider>, interval: Duration) -> Self {
+        Self(async_runtime::spawn(async move {
+            loop {
+                async_runtime::sleep(interval).await;
+
+                match provider.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(purged) => debug!("[ExpirationTask] deleted {purged} expired records"),
+                    Err(err) => error!("failed to delete the expired database records: {err}"),
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Drop for ExpirationTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[async_trait]
+impl BackgroundComponent for ExpirationTask {
+    async fn shutdown(&self) -> crate::Result<()> {
+        self.0.abort();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDatabaseProvider;
+
+    #[tokio::test]
+    async fn expiration() {
+        let provider = Arc::new(MemoryDatabaseProvider::default());
+
+        provider
+            .insert_with_ttl(b"session", b"0", Duration::from_millis(50))
+            .await
+            .unwrap();
+        provider
+            .insert_with_ttl(b"cache", b"1", Duration::from_secs(3600))
+            .await
+            .unwrap();
+        provider.insert(b"record", b"2").await.unwrap();
+        assert_eq!(provider.purge_expired().await.unwrap(), 0);
+
+        let _task = ExpirationTask::spawn(provider.clone(), Duration::from_millis(20));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(provider.get(b"session").await.unwrap(), None);
+        assert_eq!(provider.get(&expiry_key(b"session")).await.unwrap(), None);
+        assert_eq!(provider.get(b"cache").await.unwrap(), Some(b"1".to_vec()));
+        assert_eq!(provider.get(b"record").await.unwrap(), Some(b"2".to_vec()));
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Instrumentation of the operations of database providers, e.g. to track the latency and the growth of a store.
+//!
+//! Wrapping a provider in an [`InstrumentedDatabaseProvider`] reports each of its operations to a
+//! [`DatabaseInstrumentation`], like the [`MetricsInstrumentation`] recording them with the `metrics` crate, with the
+//! `metrics` feature.
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use instant::Instant;
+
+use super::{BatchOperation, DatabaseProvider};
+use crate::Result;
+
+/// An operation of a database provider, reported to a [`DatabaseInstrumentation`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DatabaseOperation {
+    /// The name of the operation, i.e. of the [`DatabaseProvider`] method.
+    pub name: &'static str,
+    /// The prefix of the key of the record, see [`InstrumentedDatabaseProvider::with_key_prefix()`], or `None` if the
+    /// operation is on several records.
+    pub key_prefix: Option<String>,
+    /// How long the operation took.
+    pub duration: Duration,
+    /// The number of bytes of the values read or written.
+    pub payload_size: usize,
+    /// Whether the operation succeeded.
+    pub success: bool,
+}
+
+/// A recipient of the operations of an [`InstrumentedDatabaseProvider`].
+pub trait DatabaseInstrumentation: Send + Sync {
+    /// Called once an operation has completed, successfully or not.
+    fn record(&self, operation: DatabaseOperation);
+}
+
+/// A database provider reporting its operations to a [`DatabaseInstrumentation`].
+pub struct InstrumentedDatabaseProvider<D> {
+    provider: D,
+    instrumentation: Arc<dyn DatabaseInstrumentation>,
+    key_prefix: fn(&[u8]) -> String,
+}
+
+impl<D: DatabaseProvider> InstrumentedDatabaseProvider<D> {
+    /// Report the operations of `provider` to `instrumentation`.
+    pub fn new(provider: D, instrumentation: Arc<dyn DatabaseInstrumentation>) -> Self {
+        Self {
+            provider,
+            instrumentation,
+            key_prefix: default_key_prefix,
+        }
+    }
+
+    /// Derive the prefixes of the keys reported with the operations with `key_prefix`, instead of taking the key up to
+    /// its first `/`, e.g. the name of a [`Collection`](super::Collection), or without its trailing digits.
+    ///
+    /// The prefixes should be few, as they're typically used as labels of metrics.
+    pub fn with_key_prefix(mut self, key_prefix: fn(&[u8]) -> String) -> Self {
+        self.key_prefix = key_prefix;
+        self
+    }
+
+    /// The instrumented provider.
+    pub fn inner(&self) -> &D {
+        &self.provider
+    }
+
+    async fn instrument<T>(
+        &self,
+        name: &'static str,
+        key: Option<&[u8]>,
+        payload_size: impl FnOnce(&T) -> usize,
+        operation: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let start = Instant::now();
+        let result = operation.await;
+
+        self.instrumentation.record(DatabaseOperation {
+            name,
+            key_prefix: key.map(self.key_prefix),
+            duration: start.elapsed(),
+            payload_size: result.as_ref().map(payload_size).unwrap_or(0),
+            success: result.is_ok(),
+        });
+
+        result
+    }
+}
+
+/// The key up to its first `/`, or without its trailing digits.
+fn default_key_prefix(key: &[u8]) -> String {
+    let prefix = match key.iter().position(|byte| *byte == b'/') {
+        Some(position) => &key[..=position],
+        None => {
+            let end = key.iter().rposition(|byte| !byte.is_ascii_digit()).map_or(0, |i| i + 1);
+            &key[..end]
+        }
+    };
+
+    String::from_utf8_lossy(prefix).into_owned()
+}
+
+fn option_size(value: &Option<Vec<u8>>) -> usize {
+    value.as_ref().map_or(0, Vec::len)
+}
+
+#[async_trait]
+impl<D: DatabaseProvider> DatabaseProvider for InstrumentedDatabaseProvider<D> {
+    async fn get(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.instrument("get", Some(k), option_size, self.provider.get(k)).await
+    }
+
+    async fn insert(&self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.instrument("insert", Some(k), |_| v.len(), self.provider.insert(k, v))
+            .await
+    }
+
+    async fn delete(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.instrument("delete", Some(k), option_size, self.provider.delete(k))
+            .await
+    }
+
+    async fn insert_batch(&self, records: &[(Vec<u8>, Vec<u8>)]) -> Result<()> {
+        let payload_size = records.iter().map(|(_, v)| v.len()).sum::<usize>();
+
+        self.instrument(
+            "insert_batch",
+            None,
+            |_| payload_size,
+            self.provider.insert_batch(records),
+        )
+        .await
+    }
+
+    async fn delete_batch(&self, keys: &[Vec<u8>]) -> Result<()> {
+        self.instrument("delete_batch", None, |_| 0, self.provider.delete_batch(keys))
+            .await
+    }
+
+    async fn apply_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        let payload_size = operations
+            .iter()
+            .map(|operation| match operation {
+                BatchOperation::Put { value, .. } => value.len(),
+                BatchOperation::Delete { .. } => 0,
+            })
+            .sum::<usize>();
+
+        self.instrument(
+            "apply_batch",
+            None,
+            |_| payload_size,
+            self.provider.apply_batch(operations),
+        )
+        .await
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        self.instrument("keys", None, |_| 0, self.provider.keys()).await
+    }
+
+    async fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.instrument(
+            "iter_prefix",
+            Some(prefix),
+            |records: &Vec<(Vec<u8>, Vec<u8>)>| records.iter().map(|(_, v)| v.len()).sum(),
+            self.provider.iter_prefix(prefix),
+        )
+        .await
+    }
+}
+
+/// An instrumentation recording the operations with the [`metrics`] crate, labelled with their name, key prefix and
+/// success:
+/// - `iota_client_db_operations_total`, a counter of the operations;
+/// - `iota_client_db_operation_duration_seconds`, a histogram of their duration;
+/// - `iota_client_db_payload_bytes_total`, a counter of the bytes read and written.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MetricsInstrumentation;
+
+#[cfg(feature = "metrics")]
+impl DatabaseInstrumentation for MetricsInstrumentation {
+    fn record(&self, operation: DatabaseOperation) {
+        let key_prefix = operation.key_prefix.unwrap_or_default();
+        let success = if operation.success { "true" } else { "false" };
+
+        metrics::increment_counter!(
+            "iota_client_db_operations_total",
+            "operation" => operation.name,
+            "key_prefix" => key_prefix.clone(),
+            "success" => success
+        );
+        metrics::histogram!(
+            "iota_client_db_operation_duration_seconds",
+            operation.duration.as_secs_f64(),
+            "operation" => operation.name,
+            "key_prefix" => key_prefix.clone(),
+            "success" => success
+        );
+        metrics::counter!(
+            "iota_client_db_payload_bytes_total",
+            operation.payload_size as u64,
+            "operation" => operation.name,
+            "key_prefix" => key_prefix,
+            "success" => success
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::db::MemoryDatabaseProvider;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<DatabaseOperation>>);
+
+    impl DatabaseInstrumentation for Recorder {
+        fn record(&self, operation: DatabaseOperation) {
+            self.0.lock().unwrap().push(operation);
+        }
+    }
+
+    #[test]
+    fn key_prefix() {
+        assert_eq!(default_key_prefix(b"outputs/0x00"), "outputs/");
+        assert_eq!(
+            default_key_prefix(b"iota-client-transaction-journal-entry-42"),
+            "iota-client-transaction-journal-entry-"
+        );
+        assert_eq!(default_key_prefix(b"42"), "");
+    }
+
+    #[tokio::test]
+    async fn instrumented_database_provider() {
+        let recorder = Arc::new(Recorder::default());
+        let provider = InstrumentedDatabaseProvider::new(MemoryDatabaseProvider::default(), recorder.clone());
+
+        provider.insert(b"outputs/0", b"value").await.unwrap();
+        provider.get(b"outputs/0").await.unwrap();
+        provider.keys().await.unwrap();
+
+        let operations = recorder.0.lock().unwrap();
+        assert_eq!(
+            operations
+                .iter()
+                .map(|operation| (operation.name, operation.key_prefix.as_deref(), operation.payload_size))
+                .collect::<Vec<_>>(),
+            vec![
+                ("insert", Some("outputs/"), 5),
+                ("get", Some("outputs/"), 5),
+                ("keys", None, 0)
+            ]
+        );
+        assert!(operations.iter().all(|operation| operation.success));
+    }
+}
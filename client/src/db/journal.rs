@@ -0,0 +1,686 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A persistent journal of transaction state transitions, with crash recovery.
+//!
+//! Every transaction goes through the states `Intent` → `Prepared` → `Signed` → `Submitted` → `Confirmed`. The
+//! [`TransactionJournal`] persists each transition through a [`DatabaseProvider`] before the corresponding action is
+//! taken, so that after a crash [`TransactionJournal::recover()`] can reconcile the in-flight entries against the
+//! node: signed transactions are resubmitted, transactions whose inputs have been spent elsewhere are marked as
+//! conflicting, and inputs locked by transactions that were never signed are released.
+
+use std::str::FromStr;
+
+use futures::lock::Mutex;
+use iota_types::{
+    api::dto::LedgerInclusionStateDto,
+    block::{
+        output::OutputId,
+        payload::{
+            transaction::{dto::TransactionPayloadDto, TransactionId},
+            Payload, TransactionPayload,
+        },
+        BlockId,
+    },
+};
+use log::{debug, warn};
+
+use super::{BatchOperation, DatabaseProvider};
+use crate::{
+    api::{PreparedTransactionData, PreparedTransactionDataDto},
+    Client, Error, Result,
+};
+
+/// Database key of the list of known journal entry ids.
+const JOURNAL_INDEX_KEY: &[u8] = b"iota-client-transaction-journal-index";
+/// Database key prefix of a journal entry.
+const JOURNAL_ENTRY_KEY_PREFIX: &str = "iota-client-transaction-journal-entry-";
+
+/// The state of a journaled transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JournalState {
+    /// The transaction has been requested and its inputs are locked.
+    Intent,
+    /// The transaction essence has been prepared.
+    Prepared,
+    /// The transaction has been signed.
+    Signed,
+    /// The transaction has been submitted in a block.
+    Submitted,
+    /// The transaction has been confirmed by a milestone.
+    Confirmed,
+    /// The transaction conflicts with the ledger and will never be confirmed.
+    Conflicting,
+    /// The transaction has been abandoned before being signed and its inputs have been released.
+    Released,
+}
+
+impl JournalState {
+    /// Returns whether no further transitions are expected from this state.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Confirmed | Self::Conflicting | Self::Released)
+    }
+}
+
+/// A journal entry, recording the latest known state of a transaction.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    /// The local id of the entry.
+    pub id: u64,
+    /// The current state of the transaction.
+    pub state: JournalState,
+    /// The inputs locked by the transaction.
+    pub locked_inputs: Vec<OutputId>,
+    /// The prepared transaction data, set from [`JournalState::Prepared`] on.
+    pub prepared: Option<PreparedTransactionDataDto>,
+    /// The signed transaction payload, set from [`JournalState::Signed`] on.
+    pub payload: Option<TransactionPayloadDto>,
+    /// The id of the transaction, set from [`JournalState::Signed`] on.
+    pub transaction_id: Option<TransactionId>,
+    /// The id of the latest block containing the transaction, set from [`JournalState::Submitted`] on.
+    pub block_id: Option<BlockId>,
+}
+
+/// The outcome of [`TransactionJournal::recover()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RecoveryReport {
+    /// Entries found to be confirmed.
+    pub confirmed: Vec<u64>,
+    /// Entries that have been resubmitted, with the id of the new block.
+    pub resubmitted: Vec<(u64, BlockId)>,
+    /// Entries that have been marked as conflicting.
+    pub conflicting: Vec<u64>,
+    /// Entries that have been released, unlocking their inputs.
+    pub released: Vec<u64>,
+    /// Entries that are still waiting for a confirmation.
+    pub pending: Vec<u64>,
+}
+
+/// A persistent journal of transaction state transitions.
+///
+/// A journal over any provider can be referenced as a `&TransactionJournal<dyn DatabaseProvider>`, e.g. to record the
+/// transactions sent with [`ClientBlockBuilder::with_journal()`](crate::api::ClientBlockBuilder::with_journal()).
+///
+/// See the [module-level documentation](self) for more details.
+pub struct TransactionJournal<D: DatabaseProvider + ?Sized> {
+    /// Serializes the changes of the index of the entries.
+    index_lock: Mutex<()>,
+    db: D,
+}
+
+impl<D: DatabaseProvider> TransactionJournal<D> {
+    /// Create a journal persisting its entries in `db`.
+    pub fn new(db: D) -> Self {
+        Self {
+            index_lock: Mutex::new(()),
+            db,
+        }
+    }
+
+    /// Consume the journal, returning the underlying database provider.
+    pub fn into_inner(self) -> D {
+        self.db
+    }
+}
+
+impl<D: DatabaseProvider + ?Sized> TransactionJournal<D> {
+    /// Record the intent to send a transaction spending `locked_inputs`, returning the id of the new entry.
+    pub async fn record_intent(&self, locked_inputs: Vec<OutputId>) -> Result<u64> {
+        let _index_lock = self.index_lock.lock().await;
+        let mut index = self.index().await?;
+        let id = index.iter().max().map_or(0, |id| id + 1);
+        let entry = JournalEntry {
+            id,
+            state: JournalState::Intent,
+            locked_inputs,
+            prepared: None,
+            payload: None,
+            transaction_id: None,
+            block_id: None,
+        };
+
+        index.push(id);
+        // Written together, so that a crash can't leave an entry out of the index, where it would never be recovered.
+        self.db
+            .apply_batch(vec![
+                BatchOperation::Put {
+                    key: entry_key(id).into_bytes(),
+                    value: serde_json::to_vec(&entry)?,
+                },
+                BatchOperation::Put {
+                    key: JOURNAL_INDEX_KEY.to_vec(),
+                    value: serde_json::to_vec(&index)?,
+                },
+            ])
+            .await?;
+
+        Ok(id)
+    }
+
+    /// Record that the transaction of entry `id` has been prepared.
+    ///
+    /// The locked inputs are replaced by the inputs of the prepared transaction.
+    pub async fn record_prepared(&self, id: u64, prepared: &PreparedTransactionData) -> Result<()> {
+        let mut entry = self.entry(id).await?;
+
+        entry.state = JournalState::Prepared;
+        entry.locked_inputs = prepared.inputs_data.iter().map(|input| *input.output_id()).collect();
+        entry.prepared = Some(PreparedTransactionDataDto::from(prepared));
+
+        self.write_entry(&entry).await
+    }
+
+    /// Record that the transaction of entry `id` has been signed.
+    pub async fn record_signed(&self, id: u64, payload: &TransactionPayload) -> Result<()> {
+        let mut entry = self.entry(id).await?;
+
+        entry.state = JournalState::Signed;
+        entry.payload = Some(TransactionPayloadDto::from(payload));
+        entry.transaction_id = Some(payload.id());
+
+        self.write_entry(&entry).await
+    }
+
+    /// Record that the transaction of entry `id` has been submitted in the block `block_id`.
+    pub async fn record_submitted(&self, id: u64, block_id: BlockId) -> Result<()> {
+        self.transition(id, JournalState::Submitted, Some(block_id)).await
+    }
+
+    /// Record that the transaction of entry `id` has been confirmed.
+    pub async fn record_confirmed(&self, id: u64) -> Result<()> {
+        self.transition(id, JournalState::Confirmed, None).await
+    }
+
+    /// Record that the transaction of entry `id` has been abandoned before being signed, releasing its inputs.
+    pub async fn record_released(&self, id: u64) -> Result<()> {
+        self.transition(id, JournalState::Released, None).await
+    }
+
+    /// Get the entry `id`.
+    pub async fn entry(&self, id: u64) -> Result<JournalEntry> {
+        let bytes = self
+            .db
+            .get(entry_key(id).as_bytes())
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("transaction journal entry {id}")))?;
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Get all entries that haven't reached a final state yet.
+    pub async fn in_flight_entries(&self) -> Result<Vec<JournalEntry>> {
+        let mut entries = Vec::new();
+
+        for id in self.index().await? {
+            let entry = self.entry(id).await?;
+
+            if !entry.state.is_final() {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Get the inputs locked by all in-flight entries.
+    pub async fn locked_inputs(&self) -> Result<Vec<OutputId>> {
+        Ok(self
+            .in_flight_entries()
+            .await?
+            .into_iter()
+            .flat_map(|entry| entry.locked_inputs)
+            .collect())
+    }
+
+    /// Remove all entries that have reached a final state.
+    pub async fn prune(&self) -> Result<()> {
+        let _index_lock = self.index_lock.lock().await;
+        let mut retained = Vec::new();
+        let mut operations = Vec::new();
+
+        for id in self.index().await? {
+            if self.entry(id).await?.state.is_final() {
+                operations.push(BatchOperation::Delete {
+                    key: entry_key(id).into_bytes(),
+                });
+            } else {
+                retained.push(id);
+            }
+        }
+
+        operations.push(BatchOperation::Put {
+            key: JOURNAL_INDEX_KEY.to_vec(),
+            value: serde_json::to_vec(&retained)?,
+        });
+        self.db.apply_batch(operations).await
+    }
+
+    /// Reconcile the in-flight entries against the node, typically after a crash.
+    ///
+    /// - Entries that were never signed are released, unlocking their inputs.
+    /// - Signed or submitted transactions that got included are marked as confirmed.
+    /// - Transactions whose inputs have been spent by another transaction are marked as conflicting.
+    /// - Signed transactions that never made it to the node, or submitted ones that should be reattached, are
+    ///   resubmitted.
+    pub async fn recover(&self, client: &Client) -> Result<RecoveryReport> {
+        let mut report = RecoveryReport::default();
+
+        for mut entry in self.in_flight_entries().await? {
+            let (payload, transaction_id) = match (&entry.payload, entry.transaction_id) {
+                (Some(payload), Some(transaction_id)) => (payload, transaction_id),
+                // Nothing has been signed, so nothing could have been sent; just release the inputs.
+                _ => {
+                    debug!("[TransactionJournal] releasing entry {}", entry.id);
+                    entry.state = JournalState::Released;
+                    self.write_entry(&entry).await?;
+                    report.released.push(entry.id);
+                    continue;
+                }
+            };
+
+            if let Some(block_id) = entry.block_id {
+                let metadata = client.get_block_metadata(&block_id).await?;
+
+                match metadata.ledger_inclusion_state {
+                    Some(LedgerInclusionStateDto::Included) => {
+                        entry.state = JournalState::Confirmed;
+                        self.write_entry(&entry).await?;
+                        report.confirmed.push(entry.id);
+                        continue;
+                    }
+                    // The block didn't make it but the transaction may have been included through another block.
+                    Some(LedgerInclusionStateDto::Conflicting | LedgerInclusionStateDto::NoTransaction) => {}
+                    None => {
+                        if !metadata.should_reattach.unwrap_or(false) {
+                            report.pending.push(entry.id);
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            match client.get_included_block(&transaction_id).await {
+                Ok(block) => {
+                    entry.state = JournalState::Confirmed;
+                    entry.block_id = Some(block.id());
+                    self.write_entry(&entry).await?;
+                    report.confirmed.push(entry.id);
+                    continue;
+                }
+                Err(Error::NotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+
+            if self.inputs_spent_elsewhere(client, &entry, &transaction_id).await? {
+                warn!(
+                    "[TransactionJournal] transaction {transaction_id} of entry {} is conflicting",
+                    entry.id
+                );
+                entry.state = JournalState::Conflicting;
+                self.write_entry(&entry).await?;
+                report.conflicting.push(entry.id);
+                continue;
+            }
+
+            let protocol_parameters = client.get_protocol_parameters().await?;
+            let payload = TransactionPayload::try_from_dto(payload, &protocol_parameters)?;
+            let block = client.block().finish_block(Some(Payload::from(payload))).await?;
+
+            debug!(
+                "[TransactionJournal] resubmitted transaction {transaction_id} of entry {} in block {}",
+                entry.id,
+                block.id()
+            );
+            entry.state = JournalState::Submitted;
+            entry.block_id = Some(block.id());
+            self.write_entry(&entry).await?;
+            report.resubmitted.push((entry.id, block.id()));
+        }
+
+        Ok(report)
+    }
+
+    /// Check whether any of the locked inputs of `entry` has been spent by a transaction other than `transaction_id`.
+    async fn inputs_spent_elsewhere(
+        &self,
+        client: &Client,
+        entry: &JournalEntry,
+        transaction_id: &TransactionId,
+    ) -> Result<bool> {
+        for output_id in &entry.locked_inputs {
+            let metadata = client.get_output_metadata(output_id).await?;
+
+            if let Some(transaction_id_spent) = &metadata.transaction_id_spent {
+                if TransactionId::from_str(transaction_id_spent)? != *transaction_id {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn transition(&self, id: u64, state: JournalState, block_id: Option<BlockId>) -> Result<()> {
+        let mut entry = self.entry(id).await?;
+
+        entry.state = state;
+        if block_id.is_some() {
+            entry.block_id = block_id;
+        }
+
+        self.write_entry(&entry).await
+    }
+
+    async fn index(&self) -> Result<Vec<u64>> {
+        match self.db.get(JOURNAL_INDEX_KEY).await? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn write_entry(&self, entry: &JournalEntry) -> Result<()> {
+        self.db
+            .insert(entry_key(entry.id).as_bytes(), &serde_json::to_vec(entry)?)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn entry_key(id: u64) -> String {
+    format!("{JOURNAL_ENTRY_KEY_PREFIX}{id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex as StdMutex},
+    };
+
+    use iota_types::{
+        api::response::{BlockMetadataResponse, OutputMetadataResponse, SubmitBlockResponse, TipsResponse},
+        block::{
+            input::{Input, UtxoInput},
+            output::{unlock_condition::AddressUnlockCondition, BasicOutputBuilder, InputsCommitment, UnlockCondition},
+            payload::transaction::{RegularTransactionEssence, TransactionEssence},
+            protocol::ProtocolParameters,
+            rand::{
+                address::rand_address, block::rand_block_id, output::rand_output_id, transaction::rand_transaction_id,
+            },
+            signature::{Ed25519Signature, Signature},
+            unlock::{SignatureUnlock, Unlock, Unlocks},
+            Block, BlockDto,
+        },
+    };
+    use packable::PackableExt;
+
+    use super::*;
+    use crate::{
+        db::MemoryDatabaseProvider,
+        node_api::mock_node::{info_response, mock_node},
+    };
+
+    /// A transaction spending `input`, with a dummy signature.
+    fn signed_payload(input: OutputId) -> TransactionPayload {
+        let protocol_parameters = ProtocolParameters::default();
+        let output = BasicOutputBuilder::new_with_amount(1_000_000)
+            .unwrap()
+            .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(rand_address())))
+            .finish_output(protocol_parameters.token_supply())
+            .unwrap();
+        let essence = RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new([&output].into_iter()),
+        )
+        .with_inputs(vec![Input::Utxo(
+            UtxoInput::new(*input.transaction_id(), input.index()).unwrap(),
+        )])
+        .with_outputs(vec![output])
+        .finish(&protocol_parameters)
+        .unwrap();
+        let unlocks = Unlocks::new(vec![Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(
+            Ed25519Signature::new([0; 32], [0; 64]),
+        )))])
+        .unwrap();
+
+        TransactionPayload::new(TransactionEssence::Regular(essence), unlocks).unwrap()
+    }
+
+    fn block_metadata_response(block_id: BlockId, ledger_inclusion_state: Option<LedgerInclusionStateDto>) -> String {
+        serde_json::to_string(&BlockMetadataResponse {
+            block_id: block_id.to_string(),
+            parents: Vec::new(),
+            is_solid: true,
+            referenced_by_milestone_index: None,
+            milestone_index: None,
+            ledger_inclusion_state,
+            conflict_reason: None,
+            white_flag_index: None,
+            should_promote: Some(false),
+            should_reattach: Some(false),
+        })
+        .unwrap()
+    }
+
+    fn output_metadata_response(output_id: OutputId, transaction_id_spent: Option<TransactionId>) -> String {
+        serde_json::to_string(&OutputMetadataResponse {
+            block_id: rand_block_id().to_string(),
+            transaction_id: output_id.transaction_id().to_string(),
+            output_index: output_id.index(),
+            is_spent: transaction_id_spent.is_some(),
+            milestone_index_spent: transaction_id_spent.map(|_| 2),
+            milestone_timestamp_spent: transaction_id_spent.map(|_| 0),
+            transaction_id_spent: transaction_id_spent.map(|transaction_id| transaction_id.to_string()),
+            milestone_index_booked: 1,
+            milestone_timestamp_booked: 0,
+            ledger_index: 2,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn journal_transitions() {
+        let journal = TransactionJournal::new(MemoryDatabaseProvider::default());
+        let output_id = rand_output_id();
+
+        let first = journal.record_intent(vec![output_id]).await.unwrap();
+        let second = journal.record_intent(Vec::new()).await.unwrap();
+        assert_ne!(first, second);
+
+        assert_eq!(journal.entry(first).await.unwrap().state, JournalState::Intent);
+        assert_eq!(journal.locked_inputs().await.unwrap(), vec![output_id]);
+
+        journal.record_confirmed(first).await.unwrap();
+        assert_eq!(journal.in_flight_entries().await.unwrap().len(), 1);
+        assert!(journal.locked_inputs().await.unwrap().is_empty());
+
+        journal.prune().await.unwrap();
+        assert!(matches!(journal.entry(first).await, Err(Error::NotFound(_))));
+        assert_eq!(journal.entry(second).await.unwrap().state, JournalState::Intent);
+    }
+
+    #[tokio::test]
+    async fn recover() {
+        let journal = TransactionJournal::new(MemoryDatabaseProvider::default());
+
+        // Crashed before signing.
+        let released = journal.record_intent(vec![rand_output_id()]).await.unwrap();
+
+        // Crashed after submitting, the block has been included since.
+        let included_input = rand_output_id();
+        let included_block_id = rand_block_id();
+        let confirmed = journal.record_intent(vec![included_input]).await.unwrap();
+        journal
+            .record_signed(confirmed, &signed_payload(included_input))
+            .await
+            .unwrap();
+        journal.record_submitted(confirmed, included_block_id).await.unwrap();
+
+        // Crashed after submitting, the block is still pending.
+        let pending_input = rand_output_id();
+        let pending_block_id = rand_block_id();
+        let pending = journal.record_intent(vec![pending_input]).await.unwrap();
+        journal
+            .record_signed(pending, &signed_payload(pending_input))
+            .await
+            .unwrap();
+        journal.record_submitted(pending, pending_block_id).await.unwrap();
+
+        // Crashed after signing, the input has been spent by another transaction since.
+        let conflicting_input = rand_output_id();
+        let conflicting = journal.record_intent(vec![conflicting_input]).await.unwrap();
+        journal
+            .record_signed(conflicting, &signed_payload(conflicting_input))
+            .await
+            .unwrap();
+
+        // Crashed after signing, before the transaction reached the node.
+        let unsent_input = rand_output_id();
+        let unsent = journal.record_intent(vec![unsent_input]).await.unwrap();
+        journal
+            .record_signed(unsent, &signed_payload(unsent_input))
+            .await
+            .unwrap();
+
+        let responses = HashMap::from([
+            ("/api/core/v2/info".to_string(), info_response(2)),
+            (
+                "/api/core/v2/tips".to_string(),
+                serde_json::to_string(&TipsResponse {
+                    tips: vec![rand_block_id().to_string()],
+                })
+                .unwrap(),
+            ),
+            (
+                format!("/api/core/v2/blocks/{included_block_id}/metadata"),
+                block_metadata_response(included_block_id, Some(LedgerInclusionStateDto::Included)),
+            ),
+            (
+                format!("/api/core/v2/blocks/{pending_block_id}/metadata"),
+                block_metadata_response(pending_block_id, None),
+            ),
+            (
+                format!("/api/core/v2/outputs/{conflicting_input}/metadata"),
+                output_metadata_response(conflicting_input, Some(rand_transaction_id())),
+            ),
+            (
+                format!("/api/core/v2/outputs/{unsent_input}/metadata"),
+                output_metadata_response(unsent_input, None),
+            ),
+        ]);
+        // The blocks posted to the node, by id.
+        let posted_blocks = Arc::new(StdMutex::new(HashMap::new()));
+        let posted_blocks_ = posted_blocks.clone();
+        let node_url = mock_node(move |request| {
+            let protocol_parameters = ProtocolParameters::default();
+            let mut posted_blocks = posted_blocks_.lock().unwrap();
+
+            if request.method == "POST" && request.path == "/api/core/v2/blocks" {
+                let block = Block::unpack_verified(&request.body, &protocol_parameters).unwrap();
+                let block_id = block.id();
+                posted_blocks.insert(format!("/api/core/v2/blocks/{block_id}"), block);
+
+                return Some(
+                    serde_json::to_string(&SubmitBlockResponse {
+                        block_id: block_id.to_string(),
+                    })
+                    .unwrap(),
+                );
+            }
+
+            responses.get(&request.path).cloned().or_else(|| {
+                posted_blocks
+                    .get(&request.path)
+                    .map(|block| serde_json::to_string(&BlockDto::from(block)).unwrap())
+            })
+        });
+        let client = Client::builder()
+            .with_node(&node_url)
+            .unwrap()
+            .with_ignore_node_health()
+            .with_local_pow(false)
+            .finish()
+            .unwrap();
+
+        let report = journal.recover(&client).await.unwrap();
+
+        assert_eq!(report.released, vec![released]);
+        assert_eq!(report.confirmed, vec![confirmed]);
+        assert_eq!(report.pending, vec![pending]);
+        assert_eq!(report.conflicting, vec![conflicting]);
+        assert_eq!(report.resubmitted.len(), 1);
+        let (resubmitted, block_id) = report.resubmitted[0];
+        assert_eq!(resubmitted, unsent);
+
+        // The transaction has been resubmitted as signed.
+        let posted_blocks = posted_blocks.lock().unwrap();
+        let posted_block = &posted_blocks[&format!("/api/core/v2/blocks/{block_id}")];
+        assert_eq!(
+            posted_block.payload(),
+            Some(&Payload::from(signed_payload_of(&journal, unsent).await))
+        );
+
+        assert_eq!(journal.entry(released).await.unwrap().state, JournalState::Released);
+        assert_eq!(journal.entry(confirmed).await.unwrap().state, JournalState::Confirmed);
+        assert_eq!(journal.entry(pending).await.unwrap().state, JournalState::Submitted);
+        assert_eq!(
+            journal.entry(conflicting).await.unwrap().state,
+            JournalState::Conflicting
+        );
+        let unsent_entry = journal.entry(unsent).await.unwrap();
+        assert_eq!(unsent_entry.state, JournalState::Submitted);
+        assert_eq!(unsent_entry.block_id, Some(block_id));
+
+        // Only the inputs of the transactions still in flight stay locked.
+        let mut locked_inputs = journal.locked_inputs().await.unwrap();
+        locked_inputs.sort_unstable();
+        let mut expected_locked_inputs = vec![pending_input, unsent_input];
+        expected_locked_inputs.sort_unstable();
+        assert_eq!(locked_inputs, expected_locked_inputs);
+    }
+
+    /// The signed payload recorded in the entry `id`.
+    async fn signed_payload_of(journal: &TransactionJournal<MemoryDatabaseProvider>, id: u64) -> TransactionPayload {
+        let payload = journal.entry(id).await.unwrap().payload.unwrap();
+
+        TransactionPayload::try_from_dto(&payload, &ProtocolParameters::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn block_builder_releases_unsent_transaction() {
+        let journal = TransactionJournal::new(MemoryDatabaseProvider::default());
+        let input = rand_output_id();
+        // The input can't be found, so the transaction can't be prepared.
+        let node_url = mock_node(|request| (request.path == "/api/core/v2/info").then(|| info_response(1)));
+        let client = Client::builder()
+            .with_node(&node_url)
+            .unwrap()
+            .with_ignore_node_health()
+            .finish()
+            .unwrap();
+
+        let output = BasicOutputBuilder::new_with_amount(1_000_000)
+            .unwrap()
+            .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(rand_address())))
+            .finish_output(ProtocolParameters::default().token_supply())
+            .unwrap();
+        let result = client
+            .block()
+            .with_input(UtxoInput::new(*input.transaction_id(), input.index()).unwrap())
+            .unwrap()
+            .with_outputs(vec![output])
+            .unwrap()
+            .with_journal(&journal)
+            .finish()
+            .await;
+        assert!(result.is_err());
+
+        let entry = journal.entry(0).await.unwrap();
+        assert_eq!(entry.state, JournalState::Released);
+        assert_eq!(entry.locked_inputs, vec![input]);
+        assert!(journal.locked_inputs().await.unwrap().is_empty());
+    }
+}
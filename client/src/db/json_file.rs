@@ -0,0 +1,247 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! JSON-lines-file-as-a-Database implementation, for debugging.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+
+use super::{BatchOperation, DatabaseProvider};
+use crate::{Error, Result};
+
+/// The name of the file holding the records, in the directory of the provider.
+const RECORDS_FILENAME: &str = "records.jsonl";
+
+/// A record, as a line of the file.
+///
+/// Keys and values are written as text when they're valid UTF-8, which they are for the JSON data of the client, and
+/// as hex otherwise.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLine {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_hex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value_hex: Option<String>,
+}
+
+impl JsonLine {
+    fn new(key: &[u8], value: &[u8]) -> Self {
+        let mut line = Self::default();
+
+        match std::str::from_utf8(key) {
+            Ok(key) => line.key = Some(key.to_string()),
+            Err(_) => line.key_hex = Some(prefix_hex::encode(key)),
+        }
+        match std::str::from_utf8(value) {
+            Ok(value) => line.value = Some(value.to_string()),
+            Err(_) => line.value_hex = Some(prefix_hex::encode(value)),
+        }
+
+        line
+    }
+
+    fn into_record(self) -> Result<(Vec<u8>, Vec<u8>)> {
+        let key = match (self.key, self.key_hex) {
+            (Some(key), _) => key.into_bytes(),
+            (None, Some(key_hex)) => prefix_hex::decode(&key_hex)?,
+            (None, None) => return Err(Error::JsonFileRecordInvalid("missing key".to_string())),
+        };
+        let value = match (self.value, self.value_hex) {
+            (Some(value), _) => value.into_bytes(),
+            (None, Some(value_hex)) => prefix_hex::decode(&value_hex)?,
+            (None, None) => return Err(Error::JsonFileRecordInvalid("missing value".to_string())),
+        };
+
+        Ok((key, value))
+    }
+}
+
+/// A database provider writing each record as a JSON line of a plain text file in a directory, e.g. to inspect the
+/// state of an application during development, or to export the records of an encrypted provider with
+/// [`export()`](Self::export()).
+///
+/// The records are held in memory and the whole file is rewritten on each change, so this provider isn't meant for
+/// large or busy databases. Nothing is encrypted.
+pub struct JsonFileDatabaseProvider {
+    path: PathBuf,
+    records: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl JsonFileDatabaseProvider {
+    /// Open the database in `directory`, creating it if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(directory: P) -> Result<Self> {
+        fs::create_dir_all(&directory)?;
+
+        let path = directory.as_ref().join(RECORDS_FILENAME);
+        let mut records = BTreeMap::new();
+
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let (key, value) = serde_json::from_str::<JsonLine>(&line)?.into_record()?;
+                records.insert(key, value);
+            }
+        }
+
+        Ok(Self {
+            path,
+            records: Mutex::new(records),
+        })
+    }
+
+    /// Copy all records of `source` in the database in `directory`, e.g. to inspect the records of an encrypted
+    /// provider.
+    pub async fn export<D: DatabaseProvider + ?Sized, P: AsRef<Path>>(source: &D, directory: P) -> Result<Self> {
+        let provider = Self::open(directory)?;
+
+        provider.insert_batch(&source.iter_prefix(&[]).await?).await?;
+
+        Ok(provider)
+    }
+
+    /// Apply `change` to the records and rewrite the file.
+    fn change<T>(&self, change: impl FnOnce(&mut BTreeMap<Vec<u8>, Vec<u8>>) -> T) -> Result<T> {
+        let mut records = self.records.lock().map_err(|_| Error::PoisonError)?;
+        let mut changed = records.clone();
+        let output = change(&mut changed);
+
+        // Written to a temporary file first, so that an interrupted write doesn't lose the records.
+        let temporary_path = self.path.with_extension("jsonl.tmp");
+        let mut writer = BufWriter::new(File::create(&temporary_path)?);
+
+        for (key, value) in &changed {
+            serde_json::to_writer(&mut writer, &JsonLine::new(key, value))?;
+            writer.write_all(b"\n")?;
+        }
+
+        writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+        fs::rename(&temporary_path, &self.path)?;
+
+        *records = changed;
+
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl DatabaseProvider for JsonFileDatabaseProvider {
+    async fn get(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.records.lock().map_err(|_| Error::PoisonError)?.get(k).cloned())
+    }
+
+    async fn insert(&self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.change(|records| records.insert(k.to_vec(), v.to_vec()))
+    }
+
+    async fn delete(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.change(|records| records.remove(k))
+    }
+
+    async fn insert_batch(&self, batch: &[(Vec<u8>, Vec<u8>)]) -> Result<()> {
+        self.change(|records| records.extend(batch.iter().cloned()))
+    }
+
+    async fn delete_batch(&self, keys: &[Vec<u8>]) -> Result<()> {
+        self.change(|records| {
+            for k in keys {
+                records.remove(k);
+            }
+        })
+    }
+
+    async fn apply_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        self.change(|records| {
+            for operation in operations {
+                match operation {
+                    BatchOperation::Put { key, value } => records.insert(key, value),
+                    BatchOperation::Delete { key } => records.remove(&key),
+                };
+            }
+        })
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        Ok(self
+            .records
+            .lock()
+            .map_err(|_| Error::PoisonError)?
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    async fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        Ok(self
+            .records
+            .lock()
+            .map_err(|_| Error::PoisonError)?
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDatabaseProvider;
+
+    #[tokio::test]
+    async fn json_file_database_provider() {
+        let directory = "test_json_file_database_provider";
+        let provider = JsonFileDatabaseProvider::open(directory).unwrap();
+
+        provider.insert(b"outputs/0", br#"{"amount":"1"}"#).await.unwrap();
+        provider.insert(&[0xff, 0x00], &[0xfe]).await.unwrap();
+        provider.insert(b"outputs/1", b"2").await.unwrap();
+        assert_eq!(provider.delete(b"outputs/1").await.unwrap(), Some(b"2".to_vec()));
+
+        let contents = fs::read_to_string(format!("{directory}/{RECORDS_FILENAME}")).unwrap();
+        assert_eq!(
+            contents,
+            "{\"key\":\"outputs/0\",\"value\":\"{\\\"amount\\\":\\\"1\\\"}\"}\n{\"keyHex\":\"0xff00\",\"valueHex\":\"0xfe\"}\n"
+        );
+
+        // The records are read back from the file.
+        let provider = JsonFileDatabaseProvider::open(directory).unwrap();
+        assert_eq!(
+            provider.iter_prefix(b"").await.unwrap(),
+            vec![
+                (b"outputs/0".to_vec(), br#"{"amount":"1"}"#.to_vec()),
+                (vec![0xff, 0x00], vec![0xfe])
+            ]
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn json_file_export() {
+        let directory = "test_json_file_export";
+        let source = MemoryDatabaseProvider::default();
+        source.insert(b"key", b"value").await.unwrap();
+
+        let export = JsonFileDatabaseProvider::export(&source, directory).await.unwrap();
+        assert_eq!(export.get(b"key").await.unwrap(), Some(b"value".to_vec()));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
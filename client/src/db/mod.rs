@@ -3,28 +3,248 @@
 
 //! Database provider interfaces and implementations.
 
+mod batch;
+mod collection;
+mod compression;
+mod expiry;
+mod instrumentation;
+mod journal;
+#[cfg(not(target_family = "wasm"))]
+mod json_file;
+#[cfg(feature = "rocksdb")]
+mod rocksdb;
+#[cfg(feature = "sled")]
+mod sled;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 #[cfg(feature = "stronghold")]
 mod stronghold;
 
+use std::time::Duration;
+
 use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use serde::{de::DeserializeOwned, Serialize};
 
+#[cfg(not(target_family = "wasm"))]
+pub use self::expiry::ExpirationTask;
+#[cfg(feature = "metrics")]
+pub use self::instrumentation::MetricsInstrumentation;
+#[cfg(not(target_family = "wasm"))]
+pub use self::json_file::JsonFileDatabaseProvider;
+#[cfg(feature = "rocksdb")]
+pub use self::rocksdb::RocksdbDatabaseProvider;
+#[cfg(feature = "sled")]
+pub use self::sled::SledDatabaseProvider;
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::{Migration, SqliteDatabaseProvider};
 #[cfg(feature = "stronghold")]
 pub use self::stronghold::StrongholdDatabaseProvider;
+pub use self::{
+    batch::{BatchOperation, WriteBatch},
+    collection::Collection,
+    compression::Compression,
+    expiry::EXPIRY_KEY_PREFIX,
+    instrumentation::{DatabaseInstrumentation, DatabaseOperation, InstrumentedDatabaseProvider},
+    journal::{JournalEntry, JournalState, RecoveryReport, TransactionJournal},
+};
 use crate::Result;
 
 /// The interface for database providers.
+///
+/// All operations take `&self`, so that a provider can be shared, e.g. in an `Arc`, and used concurrently; providers
+/// synchronize their state internally.
 #[async_trait]
-pub trait DatabaseProvider {
+pub trait DatabaseProvider: Send + Sync {
     /// Get a value out of the database.
-    async fn get(&mut self, k: &[u8]) -> Result<Option<Vec<u8>>>;
+    async fn get(&self, k: &[u8]) -> Result<Option<Vec<u8>>>;
 
     /// Insert a value into the database.
     ///
     /// If there exists a record under the same key as `k`, it will be replaced by the new value (`v`) and returned.
-    async fn insert(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>>;
+    async fn insert(&self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Insert a value into the database, to be deleted once `ttl` has elapsed, e.g. for cached node responses or
+    /// session data.
+    ///
+    /// The expiry time is held in another record, under the key prefixed with [`EXPIRY_KEY_PREFIX`], and expired
+    /// records are deleted by [`purge_expired()`](DatabaseProvider::purge_expired()), so they can still be read until
+    /// then. Inserting under the same key with [`insert()`](DatabaseProvider::insert()) keeps the expiry time.
+    async fn insert_with_ttl(&self, k: &[u8], v: &[u8], ttl: Duration) -> Result<Option<Vec<u8>>> {
+        let old_value = self.get(k).await?;
+
+        self.apply_batch(expiry::insert_with_ttl_operations(k, v, ttl)).await?;
+
+        Ok(old_value)
+    }
+
+    /// Delete the records inserted with [`insert_with_ttl()`](DatabaseProvider::insert_with_ttl()) that have expired,
+    /// returning how many have been deleted.
+    ///
+    /// See [`ExpirationTask`] to do it periodically in the background.
+    async fn purge_expired(&self) -> Result<usize> {
+        let operations = expiry::purge_operations(self.iter_prefix(EXPIRY_KEY_PREFIX).await?);
+        let purged = operations.len() / 2;
+
+        self.apply_batch(operations).await?;
+
+        Ok(purged)
+    }
 
     /// Delete a value from the database.
     ///
     /// The deleted value is returned.
-    async fn delete(&mut self, k: &[u8]) -> Result<Option<Vec<u8>>>;
+    async fn delete(&self, k: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Insert several values into the database, replacing the records under the same keys, if any.
+    ///
+    /// Providers persisting their changes do it once, after all the values have been inserted, which makes bulk
+    /// imports faster than inserting the values one by one.
+    async fn insert_batch(&self, records: &[(Vec<u8>, Vec<u8>)]) -> Result<()> {
+        for (k, v) in records {
+            self.insert(k, v).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete several values from the database.
+    ///
+    /// Providers persisting their changes do it once, after all the values have been deleted.
+    async fn delete_batch(&self, keys: &[Vec<u8>]) -> Result<()> {
+        for k in keys {
+            self.delete(k).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Start a batch of changes, applied atomically when it's committed with [`WriteBatch::commit()`].
+    ///
+    /// Use [`WriteBatch::new()`] to start a batch on a `dyn DatabaseProvider`.
+    fn batch(&self) -> WriteBatch<'_>
+    where
+        Self: Sized,
+    {
+        WriteBatch::new(self)
+    }
+
+    /// Get the collection `name` of values of type `T`, see [`Collection`].
+    ///
+    /// Use [`Collection::new()`] to get a collection of a `dyn DatabaseProvider`.
+    fn collection<T: Serialize + DeserializeOwned>(&self, name: &str) -> Collection<'_, T>
+    where
+        Self: Sized,
+    {
+        Collection::new(self, name)
+    }
+
+    /// Apply the `operations` of a [`WriteBatch`] atomically: after a crash, either all or none of them have been
+    /// applied.
+    ///
+    /// The default implementation applies the operations one by one, so providers able to guarantee atomicity
+    /// override it.
+    async fn apply_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        for operation in operations {
+            match operation {
+                BatchOperation::Put { key, value } => self.insert(&key, &value).await?,
+                BatchOperation::Delete { key } => self.delete(&key).await?,
+            };
+        }
+
+        Ok(())
+    }
+
+    /// List the keys of all records in the database, in no particular order.
+    async fn keys(&self) -> Result<Vec<Vec<u8>>>;
+
+    /// Stream all records of the database, in no particular order.
+    ///
+    /// Only the keys are held in memory; the values are read one by one as the stream is polled, so that large
+    /// databases can be processed incrementally, e.g. to export or re-encrypt them. Records deleted in the meantime
+    /// are skipped.
+    fn entries(&self) -> BoxStream<'_, Result<(Vec<u8>, Vec<u8>)>> {
+        futures::stream::once(self.keys())
+            .map_ok(move |keys| {
+                futures::stream::iter(keys)
+                    .then(move |key| async move { Ok(self.get(&key).await?.map(|value| (key, value))) })
+                    .try_filter_map(|record| futures::future::ready(Ok(record)))
+            })
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Get the records whose key starts with `prefix`, sorted by key.
+    async fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut keys = self.keys().await?;
+        keys.retain(|key| key.starts_with(prefix));
+        keys.sort();
+
+        let mut records = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            // A record could have been deleted in the meantime.
+            if let Some(value) = self.get(&key).await? {
+                records.push((key, value));
+            }
+        }
+
+        Ok(records)
+    }
+}
+
+/// A database provider keeping the records in memory, for tests.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MemoryDatabaseProvider(std::sync::Mutex<std::collections::HashMap<Vec<u8>, Vec<u8>>>);
+
+#[cfg(test)]
+#[async_trait]
+impl DatabaseProvider for MemoryDatabaseProvider {
+    async fn get(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.lock().unwrap().get(k).cloned())
+    }
+
+    async fn insert(&self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.lock().unwrap().insert(k.to_vec(), v.to_vec()))
+    }
+
+    async fn delete(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.lock().unwrap().remove(k))
+    }
+
+    async fn apply_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        let mut records = self.0.lock().unwrap();
+
+        for operation in operations {
+            match operation {
+                BatchOperation::Put { key, value } => records.insert(key, value),
+                BatchOperation::Delete { key } => records.remove(&key),
+            };
+        }
+
+        Ok(())
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        Ok(self.0.lock().unwrap().keys().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn entries() {
+        let provider = MemoryDatabaseProvider::default();
+        let records = (0..10)
+            .map(|i| (format!("key-{i}").into_bytes(), i.to_string().into_bytes()))
+            .collect::<Vec<_>>();
+        provider.insert_batch(&records).await.unwrap();
+
+        let mut entries = provider.entries().try_collect::<Vec<_>>().await.unwrap();
+        entries.sort();
+        assert_eq!(entries, records);
+    }
 }
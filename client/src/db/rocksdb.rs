@@ -0,0 +1,253 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! RocksDB-as-a-Database implementation.
+
+use std::{path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use rocksdb::{ColumnFamily, Direction, IteratorMode, Options, WriteBatch, DB, DEFAULT_COLUMN_FAMILY_NAME};
+
+use super::{BatchOperation, Compression, DatabaseProvider};
+use crate::{Error, Result};
+
+/// The column family recording the compression of the values of each column family of a database.
+const COMPRESSION_COLUMN_FAMILY: &str = "iota_client_compression";
+
+/// RocksDB as a database provider, e.g. for high-volume data like cached outputs.
+///
+/// The records are held in a column family of the database, the default one unless another one is selected with
+/// [`with_column_family()`](Self::with_column_family()); providers of the same database with different column
+/// families share it, while keeping their records apart.
+///
+/// The values can be compressed by the provider, see [`with_compression()`](Self::with_compression()), or by RocksDB
+/// itself, per block, with [`Options::set_compression_type()`].
+pub struct RocksdbDatabaseProvider {
+    db: Arc<DB>,
+    column_family: String,
+    compression: Compression,
+}
+
+impl RocksdbDatabaseProvider {
+    /// Open the database at `path`, creating it if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_options(path, Options::default(), &[])
+    }
+
+    /// Open the database at `path` with `options`, creating it and its `column_families` if they don't exist.
+    pub fn open_with_options<P: AsRef<Path>>(path: P, mut options: Options, column_families: &[&str]) -> Result<Self> {
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+
+        let column_families = column_families
+            .iter()
+            .copied()
+            .chain(core::iter::once(COMPRESSION_COLUMN_FAMILY));
+        let mut provider = Self {
+            db: Arc::new(DB::open_cf(&options, path, column_families)?),
+            column_family: DEFAULT_COLUMN_FAMILY_NAME.to_string(),
+            compression: Compression::None,
+        };
+        provider.compression = provider.recorded_compression()?;
+
+        Ok(provider)
+    }
+
+    /// Get a provider of the same database holding its records in `column_family`, which has to be one of the column
+    /// families the database has been opened with.
+    pub fn with_column_family(&self, column_family: &str) -> Result<Self> {
+        let mut provider = Self {
+            db: self.db.clone(),
+            column_family: column_family.to_string(),
+            compression: Compression::None,
+        };
+
+        // Fail early rather than on the first access.
+        provider.column_family()?;
+        provider.compression = provider.recorded_compression()?;
+
+        Ok(provider)
+    }
+
+    /// Compress the values before storing them, see [`Compression`].
+    ///
+    /// Fails with [`Error::CompressionMismatch`] if the column family already holds values stored with another
+    /// compression.
+    pub fn with_compression(mut self, compression: Compression) -> Result<Self> {
+        if compression != self.compression {
+            let has_records = self
+                .db
+                .iterator_cf(self.column_family()?, IteratorMode::Start)
+                .next()
+                .transpose()?
+                .is_some();
+            self.compression.check_switch(compression, has_records)?;
+
+            self.db.put_cf(
+                self.compression_column_family()?,
+                &self.column_family,
+                compression.name(),
+            )?;
+            self.compression = compression;
+        }
+
+        Ok(self)
+    }
+
+    /// The compression recorded for the column family of the provider.
+    fn recorded_compression(&self) -> Result<Compression> {
+        Compression::from_recorded(
+            self.db
+                .get_cf(self.compression_column_family()?, &self.column_family)?
+                .as_deref(),
+        )
+    }
+
+    fn compression_column_family(&self) -> Result<&ColumnFamily> {
+        self.db
+            .cf_handle(COMPRESSION_COLUMN_FAMILY)
+            .ok_or_else(|| Error::RocksdbColumnFamilyNotFound(COMPRESSION_COLUMN_FAMILY.to_string()))
+    }
+
+    fn column_family(&self) -> Result<&ColumnFamily> {
+        self.db
+            .cf_handle(&self.column_family)
+            .ok_or_else(|| Error::RocksdbColumnFamilyNotFound(self.column_family.clone()))
+    }
+}
+
+#[async_trait]
+impl DatabaseProvider for RocksdbDatabaseProvider {
+    async fn get(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.db
+            .get_cf(self.column_family()?, k)?
+            .map(|value| self.compression.decompress(value))
+            .transpose()
+    }
+
+    async fn insert(&self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>> {
+        let column_family = self.column_family()?;
+        let old_value = self.db.get_cf(column_family, k)?;
+
+        self.db.put_cf(column_family, k, self.compression.compress(v)?)?;
+
+        old_value.map(|value| self.compression.decompress(value)).transpose()
+    }
+
+    async fn delete(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        let column_family = self.column_family()?;
+        let old_value = self.db.get_cf(column_family, k)?;
+
+        self.db.delete_cf(column_family, k)?;
+
+        old_value.map(|value| self.compression.decompress(value)).transpose()
+    }
+
+    async fn insert_batch(&self, records: &[(Vec<u8>, Vec<u8>)]) -> Result<()> {
+        let column_family = self.column_family()?;
+        let mut batch = WriteBatch::default();
+
+        for (k, v) in records {
+            batch.put_cf(column_family, k, self.compression.compress(v)?);
+        }
+
+        Ok(self.db.write(batch)?)
+    }
+
+    async fn delete_batch(&self, keys: &[Vec<u8>]) -> Result<()> {
+        let column_family = self.column_family()?;
+        let mut batch = WriteBatch::default();
+
+        for k in keys {
+            batch.delete_cf(column_family, k);
+        }
+
+        Ok(self.db.write(batch)?)
+    }
+
+    async fn apply_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        let column_family = self.column_family()?;
+        let mut batch = WriteBatch::default();
+
+        for operation in operations {
+            match operation {
+                BatchOperation::Put { key, value } => {
+                    batch.put_cf(column_family, key, self.compression.compress(&value)?)
+                }
+                BatchOperation::Delete { key } => batch.delete_cf(column_family, key),
+            }
+        }
+
+        Ok(self.db.write(batch)?)
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        self.db
+            .iterator_cf(self.column_family()?, IteratorMode::Start)
+            .map(|record| Ok(record?.0.into_vec()))
+            .collect()
+    }
+
+    async fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut records = Vec::new();
+
+        // The records are sorted by key, so the ones with the prefix follow each other.
+        for record in self
+            .db
+            .iterator_cf(self.column_family()?, IteratorMode::From(prefix, Direction::Forward))
+        {
+            let (key, value) = record?;
+
+            if !key.starts_with(prefix) {
+                break;
+            }
+
+            records.push((key.into_vec(), self.compression.decompress(value.into_vec())?));
+        }
+
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn rocksdb_database_provider() {
+        let path = "test_rocksdb_database_provider";
+        let provider = RocksdbDatabaseProvider::open_with_options(path, Options::default(), &["outputs"]).unwrap();
+
+        assert_eq!(provider.insert(b"b", b"1").await.unwrap(), None);
+        assert_eq!(provider.insert(b"b", b"2").await.unwrap().as_deref(), Some(&b"1"[..]));
+        provider
+            .insert_batch(&[(b"a1".to_vec(), b"3".to_vec()), (b"a0".to_vec(), b"4".to_vec())])
+            .await
+            .unwrap();
+
+        assert_eq!(provider.get(b"b").await.unwrap().as_deref(), Some(&b"2"[..]));
+        assert_eq!(
+            provider.iter_prefix(b"a").await.unwrap(),
+            vec![(b"a0".to_vec(), b"4".to_vec()), (b"a1".to_vec(), b"3".to_vec())]
+        );
+
+        // The records of another column family are kept apart.
+        let outputs = provider.with_column_family("outputs").unwrap();
+        assert_eq!(outputs.get(b"b").await.unwrap(), None);
+        outputs.insert(b"c", b"5").await.unwrap();
+        assert_eq!(outputs.keys().await.unwrap(), vec![b"c".to_vec()]);
+        assert!(matches!(
+            provider.with_column_family("missing"),
+            Err(Error::RocksdbColumnFamilyNotFound(_))
+        ));
+
+        assert_eq!(provider.delete(b"b").await.unwrap().as_deref(), Some(&b"2"[..]));
+        provider.delete_batch(&[b"a0".to_vec(), b"a1".to_vec()]).await.unwrap();
+        assert!(provider.keys().await.unwrap().is_empty());
+
+        drop((provider, outputs));
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,280 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sled-as-a-Database implementation.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use crypto::ciphers::chacha;
+use sled::{Batch, Db, IVec, Tree};
+use zeroize::Zeroizing;
+
+use super::{BatchOperation, Compression, DatabaseProvider};
+use crate::Result;
+
+/// The tree recording the compression of the values of each tree of a database.
+const COMPRESSION_TREE: &str = "iota_client_compression";
+
+/// Sled as a pure Rust embedded database provider, e.g. for data too bulky to be held in a Stronghold snapshot.
+///
+/// The values can be compressed, see [`with_compression()`](Self::with_compression()), and encrypted with a key set
+/// with [`with_encryption_key()`](Self::with_encryption_key()); the keys are stored as is, so that the records can
+/// still be looked up and iterated by prefix.
+pub struct SledDatabaseProvider {
+    tree: Tree,
+    compression_tree: Tree,
+    encryption_key: Option<Zeroizing<[u8; 32]>>,
+    compression: Compression,
+}
+
+impl SledDatabaseProvider {
+    /// Open the database at `path`, creating it if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db = sled::open(path)?;
+        // The default tree of the database.
+        let tree = (*db).clone();
+
+        Self::with_tree(&db, tree)
+    }
+
+    /// Open the tree `name` of the database at `path`, creating them if they don't exist.
+    ///
+    /// The trees of a database hold their records apart from each other.
+    pub fn open_tree<P: AsRef<Path>>(path: P, name: &str) -> Result<Self> {
+        let db = sled::open(path)?;
+        let tree = db.open_tree(name)?;
+
+        Self::with_tree(&db, tree)
+    }
+
+    fn with_tree(db: &Db, tree: Tree) -> Result<Self> {
+        let compression_tree = db.open_tree(COMPRESSION_TREE)?;
+        let compression = Compression::from_recorded(compression_tree.get(tree.name())?.as_deref())?;
+
+        Ok(Self {
+            tree,
+            compression_tree,
+            encryption_key: None,
+            compression,
+        })
+    }
+
+    /// Encrypt the values with `key`, with XChaCha20-Poly1305.
+    ///
+    /// The same key has to be used every time the database is opened; values can't be decrypted with another one.
+    pub fn with_encryption_key(mut self, key: [u8; 32]) -> Self {
+        self.encryption_key = Some(Zeroizing::new(key));
+        self
+    }
+
+    /// Compress the values, before encrypting them, see [`Compression`].
+    ///
+    /// Fails with [`Error::CompressionMismatch`](crate::Error::CompressionMismatch) if the tree already holds values
+    /// stored with another compression.
+    pub fn with_compression(mut self, compression: Compression) -> Result<Self> {
+        if compression != self.compression {
+            self.compression.check_switch(compression, !self.tree.is_empty())?;
+            self.compression_tree.insert(self.tree.name(), compression.name())?;
+            self.compression_tree.flush()?;
+            self.compression = compression;
+        }
+
+        Ok(self)
+    }
+
+    fn encrypt(&self, value: &[u8]) -> Result<Vec<u8>> {
+        let value = self.compression.compress(value)?;
+
+        match &self.encryption_key {
+            Some(key) => Ok(chacha::aead_encrypt(key.as_slice(), &value)?),
+            None => Ok(value),
+        }
+    }
+
+    fn decrypt(&self, value: IVec) -> Result<Vec<u8>> {
+        let value = match &self.encryption_key {
+            Some(key) => chacha::aead_decrypt(key.as_slice(), &value)?,
+            None => value.to_vec(),
+        };
+
+        self.compression.decompress(value)
+    }
+
+    fn decrypt_option(&self, value: Option<IVec>) -> Result<Option<Vec<u8>>> {
+        value.map(|value| self.decrypt(value)).transpose()
+    }
+}
+
+#[async_trait]
+impl DatabaseProvider for SledDatabaseProvider {
+    async fn get(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.decrypt_option(self.tree.get(k)?)
+    }
+
+    async fn insert(&self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>> {
+        let old_value = self.tree.insert(k, self.encrypt(v)?)?;
+
+        self.tree.flush_async().await?;
+
+        self.decrypt_option(old_value)
+    }
+
+    async fn delete(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        let old_value = self.tree.remove(k)?;
+
+        self.tree.flush_async().await?;
+
+        self.decrypt_option(old_value)
+    }
+
+    async fn insert_batch(&self, records: &[(Vec<u8>, Vec<u8>)]) -> Result<()> {
+        let mut batch = Batch::default();
+
+        for (k, v) in records {
+            batch.insert(k.as_slice(), self.encrypt(v)?);
+        }
+
+        self.tree.apply_batch(batch)?;
+        self.tree.flush_async().await?;
+
+        Ok(())
+    }
+
+    async fn delete_batch(&self, keys: &[Vec<u8>]) -> Result<()> {
+        let mut batch = Batch::default();
+
+        for k in keys {
+            batch.remove(k.as_slice());
+        }
+
+        self.tree.apply_batch(batch)?;
+        self.tree.flush_async().await?;
+
+        Ok(())
+    }
+
+    async fn apply_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        let mut batch = Batch::default();
+
+        for operation in operations {
+            match operation {
+                BatchOperation::Put { key, value } => batch.insert(key, self.encrypt(&value)?),
+                BatchOperation::Delete { key } => batch.remove(key),
+            }
+        }
+
+        self.tree.apply_batch(batch)?;
+        self.tree.flush_async().await?;
+
+        Ok(())
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        self.tree.iter().keys().map(|key| Ok(key?.to_vec())).collect()
+    }
+
+    async fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.tree
+            .scan_prefix(prefix)
+            .map(|record| {
+                let (key, value) = record?;
+                Ok((key.to_vec(), self.decrypt(value)?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sled_database_provider() {
+        let path = "test_sled_database_provider";
+        let provider = SledDatabaseProvider::open(path).unwrap();
+
+        assert_eq!(provider.insert(b"b", b"1").await.unwrap(), None);
+        assert_eq!(provider.insert(b"b", b"2").await.unwrap(), Some(b"1".to_vec()));
+        provider
+            .insert_batch(&[(b"a1".to_vec(), b"3".to_vec()), (b"a0".to_vec(), b"4".to_vec())])
+            .await
+            .unwrap();
+
+        assert_eq!(provider.get(b"b").await.unwrap(), Some(b"2".to_vec()));
+        assert_eq!(
+            provider.iter_prefix(b"a").await.unwrap(),
+            vec![(b"a0".to_vec(), b"4".to_vec()), (b"a1".to_vec(), b"3".to_vec())]
+        );
+
+        assert_eq!(provider.delete(b"b").await.unwrap(), Some(b"2".to_vec()));
+        provider.delete_batch(&[b"a0".to_vec(), b"a1".to_vec()]).await.unwrap();
+        assert!(provider.keys().await.unwrap().is_empty());
+
+        drop(provider);
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sled_database_provider_encryption() {
+        let path = "test_sled_database_provider_encryption";
+        let provider = SledDatabaseProvider::open_tree(path, "encrypted")
+            .unwrap()
+            .with_encryption_key([1; 32]);
+
+        provider.insert(b"key", b"value").await.unwrap();
+        assert_eq!(provider.get(b"key").await.unwrap(), Some(b"value".to_vec()));
+
+        // The value is stored encrypted.
+        let stored = provider.tree.get(b"key").unwrap().unwrap();
+        assert_ne!(stored.as_ref(), b"value");
+
+        // And can't be decrypted with another key.
+        let provider = SledDatabaseProvider {
+            tree: provider.tree.clone(),
+            compression_tree: provider.compression_tree.clone(),
+            encryption_key: None,
+            compression: Compression::None,
+        }
+        .with_encryption_key([2; 32]);
+        assert!(provider.get(b"key").await.is_err());
+
+        drop(provider);
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn sled_database_provider_compression() {
+        let path = "test_sled_database_provider_compression";
+        let provider = SledDatabaseProvider::open_tree(path, "compressed")
+            .unwrap()
+            .with_compression(Compression::Zstd)
+            .unwrap();
+        provider.insert(b"key", &[42; 1024]).await.unwrap();
+        drop(provider);
+
+        // The recorded compression is used, and can't be switched as the tree holds values.
+        let provider = SledDatabaseProvider::open_tree(path, "compressed").unwrap();
+        assert_eq!(provider.get(b"key").await.unwrap(), Some(vec![42; 1024]));
+        assert!(matches!(
+            provider.with_compression(Compression::None),
+            Err(crate::Error::CompressionMismatch { .. })
+        ));
+
+        // An uncompressed tree can't be switched either, also with values starting like a marker.
+        let provider = SledDatabaseProvider::open(path).unwrap();
+        provider.insert(b"key", &[0, 1, 2]).await.unwrap();
+        assert!(matches!(
+            provider.with_compression(Compression::Zstd),
+            Err(crate::Error::CompressionMismatch { .. })
+        ));
+        let provider = SledDatabaseProvider::open(path).unwrap();
+        assert_eq!(provider.get(b"key").await.unwrap(), Some(vec![0, 1, 2]));
+
+        drop(provider);
+        fs::remove_dir_all(path).unwrap();
+    }
+}
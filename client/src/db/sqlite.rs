@@ -0,0 +1,401 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! SQLite-as-a-Database implementation.
+//!
+//! The records are held in the `records` table, next to which downstream applications can create their own tables
+//! with [`SqliteDatabaseProvider::migrate()`], to persist their state in a queryable form.
+
+use std::{path::Path, sync::Mutex};
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{BatchOperation, Compression, DatabaseProvider};
+use crate::{Error, Result};
+
+/// The namespace of the migrations of the `records` table.
+const RECORDS_NAMESPACE: &str = "iota-client";
+
+/// The migrations of the `records` table.
+const RECORDS_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create the records table",
+        sql: "CREATE TABLE records (key BLOB PRIMARY KEY NOT NULL, value BLOB NOT NULL) WITHOUT ROWID;",
+    },
+    Migration {
+        version: 2,
+        description: "create the table recording the compression of the records",
+        sql: "CREATE TABLE records_compression (id INTEGER PRIMARY KEY CHECK (id = 0), compression TEXT NOT NULL);",
+    },
+];
+
+/// A schema migration, applied once to a database.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Migration {
+    /// The version the migration brings its namespace to. Migrations are applied in increasing order of version.
+    pub version: u32,
+    /// What the migration does.
+    pub description: &'static str,
+    /// The SQL statements of the migration.
+    pub sql: &'static str,
+}
+
+/// SQLite as a database provider.
+pub struct SqliteDatabaseProvider {
+    connection: Mutex<Connection>,
+    compression: Compression,
+}
+
+impl SqliteDatabaseProvider {
+    /// Open the database at `path` in WAL mode, creating it if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_wal_mode(path, true)
+    }
+
+    /// Open the database at `path`, creating it if it doesn't exist.
+    ///
+    /// In WAL mode, readers don't block the writer and the other way around, which is usually faster, but requires
+    /// the database to be on a local file system.
+    pub fn open_with_wal_mode<P: AsRef<Path>>(path: P, wal_mode: bool) -> Result<Self> {
+        let connection = Connection::open(path)?;
+
+        if wal_mode {
+            // The statement returns the new journal mode, hence the query.
+            connection.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+            connection.pragma_update(None, "synchronous", "NORMAL")?;
+        }
+
+        let mut provider = Self {
+            connection: Mutex::new(connection),
+            compression: Compression::None,
+        };
+        provider.migrate(RECORDS_NAMESPACE, RECORDS_MIGRATIONS)?;
+
+        let recorded: Option<String> = provider.with_connection(|connection| {
+            connection
+                .query_row("SELECT compression FROM records_compression WHERE id = 0", [], |row| {
+                    row.get(0)
+                })
+                .optional()
+        })?;
+        provider.compression = Compression::from_recorded(recorded.as_deref().map(str::as_bytes))?;
+
+        Ok(provider)
+    }
+
+    /// Apply the `migrations` of `namespace` that haven't been applied to the database yet.
+    ///
+    /// The applied versions are recorded per namespace in the `migrations` table, so that the migrations of
+    /// different applications sharing a database don't interfere. All pending migrations are applied in a single
+    /// transaction, so that a failing migration leaves the database as it was.
+    pub fn migrate(&self, namespace: &str, migrations: &[Migration]) -> Result<()> {
+        let mut connection = self.connection.lock().map_err(|_| Error::PoisonError)?;
+
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS migrations (
+                namespace TEXT NOT NULL,
+                version INTEGER NOT NULL,
+                description TEXT NOT NULL,
+                PRIMARY KEY (namespace, version)
+            );",
+        )?;
+
+        let transaction = connection.transaction()?;
+        let current_version: u32 = transaction.query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM migrations WHERE namespace = ?1",
+            [namespace],
+            |row| row.get(0),
+        )?;
+
+        let mut pending = migrations
+            .iter()
+            .filter(|migration| migration.version > current_version)
+            .collect::<Vec<_>>();
+        pending.sort_by_key(|migration| migration.version);
+
+        for migration in pending {
+            log::debug!(
+                "[SqliteDatabaseProvider] applying migration {} of {namespace}: {}",
+                migration.version,
+                migration.description
+            );
+
+            transaction.execute_batch(migration.sql)?;
+            transaction.execute(
+                "INSERT INTO migrations (namespace, version, description) VALUES (?1, ?2, ?3)",
+                params![namespace, migration.version, migration.description],
+            )?;
+        }
+
+        Ok(transaction.commit()?)
+    }
+
+    /// Compress the values before storing them, see [`Compression`].
+    ///
+    /// Fails with [`Error::CompressionMismatch`] if the database already holds values stored with another
+    /// compression.
+    pub fn with_compression(mut self, compression: Compression) -> Result<Self> {
+        if compression != self.compression {
+            let has_records = self.with_connection(|connection| {
+                connection.query_row("SELECT EXISTS (SELECT 1 FROM records)", [], |row| row.get(0))
+            })?;
+            self.compression.check_switch(compression, has_records)?;
+
+            self.with_connection(|connection| {
+                connection.execute(
+                    "INSERT OR REPLACE INTO records_compression (id, compression) VALUES (0, ?1)",
+                    [compression.name()],
+                )
+            })?;
+            self.compression = compression;
+        }
+
+        Ok(self)
+    }
+
+    /// Run `f` with the connection to the database, e.g. to query the tables created by migrations.
+    pub fn with_connection<T>(&self, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T> {
+        let connection = self.connection.lock().map_err(|_| Error::PoisonError)?;
+
+        Ok(f(&connection)?)
+    }
+}
+
+fn get_value(connection: &Connection, k: &[u8]) -> rusqlite::Result<Option<Vec<u8>>> {
+    connection
+        .query_row("SELECT value FROM records WHERE key = ?1", [k], |row| row.get(0))
+        .optional()
+}
+
+#[async_trait]
+impl DatabaseProvider for SqliteDatabaseProvider {
+    async fn get(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.with_connection(|connection| get_value(connection, k))?
+            .map(|value| self.compression.decompress(value))
+            .transpose()
+    }
+
+    async fn insert(&self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>> {
+        let v = self.compression.compress(v)?;
+        let mut connection = self.connection.lock().map_err(|_| Error::PoisonError)?;
+        let transaction = connection.transaction()?;
+
+        let old_value = get_value(&transaction, k)?;
+        transaction.execute(
+            "INSERT INTO records (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            [k, v.as_slice()],
+        )?;
+        transaction.commit()?;
+
+        old_value.map(|value| self.compression.decompress(value)).transpose()
+    }
+
+    async fn delete(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        let mut connection = self.connection.lock().map_err(|_| Error::PoisonError)?;
+        let transaction = connection.transaction()?;
+
+        let old_value = get_value(&transaction, k)?;
+        transaction.execute("DELETE FROM records WHERE key = ?1", [k])?;
+        transaction.commit()?;
+
+        old_value.map(|value| self.compression.decompress(value)).transpose()
+    }
+
+    async fn insert_batch(&self, records: &[(Vec<u8>, Vec<u8>)]) -> Result<()> {
+        let mut connection = self.connection.lock().map_err(|_| Error::PoisonError)?;
+        let transaction = connection.transaction()?;
+
+        {
+            let mut statement = transaction.prepare(
+                "INSERT INTO records (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            )?;
+
+            for (k, v) in records {
+                statement.execute([k, &self.compression.compress(v)?])?;
+            }
+        }
+
+        Ok(transaction.commit()?)
+    }
+
+    async fn delete_batch(&self, keys: &[Vec<u8>]) -> Result<()> {
+        let mut connection = self.connection.lock().map_err(|_| Error::PoisonError)?;
+        let transaction = connection.transaction()?;
+
+        {
+            let mut statement = transaction.prepare("DELETE FROM records WHERE key = ?1")?;
+
+            for k in keys {
+                statement.execute([k])?;
+            }
+        }
+
+        Ok(transaction.commit()?)
+    }
+
+    async fn apply_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        let mut connection = self.connection.lock().map_err(|_| Error::PoisonError)?;
+        let transaction = connection.transaction()?;
+
+        for operation in operations {
+            match operation {
+                BatchOperation::Put { key, value } => transaction.execute(
+                    "INSERT INTO records (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                    [key, self.compression.compress(&value)?],
+                )?,
+                BatchOperation::Delete { key } => transaction.execute("DELETE FROM records WHERE key = ?1", [key])?,
+            };
+        }
+
+        Ok(transaction.commit()?)
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        self.with_connection(|connection| {
+            connection
+                .prepare("SELECT key FROM records")?
+                .query_map([], |row| row.get(0))?
+                .collect()
+        })
+    }
+
+    async fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.with_connection(|connection| {
+            // BLOBs are compared with memcmp(), so the records with the prefix are the ones from the prefix on, up to
+            // the first one that doesn't start with it.
+            let mut statement = connection.prepare("SELECT key, value FROM records WHERE key >= ?1 ORDER BY key")?;
+            let mut records: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+
+            for record in statement.query_map([prefix], |row| Ok((row.get::<_, Vec<u8>>(0)?, row.get(1)?)))? {
+                let record = record?;
+
+                if !record.0.starts_with(prefix) {
+                    break;
+                }
+
+                records.push(record);
+            }
+
+            Ok(records)
+        })?
+        .into_iter()
+        .map(|(key, value)| Ok((key, self.compression.decompress(value)?)))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sqlite_database_provider() {
+        let path = "test_sqlite_database_provider.db";
+        let provider = SqliteDatabaseProvider::open(path).unwrap();
+
+        assert_eq!(provider.insert(b"b", b"1").await.unwrap(), None);
+        assert_eq!(provider.insert(b"b", b"2").await.unwrap(), Some(b"1".to_vec()));
+        provider
+            .insert_batch(&[(b"a1".to_vec(), b"3".to_vec()), (b"a0".to_vec(), b"4".to_vec())])
+            .await
+            .unwrap();
+
+        assert_eq!(provider.get(b"b").await.unwrap(), Some(b"2".to_vec()));
+        assert_eq!(
+            provider.iter_prefix(b"a").await.unwrap(),
+            vec![(b"a0".to_vec(), b"4".to_vec()), (b"a1".to_vec(), b"3".to_vec())]
+        );
+
+        assert_eq!(provider.delete(b"b").await.unwrap(), Some(b"2".to_vec()));
+        provider.delete_batch(&[b"a0".to_vec(), b"a1".to_vec()]).await.unwrap();
+        assert!(provider.keys().await.unwrap().is_empty());
+
+        drop(provider);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{path}{suffix}"));
+        }
+    }
+
+    #[test]
+    fn sqlite_migrations() {
+        let path = "test_sqlite_migrations.db";
+        let migrations = [
+            Migration {
+                version: 2,
+                description: "add the account index",
+                sql: "ALTER TABLE accounts ADD COLUMN account_index INTEGER NOT NULL DEFAULT 0;",
+            },
+            Migration {
+                version: 1,
+                description: "create the accounts table",
+                sql: "CREATE TABLE accounts (alias TEXT PRIMARY KEY NOT NULL);",
+            },
+        ];
+
+        let provider = SqliteDatabaseProvider::open_with_wal_mode(path, false).unwrap();
+        provider.migrate("wallet", &migrations[1..]).unwrap();
+        provider.migrate("wallet", &migrations).unwrap();
+        // Already applied migrations are skipped.
+        provider.migrate("wallet", &migrations).unwrap();
+
+        provider
+            .with_connection(|connection| {
+                connection.execute("INSERT INTO accounts (alias, account_index) VALUES ('alice', 1)", [])
+            })
+            .unwrap();
+
+        // A failing migration is rolled back as a whole.
+        let failing = [
+            Migration {
+                version: 3,
+                description: "create the addresses table",
+                sql: "CREATE TABLE addresses (address TEXT NOT NULL);",
+            },
+            Migration {
+                version: 4,
+                description: "invalid",
+                sql: "NOT SQL;",
+            },
+        ];
+        assert!(provider.migrate("wallet", &failing).is_err());
+
+        let versions = provider
+            .with_connection(|connection| {
+                connection
+                    .prepare("SELECT version FROM migrations WHERE namespace = 'wallet' ORDER BY version")?
+                    .query_map([], |row| row.get::<_, u32>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .unwrap();
+        assert_eq!(versions, vec![1, 2]);
+
+        drop(provider);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn sqlite_database_provider_compression() {
+        let path = "test_sqlite_database_provider_compression.db";
+        let provider = SqliteDatabaseProvider::open_with_wal_mode(path, false)
+            .unwrap()
+            .with_compression(Compression::Zstd)
+            .unwrap();
+        provider.insert(b"key", &[42; 1024]).await.unwrap();
+        drop(provider);
+
+        // The recorded compression is used, and can't be switched as the database holds values.
+        let provider = SqliteDatabaseProvider::open_with_wal_mode(path, false).unwrap();
+        assert_eq!(provider.get(b"key").await.unwrap(), Some(vec![42; 1024]));
+        assert!(matches!(
+            provider.with_compression(Compression::None),
+            Err(Error::CompressionMismatch { .. })
+        ));
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Devnet presets and a harness providing funded secret managers, for examples and integration tests.
+//!
+//! ```no_run
+//! # use iota_client::{devnet::{DevnetHarness, DevnetPreset}, Result};
+//! # #[tokio::main]
+//! # async fn main() -> Result<()> {
+//! let harness = DevnetHarness::new(DevnetPreset::from_env())?;
+//! let funded = harness.funded().await?;
+//!
+//! println!("{} received {}", funded.address, funded.balance);
+//! # Ok(())}
+//! ```
+
+use std::time::Duration;
+
+use iota_types::block::output::Output;
+
+use crate::{
+    constants::{DEFAULT_FAUCET_POLL_INTERVAL, DEFAULT_FAUCET_POLL_MAX_ATTEMPTS},
+    node_api::indexer::query_parameters::QueryParameter,
+    secret::{mnemonic::MnemonicSecretManager, SecretManager},
+    utils::{generate_mnemonic, request_funds_from_faucet},
+    Client, Error, Result,
+};
+
+/// Node and faucet endpoints of a development network.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevnetPreset {
+    /// The URL of the node.
+    pub node_url: String,
+    /// The URL of the faucet enqueue endpoint.
+    pub faucet_url: String,
+}
+
+impl DevnetPreset {
+    /// A private tangle running on the local machine, as set up by the Hornet private tangle scripts.
+    pub fn local() -> Self {
+        Self {
+            node_url: "http://localhost:14265".to_string(),
+            faucet_url: "http://localhost:8091/api/enqueue".to_string(),
+        }
+    }
+
+    /// The endpoints set in the `NODE_URL` and `FAUCET_URL` environment variables, falling back to [`Self::local()`]
+    /// for the missing ones.
+    pub fn from_env() -> Self {
+        let local = Self::local();
+
+        Self {
+            node_url: std::env::var("NODE_URL").unwrap_or(local.node_url),
+            faucet_url: std::env::var("FAUCET_URL").unwrap_or(local.faucet_url),
+        }
+    }
+}
+
+/// A secret manager whose first address has been funded by the faucet.
+pub struct FundedSecretManager {
+    /// A client connected to the devnet node.
+    pub client: Client,
+    /// The funded secret manager.
+    pub secret_manager: SecretManager,
+    /// The bech32 encoded funded address, i.e. the first public address of account 0.
+    pub address: String,
+    /// The balance of the funded address once the funds arrived.
+    pub balance: u64,
+}
+
+/// Provisions funded secret managers on a devnet.
+pub struct DevnetHarness {
+    preset: DevnetPreset,
+    client: Client,
+    poll_interval: Duration,
+    max_attempts: u64,
+}
+
+impl DevnetHarness {
+    /// Create a harness connecting to the endpoints of `preset`.
+    pub fn new(preset: DevnetPreset) -> Result<Self> {
+        let client = Client::builder().with_node(&preset.node_url)?.finish()?;
+
+        Ok(Self {
+            preset,
+            client,
+            poll_interval: DEFAULT_FAUCET_POLL_INTERVAL,
+            max_attempts: DEFAULT_FAUCET_POLL_MAX_ATTEMPTS,
+        })
+    }
+
+    /// Set how often and how many times the balance is polled while waiting for funds.
+    pub fn with_polling(mut self, poll_interval: Duration, max_attempts: u64) -> Self {
+        self.poll_interval = poll_interval;
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// The client connected to the devnet node.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Create a secret manager from a new random mnemonic and fund its first address.
+    pub async fn funded(&self) -> Result<FundedSecretManager> {
+        let secret_manager = SecretManager::Mnemonic(MnemonicSecretManager::try_from_mnemonic(&generate_mnemonic()?)?);
+
+        self.fund(secret_manager).await
+    }
+
+    /// Fund the first address of `secret_manager` and wait for the funds to arrive.
+    pub async fn fund(&self, secret_manager: SecretManager) -> Result<FundedSecretManager> {
+        let address = self
+            .client
+            .get_addresses(&secret_manager)
+            .with_range(0..1)
+            .finish()
+            .await?
+            .remove(0);
+
+        let faucet_response = request_funds_from_faucet(&self.preset.faucet_url, &address).await?;
+        log::debug!("[DevnetHarness] faucet response: {faucet_response}");
+
+        let balance = self.wait_for_funds(&address).await?;
+
+        Ok(FundedSecretManager {
+            client: self.client.clone(),
+            secret_manager,
+            address,
+            balance,
+        })
+    }
+
+    /// Poll the balance of `address` until it's not zero, returning it.
+    pub async fn wait_for_funds(&self, address: &str) -> Result<u64> {
+        let token_supply = self.client.get_token_supply().await?;
+
+        for _ in 0..self.max_attempts {
+            let output_ids = self
+                .client
+                .basic_output_ids(vec![QueryParameter::Address(address.to_string())])
+                .await?;
+
+            let mut balance = 0;
+            for output_response in self.client.get_outputs(output_ids).await? {
+                balance += Output::try_from_dto(&output_response.output, token_supply)?.amount();
+            }
+
+            if balance > 0 {
+                return Ok(balance);
+            }
+
+            crate::async_runtime::sleep(self.poll_interval).await;
+        }
+
+        Err(Error::FaucetFundsNotReceived(address.to_string()))
+    }
+}
@@ -22,10 +22,37 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[allow(clippy::large_enum_variant)]
 #[serde(tag = "type", content = "error", rename_all = "camelCase")]
 pub enum Error {
+    /// An address index to scan exceeds the maximal index
+    #[error("address index range {start_index}+{range_length} exceeds the maximal index of account {account_index}")]
+    #[serde(rename_all = "camelCase")]
+    AddressIndexOverflow {
+        /// The account index scanned.
+        account_index: u32,
+        /// The first address index of the range.
+        start_index: u32,
+        /// The number of address indexes of the range.
+        range_length: u32,
+    },
+    /// The secret manager can't display addresses for their confirmation
+    #[error("the secret manager can't display addresses for their confirmation")]
+    AddressPromptUnsupported,
+    /// User entered amount parsing error
+    #[error("{0}")]
+    AmountParse(#[from] crate::amount::AmountParseError),
     /// Block dtos error
     #[error("{0}")]
     #[serde(serialize_with = "display_string")]
     ApiTypes(#[from] iota_types::api::error::Error),
+    /// A bech32 encoded address belongs to another network
+    #[error(
+        "the bech32 HRP {found} doesn't match the expected HRP {expected}, the address belongs to another network"
+    )]
+    Bech32HrpMismatch {
+        /// The expected HRP.
+        expected: String,
+        /// The HRP found in the address.
+        found: String,
+    },
     /// Blake2b256 Error
     #[error("{0}")]
     Blake2b256Error(&'static str),
@@ -37,6 +64,20 @@ pub enum Error {
     #[error("{0}")]
     #[serde(serialize_with = "display_string")]
     BlockError(#[from] iota_types::block::Error),
+    /// A database value isn't marked with a known compression
+    #[error("the database value isn't marked with a known compression")]
+    CompressedValueInvalid,
+    /// A database holding values is configured with another compression than the one its values are stored with
+    #[error("the database values are stored with the compression {recorded}, not {configured}")]
+    CompressionMismatch {
+        /// The compression recorded for the database.
+        recorded: String,
+        /// The configured compression.
+        configured: String,
+    },
+    /// The compression recorded for a database isn't supported, e.g. as its feature isn't enabled
+    #[error("the database compression {0} isn't supported")]
+    CompressionUnsupported(String),
     /// The wallet account has enough funds, but split on too many outputs
     #[error("the wallet account has enough funds, but split on too many outputs: {0}, max. is 128, consolidate them")]
     ConsolidationRequired(usize),
@@ -44,18 +85,54 @@ pub enum Error {
     #[error("{0}")]
     #[serde(serialize_with = "display_string")]
     CryptoError(#[from] crypto::Error),
+    /// The export of a secret hasn't been acknowledged with the expected phrase
+    #[cfg(feature = "dangerous-exports")]
+    #[error("the export of the secret hasn't been acknowledged")]
+    ExportNotAcknowledged,
+    /// The external signer returned an invalid signature
+    #[error("the external signer returned an invalid signature")]
+    ExternalSignerInvalidSignature,
+    /// Funds requested from the faucet haven't been received in time
+    #[error("funds requested from the faucet for address {0} haven't been received in time")]
+    FaucetFundsNotReceived(String),
     /// Address not found
-    #[error("address: {0} not found in range: {1}")]
-    InputAddressNotFound(String, String),
+    #[error(
+        "address {address} not found in range {range:?} of account index {account_index}, widen the input range to \
+         search further"
+    )]
+    #[serde(rename_all = "camelCase")]
+    InputAddressNotFound {
+        /// The bech32 encoded address.
+        address: String,
+        /// The account index searched.
+        account_index: u32,
+        /// The range of address indexes searched.
+        range: std::ops::Range<u32>,
+        /// Whether the internal addresses have been searched, besides the public ones.
+        internal_addresses_searched: bool,
+    },
     /// Invalid amount in API response
     #[error("invalid amount in API response: {0}")]
     InvalidAmount(String),
     /// Invalid BIP32 chain data
     #[error("invalid BIP32 chain data")]
     InvalidBIP32ChainData,
+    /// The length of BIP-85 entropy isn't supported
+    #[cfg(feature = "secp256k1")]
+    #[error("invalid BIP-85 entropy length {0}, expected 16 to 64 bytes")]
+    InvalidBIP85EntropyLength(usize),
+    /// Invalid derivation path
+    #[error("invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
     /// Invalid mnemonic error
     #[error("invalid mnemonic {0}")]
     InvalidMnemonic(String),
+    /// The shares of a secret are invalid
+    #[error("invalid secret shares: {0}")]
+    InvalidSecretShares(String),
+    /// A raw seed doesn't have a supported length
+    #[error("invalid seed length {0}, expected 32 or 64 bytes")]
+    InvalidSeedLength(usize),
     /// The transaction essence is too large
     #[error("the transaction essence is too large. Its length is {length}, max length is {max_length}")]
     InvalidRegularTransactionEssenceLength {
@@ -76,6 +153,38 @@ pub enum Error {
     #[error("{0}")]
     #[serde(serialize_with = "display_string")]
     Json(#[from] serde_json::Error),
+    /// A line of the file of a JSON file database provider isn't a valid record
+    #[error("invalid record in the JSON file database: {0}")]
+    JsonFileRecordInvalid(String),
+    /// Too many milestones have been confirmed since the milestone of a ledger state to replay them
+    #[error(
+        "{replay_depth} milestones have been confirmed since milestone index {milestone_index}, more than the maximal \
+         replay depth of {max_replay_depth}"
+    )]
+    #[serde(rename_all = "camelCase")]
+    LedgerStateReplayTooDeep {
+        /// The requested milestone index.
+        milestone_index: u32,
+        /// The number of milestones confirmed since.
+        replay_depth: u32,
+        /// The maximal number of milestones to replay.
+        max_replay_depth: u32,
+    },
+    /// The secret manager can't sign messages
+    #[error("the secret manager doesn't support the signing of messages")]
+    MessageSigningUnsupported,
+    /// A milestone index isn't confirmed yet
+    #[error(
+        "milestone index {milestone_index} isn't confirmed yet, the confirmed milestone index is \
+         {confirmed_milestone_index}"
+    )]
+    #[serde(rename_all = "camelCase")]
+    MilestoneIndexNotConfirmed {
+        /// The requested milestone index.
+        milestone_index: u32,
+        /// The confirmed milestone index.
+        confirmed_milestone_index: u32,
+    },
     /// Missing input for utxo chain
     #[error("missing input: {0}")]
     MissingInput(String),
@@ -85,6 +194,26 @@ pub enum Error {
     /// No input with matching ed25519 address provided
     #[error("no input with matching ed25519 address provided")]
     MissingInputWithEd25519Address,
+    /// An input of a transaction needing a signature hasn't been signed by any signer
+    #[error("the input {0} hasn't been signed by any signer")]
+    MissingSignatureUnlock(usize),
+    /// A file already exists where a mnemonic file would be created
+    #[error("a file already exists at {0}, the mnemonic file isn't overwritten")]
+    MnemonicFileAlreadyExists(String),
+    /// The mnemonic file isn't valid
+    #[error("invalid mnemonic file: {0}")]
+    MnemonicFileInvalid(&'static str),
+    /// The password of the mnemonic file isn't the one it has been encrypted with
+    #[error("invalid mnemonic file password")]
+    MnemonicFilePasswordInvalid,
+    /// A transaction essence belongs to another network
+    #[error("the network id {found} doesn't match the expected network id {expected}")]
+    NetworkIdMismatch {
+        /// The expected network id.
+        expected: u64,
+        /// The network id found in the essence.
+        found: u64,
+    },
     /// Error on API request
     #[error("node error: {0}")]
     NodeError(String),
@@ -111,9 +240,18 @@ pub enum Error {
     /// The wallet account doesn't have enough balance for an output with the remaining native tokens.
     #[error("the wallet account doesn't have enough balance for an output with the remaining native tokens.")]
     NoBalanceForNativeTokenRemainder,
+    /// Offline signing data isn't valid
+    #[error("invalid offline signing data: {0}")]
+    OfflineSigningDataInvalid(&'static str),
+    /// Offline signing data has been written in a version of the binary format which isn't supported
+    #[error("unsupported version {0} of the offline signing data")]
+    OfflineSigningDataVersionUnsupported(u8),
     /// Output Error
     #[error("output error: {0}")]
     OutputError(&'static str),
+    /// The outputs of a transaction violate output policies
+    #[error("the outputs violate output policies: {0:?}")]
+    OutputPolicyViolations(Vec<crate::api::PolicyViolation>),
     /// PlaceholderSecretManager can't be used for address generation or signing
     #[error("placeholderSecretManager can't be used for address generation or signing")]
     PlaceholderSecretManager,
@@ -157,12 +295,23 @@ pub enum Error {
     #[error("{0}")]
     #[serde(serialize_with = "display_string")]
     ReqwestError(#[from] reqwest::Error),
+    /// A step of a scenario didn't have the expected outcome
+    #[error("scenario assertion failed: {0}")]
+    ScenarioAssertion(String),
+    /// secp256k1 ECDSA error
+    #[cfg(feature = "secp256k1")]
+    #[error("{0}")]
+    #[serde(serialize_with = "display_string")]
+    Secp256k1Ecdsa(#[from] k256::ecdsa::Error),
     /// Specifically used for `TryInfo` implementations for `SecretManager`.
     #[error("cannot unwrap a SecretManager: type mismatch!")]
     SecretManagerMismatch,
     /// No node available in the healthy node pool
     #[error("no healthy node available")]
     HealthyNodePoolEmpty,
+    /// The secret manager can't derive tag keys
+    #[error("the secret manager doesn't support the derivation of tag keys")]
+    TagKeyDerivationUnsupported,
     /// Error when building tagged_data blocks
     #[error("error when building tagged_data block: {0}")]
     TaggedDataError(String),
@@ -209,6 +358,25 @@ pub enum Error {
     /// URL validation error
     #[error("{0}")]
     UrlValidationError(String),
+    /// The watch-only secret manager doesn't hold the requested address
+    #[error(
+        "the watch-only secret manager doesn't hold the address index {address_index} (internal: {internal}) of \
+         account index {account_index} with coin type {coin_type}"
+    )]
+    #[serde(rename_all = "camelCase")]
+    WatchOnlyAddressUnknown {
+        /// The coin type.
+        coin_type: u32,
+        /// The account index.
+        account_index: u32,
+        /// Whether the address is an internal address.
+        internal: bool,
+        /// The address index.
+        address_index: u32,
+    },
+    /// The watch-only secret manager can't sign
+    #[error("the watch-only secret manager can't sign")]
+    WatchOnlySigningUnsupported,
 
     //////////////////////////////////////////////////////////////////////
     // Participation
@@ -218,7 +386,13 @@ pub enum Error {
     #[error("invalid participations")]
     InvalidParticipations,
     /// IO error
-    #[cfg(feature = "participation")]
+    #[cfg(any(
+        feature = "blocking",
+        feature = "participation",
+        feature = "stronghold",
+        feature = "zstd",
+        not(target_family = "wasm")
+    ))]
     #[error("`{0}`")]
     #[serde(serialize_with = "display_string")]
     IoError(#[from] std::io::Error),
@@ -234,10 +408,18 @@ pub enum Error {
     #[cfg(feature = "ledger_nano")]
     #[error("ledger locked")]
     LedgerDongleLocked,
+    /// Ledger derivation path unsupported
+    #[cfg(feature = "ledger_nano")]
+    #[error("the ledger app only supports the default derivation path")]
+    LedgerDerivationPathUnsupported,
     /// Ledger Device not found
     #[cfg(feature = "ledger_nano")]
     #[error("ledger device not found")]
     LedgerDeviceNotFound,
+    /// Ledger essence hash signing unsupported
+    #[cfg(feature = "ledger_nano")]
+    #[error("the ledger app signs transaction essences, not their hashes")]
+    LedgerEssenceHashSigningUnsupported,
     /// Ledger Essence Too Large
     #[cfg(feature = "ledger_nano")]
     #[error("ledger essence too large")]
@@ -264,6 +446,82 @@ pub enum Error {
     #[error("mQTT connection not found (all nodes have the MQTT plugin disabled)")]
     MqttConnectionNotFound,
 
+    //////////////////////////////////////////////////////////////////////
+    // Cloud KMS
+    //////////////////////////////////////////////////////////////////////
+    /// An input to sign doesn't belong to the address of the Cloud KMS key
+    #[cfg(feature = "cloud_kms")]
+    #[error("the input address {0} isn't the address of the cloud kms key")]
+    CloudKmsAddressMismatch(String),
+    /// The public key of a Cloud KMS key isn't a valid Ed25519 public key
+    #[cfg(feature = "cloud_kms")]
+    #[error("the cloud kms public key isn't a valid ed25519 public key")]
+    CloudKmsInvalidPublicKey,
+    /// Cloud KMS returned a signature which isn't a valid Ed25519 signature
+    #[cfg(feature = "cloud_kms")]
+    #[error("cloud kms returned an invalid ed25519 signature")]
+    CloudKmsInvalidSignature,
+
+    //////////////////////////////////////////////////////////////////////
+    // PKCS#11
+    //////////////////////////////////////////////////////////////////////
+    /// PKCS#11 error
+    #[cfg(feature = "pkcs11")]
+    #[error("pkcs11 error: {0}")]
+    #[serde(serialize_with = "display_string")]
+    Pkcs11(#[from] cryptoki::error::Error),
+    /// An input to sign doesn't belong to the address of the PKCS#11 key
+    #[cfg(feature = "pkcs11")]
+    #[error("the input address {0} isn't the address of the pkcs11 key")]
+    Pkcs11AddressMismatch(String),
+    /// The public key of a PKCS#11 key pair isn't a valid Ed25519 public key
+    #[cfg(feature = "pkcs11")]
+    #[error("the pkcs11 public key isn't a valid ed25519 public key")]
+    Pkcs11InvalidPublicKey,
+    /// A PKCS#11 token returned a signature which isn't a valid Ed25519 signature
+    #[cfg(feature = "pkcs11")]
+    #[error("the pkcs11 token returned an invalid ed25519 signature")]
+    Pkcs11InvalidSignature,
+    /// No Ed25519 key pair with the label has been found in the PKCS#11 token
+    #[cfg(feature = "pkcs11")]
+    #[error("no ed25519 key pair labelled {0} in the pkcs11 token")]
+    Pkcs11KeyNotFound(String),
+    /// No PKCS#11 token is present in the slot
+    #[cfg(feature = "pkcs11")]
+    #[error("no pkcs11 token in slot {0}")]
+    Pkcs11SlotNotFound(u64),
+
+    //////////////////////////////////////////////////////////////////////
+    // RocksDB
+    //////////////////////////////////////////////////////////////////////
+    /// RocksDB error
+    #[cfg(feature = "rocksdb")]
+    #[error("rocksdb error: {0}")]
+    #[serde(serialize_with = "display_string")]
+    Rocksdb(#[from] rocksdb::Error),
+    /// A column family hasn't been opened in a RocksDB database
+    #[cfg(feature = "rocksdb")]
+    #[error("the rocksdb column family {0} doesn't exist")]
+    RocksdbColumnFamilyNotFound(String),
+
+    //////////////////////////////////////////////////////////////////////
+    // Sled
+    //////////////////////////////////////////////////////////////////////
+    /// Sled error
+    #[cfg(feature = "sled")]
+    #[error("sled error: {0}")]
+    #[serde(serialize_with = "display_string")]
+    Sled(#[from] sled::Error),
+
+    //////////////////////////////////////////////////////////////////////
+    // SQLite
+    //////////////////////////////////////////////////////////////////////
+    /// SQLite error
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite error: {0}")]
+    #[serde(serialize_with = "display_string")]
+    Sqlite(#[from] rusqlite::Error),
+
     //////////////////////////////////////////////////////////////////////
     // Stronghold
     //////////////////////////////////////////////////////////////////////
@@ -289,11 +547,77 @@ pub enum Error {
     #[cfg(feature = "stronghold")]
     #[error("a mnemonic has already been stored in the Stronghold vault")]
     StrongholdMnemonicAlreadyStored,
+    /// No mnemonic has been stored into a Stronghold vault
+    #[cfg(feature = "stronghold")]
+    #[error("no mnemonic has been stored in the Stronghold vault")]
+    StrongholdMnemonicMissing,
+    /// A password has been rejected by the password policy of a Stronghold adapter
+    #[cfg(feature = "stronghold")]
+    #[error("the password has been rejected by the password policy: {0}")]
+    StrongholdPasswordRejected(String),
     /// Procedure execution error from Stronghold
     #[cfg(feature = "stronghold")]
     #[error("Stronghold reported a procedure error: {0}")]
     #[serde(serialize_with = "display_string")]
     StrongholdProcedureError(#[from] iota_stronghold::procedures::ProcedureError),
+    /// The Stronghold snapshot is corrupted
+    #[cfg(feature = "stronghold")]
+    #[error("the stronghold snapshot is corrupted: {0}")]
+    StrongholdSnapshotCorrupted(String),
+    /// The Stronghold snapshot file doesn't exist
+    #[cfg(feature = "stronghold")]
+    #[error("the stronghold snapshot file {0} doesn't exist")]
+    StrongholdSnapshotNotFound(String),
+    /// The Stronghold snapshot couldn't be written when locking, so the key has been kept
+    #[cfg(feature = "stronghold")]
+    #[error("failed to write the stronghold snapshot, the key has been kept: {0}")]
+    #[serde(serialize_with = "display_string")]
+    StrongholdSnapshotWrite(iota_stronghold::ClientError),
+    /// A Stronghold operation didn't complete within the configured operation timeout
+    #[cfg(feature = "stronghold")]
+    #[error("the stronghold operation didn't complete within {0:?}")]
+    StrongholdTimeout(std::time::Duration),
+
+    //////////////////////////////////////////////////////////////////////
+    // YubiKey
+    //////////////////////////////////////////////////////////////////////
+    /// PC/SC error
+    #[cfg(feature = "yubikey")]
+    #[error("pcsc error: {0}")]
+    #[serde(serialize_with = "display_string")]
+    Pcsc(#[from] pcsc::Error),
+    /// An input to sign doesn't belong to the address of the YubiKey key
+    #[cfg(feature = "yubikey")]
+    #[error("the input address {0} isn't the address of the yubikey key")]
+    YubikeyAddressMismatch(String),
+    /// The signature key of a YubiKey isn't an Ed25519 key
+    #[cfg(feature = "yubikey")]
+    #[error("the yubikey signature key isn't an ed25519 key")]
+    YubikeyInvalidPublicKey,
+    /// A YubiKey returned a signature which isn't a valid Ed25519 signature
+    #[cfg(feature = "yubikey")]
+    #[error("the yubikey returned an invalid ed25519 signature")]
+    YubikeyInvalidSignature,
+    /// A message to sign is longer than a YubiKey can sign in a single command
+    #[cfg(feature = "yubikey")]
+    #[error("the message to sign is {0} bytes long, a yubikey signs at most 255 bytes")]
+    YubikeyMessageTooLong(usize),
+    /// No YubiKey is connected
+    #[cfg(feature = "yubikey")]
+    #[error("no yubikey found")]
+    YubikeyNotFound,
+    /// The PIN of a YubiKey has been rejected
+    #[cfg(feature = "yubikey")]
+    #[error("the yubikey pin is invalid")]
+    YubikeyPinInvalid,
+    /// A YubiKey returned an unexpected status word
+    #[cfg(feature = "yubikey")]
+    #[error("the yubikey returned the status word {0:#06x}")]
+    YubikeyStatus(u16),
+    /// A YubiKey hasn't been touched in time to sign
+    #[cfg(feature = "yubikey")]
+    #[error("the yubikey hasn't been touched in time to sign")]
+    YubikeyTouchTimeout,
 }
 
 // map most errors to a single error but there are some errors that
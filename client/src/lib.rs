@@ -40,15 +40,24 @@ macro_rules! lazy_static {
     }};
 }
 
+pub mod amount;
 pub mod api;
+#[cfg(not(target_family = "wasm"))]
+mod async_runtime;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod client;
 pub mod constants;
 pub mod db;
+#[cfg(not(target_family = "wasm"))]
+pub mod devnet;
 pub mod error;
 #[cfg(feature = "message_interface")]
 pub mod message_interface;
 pub mod node_api;
 pub mod node_manager;
+#[cfg(not(target_family = "wasm"))]
+pub mod scenarios;
 pub mod secret;
 #[cfg(feature = "stronghold")]
 pub mod stronghold;
@@ -63,27 +72,3 @@ pub use url::Url;
 #[cfg(feature = "mqtt")]
 pub use self::node_api::mqtt::*;
 pub use self::{client::*, error::*, node_api::core::routes::NodeInfoWrapper, utils::*};
-
-#[cfg(feature = "mqtt")]
-mod async_runtime {
-    use std::sync::Mutex;
-
-    use once_cell::sync::OnceCell;
-    use tokio::runtime::Runtime;
-
-    static RUNTIME: OnceCell<Mutex<Runtime>> = OnceCell::new();
-
-    pub(crate) fn block_on<C: futures::Future>(cb: C) -> C::Output {
-        let runtime = RUNTIME.get_or_init(|| Mutex::new(Runtime::new().expect("failed to create Tokio runtime")));
-        runtime.lock().expect("failed to lock the runtime.").block_on(cb)
-    }
-
-    pub(crate) fn spawn<F>(future: F)
-    where
-        F: futures::Future + Send + 'static,
-        F::Output: Send + 'static,
-    {
-        let runtime = RUNTIME.get_or_init(|| Mutex::new(Runtime::new().expect("failed to create Tokio runtime")));
-        runtime.lock().expect("failed to lock the runtime.").spawn(future);
-    }
-}
@@ -19,6 +19,7 @@ use iota_types::block::{
 use serde::Deserialize;
 
 use crate::{
+    amount::DecimalSeparator,
     api::{
         ClientBlockBuilderOptions as BuildBlockOptions, GetAddressesBuilderOptions as GenerateAddressesOptions,
         PreparedTransactionDataDto,
@@ -134,6 +135,13 @@ pub enum Message {
     GetLocalPow,
     /// Get fallback to local proof of work timeout
     GetFallbackToLocalPow,
+    /// Estimate the duration of local PoW on this machine for a block of the given length, in milliseconds
+    #[cfg(not(target_family = "wasm"))]
+    EstimateLocalPowDuration {
+        /// Length of the block in bytes
+        #[serde(rename = "blockLength")]
+        block_length: usize,
+    },
     /// Returns the unhealthy nodes.
     #[cfg(not(target_family = "wasm"))]
     UnhealthyNodes,
@@ -170,6 +178,15 @@ pub enum Message {
         secret_manager: SecretManagerDto,
         /// Mnemonic
         mnemonic: String,
+        /// Optional BIP-39 passphrase
+        passphrase: Option<String>,
+    },
+    /// Check whether a mnemonic has been stored in the Stronghold vault
+    #[cfg(feature = "stronghold")]
+    IsMnemonicStored {
+        /// Stronghold secret manager
+        #[serde(rename = "secretManager")]
+        secret_manager: SecretManagerDto,
     },
     /// Build a block containing the specified payload and post it to the network.
     PostBlockPayload {
@@ -482,6 +499,14 @@ pub enum Message {
         /// Address
         address: String,
     },
+    /// Parses a user entered amount, e.g. "1.5 Mi", "1,5 SMR" or "1500 glow", into base units.
+    ParseAmount {
+        /// Amount with its unit
+        input: String,
+        /// Decimal separator, detected from the input if not provided; an ambiguous one, as in "1,500", is rejected
+        #[serde(rename = "decimalSeparator")]
+        decimal_separator: Option<DecimalSeparator>,
+    },
     /// Generates a new mnemonic.
     GenerateMnemonic,
     /// Returns a hex encoded seed for a mnemonic.
@@ -27,6 +27,7 @@ use crate::secret::ledger_nano::LedgerSecretManager;
 #[cfg(feature = "stronghold")]
 use crate::secret::SecretManager;
 use crate::{
+    amount::parse_amount,
     api::{PreparedTransactionData, PreparedTransactionDataDto},
     message_interface::{message::Message, response::Response},
     request_funds_from_faucet, Client, Result,
@@ -108,6 +109,10 @@ impl ClientMessageHandler {
             Message::StoreMnemonic { .. } => {
                 log::debug!("Response: StoreMnemonic{{ <omitted> }}")
             }
+            #[cfg(feature = "stronghold")]
+            Message::IsMnemonicStored { .. } => {
+                log::debug!("Response: IsMnemonicStored{{ secret_manager: <omitted> }}")
+            }
             Message::ConsolidateFunds {
                 secret_manager: _,
                 generate_addresses_options,
@@ -311,6 +316,10 @@ impl ClientMessageHandler {
             }
             Message::GetLocalPow => Ok(Response::LocalPow(self.client.get_local_pow())),
             Message::GetFallbackToLocalPow => Ok(Response::FallbackToLocalPow(self.client.get_fallback_to_local_pow())),
+            #[cfg(not(target_family = "wasm"))]
+            Message::EstimateLocalPowDuration { block_length } => Ok(Response::LocalPowDuration(
+                self.client.estimate_local_pow_duration(block_length).await?.as_millis() as u64,
+            )),
             #[cfg(feature = "ledger_nano")]
             Message::GetLedgerNanoStatus { is_simulator } => {
                 let ledger_nano = LedgerSecretManager::new(is_simulator);
@@ -362,16 +371,28 @@ impl ClientMessageHandler {
             Message::StoreMnemonic {
                 secret_manager,
                 mnemonic,
+                passphrase,
             } => {
                 let mut secret_manager = (&secret_manager).try_into()?;
                 if let SecretManager::Stronghold(secret_manager) = &mut secret_manager {
-                    secret_manager.store_mnemonic(mnemonic).await?;
+                    secret_manager
+                        .store_mnemonic_with_passphrase(mnemonic, passphrase)
+                        .await?;
                 } else {
                     return Err(crate::Error::SecretManagerMismatch);
                 }
 
                 Ok(Response::Ok)
             }
+            #[cfg(feature = "stronghold")]
+            Message::IsMnemonicStored { secret_manager } => {
+                let secret_manager = (&secret_manager).try_into()?;
+                if let SecretManager::Stronghold(secret_manager) = &secret_manager {
+                    Ok(Response::IsMnemonicStored(secret_manager.is_mnemonic_stored().await?))
+                } else {
+                    Err(crate::Error::SecretManagerMismatch)
+                }
+            }
             Message::PostBlockPayload { payload_dto } => {
                 let block_builder = self.client.block();
 
@@ -553,6 +574,12 @@ impl ClientMessageHandler {
                 &Client::parse_bech32_address(&address)?,
             ))),
             Message::IsAddressValid { address } => Ok(Response::IsAddressValid(Client::is_address_valid(&address))),
+            Message::ParseAmount {
+                input,
+                decimal_separator,
+            } => Ok(Response::ParsedAmount(
+                parse_amount(&input, decimal_separator.unwrap_or_default())?.to_string(),
+            )),
             Message::GenerateMnemonic => Ok(Response::GeneratedMnemonic(Client::generate_mnemonic()?)),
             Message::MnemonicToHexSeed { mut mnemonic } => {
                 let response = Response::MnemonicHexSeed(Client::mnemonic_to_hex_seed(&mnemonic)?);
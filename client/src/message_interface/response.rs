@@ -81,6 +81,11 @@ pub enum Response {
     /// - [`SignTransaction`](crate::message_interface::Message::SignTransaction)
     SignedTransaction(PayloadDto),
     /// Response for:
+    /// - [`EstimateLocalPowDuration`](crate::message_interface::Message::EstimateLocalPowDuration)
+    /// Expected duration in milliseconds
+    #[cfg(not(target_family = "wasm"))]
+    LocalPowDuration(u64),
+    /// Response for:
     /// - [`UnhealthyNodes`](crate::message_interface::Message::UnhealthyNodes)
     #[cfg(not(target_family = "wasm"))]
     UnhealthyNodes(HashSet<Node>),
@@ -191,6 +196,10 @@ pub enum Response {
     /// - [`IsAddressValid`](crate::message_interface::Message::IsAddressValid)
     IsAddressValid(bool),
     /// Response for:
+    /// - [`ParseAmount`](crate::message_interface::Message::ParseAmount)
+    /// Amount in base units, as a String to prevent overflow issues in bindings
+    ParsedAmount(String),
+    /// Response for:
     /// - [`GenerateMnemonic`](crate::message_interface::Message::GenerateMnemonic)
     GeneratedMnemonic(String),
     /// Response for:
@@ -217,6 +226,10 @@ pub enum Response {
     /// - [`Faucet`](crate::message_interface::Message::Faucet)
     Faucet(String),
     /// Response for:
+    /// - [`IsMnemonicStored`](crate::message_interface::Message::IsMnemonicStored)
+    #[cfg(feature = "stronghold")]
+    IsMnemonicStored(bool),
+    /// Response for:
     /// - [`StoreMnemonic`](crate::message_interface::Message::StoreMnemonic)
     Ok,
     /// Response for any method that returns an error.
@@ -5,6 +5,7 @@
 
 pub mod routes;
 
+use futures::{Stream, StreamExt};
 use iota_types::{
     api::response::{OutputMetadataResponse, OutputWithMetadataResponse},
     block::output::OutputId,
@@ -30,13 +31,10 @@ impl Client {
             for output_id in output_ids_chunk {
                 let client_ = self.clone();
 
-                tasks.push(async move {
-                    tokio::spawn(async move {
-                        let output_response = client_.get_output(&output_id).await?;
-                        crate::Result::Ok(output_response)
-                    })
-                    .await
-                });
+                tasks.push(
+                    self.node_manager
+                        .spawn(async move { client_.get_output(&output_id).await }),
+                );
             }
             for res in futures::future::try_join_all(tasks).await? {
                 let output_response = res?;
@@ -64,9 +62,12 @@ impl Client {
             for output_id in output_ids_chunk {
                 let client_ = self.clone();
 
-                tasks.push(async move { tokio::spawn(async move { client_.get_output(&output_id).await.ok() }).await });
+                tasks.push(
+                    self.node_manager
+                        .spawn(async move { client_.get_output(&output_id).await.ok() }),
+                );
             }
-            for output_response in (futures::future::try_join_all(tasks).await?).into_iter().flatten() {
+            for output_response in futures::future::try_join_all(tasks).await?.into_iter().flatten() {
                 outputs.push(output_response);
             }
         }
@@ -90,15 +91,113 @@ impl Client {
             for output_id in output_ids_chunk {
                 let client_ = self.clone();
 
-                tasks.push(async move {
-                    tokio::spawn(async move { client_.get_output_metadata(&output_id).await.ok() }).await
-                });
+                tasks.push(
+                    self.node_manager
+                        .spawn(async move { client_.get_output_metadata(&output_id).await.ok() }),
+                );
             }
-            for output_metadata_response in (futures::future::try_join_all(tasks).await?).into_iter().flatten() {
+            for output_metadata_response in futures::future::try_join_all(tasks).await?.into_iter().flatten() {
                 output_metadata_responses.push(output_metadata_response);
             }
         }
 
         Ok(output_metadata_responses)
     }
+
+    /// Request outputs by their output ID in order, prefetching the `depth` next ones while the current one is being
+    /// processed.
+    ///
+    /// Meant for sequential workloads walking long lists of outputs, e.g. history or audit tools, which would otherwise
+    /// wait for each request in turn. `output_ids` is consumed lazily, so it can be generated on the fly.
+    pub fn prefetch_outputs<I>(
+        &self,
+        output_ids: I,
+        depth: usize,
+    ) -> impl Stream<Item = Result<OutputWithMetadataResponse>> + 'static
+    where
+        I: IntoIterator<Item = OutputId>,
+        I::IntoIter: Send + 'static,
+    {
+        let client = self.clone();
+
+        futures::stream::iter(output_ids)
+            .map(move |output_id| {
+                let client_ = client.clone();
+                let request = async move { client_.get_output(&output_id).await };
+
+                // Spawned, so that the prefetched requests make progress while the stream isn't polled.
+                #[cfg(not(target_family = "wasm"))]
+                let request = {
+                    let task = client.node_manager.spawn(request);
+                    async move { task.await? }
+                };
+
+                request
+            })
+            // One more than `depth`, so that the next `depth` outputs are in flight once the current one is returned.
+            .buffered(depth + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use iota_types::block::rand::output::rand_output_id;
+
+    use super::*;
+
+    /// Answer all requests with a 404, counting the requests of outputs.
+    fn mock_node() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let output_requests = Arc::new(AtomicUsize::new(0));
+        let output_requests_ = output_requests.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                BufReader::new(&stream).read_line(&mut request_line).unwrap();
+
+                if request_line.starts_with("GET /api/core/v2/outputs/") {
+                    output_requests_.fetch_add(1, Ordering::SeqCst);
+                }
+
+                stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+            }
+        });
+
+        (url, output_requests)
+    }
+
+    #[test]
+    fn parallel_requests_outside_of_tokio() {
+        let (node_url, output_requests) = mock_node();
+        let client = Client::builder()
+            .with_node(&node_url)
+            .unwrap()
+            .with_ignore_node_health()
+            .finish()
+            .unwrap();
+        let output_ids = vec![rand_output_id(), rand_output_id()];
+
+        // The requests run on the Tokio runtime of the client, whatever the executor of the application.
+        #[cfg(feature = "async_std_runtime")]
+        let outputs = async_std::task::block_on(client.try_get_outputs(output_ids)).unwrap();
+        #[cfg(not(feature = "async_std_runtime"))]
+        let outputs = futures::executor::block_on(client.try_get_outputs(output_ids)).unwrap();
+
+        assert!(outputs.is_empty());
+        assert_eq!(output_requests.load(Ordering::SeqCst), 2);
+    }
 }
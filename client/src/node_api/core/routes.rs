@@ -445,7 +445,7 @@ impl Client {
         let path = &format!("api/core/v2/milestones/{milestone_id}/utxo-changes");
 
         self.node_manager
-            .get_request(path, None, self.get_timeout(), false, false)
+            .get_request(path, None, self.get_timeout(), false, true)
             .await
     }
 
@@ -484,7 +484,7 @@ impl Client {
         let path = &format!("api/core/v2/milestones/by-index/{index}/utxo-changes");
 
         self.node_manager
-            .get_request(path, None, self.get_timeout(), false, false)
+            .get_request(path, None, self.get_timeout(), false, true)
             .await
     }
 
@@ -0,0 +1,146 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Local scanning of indexer results by metadata content.
+//!
+//! The indexer API can filter outputs by address or tag, but not by the content of their metadata feature. The helpers
+//! in this module page through the indexer results, fetch the outputs with a bounded number of parallel requests, and
+//! filter them locally with a user-provided predicate over the metadata.
+
+use std::str::FromStr;
+
+use futures::{StreamExt, TryStreamExt};
+use iota_types::{
+    api::response::OutputWithMetadataResponse,
+    block::output::{Output, OutputId},
+};
+
+use super::{QueryParameter, QueryParameters};
+use crate::{node_api::indexer::OutputIdsResponse, Client, Error, Result};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const DEFAULT_MAX_PARALLEL_REQUESTS: usize = 10;
+
+/// Options for [`Client::search_basic_outputs_by_metadata()`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataSearchOptions {
+    /// Number of output ids requested per indexer page.
+    pub page_size: usize,
+    /// Maximum number of outputs requested in parallel.
+    pub max_parallel_requests: usize,
+    /// Stop scanning once this many matching outputs have been found.
+    pub max_results: Option<usize>,
+}
+
+impl Default for MetadataSearchOptions {
+    fn default() -> Self {
+        Self {
+            page_size: DEFAULT_PAGE_SIZE,
+            max_parallel_requests: DEFAULT_MAX_PARALLEL_REQUESTS,
+            max_results: None,
+        }
+    }
+}
+
+impl Client {
+    /// Page through the basic outputs matching `query_parameters` (e.g. an address or a tag) and return the ones with
+    /// a metadata feature for which `predicate` returns `true`.
+    ///
+    /// Outputs are fetched with at most `options.max_parallel_requests` requests in flight. Outputs without a metadata
+    /// feature are skipped.
+    pub async fn search_basic_outputs_by_metadata<F>(
+        &self,
+        query_parameters: Vec<QueryParameter>,
+        options: MetadataSearchOptions,
+        predicate: F,
+    ) -> Result<Vec<OutputWithMetadataResponse>>
+    where
+        F: Fn(&[u8]) -> bool,
+    {
+        let route = "api/indexer/v1/outputs/basic";
+
+        if let Some(qp) = query_parameters
+            .iter()
+            .find(|qp| matches!(qp, QueryParameter::Cursor(_) | QueryParameter::PageSize(_)))
+        {
+            return Err(Error::UnsupportedQueryParameter(qp.clone()));
+        }
+
+        let token_supply = self.get_token_supply().await?;
+        let mut query_parameters = QueryParameters::new(query_parameters);
+        query_parameters.replace(QueryParameter::PageSize(options.page_size));
+
+        let mut matches = Vec::new();
+
+        loop {
+            let outputs_response = self
+                .node_manager
+                .get_request::<OutputIdsResponse>(
+                    route,
+                    query_parameters.to_query_string().as_deref(),
+                    self.get_timeout(),
+                    true,
+                    false,
+                )
+                .await?;
+
+            let output_ids = outputs_response
+                .items
+                .iter()
+                .map(|output_id| OutputId::from_str(output_id))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            let outputs: Vec<OutputWithMetadataResponse> = futures::stream::iter(output_ids)
+                .map(|output_id| async move { self.get_output(&output_id).await })
+                .buffered(options.max_parallel_requests.max(1))
+                .try_collect()
+                .await?;
+
+            for output_response in outputs {
+                let output = Output::try_from_dto(&output_response.output, token_supply)?;
+
+                if let Some(metadata) = output.features().and_then(|features| features.metadata()) {
+                    if predicate(metadata.data()) {
+                        matches.push(output_response);
+
+                        if options.max_results.map_or(false, |max| matches.len() >= max) {
+                            return Ok(matches);
+                        }
+                    }
+                }
+            }
+
+            match outputs_response.cursor {
+                Some(cursor) => query_parameters.replace(QueryParameter::Cursor(cursor)),
+                None => break,
+            }
+        }
+
+        Ok(matches)
+    }
+}
+
+/// Returns a predicate matching metadata that decodes as a JSON object with `field` set to `value`.
+///
+/// To be used with [`Client::search_basic_outputs_by_metadata()`].
+pub fn json_field_matches<'a>(field: &'a str, value: &'a serde_json::Value) -> impl Fn(&[u8]) -> bool + 'a {
+    move |metadata| {
+        serde_json::from_slice::<serde_json::Value>(metadata).map_or(false, |json| json.get(field) == Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_field_predicate() {
+        let value = serde_json::Value::from("invoice-42");
+        let predicate = json_field_matches("reference", &value);
+
+        assert!(predicate(br#"{"reference":"invoice-42","amount":1}"#));
+        assert!(!predicate(br#"{"reference":"invoice-43"}"#));
+        assert!(!predicate(b"not json"));
+    }
+}
@@ -3,6 +3,7 @@
 
 //! Node indexer API.
 
+pub mod metadata_search;
 pub mod query_parameters;
 pub mod responses;
 pub mod routes;
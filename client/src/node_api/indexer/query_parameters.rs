@@ -167,6 +167,192 @@ impl fmt::Display for QueryParameter {
     }
 }
 
+macro_rules! query_parameters_builder {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $(
+                $(#[$method_meta:meta])*
+                $method:ident($value:ident: $ty:ty) => $variant:ident($conversion:expr)
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        #[must_use]
+        pub struct $name(QueryParameters);
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self(QueryParameters::new(Vec::new()))
+            }
+        }
+
+        impl $name {
+            /// Creates an empty query, matching all the outputs of this kind.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            $(
+                $(#[$method_meta])*
+                pub fn $method(mut self, $value: $ty) -> Self {
+                    self.0.replace(QueryParameter::$variant($conversion));
+                    self
+                }
+            )*
+        }
+
+        impl From<$name> for Vec<QueryParameter> {
+            fn from(query: $name) -> Self {
+                query.0 .0
+            }
+        }
+    };
+}
+
+query_parameters_builder! {
+    /// Typed query parameters of [`Client::basic_output_ids()`](crate::Client::basic_output_ids()), only accepting
+    /// the filters of basic outputs.
+    BasicOutputsQuery {
+        /// Filters outputs unlockable by the Bech32-encoded `address`.
+        address(address: impl Into<String>) => Address(address.into()),
+        /// Filters outputs based on the presence of native tokens.
+        has_native_tokens(value: bool) => HasNativeTokens(value),
+        /// Filters outputs that have at least `count` distinct native tokens.
+        min_native_token_count(count: u32) => MinNativeTokenCount(count),
+        /// Filters outputs that have at most `count` distinct native tokens.
+        max_native_token_count(count: u32) => MaxNativeTokenCount(count),
+        /// Filters outputs based on the presence of a storage deposit return unlock condition.
+        has_storage_deposit_return(value: bool) => HasStorageDepositReturn(value),
+        /// Filters outputs with the Bech32-encoded `address` in their storage deposit return unlock condition.
+        storage_deposit_return_address(address: impl Into<String>) => StorageDepositReturnAddress(address.into()),
+        /// Filters outputs based on the presence of a timelock unlock condition.
+        has_timelock(value: bool) => HasTimelock(value),
+        /// Filters outputs timelocked before the Unix `timestamp`.
+        timelocked_before(timestamp: u32) => TimelockedBefore(timestamp),
+        /// Filters outputs timelocked after the Unix `timestamp`.
+        timelocked_after(timestamp: u32) => TimelockedAfter(timestamp),
+        /// Filters outputs based on the presence of an expiration unlock condition.
+        has_expiration(value: bool) => HasExpiration(value),
+        /// Filters outputs expiring before the Unix `timestamp`.
+        expires_before(timestamp: u32) => ExpiresBefore(timestamp),
+        /// Filters outputs expiring after the Unix `timestamp`.
+        expires_after(timestamp: u32) => ExpiresAfter(timestamp),
+        /// Filters outputs with the Bech32-encoded `address` in their expiration unlock condition.
+        expiration_return_address(address: impl Into<String>) => ExpirationReturnAddress(address.into()),
+        /// Filters outputs with the validated Bech32-encoded `address` in their sender feature.
+        sender(address: impl Into<String>) => Sender(address.into()),
+        /// Filters outputs with `tag` in their tag feature.
+        tag(tag: impl AsRef<[u8]>) => Tag(prefix_hex::encode(tag.as_ref())),
+        /// Filters outputs created before the Unix `timestamp`.
+        created_before(timestamp: u32) => CreatedBefore(timestamp),
+        /// Filters outputs created after the Unix `timestamp`.
+        created_after(timestamp: u32) => CreatedAfter(timestamp),
+        /// The maximum number of output ids returned per page.
+        page_size(page_size: usize) => PageSize(page_size),
+        /// Starts the search from `cursor`, returned with a previous page.
+        cursor(cursor: impl Into<String>) => Cursor(cursor.into()),
+    }
+}
+
+query_parameters_builder! {
+    /// Typed query parameters of [`Client::alias_output_ids()`](crate::Client::alias_output_ids()), only accepting
+    /// the filters of alias outputs.
+    AliasOutputsQuery {
+        /// Filters outputs with the Bech32-encoded `address` as state controller.
+        state_controller(address: impl Into<String>) => StateController(address.into()),
+        /// Filters outputs with the Bech32-encoded `address` as governor.
+        governor(address: impl Into<String>) => Governor(address.into()),
+        /// Filters outputs with the validated Bech32-encoded `address` in their issuer feature.
+        issuer(address: impl Into<String>) => Issuer(address.into()),
+        /// Filters outputs with the validated Bech32-encoded `address` in their sender feature.
+        sender(address: impl Into<String>) => Sender(address.into()),
+        /// Filters outputs based on the presence of native tokens.
+        has_native_tokens(value: bool) => HasNativeTokens(value),
+        /// Filters outputs that have at least `count` distinct native tokens.
+        min_native_token_count(count: u32) => MinNativeTokenCount(count),
+        /// Filters outputs that have at most `count` distinct native tokens.
+        max_native_token_count(count: u32) => MaxNativeTokenCount(count),
+        /// Filters outputs created before the Unix `timestamp`.
+        created_before(timestamp: u32) => CreatedBefore(timestamp),
+        /// Filters outputs created after the Unix `timestamp`.
+        created_after(timestamp: u32) => CreatedAfter(timestamp),
+        /// The maximum number of output ids returned per page.
+        page_size(page_size: usize) => PageSize(page_size),
+        /// Starts the search from `cursor`, returned with a previous page.
+        cursor(cursor: impl Into<String>) => Cursor(cursor.into()),
+    }
+}
+
+query_parameters_builder! {
+    /// Typed query parameters of [`Client::foundry_output_ids()`](crate::Client::foundry_output_ids()), only
+    /// accepting the filters of foundry outputs.
+    FoundryOutputsQuery {
+        /// Filters outputs controlled by the alias of the Bech32-encoded `address`.
+        alias_address(address: impl Into<String>) => AliasAddress(address.into()),
+        /// Filters outputs based on the presence of native tokens.
+        has_native_tokens(value: bool) => HasNativeTokens(value),
+        /// Filters outputs that have at least `count` distinct native tokens.
+        min_native_token_count(count: u32) => MinNativeTokenCount(count),
+        /// Filters outputs that have at most `count` distinct native tokens.
+        max_native_token_count(count: u32) => MaxNativeTokenCount(count),
+        /// Filters outputs created before the Unix `timestamp`.
+        created_before(timestamp: u32) => CreatedBefore(timestamp),
+        /// Filters outputs created after the Unix `timestamp`.
+        created_after(timestamp: u32) => CreatedAfter(timestamp),
+        /// The maximum number of output ids returned per page.
+        page_size(page_size: usize) => PageSize(page_size),
+        /// Starts the search from `cursor`, returned with a previous page.
+        cursor(cursor: impl Into<String>) => Cursor(cursor.into()),
+    }
+}
+
+query_parameters_builder! {
+    /// Typed query parameters of [`Client::nft_output_ids()`](crate::Client::nft_output_ids()), only accepting the
+    /// filters of NFT outputs.
+    NftOutputsQuery {
+        /// Filters outputs unlockable by the Bech32-encoded `address`.
+        address(address: impl Into<String>) => Address(address.into()),
+        /// Filters outputs based on the presence of native tokens.
+        has_native_tokens(value: bool) => HasNativeTokens(value),
+        /// Filters outputs that have at least `count` distinct native tokens.
+        min_native_token_count(count: u32) => MinNativeTokenCount(count),
+        /// Filters outputs that have at most `count` distinct native tokens.
+        max_native_token_count(count: u32) => MaxNativeTokenCount(count),
+        /// Filters outputs based on the presence of a storage deposit return unlock condition.
+        has_storage_deposit_return(value: bool) => HasStorageDepositReturn(value),
+        /// Filters outputs with the Bech32-encoded `address` in their storage deposit return unlock condition.
+        storage_deposit_return_address(address: impl Into<String>) => StorageDepositReturnAddress(address.into()),
+        /// Filters outputs based on the presence of a timelock unlock condition.
+        has_timelock(value: bool) => HasTimelock(value),
+        /// Filters outputs timelocked before the Unix `timestamp`.
+        timelocked_before(timestamp: u32) => TimelockedBefore(timestamp),
+        /// Filters outputs timelocked after the Unix `timestamp`.
+        timelocked_after(timestamp: u32) => TimelockedAfter(timestamp),
+        /// Filters outputs based on the presence of an expiration unlock condition.
+        has_expiration(value: bool) => HasExpiration(value),
+        /// Filters outputs expiring before the Unix `timestamp`.
+        expires_before(timestamp: u32) => ExpiresBefore(timestamp),
+        /// Filters outputs expiring after the Unix `timestamp`.
+        expires_after(timestamp: u32) => ExpiresAfter(timestamp),
+        /// Filters outputs with the Bech32-encoded `address` in their expiration unlock condition.
+        expiration_return_address(address: impl Into<String>) => ExpirationReturnAddress(address.into()),
+        /// Filters outputs with the validated Bech32-encoded `address` in their sender feature.
+        sender(address: impl Into<String>) => Sender(address.into()),
+        /// Filters outputs with `tag` in their tag feature.
+        tag(tag: impl AsRef<[u8]>) => Tag(prefix_hex::encode(tag.as_ref())),
+        /// Filters outputs created before the Unix `timestamp`.
+        created_before(timestamp: u32) => CreatedBefore(timestamp),
+        /// Filters outputs created after the Unix `timestamp`.
+        created_after(timestamp: u32) => CreatedAfter(timestamp),
+        /// The maximum number of output ids returned per page.
+        page_size(page_size: usize) => PageSize(page_size),
+        /// Starts the search from `cursor`, returned with a previous page.
+        cursor(cursor: impl Into<String>) => Cursor(cursor.into()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +375,31 @@ mod tests {
         query_parameters.replace(address3);
         assert!(query_parameters.0.len() == 2);
     }
+
+    #[test]
+    fn typed_query() {
+        let address = "atoi1qzt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupx3y7x0r";
+        let query_parameters: Vec<QueryParameter> = BasicOutputsQuery::new()
+            .tag(b"tag")
+            .address("atoi1qprxpfvaz2peggq6f8k9cj8zfsxuw69e4nszjyv5kuf8yt70t2847shpjak")
+            .has_expiration(false)
+            // replaces the previous address
+            .address(address)
+            .into();
+
+        assert_eq!(
+            query_parameters,
+            vec![
+                QueryParameter::Address(address.to_string()),
+                QueryParameter::HasExpiration(false),
+                QueryParameter::Tag("0x746167".to_string()),
+            ]
+        );
+        assert_eq!(
+            QueryParameters::new(FoundryOutputsQuery::new().alias_address(address).page_size(10).into())
+                .to_query_string(),
+            Some(format!("aliasAddress={address}&pageSize=10"))
+        );
+        assert!(Vec::<QueryParameter>::from(NftOutputsQuery::new()).is_empty());
+    }
 }
@@ -27,7 +27,11 @@ impl Client {
     /// "hasExpiration", "expiresBefore", "expiresAfter", "hasTimelock", "timelockedBefore",
     /// "timelockedAfter", "sender", "tag", "createdBefore" and "createdAfter". Returns an empty Vec if no results
     /// are found. api/indexer/v1/outputs/basic
-    pub async fn basic_output_ids(&self, query_parameters: Vec<QueryParameter>) -> Result<Vec<OutputId>> {
+    /// The parameters are either a
+    /// [`BasicOutputsQuery`](crate::node_api::indexer::query_parameters::BasicOutputsQuery), only accepting the filters
+    /// of these outputs, or a list of [`QueryParameter`]s, verified at runtime.
+    pub async fn basic_output_ids(&self, query_parameters: impl Into<Vec<QueryParameter>>) -> Result<Vec<OutputId>> {
+        let query_parameters = query_parameters.into();
         let route = "api/indexer/v1/outputs/basic";
 
         verify_query_parameters!(
@@ -62,7 +66,11 @@ impl Client {
     /// Query parameters: "stateController", "governor", "issuer", "sender", "createdBefore", "createdAfter"
     /// Returns an empty list if no results are found.
     /// api/indexer/v1/outputs/alias
-    pub async fn alias_output_ids(&self, query_parameters: Vec<QueryParameter>) -> Result<Vec<OutputId>> {
+    /// The parameters are either a
+    /// [`AliasOutputsQuery`](crate::node_api::indexer::query_parameters::AliasOutputsQuery), only accepting the filters
+    /// of these outputs, or a list of [`QueryParameter`]s, verified at runtime.
+    pub async fn alias_output_ids(&self, query_parameters: impl Into<Vec<QueryParameter>>) -> Result<Vec<OutputId>> {
+        let query_parameters = query_parameters.into();
         let route = "api/indexer/v1/outputs/alias";
 
         verify_query_parameters!(
@@ -101,7 +109,11 @@ impl Client {
     /// Query parameters: "address", "createdBefore", "createdAfter"
     /// Returns an empty list if no results are found.
     /// api/indexer/v1/outputs/foundry
-    pub async fn foundry_output_ids(&self, query_parameters: Vec<QueryParameter>) -> Result<Vec<OutputId>> {
+    /// The parameters are either a
+    /// [`FoundryOutputsQuery`](crate::node_api::indexer::query_parameters::FoundryOutputsQuery), only accepting the
+    /// filters of these outputs, or a list of [`QueryParameter`]s, verified at runtime.
+    pub async fn foundry_output_ids(&self, query_parameters: impl Into<Vec<QueryParameter>>) -> Result<Vec<OutputId>> {
+        let query_parameters = query_parameters.into();
         let route = "api/indexer/v1/outputs/foundry";
 
         verify_query_parameters!(
@@ -138,7 +150,11 @@ impl Client {
     /// "timelockedAfter", "issuer", "sender", "tag", "createdBefore", "createdAfter"
     /// Returns an empty list if no results are found.
     /// api/indexer/v1/outputs/nft
-    pub async fn nft_output_ids(&self, query_parameters: Vec<QueryParameter>) -> Result<Vec<OutputId>> {
+    /// The parameters are either a
+    /// [`NftOutputsQuery`](crate::node_api::indexer::query_parameters::NftOutputsQuery), only accepting the filters of
+    /// these outputs, or a list of [`QueryParameter`]s, verified at runtime.
+    pub async fn nft_output_ids(&self, query_parameters: impl Into<Vec<QueryParameter>>) -> Result<Vec<OutputId>> {
+        let query_parameters = query_parameters.into();
         let route = "api/indexer/v1/outputs/nft";
 
         verify_query_parameters!(
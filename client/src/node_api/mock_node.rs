@@ -0,0 +1,139 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A node answering the requests of the client with canned responses, for tests.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+};
+
+use iota_types::{
+    api::response::{
+        BaseTokenResponse, ConfirmedMilestoneResponse, InfoResponse, LatestMilestoneResponse, MetricsResponse,
+        StatusResponse,
+    },
+    block::{
+        output::dto::RentStructureDto,
+        protocol::{dto::ProtocolParametersDto, ProtocolParameters},
+    },
+};
+
+/// A request received by the mock node.
+pub(crate) struct MockRequest {
+    /// The method of the request, e.g. `GET`.
+    pub(crate) method: String,
+    /// The path of the request, with its query.
+    pub(crate) path: String,
+    /// The body of the request.
+    pub(crate) body: Vec<u8>,
+}
+
+/// Serve the requests with `handler`, answering the JSON body it returns, or a 404 for `None`, and return the URL of
+/// the node.
+pub(crate) fn mock_node(handler: impl Fn(&MockRequest) -> Option<String> + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                let header = header.trim_end();
+                if header.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(": ") {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let mut request_line = request_line.split(' ');
+            let request = MockRequest {
+                method: request_line.next().unwrap_or_default().to_string(),
+                path: request_line.next().unwrap_or_default().to_string(),
+                body,
+            };
+
+            let response = match handler(&request) {
+                Some(body) => format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: \
+                     close\r\n\r\n{body}",
+                    body.len()
+                ),
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    url
+}
+
+/// The info of a healthy node with the default protocol parameters and a PoW score of 0, at
+/// `confirmed_milestone_index`.
+pub(crate) fn info_response(confirmed_milestone_index: u32) -> String {
+    let protocol_parameters = ProtocolParameters::default();
+    let rent_structure = protocol_parameters.rent_structure();
+
+    serde_json::to_string(&InfoResponse {
+        name: "HORNET".to_string(),
+        version: "2.0.0".to_string(),
+        status: StatusResponse {
+            is_healthy: true,
+            latest_milestone: LatestMilestoneResponse {
+                index: confirmed_milestone_index,
+                timestamp: None,
+                milestone_id: None,
+            },
+            confirmed_milestone: ConfirmedMilestoneResponse {
+                index: confirmed_milestone_index,
+                timestamp: None,
+                milestone_id: None,
+            },
+            pruning_index: 0,
+        },
+        supported_protocol_versions: vec![protocol_parameters.protocol_version()],
+        protocol: ProtocolParametersDto {
+            protocol_version: protocol_parameters.protocol_version(),
+            network_name: protocol_parameters.network_name().to_string(),
+            bech32_hrp: protocol_parameters.bech32_hrp().to_string(),
+            min_pow_score: 0,
+            below_max_depth: protocol_parameters.below_max_depth(),
+            rent_structure: RentStructureDto {
+                v_byte_cost: rent_structure.byte_cost(),
+                v_byte_factor_key: rent_structure.byte_factor_key(),
+                v_byte_factor_data: rent_structure.byte_factor_data(),
+            },
+            token_supply: protocol_parameters.token_supply().to_string(),
+        },
+        pending_protocol_parameters: Vec::new(),
+        base_token: BaseTokenResponse {
+            name: "Shimmer".to_string(),
+            ticker_symbol: "SMR".to_string(),
+            unit: "SMR".to_string(),
+            subunit: Some("glow".to_string()),
+            decimals: 6,
+            use_metric_prefix: false,
+        },
+        metrics: MetricsResponse {
+            blocks_per_second: 0.0,
+            referenced_blocks_per_second: 0.0,
+            referenced_rate: 0.0,
+        },
+        features: Vec::new(),
+    })
+    .unwrap()
+}
@@ -5,6 +5,8 @@
 
 pub mod core;
 pub mod indexer;
+#[cfg(test)]
+pub(crate) mod mock_node;
 #[cfg(feature = "mqtt")]
 pub mod mqtt;
 #[cfg(feature = "participation")]
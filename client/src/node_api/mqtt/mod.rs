@@ -4,21 +4,23 @@
 //! IOTA node MQTT API
 pub mod types;
 
-use std::{
-    sync::{Arc, RwLock as StdRwLock},
-    time::Instant,
-};
+use std::sync::{Arc, RwLock as StdRwLock};
 
 use crypto::utils;
-use iota_types::block::{
-    payload::{milestone::ReceiptMilestoneOption, MilestonePayload},
-    Block,
+use iota_types::{
+    api::response::OutputWithMetadataResponse,
+    block::{
+        output::Output,
+        payload::{milestone::ReceiptMilestoneOption, MilestonePayload},
+        protocol::ProtocolParameters,
+        Block,
+    },
 };
 use log::warn;
 use packable::PackableExt;
 use rumqttc::{
-    AsyncClient as MqttClient, Event, EventLoop, Incoming, MqttOptions, QoS, Request, Subscribe, SubscribeFilter,
-    Transport,
+    AsyncClient as MqttClient, ConnectionError, Event, EventLoop, Incoming, MqttOptions, QoS, Request, Subscribe,
+    SubscribeFilter, Transport,
 };
 use tokio::sync::{
     watch::{Receiver as WatchReceiver, Sender},
@@ -26,7 +28,7 @@ use tokio::sync::{
 };
 
 pub use self::types::*;
-use crate::{Client, NetworkInfo, Result};
+use crate::{secret::types::OutputMetadata, Client, NetworkInfo, Result};
 
 impl Client {
     /// Returns a handle to the MQTT topics manager.
@@ -90,18 +92,18 @@ async fn get_mqtt_client(client: &mut Client) -> Result<&mut MqttClient> {
                 utils::rand::fill(&mut entropy)?;
                 let id = format!("iotars{}", prefix_hex::encode(entropy));
                 let port = client.broker_options.port;
-                let mut uri = format!(
-                    "{}://{}:{}/api/mqtt/v1",
-                    if node.url.scheme() == "https" { "wss" } else { "ws" },
-                    host,
-                    node.url.port_or_known_default().unwrap_or(port)
-                );
-
-                if !client.broker_options.use_ws {
-                    uri = host.to_string();
+                let transport = client.broker_options.transport(&node.url);
+                let uri = match transport {
+                    MqttTransport::WebSocket => format!(
+                        "{}://{}:{}/api/mqtt/v1",
+                        if node.url.scheme() == "https" { "wss" } else { "ws" },
+                        host,
+                        node.url.port_or_known_default().unwrap_or(port)
+                    ),
+                    MqttTransport::Tcp => host.to_string(),
                 };
                 let mut mqtt_options = MqttOptions::new(id, uri, port);
-                if client.broker_options.use_ws {
+                if transport == MqttTransport::WebSocket {
                     mqtt_options.set_transport(Transport::ws());
                 }
                 mqtt_options.set_connection_timeout(client.broker_options.timeout.as_secs());
@@ -153,8 +155,8 @@ fn poll_mqtt(
             // the `is_subscribed` flag is set to false on event error, so the ConnAck event
             // can perform the re-subscriptions and reset `is_subscribed` to true.
             // we need the flag since the first ConnAck must be ignored.
+            // the reconnection attempts are delayed with an exponential backoff, reset once connected again.
             let mut is_subscribed = true;
-            let mut error_instant = Instant::now();
             let mut connection_failure_count = 0;
             let handle = event_loop.handle();
 
@@ -164,6 +166,7 @@ fn poll_mqtt(
 
                 match event {
                     Ok(Event::Incoming(Incoming::ConnAck(_))) => {
+                        connection_failure_count = 0;
                         let _ = event_sender.send(MqttEvent::Connected);
                         if !is_subscribed {
                             is_subscribed = true;
@@ -187,58 +190,13 @@ fn poll_mqtt(
 
                             if let Some(handlers) = mqtt_topic_handlers.get(&Topic::new_unchecked(topic.clone())) {
                                 let event = {
-                                    if topic.contains("blocks") || topic.contains("included-block") {
-                                        let payload = &*p.payload;
-                                        let protocol_parameters = &network_info.read().unwrap().protocol_parameters;
-
-                                        match Block::unpack_verified(payload, protocol_parameters) {
-                                            Ok(block) => Ok(TopicEvent {
-                                                topic,
-                                                payload: MqttPayload::Block(block),
-                                            }),
-                                            Err(e) => {
-                                                warn!("Block unpacking failed: {:?}", e);
-                                                Err(())
-                                            }
-                                        }
-                                    } else if topic.contains("milestones") {
-                                        let payload = &*p.payload;
-                                        let protocol_parameters = &network_info.read().unwrap().protocol_parameters;
-
-                                        match MilestonePayload::unpack_verified(payload, protocol_parameters) {
-                                            Ok(milestone_payload) => Ok(TopicEvent {
-                                                topic,
-                                                payload: MqttPayload::MilestonePayload(milestone_payload),
-                                            }),
-                                            Err(e) => {
-                                                warn!("MilestonePayload unpacking failed: {:?}", e);
-                                                Err(())
-                                            }
-                                        }
-                                    } else if topic.contains("receipts") {
-                                        let payload = &*p.payload;
-                                        let protocol_parameters = &network_info.read().unwrap().protocol_parameters;
-
-                                        match ReceiptMilestoneOption::unpack_verified(payload, protocol_parameters) {
-                                            Ok(receipt) => Ok(TopicEvent {
-                                                topic,
-                                                payload: MqttPayload::Receipt(receipt),
-                                            }),
-                                            Err(e) => {
-                                                warn!("Receipt unpacking failed: {:?}", e);
-                                                Err(())
-                                            }
-                                        }
-                                    } else {
-                                        match serde_json::from_slice(&p.payload) {
-                                            Ok(value) => Ok(TopicEvent {
-                                                topic,
-                                                payload: MqttPayload::Json(value),
-                                            }),
-                                            Err(e) => {
-                                                warn!("Cannot parse JSON: {:?}", e);
-                                                Err(())
-                                            }
+                                    let protocol_parameters = &network_info.read().unwrap().protocol_parameters;
+
+                                    match parse_payload(&topic, &p.payload, protocol_parameters) {
+                                        Ok(payload) => Ok(TopicEvent { topic, payload }),
+                                        Err(e) => {
+                                            warn!("Payload parsing failed for topic {}: {:?}", topic, e);
+                                            Err(())
                                         }
                                     }
                                 };
@@ -250,18 +208,20 @@ fn poll_mqtt(
                             }
                         });
                     }
+                    // The event loop was cancelled, e.g. on disconnection.
+                    Err(ConnectionError::Cancel) => {
+                        let _ = event_sender.send(MqttEvent::Disconnected);
+                        break;
+                    }
                     Err(_) => {
-                        if error_instant.elapsed().as_secs() < 5 {
-                            connection_failure_count += 1;
-                        } else {
-                            connection_failure_count = 1;
-                        }
+                        connection_failure_count += 1;
                         if connection_failure_count == options.max_reconnection_attempts {
                             let _ = event_sender.send(MqttEvent::Disconnected);
                             break;
                         }
-                        error_instant = Instant::now();
                         is_subscribed = false;
+                        let _ = event_sender.send(MqttEvent::Reconnecting(connection_failure_count));
+                        tokio::time::sleep(options.reconnection_delay(connection_failure_count)).await;
                     }
                     _ => {}
                 }
@@ -270,6 +230,30 @@ fn poll_mqtt(
     });
 }
 
+/// Parses the payload of an event of `topic` into the types of the crate.
+fn parse_payload(topic: &str, payload: &[u8], protocol_parameters: &ProtocolParameters) -> Result<MqttPayload> {
+    Ok(if topic.contains("blocks") || topic.contains("included-block") {
+        MqttPayload::Block(Block::unpack_verified(payload, protocol_parameters)?)
+    } else if topic.contains("milestones") {
+        MqttPayload::MilestonePayload(MilestonePayload::unpack_verified(payload, protocol_parameters)?)
+    } else if topic.contains("receipts") {
+        MqttPayload::Receipt(ReceiptMilestoneOption::unpack_verified(payload, protocol_parameters)?)
+    } else if topic.starts_with("milestone-info/") {
+        MqttPayload::MilestoneInfo(serde_json::from_slice(payload)?)
+    } else if topic.starts_with("block-metadata/") {
+        MqttPayload::BlockMetadata(serde_json::from_slice(payload)?)
+    } else if topic.starts_with("outputs/") {
+        let output_response: OutputWithMetadataResponse = serde_json::from_slice(payload)?;
+
+        MqttPayload::OutputWithMetadata(OutputWithMetadata {
+            output: Output::try_from_dto(&output_response.output, protocol_parameters.token_supply())?,
+            metadata: OutputMetadata::try_from(&output_response.metadata)?,
+        })
+    } else {
+        MqttPayload::Json(serde_json::from_slice(payload)?)
+    })
+}
+
 /// MQTT subscriber.
 pub struct MqttManager<'a> {
     client: &'a mut Client,
@@ -297,10 +281,12 @@ impl<'a> MqttManager<'a> {
     }
 
     /// Disconnects the broker.
-    /// This will clear the stored topic handlers and close the MQTT connection.
+    /// This will clear the stored topic handlers, close the MQTT connection and end its event loop.
     pub async fn disconnect(self) -> Result<()> {
         if let Some(client) = &self.client.mqtt_client {
             client.disconnect().await?;
+            // Otherwise the event loop would keep reconnecting; ignore errors in case it already ended.
+            let _ = client.cancel().await;
             self.client.mqtt_client = None;
 
             let mqtt_topic_handlers = &self.client.mqtt_topic_handlers;
@@ -403,3 +389,76 @@ impl<'a> MqttTopicManager<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use iota_types::block::{
+        payload::{milestone::MilestoneId, transaction::TransactionId},
+        protocol::protocol_parameters,
+    };
+    use url::Url;
+
+    use super::*;
+
+    #[test]
+    fn typed_topics() {
+        for topic in [
+            Topic::blocks(),
+            Topic::latest_milestone_info(),
+            Topic::confirmed_milestone_info(),
+            Topic::address_outputs("rms1qzev36lk0gzld0k28fd2fauz26qqzh4hd4cwymlqlv96x7phjxcw6v3ea5a").unwrap(),
+            Topic::transaction_included_block(&TransactionId::null()),
+        ] {
+            assert_eq!(Topic::try_new(topic.topic().to_string()).unwrap(), topic);
+        }
+    }
+
+    #[test]
+    fn reconnection_backoff() {
+        let options = BrokerOptions::new()
+            .initial_reconnection_delay(Duration::from_millis(500))
+            .max_reconnection_delay(Duration::from_secs(5));
+
+        assert_eq!(options.reconnection_delay(1), Duration::from_millis(500));
+        assert_eq!(options.reconnection_delay(2), Duration::from_secs(1));
+        assert_eq!(options.reconnection_delay(4), Duration::from_secs(4));
+        assert_eq!(options.reconnection_delay(5), Duration::from_secs(5));
+        assert_eq!(options.reconnection_delay(usize::MAX), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn node_transports() {
+        let proxied_node = Url::parse("https://proxied.node/").unwrap();
+        let options = BrokerOptions::new()
+            .use_ws(false)
+            .node_transport(proxied_node.clone(), MqttTransport::WebSocket);
+
+        assert_eq!(options.transport(&proxied_node), MqttTransport::WebSocket);
+        assert_eq!(
+            options.transport(&Url::parse("https://other.node/").unwrap()),
+            MqttTransport::Tcp
+        );
+    }
+
+    #[test]
+    fn parse_milestone_info() {
+        let payload = parse_payload(
+            "milestone-info/confirmed",
+            br#"{"index":12,"timestamp":1668000000,"milestoneId":"0x0000000000000000000000000000000000000000000000000000000000000000"}"#,
+            &protocol_parameters(),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            payload,
+            MqttPayload::MilestoneInfo(MilestoneInfo {
+                index: 12,
+                timestamp: 1668000000,
+                milestone_id,
+            }) if milestone_id == MilestoneId::null()
+        ));
+        assert!(parse_payload("outputs/unlock/address/rms1", b"{}", &protocol_parameters()).is_err());
+    }
+}
@@ -5,14 +5,23 @@
 
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use iota_types::block::{
-    payload::{milestone::ReceiptMilestoneOption, MilestonePayload},
-    Block,
+use iota_types::{
+    api::response::BlockMetadataResponse,
+    block::{
+        output::Output,
+        payload::{
+            milestone::{MilestoneId, ReceiptMilestoneOption},
+            transaction::TransactionId,
+            MilestonePayload,
+        },
+        Block,
+    },
 };
 use regex::RegexSet;
 use serde_json::Value;
+use url::Url;
 
-use crate::Result;
+use crate::{secret::types::OutputMetadata, Result};
 
 type TopicHandler = Box<dyn Fn(&TopicEvent) + Send + Sync>;
 
@@ -40,6 +49,33 @@ pub enum MqttPayload {
     MilestonePayload(MilestonePayload),
     /// In case it contains a `Receipt` object.
     Receipt(ReceiptMilestoneOption),
+    /// In case it contains the metadata of a block.
+    BlockMetadata(BlockMetadataResponse),
+    /// In case it contains the info of a milestone.
+    MilestoneInfo(MilestoneInfo),
+    /// In case it contains an `Output` object and its metadata.
+    OutputWithMetadata(OutputWithMetadata),
+}
+
+/// The info of a milestone, published on the `milestone-info/latest` and `milestone-info/confirmed` topics.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MilestoneInfo {
+    /// The index of the milestone.
+    pub index: u32,
+    /// The timestamp of the milestone.
+    pub timestamp: u32,
+    /// The identifier of the milestone.
+    pub milestone_id: MilestoneId,
+}
+
+/// An output and its metadata, published on the `outputs/...` topics.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct OutputWithMetadata {
+    /// The output.
+    pub output: Output,
+    /// The metadata of the output.
+    pub metadata: OutputMetadata,
 }
 
 /// Mqtt events.
@@ -48,10 +84,22 @@ pub enum MqttPayload {
 pub enum MqttEvent {
     /// Client was connected.
     Connected,
+    /// Client lost the connection and is reconnecting, with the number of failed attempts since the last connection.
+    Reconnecting(usize),
     /// Client was disconnected.
     Disconnected,
 }
 
+/// The transport of the MQTT connection to a node.
+#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+pub enum MqttTransport {
+    /// Plain MQTT over TCP.
+    Tcp,
+    /// MQTT over WebSocket, at the `/api/mqtt/v1` route of the node, e.g. for nodes behind a reverse proxy only
+    /// forwarding HTTP.
+    WebSocket,
+}
+
 /// The MQTT broker options.
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
@@ -67,6 +115,12 @@ pub struct BrokerOptions {
     pub(crate) port: u16,
     #[serde(default = "default_max_reconnection_attempts", rename = "maxReconnectionAttempts")]
     pub(crate) max_reconnection_attempts: usize,
+    #[serde(default = "default_initial_reconnection_delay", rename = "initialReconnectionDelay")]
+    pub(crate) initial_reconnection_delay: Duration,
+    #[serde(default = "default_max_reconnection_delay", rename = "maxReconnectionDelay")]
+    pub(crate) max_reconnection_delay: Duration,
+    #[serde(default, rename = "nodeTransports")]
+    pub(crate) node_transports: HashMap<Url, MqttTransport>,
 }
 
 fn default_broker_automatic_disconnect() -> bool {
@@ -89,6 +143,14 @@ fn default_max_reconnection_attempts() -> usize {
     0
 }
 
+fn default_initial_reconnection_delay() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_max_reconnection_delay() -> Duration {
+    Duration::from_secs(60)
+}
+
 impl Default for BrokerOptions {
     fn default() -> Self {
         Self {
@@ -97,6 +159,9 @@ impl Default for BrokerOptions {
             use_ws: default_broker_use_ws(),
             port: default_broker_port(),
             max_reconnection_attempts: default_max_reconnection_attempts(),
+            initial_reconnection_delay: default_initial_reconnection_delay(),
+            max_reconnection_delay: default_max_reconnection_delay(),
+            node_transports: HashMap::new(),
         }
     }
 }
@@ -136,6 +201,43 @@ impl BrokerOptions {
         self.max_reconnection_attempts = max_reconnection_attempts;
         self
     }
+
+    /// Sets the delay before the first reconnection attempt, doubled after each failed attempt.
+    pub fn initial_reconnection_delay(mut self, initial_reconnection_delay: Duration) -> Self {
+        self.initial_reconnection_delay = initial_reconnection_delay;
+        self
+    }
+
+    /// Sets the maximum delay between two reconnection attempts.
+    pub fn max_reconnection_delay(mut self, max_reconnection_delay: Duration) -> Self {
+        self.max_reconnection_delay = max_reconnection_delay;
+        self
+    }
+
+    /// Sets the transport of the connection to the node at `url`, instead of the one of [`use_ws()`](Self::use_ws()).
+    pub fn node_transport(mut self, url: Url, transport: MqttTransport) -> Self {
+        self.node_transports.insert(url, transport);
+        self
+    }
+
+    /// The transport of the connection to the node at `url`.
+    pub(crate) fn transport(&self, url: &Url) -> MqttTransport {
+        self.node_transports.get(url).copied().unwrap_or(if self.use_ws {
+            MqttTransport::WebSocket
+        } else {
+            MqttTransport::Tcp
+        })
+    }
+
+    /// The delay before the reconnection attempt following `failed_attempts` consecutive failures, growing
+    /// exponentially up to the maximum delay.
+    pub(crate) fn reconnection_delay(&self, failed_attempts: usize) -> Duration {
+        let exponent = failed_attempts.saturating_sub(1).min(31) as u32;
+
+        self.initial_reconnection_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_reconnection_delay)
+    }
 }
 
 /// A MQTT topic.
@@ -189,6 +291,31 @@ impl Topic {
         }
     }
 
+    /// The topic of all blocks.
+    pub fn blocks() -> Self {
+        Self("blocks".to_string())
+    }
+
+    /// The topic of the info of the latest milestones.
+    pub fn latest_milestone_info() -> Self {
+        Self("milestone-info/latest".to_string())
+    }
+
+    /// The topic of the info of the confirmed milestones.
+    pub fn confirmed_milestone_info() -> Self {
+        Self("milestone-info/confirmed".to_string())
+    }
+
+    /// The topic of the outputs unlockable by `bech32_address` with an address unlock condition.
+    pub fn address_outputs(bech32_address: &str) -> Result<Self> {
+        Self::try_new(format!("outputs/unlock/address/{bech32_address}"))
+    }
+
+    /// The topic of the block including the transaction `transaction_id`, published once it's included.
+    pub fn transaction_included_block(transaction_id: &TransactionId) -> Self {
+        Self(format!("transactions/{transaction_id}/included-block"))
+    }
+
     /// Creates a new topic without checking if the given string represents a valid topic.
     pub fn new_unchecked(value: String) -> Self {
         Self(value)
@@ -14,11 +14,16 @@ use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
-    constants::{DEFAULT_MIN_QUORUM_SIZE, DEFAULT_QUORUM_THRESHOLD, DEFAULT_USER_AGENT, NODE_SYNC_INTERVAL},
+    constants::{
+        DEFAULT_MIN_QUORUM_SIZE, DEFAULT_NODE_COOL_DOWN, DEFAULT_QUORUM_THRESHOLD, DEFAULT_USER_AGENT,
+        NODE_SYNC_INTERVAL,
+    },
     error::{Error, Result},
     node_manager::{
         http_client::HttpClient,
         node::{Node, NodeAuth, NodeDto},
+        node_health::NodeHealthScores,
+        response_parsing::ResponseParsing,
         NodeManager,
     },
 };
@@ -44,6 +49,9 @@ pub struct NodeManagerBuilder {
     /// gets updated
     #[serde(rename = "nodeSyncInterval", default = "default_node_sync_interval")]
     pub node_sync_interval: Duration,
+    /// Duration for which a node is skipped after failed requests, as long as there are other nodes
+    #[serde(rename = "nodeCoolDown", default = "default_node_cool_down")]
+    pub node_cool_down: Duration,
     /// If node quorum is enabled. Will compare the responses from multiple nodes and only returns the response if
     /// `quorum_threshold`% of the nodes return the same one
     #[serde(default)]
@@ -57,6 +65,9 @@ pub struct NodeManagerBuilder {
     /// The User-Agent header for requests
     #[serde(rename = "userAgent", default = "default_user_agent")]
     pub user_agent: String,
+    /// How the JSON responses of the nodes are parsed
+    #[serde(rename = "responseParsing", default)]
+    pub response_parsing: ResponseParsing,
 }
 
 fn default_user_agent() -> String {
@@ -67,6 +78,10 @@ fn default_node_sync_interval() -> Duration {
     NODE_SYNC_INTERVAL
 }
 
+fn default_node_cool_down() -> Duration {
+    DEFAULT_NODE_COOL_DOWN
+}
+
 fn default_min_quorum_size() -> usize {
     DEFAULT_MIN_QUORUM_SIZE
 }
@@ -197,6 +212,11 @@ impl NodeManagerBuilder {
         self
     }
 
+    pub(crate) fn with_node_cool_down(mut self, node_cool_down: Duration) -> Self {
+        self.node_cool_down = node_cool_down;
+        self
+    }
+
     pub(crate) fn with_quorum(mut self, quorum: bool) -> Self {
         self.quorum = quorum;
         self
@@ -217,7 +237,16 @@ impl NodeManagerBuilder {
         self
     }
 
-    pub(crate) fn build(self, healthy_nodes: Arc<RwLock<HashMap<Node, InfoResponse>>>) -> NodeManager {
+    pub(crate) fn with_response_parsing(mut self, response_parsing: ResponseParsing) -> Self {
+        self.response_parsing = response_parsing;
+        self
+    }
+
+    pub(crate) fn build(
+        self,
+        healthy_nodes: Arc<RwLock<HashMap<Node, InfoResponse>>>,
+        node_health: NodeHealthScores,
+    ) -> NodeManager {
         NodeManager {
             primary_node: self.primary_node.map(|node| node.into()),
             primary_pow_node: self.primary_pow_node.map(|node| node.into()),
@@ -228,10 +257,15 @@ impl NodeManagerBuilder {
             ignore_node_health: self.ignore_node_health,
             node_sync_interval: self.node_sync_interval,
             healthy_nodes,
+            node_health,
             quorum: self.quorum,
             min_quorum_size: self.min_quorum_size,
             quorum_threshold: self.quorum_threshold,
             http_client: HttpClient::new(self.user_agent),
+            response_parsing: self.response_parsing,
+            response_deviation_senders: Arc::new(RwLock::new(Vec::new())),
+            #[cfg(not(target_family = "wasm"))]
+            runtime: None,
         }
     }
 }
@@ -245,10 +279,12 @@ impl Default for NodeManagerBuilder {
             permanodes: None,
             ignore_node_health: false,
             node_sync_interval: NODE_SYNC_INTERVAL,
+            node_cool_down: DEFAULT_NODE_COOL_DOWN,
             quorum: false,
             min_quorum_size: DEFAULT_MIN_QUORUM_SIZE,
             quorum_threshold: DEFAULT_QUORUM_THRESHOLD,
             user_agent: DEFAULT_USER_AGENT.to_string(),
+            response_parsing: ResponseParsing::default(),
         }
     }
 }
@@ -7,6 +7,12 @@ pub mod builder;
 pub(crate) mod http_client;
 /// Structs for nodes
 pub mod node;
+/// Health scores and latencies of the nodes
+pub mod node_health;
+/// Persistence of the healthy node pool
+pub mod node_pool;
+/// Strict and lenient parsing of the node responses
+pub mod response_parsing;
 pub(crate) mod syncing;
 
 use std::{
@@ -15,10 +21,17 @@ use std::{
     time::Duration,
 };
 
+use futures::channel::mpsc::UnboundedSender;
 use iota_types::api::response::InfoResponse;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 
-use self::{http_client::HttpClient, node::Node};
+use self::{
+    http_client::{HttpClient, Response},
+    node::Node,
+    node_health::NodeHealthScores,
+    response_parsing::{ResponseDeviation, ResponseParsing},
+};
 use crate::{
     error::{Error, Result},
     node_manager::builder::NodeManagerBuilder,
@@ -35,10 +48,16 @@ pub(crate) struct NodeManager {
     pub(crate) ignore_node_health: bool,
     node_sync_interval: Duration,
     pub(crate) healthy_nodes: Arc<RwLock<HashMap<Node, InfoResponse>>>,
+    node_health: NodeHealthScores,
     quorum: bool,
     min_quorum_size: usize,
     quorum_threshold: usize,
     pub(crate) http_client: HttpClient,
+    response_parsing: ResponseParsing,
+    pub(crate) response_deviation_senders: Arc<RwLock<Vec<UnboundedSender<ResponseDeviation>>>>,
+    /// The Tokio runtime of the client, which the HTTP requests run on in parallel are spawned on.
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) runtime: Option<tokio::runtime::Handle>,
 }
 
 impl std::fmt::Debug for NodeManager {
@@ -51,9 +70,11 @@ impl std::fmt::Debug for NodeManager {
         d.field("ignore_node_health", &self.ignore_node_health);
         d.field("node_sync_interval", &self.node_sync_interval);
         d.field("healthy_nodes", &self.healthy_nodes);
+        d.field("node_health", &self.node_health);
         d.field("quorum", &self.quorum);
         d.field("min_quorum_size", &self.min_quorum_size);
-        d.field("quorum_threshold", &self.quorum_threshold).finish()
+        d.field("quorum_threshold", &self.quorum_threshold);
+        d.field("response_parsing", &self.response_parsing).finish()
     }
 }
 
@@ -62,24 +83,38 @@ impl NodeManager {
         NodeManagerBuilder::new()
     }
 
+    /// Spawn `future`, e.g. HTTP requests, on the Tokio runtime of the client, as `reqwest` requires one whatever the
+    /// runtime of the application, returning a future resolving to its output.
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) fn spawn<F>(&self, future: F) -> impl std::future::Future<Output = Result<F::Output>>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let task = match &self.runtime {
+            Some(runtime) => runtime.spawn(future),
+            None => tokio::spawn(future),
+        };
+
+        async move { Ok(task.await?) }
+    }
+
     fn get_nodes(
         &self,
         path: &str,
         query: Option<&str>,
         use_pow_nodes: bool,
-        prefer_permanode: bool,
+        fallback_to_permanode: bool,
+        prefer_fastest: bool,
     ) -> Result<Vec<Node>> {
         let mut nodes_with_modified_url: Vec<Node> = Vec::new();
+        // Only permanodes answer these queries, so they are tried first
+        let permanode_only = path == "api/core/v2/blocks" && query.is_some();
 
-        if prefer_permanode || (path == "api/core/v2/blocks" && query.is_some()) {
-            if let Some(permanodes) = self.permanodes.clone() {
-                for permanode in permanodes {
-                    if !nodes_with_modified_url.iter().any(|n| n.url == permanode.url) {
-                        nodes_with_modified_url.push(permanode);
-                    }
-                }
-            }
+        if permanode_only {
+            self.add_permanodes(&mut nodes_with_modified_url);
         }
+        let permanode_count = nodes_with_modified_url.len();
 
         if use_pow_nodes {
             if let Some(pow_node) = self.primary_pow_node.clone() {
@@ -134,9 +169,26 @@ impl NodeManager {
             }
         }
 
+        // the fastest nodes are tried first, after the permanodes, instead of the primary node
+        if prefer_fastest {
+            self.node_health
+                .sort_by_latency(&mut nodes_with_modified_url[permanode_count..])?;
+        }
+
+        // historical data is requested from the permanodes once the other nodes couldn't find it, e.g. because they
+        // pruned it
+        if fallback_to_permanode && !permanode_only {
+            self.add_permanodes(&mut nodes_with_modified_url);
+        }
+
         // remove disabled nodes
         nodes_with_modified_url.retain(|n| !n.disabled);
 
+        // skip the nodes cooling down after failed requests, as long as there are other nodes
+        if !self.ignore_node_health {
+            self.node_health.skip_cooling_down(&mut nodes_with_modified_url)?;
+        }
+
         if nodes_with_modified_url.is_empty() {
             return Err(crate::Error::HealthyNodePoolEmpty);
         }
@@ -150,18 +202,80 @@ impl NodeManager {
         Ok(nodes_with_modified_url)
     }
 
+    fn add_permanodes(&self, nodes: &mut Vec<Node>) {
+        if let Some(permanodes) = self.permanodes.clone() {
+            for permanode in permanodes {
+                if !nodes.iter().any(|n| n.url == permanode.url) {
+                    nodes.push(permanode);
+                }
+            }
+        }
+    }
+
+    /// Update the health score of `node` with the outcome of a request, client errors not counting as failures, and
+    /// its average latency with the `latency` of the request if measured.
+    fn record_node_health(&self, node: &Node, response: &Result<Response>, latency: Option<Duration>) -> Result<()> {
+        match response {
+            Ok(_) => self.node_health.record_success(node, latency),
+            Err(Error::ResponseError { code, .. }) if *code < 500 => Ok(()),
+            Err(_) => self.node_health.record_failure(node),
+        }
+    }
+
+    /// Parse the JSON body of a response of `node` to a request to `path`, as per the [`ResponseParsing`] mode.
+    async fn parse_json<T: DeserializeOwned>(&self, response: Response, node: Option<&Node>, path: &str) -> Result<T> {
+        match self.response_parsing {
+            ResponseParsing::Strict => response.into_json().await,
+            ResponseParsing::Lenient => self.parse_value(response.into_json().await?, node, path),
+        }
+    }
+
+    /// Parse a JSON response of `node`, or of a quorum of nodes, to a request to `path`, as per the [`ResponseParsing`]
+    /// mode, reporting the deviations from the API specification.
+    fn parse_value<T: DeserializeOwned>(&self, value: Value, node: Option<&Node>, path: &str) -> Result<T> {
+        if self.response_parsing == ResponseParsing::Strict {
+            return Ok(serde_json::from_value(value)?);
+        }
+
+        let (result, deviations) = response_parsing::parse_lenient(value);
+
+        if !deviations.is_empty() {
+            // Credentials are stripped from the URL.
+            let url = node.map(|node| format!("{}://{}", node.url.scheme(), node.url.host_str().unwrap_or("")));
+            let mut senders = self
+                .response_deviation_senders
+                .write()
+                .map_err(|_| crate::Error::PoisonError)?;
+
+            for (field, fallback) in deviations {
+                log::warn!("non-conforming field {field} in the response to {path}: {fallback}");
+
+                let deviation = ResponseDeviation {
+                    url: url.clone(),
+                    path: path.to_string(),
+                    field,
+                    fallback,
+                };
+                // Senders of dropped receivers are removed.
+                senders.retain(|sender| sender.unbounded_send(deviation.clone()).is_ok());
+            }
+        }
+
+        result
+    }
+
     pub(crate) async fn get_request<T: serde::de::DeserializeOwned + std::fmt::Debug + serde::Serialize>(
         &self,
         path: &str,
         query: Option<&str>,
         timeout: Duration,
         need_quorum: bool,
-        prefer_permanode: bool,
+        fallback_to_permanode: bool,
     ) -> Result<T> {
         let mut result: HashMap<String, usize> = HashMap::new();
         // primary_pow_node should only be used for post request with remote PoW
         // Get node urls and set path
-        let nodes = self.get_nodes(path, query, false, prefer_permanode)?;
+        let nodes = self.get_nodes(path, query, false, fallback_to_permanode, true)?;
         if self.quorum && need_quorum && nodes.len() < self.min_quorum_size {
             return Err(Error::QuorumPoolSizeError {
                 available_nodes: nodes.len(),
@@ -180,14 +294,19 @@ impl NodeManager {
         if !wasm && self.quorum && need_quorum && query.is_none() {
             #[cfg(not(target_family = "wasm"))]
             {
+                let nodes = nodes.into_iter().take(self.min_quorum_size).collect::<Vec<_>>();
                 let mut tasks = Vec::new();
-                for (index, node) in nodes.into_iter().enumerate() {
-                    if index < self.min_quorum_size {
-                        let client_ = self.http_client.clone();
-                        tasks.push(async move { tokio::spawn(async move { client_.get(node, timeout).await }).await });
-                    }
+                for node in &nodes {
+                    let client_ = self.http_client.clone();
+                    let node = node.clone();
+                    tasks.push(self.spawn(async move {
+                        let start_time = instant::Instant::now();
+                        let res = client_.get(node, timeout).await;
+                        (res, start_time.elapsed())
+                    }));
                 }
-                for res in futures::future::try_join_all(tasks).await? {
+                for (node, (res, latency)) in nodes.iter().zip(futures::future::try_join_all(tasks).await?) {
+                    self.record_node_health(node, &res, Some(latency))?;
                     match res {
                         Ok(res) => {
                             if let Ok(res_text) = res.into_text().await {
@@ -210,13 +329,16 @@ impl NodeManager {
         } else {
             // Send requests
             for node in nodes {
-                match self.http_client.get(node.clone(), timeout).await {
+                let start_time = instant::Instant::now();
+                let res = self.http_client.get(node.clone(), timeout).await;
+                self.record_node_health(&node, &res, Some(start_time.elapsed()))?;
+                match res {
                     Ok(res) => {
                         match res.status() {
                             200 => {
                                 // Handle node_info extra because we also want to return the url
                                 if path == "api/core/v2/info" {
-                                    let node_info: InfoResponse = res.into_json().await?;
+                                    let node_info: InfoResponse = self.parse_json(res, Some(&node), path).await?;
                                     let wrapper = crate::node_api::core::routes::NodeInfoWrapper {
                                         node_info,
                                         url: format!("{}://{}", node.url.scheme(), node.url.host_str().unwrap_or("")),
@@ -225,7 +347,7 @@ impl NodeManager {
                                     return Ok(serde_json::from_str(&serde_res)?);
                                 }
 
-                                match res.into_json::<T>().await {
+                                match self.parse_json::<T>(res, Some(&node), path).await {
                                     Ok(result_data) => {
                                         let counters = result.entry(serde_json::to_string(&result_data)?).or_insert(0);
                                         *counters += 1;
@@ -277,7 +399,7 @@ impl NodeManager {
             // with query we ignore quorum because the nodes can store a different amount of history
             || query.is_some()
         {
-            Ok(serde_json::from_str(&res.0)?)
+            self.parse_value(serde_json::from_str(&res.0)?, None, path)
         } else {
             Err(Error::QuorumThresholdError {
                 quorum_size: res.1,
@@ -286,7 +408,8 @@ impl NodeManager {
         }
     }
 
-    // Only used for api/core/v2/blocks/{blockID}, that's why we don't need the quorum stuff
+    // Only used for the raw blocks, outputs and milestones, that's why we don't need the quorum stuff, and why the
+    // permanodes are used as fallback
     pub(crate) async fn get_request_bytes(
         &self,
         path: &str,
@@ -295,11 +418,14 @@ impl NodeManager {
     ) -> Result<Vec<u8>> {
         // primary_pow_node should only be used for post request with remote Pow
         // Get node urls and set path
-        let nodes = self.get_nodes(path, query, false, false)?;
+        let nodes = self.get_nodes(path, query, false, true, true)?;
         let mut error = None;
         // Send requests
         for node in nodes {
-            match self.http_client.get_bytes(node, timeout).await {
+            let start_time = instant::Instant::now();
+            let res = self.http_client.get_bytes(node.clone(), timeout).await;
+            self.record_node_health(&node, &res, Some(start_time.elapsed()))?;
+            match res {
                 Ok(res) => {
                     let status = res.status();
                     if let Ok(res_text) = res.into_bytes().await {
@@ -332,17 +458,19 @@ impl NodeManager {
         local_pow: bool,
     ) -> Result<T> {
         // primary_pow_node should only be used for post request with remote PoW
-        let nodes = self.get_nodes(path, None, !local_pow, false)?;
+        let nodes = self.get_nodes(path, None, !local_pow, false, false)?;
         if nodes.is_empty() {
             return Err(Error::NodeError("no available nodes with remote Pow".into()));
         }
         let mut error = None;
         // Send requests
         for node in nodes {
-            match self.http_client.post_bytes(node, timeout, body).await {
+            let res = self.http_client.post_bytes(node.clone(), timeout, body).await;
+            self.record_node_health(&node, &res, None)?;
+            match res {
                 Ok(res) => {
                     match res.status() {
-                        200 | 201 => match res.into_json::<T>().await {
+                        200 | 201 => match self.parse_json::<T>(res, Some(&node), path).await {
                             Ok(res) => return Ok(res),
                             Err(e) => error.replace(e),
                         },
@@ -369,17 +497,19 @@ impl NodeManager {
         local_pow: bool,
     ) -> Result<T> {
         // primary_pow_node should only be used for post request with remote PoW
-        let nodes = self.get_nodes(path, None, !local_pow, false)?;
+        let nodes = self.get_nodes(path, None, !local_pow, false, false)?;
         if nodes.is_empty() {
             return Err(Error::NodeError("no available nodes with remote Pow".into()));
         }
         let mut error = None;
         // Send requests
         for node in nodes {
-            match self.http_client.post_json(node, timeout, json.clone()).await {
+            let res = self.http_client.post_json(node.clone(), timeout, json.clone()).await;
+            self.record_node_health(&node, &res, None)?;
+            match res {
                 Ok(res) => {
                     match res.status() {
-                        200 | 201 => match res.into_json::<T>().await {
+                        200 | 201 => match self.parse_json::<T>(res, Some(&node), path).await {
                             Ok(res) => return Ok(res),
                             Err(e) => error.replace(e),
                         },
@@ -398,3 +528,45 @@ impl NodeManager {
         Err(error.unwrap_or_else(|| Error::NodeError("couldn't get a result from any node".into())))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permanode_fallback() {
+        let node_manager = NodeManager::builder()
+            .with_node("https://node.one")
+            .unwrap()
+            .with_permanode("https://permanode.one", None)
+            .unwrap()
+            .with_ignore_node_health()
+            .build(Default::default(), NodeHealthScores::new(Duration::from_secs(60)));
+        let hosts = |nodes: Vec<Node>| {
+            nodes
+                .into_iter()
+                .map(|node| node.url.host_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        let path = "api/core/v2/outputs/0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c6490000";
+
+        assert_eq!(
+            hosts(node_manager.get_nodes(path, None, false, false, true).unwrap()),
+            ["node.one"]
+        );
+        // Historical data is requested from the permanodes last
+        assert_eq!(
+            hosts(node_manager.get_nodes(path, None, false, true, true).unwrap()),
+            ["node.one", "permanode.one"]
+        );
+        // And first if only they can answer
+        assert_eq!(
+            hosts(
+                node_manager
+                    .get_nodes("api/core/v2/blocks", Some("tag=0x01"), false, true, true)
+                    .unwrap()
+            ),
+            ["permanode.one", "node.one"]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Health scores and latencies of the nodes, from the outcomes of the requests sent to them.
+//!
+//! Each failed request, i.e. an unreachable node, a timeout or a server error, lowers the score of a node, and each
+//! successful one raises it again. A node whose score drops to zero is skipped for a cool-down period, then tried
+//! again with half of the maximum score, so that a node failing again is quickly skipped again.
+//!
+//! The latencies of the successful reads, including the periodic requests of the node infos, are averaged with an
+//! exponential moving average, to send the reads to the fastest nodes first.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use instant::Instant;
+
+use crate::{
+    error::{Error, Result},
+    node_manager::node::Node,
+};
+
+/// The score of a node without failures.
+const MAX_NODE_HEALTH_SCORE: u8 = 100;
+/// The score lost by a node with each failed request.
+const FAILURE_PENALTY: u8 = 34;
+/// The score regained by a node with each successful request.
+const SUCCESS_REWARD: u8 = 20;
+/// The inverse of the weight of a new measurement in the moving average of the latency of a node.
+const LATENCY_SMOOTHING: u32 = 5;
+
+/// The health and latency of a node, see [`Client::node_stats()`](crate::Client::node_stats()).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeStats {
+    /// The origin of the URL of the node, e.g. `https://api.testnet.shimmer.network`.
+    pub url: String,
+    /// The health score of the node, from 0 after failed requests to 100.
+    pub health_score: u8,
+    /// The average latency of the successful reads, if any.
+    pub latency: Option<Duration>,
+    /// Whether the node is skipped after failed requests.
+    pub cooling_down: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct NodeHealth {
+    score: u8,
+    cool_down_until: Option<Instant>,
+    latency: Option<Duration>,
+}
+
+impl Default for NodeHealth {
+    fn default() -> Self {
+        Self {
+            score: MAX_NODE_HEALTH_SCORE,
+            cool_down_until: None,
+            latency: None,
+        }
+    }
+}
+
+impl NodeHealth {
+    fn is_cooling_down(&self, now: Instant) -> bool {
+        self.cool_down_until
+            .map_or(false, |cool_down_until| now < cool_down_until)
+    }
+}
+
+/// The health scores of the nodes, by origin of their URL.
+#[derive(Clone, Debug)]
+pub(crate) struct NodeHealthScores {
+    scores: Arc<RwLock<HashMap<String, NodeHealth>>>,
+    cool_down: Duration,
+}
+
+impl NodeHealthScores {
+    pub(crate) fn new(cool_down: Duration) -> Self {
+        Self {
+            scores: Default::default(),
+            cool_down,
+        }
+    }
+
+    /// The key of a node, independent of the path and query of its URL.
+    fn key(node: &Node) -> String {
+        node.url.origin().ascii_serialization()
+    }
+
+    /// Raise the score of `node` after a successful request, averaging its `latency` if measured.
+    pub(crate) fn record_success(&self, node: &Node, latency: Option<Duration>) -> Result<()> {
+        let mut scores = self.scores.write().map_err(|_| Error::PoisonError)?;
+        let health = scores.entry(Self::key(node)).or_default();
+
+        health.score = health.score.saturating_add(SUCCESS_REWARD).min(MAX_NODE_HEALTH_SCORE);
+        health.cool_down_until = None;
+
+        if let Some(latency) = latency {
+            health.latency = Some(health.latency.map_or(latency, |average| {
+                (average * (LATENCY_SMOOTHING - 1) + latency) / LATENCY_SMOOTHING
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Lower the score of `node` after a failed request, starting its cool-down once the score drops to zero.
+    pub(crate) fn record_failure(&self, node: &Node) -> Result<()> {
+        let mut scores = self.scores.write().map_err(|_| Error::PoisonError)?;
+        let health = scores.entry(Self::key(node)).or_default();
+
+        health.score = health.score.saturating_sub(FAILURE_PENALTY);
+
+        if health.score == 0 {
+            log::warn!(
+                "skipping node {} for {:?} after failed requests",
+                Self::key(node),
+                self.cool_down
+            );
+            health.score = MAX_NODE_HEALTH_SCORE / 2;
+            health.cool_down_until = Some(Instant::now() + self.cool_down);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the nodes cooling down from `nodes`, unless no other node is left.
+    pub(crate) fn skip_cooling_down(&self, nodes: &mut Vec<Node>) -> Result<()> {
+        let scores = self.scores.read().map_err(|_| Error::PoisonError)?;
+        let now = Instant::now();
+        let is_cooling_down = |node: &Node| {
+            scores
+                .get(&Self::key(node))
+                .map_or(false, |health| health.is_cooling_down(now))
+        };
+
+        if nodes.iter().any(|node| !is_cooling_down(node)) {
+            nodes.retain(|node| !is_cooling_down(node));
+        }
+
+        Ok(())
+    }
+
+    /// Sort `nodes` by average latency, the nodes without measurement last in their current order.
+    pub(crate) fn sort_by_latency(&self, nodes: &mut [Node]) -> Result<()> {
+        let scores = self.scores.read().map_err(|_| Error::PoisonError)?;
+
+        nodes.sort_by_key(|node| {
+            scores
+                .get(&Self::key(node))
+                .and_then(|health| health.latency)
+                .unwrap_or(Duration::MAX)
+        });
+
+        Ok(())
+    }
+
+    /// The stats of the nodes with recorded requests, by URL.
+    pub(crate) fn stats(&self) -> Result<Vec<NodeStats>> {
+        let scores = self.scores.read().map_err(|_| Error::PoisonError)?;
+        let now = Instant::now();
+        let mut stats = scores
+            .iter()
+            .map(|(url, health)| NodeStats {
+                url: url.clone(),
+                health_score: health.score,
+                latency: health.latency,
+                cooling_down: health.is_cooling_down(now),
+            })
+            .collect::<Vec<_>>();
+
+        stats.sort_by(|a, b| a.url.cmp(&b.url));
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::*;
+
+    fn node(url: &str) -> Node {
+        Url::parse(url).unwrap().into()
+    }
+
+    fn score(scores: &NodeHealthScores, node: &Node) -> u8 {
+        scores.scores.read().unwrap()[&NodeHealthScores::key(node)].score
+    }
+
+    #[test]
+    fn cool_down() {
+        let scores = NodeHealthScores::new(Duration::from_secs(60));
+        let flaky_node = node("https://flaky.node/api/core/v2/info");
+        let nodes = vec![node("https://flaky.node"), node("https://other.node")];
+
+        scores.record_failure(&flaky_node).unwrap();
+        scores.record_failure(&flaky_node).unwrap();
+        assert_eq!(score(&scores, &flaky_node), 32);
+
+        let mut available_nodes = nodes.clone();
+        scores.skip_cooling_down(&mut available_nodes).unwrap();
+        assert_eq!(available_nodes, nodes);
+
+        // The score drops to zero, whatever the path of the request
+        scores
+            .record_failure(&node("https://flaky.node/api/core/v2/tips"))
+            .unwrap();
+        assert_eq!(score(&scores, &flaky_node), MAX_NODE_HEALTH_SCORE / 2);
+
+        let mut available_nodes = nodes.clone();
+        scores.skip_cooling_down(&mut available_nodes).unwrap();
+        assert_eq!(available_nodes, nodes[1..]);
+
+        // Nodes cooling down are still used if there is no other node
+        let mut available_nodes = nodes[..1].to_vec();
+        scores.skip_cooling_down(&mut available_nodes).unwrap();
+        assert_eq!(available_nodes, nodes[..1]);
+
+        // A successful request ends the cool-down
+        scores.record_success(&flaky_node, None).unwrap();
+        assert_eq!(score(&scores, &flaky_node), 70);
+
+        let mut available_nodes = nodes.clone();
+        scores.skip_cooling_down(&mut available_nodes).unwrap();
+        assert_eq!(available_nodes, nodes);
+    }
+
+    #[test]
+    fn latency() {
+        let scores = NodeHealthScores::new(Duration::from_secs(60));
+        let mut nodes = vec![
+            node("https://unmeasured.node"),
+            node("https://slow.node"),
+            node("https://fast.node"),
+        ];
+
+        scores
+            .record_success(&nodes[1], Some(Duration::from_millis(500)))
+            .unwrap();
+        scores
+            .record_success(&nodes[2], Some(Duration::from_millis(100)))
+            .unwrap();
+        scores
+            .record_success(&nodes[2], Some(Duration::from_millis(600)))
+            .unwrap();
+
+        scores.sort_by_latency(&mut nodes).unwrap();
+        assert_eq!(
+            nodes,
+            vec![
+                node("https://fast.node"),
+                node("https://slow.node"),
+                node("https://unmeasured.node")
+            ]
+        );
+
+        let stats = scores.stats().unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].url, "https://fast.node");
+        assert_eq!(stats[0].latency, Some(Duration::from_millis(200)));
+        assert_eq!(stats[0].health_score, MAX_NODE_HEALTH_SCORE);
+        assert!(!stats[0].cooling_down);
+    }
+}
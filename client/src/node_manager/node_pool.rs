@@ -0,0 +1,113 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Persistence of the healthy node pool, so that a client can be restarted with a warm pool, see
+//! [`ClientBuilder::finish_with_node_pool()`](crate::ClientBuilder::finish_with_node_pool()).
+
+use std::time::Duration;
+
+use iota_types::api::response::InfoResponse;
+
+use super::node::Node;
+use crate::{db::DatabaseProvider, Client, Error, Result};
+
+/// Database key of the node pool.
+const NODE_POOL_KEY: &[u8] = b"iota-client-node-pool";
+
+/// A snapshot of the healthy node pool of a [`Client`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodePool {
+    /// The healthy nodes, with the node info they returned on the latest sync, e.g. their confirmed milestone.
+    pub healthy_nodes: Vec<(Node, InfoResponse)>,
+    /// The UNIX timestamp, in seconds, at which the snapshot has been taken.
+    pub timestamp: u64,
+}
+
+impl NodePool {
+    /// Load the node pool saved in `db` with [`save()`](Self::save()), if there is one taken less than `max_age` ago.
+    pub async fn load<D: DatabaseProvider + ?Sized>(db: &D, max_age: Duration) -> Result<Option<Self>> {
+        let node_pool: Self = match db.get(NODE_POOL_KEY).await? {
+            Some(node_pool) => serde_json::from_slice(&node_pool)?,
+            None => return Ok(None),
+        };
+
+        if unix_timestamp().saturating_sub(node_pool.timestamp) > max_age.as_secs() {
+            log::debug!("ignoring the saved node pool, taken at {}", node_pool.timestamp);
+
+            return Ok(None);
+        }
+
+        Ok(Some(node_pool))
+    }
+
+    /// Save the node pool in `db`, replacing the one saved before.
+    pub async fn save<D: DatabaseProvider + ?Sized>(&self, db: &D) -> Result<()> {
+        db.insert(NODE_POOL_KEY, &serde_json::to_vec(self)?).await?;
+
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Take a snapshot of the healthy node pool, e.g. to [`save()`](NodePool::save()) it before shutting down.
+    pub fn node_pool(&self) -> Result<NodePool> {
+        let healthy_nodes = self
+            .node_manager
+            .healthy_nodes
+            .read()
+            .map_err(|_| Error::PoisonError)?
+            .iter()
+            .map(|(node, info)| (node.clone(), info.clone()))
+            .collect();
+
+        Ok(NodePool {
+            healthy_nodes,
+            timestamp: unix_timestamp(),
+        })
+    }
+
+    /// Save a snapshot of the healthy node pool in `db`, see [`NodePool::load()`].
+    pub async fn save_node_pool<D: DatabaseProvider + ?Sized>(&self, db: &D) -> Result<()> {
+        self.node_pool()?.save(db).await
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    instant::SystemTime::now()
+        .duration_since(instant::SystemTime::UNIX_EPOCH)
+        .expect("time went backwards")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MemoryDatabaseProvider;
+
+    #[tokio::test]
+    async fn save_load_node_pool() {
+        let db = MemoryDatabaseProvider::default();
+        assert_eq!(NodePool::load(&db, Duration::from_secs(60)).await.unwrap(), None);
+
+        let node_pool = NodePool {
+            healthy_nodes: Vec::new(),
+            timestamp: unix_timestamp(),
+        };
+        node_pool.save(&db).await.unwrap();
+        assert_eq!(
+            NodePool::load(&db, Duration::from_secs(60)).await.unwrap(),
+            Some(node_pool)
+        );
+
+        // A pool older than the maximum age is ignored.
+        NodePool {
+            healthy_nodes: Vec::new(),
+            timestamp: unix_timestamp() - 120,
+        }
+        .save(&db)
+        .await
+        .unwrap();
+        assert_eq!(NodePool::load(&db, Duration::from_secs(60)).await.unwrap(), None);
+    }
+}
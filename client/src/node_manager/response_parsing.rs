@@ -0,0 +1,177 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Strict and lenient parsing of the JSON responses of the nodes.
+//!
+//! Some nodes return slightly non-conforming JSON. With [`ResponseParsing::Lenient`], a response that doesn't parse
+//! as is gets fields in another casing renamed to camelCase and `null` fields removed, so that their defaults apply,
+//! before being parsed again. Each such fallback is reported as a [`ResponseDeviation`], see
+//! [`Client::response_deviation_receiver()`](crate::Client::response_deviation_receiver()).
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::Result;
+
+/// How the JSON responses of the nodes are parsed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResponseParsing {
+    /// Responses have to conform to the API specification.
+    #[default]
+    Strict,
+    /// Non-conforming fields of responses are fixed up where possible, reporting a [`ResponseDeviation`] for each.
+    Lenient,
+}
+
+/// A deviation from the API specification in the response of a node, tolerated with [`ResponseParsing::Lenient`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseDeviation {
+    /// The URL of the node, if the response comes from a single one rather than from a quorum.
+    pub url: Option<String>,
+    /// The path of the request.
+    pub path: String,
+    /// The JSON path of the non-conforming field.
+    pub field: String,
+    /// What has been done to tolerate it.
+    pub fallback: String,
+}
+
+/// Parse `value` as is and, if it doesn't conform, again with the fallbacks applied.
+///
+/// Returns the JSON paths of the fields to which a fallback has been applied, with what has been done.
+pub(crate) fn parse_lenient<T: DeserializeOwned>(value: Value) -> (Result<T>, Vec<(String, String)>) {
+    if let Ok(parsed) = serde_json::from_value(value.clone()) {
+        return (Ok(parsed), Vec::new());
+    }
+
+    let mut deviations = Vec::new();
+    let value = apply_fallbacks(value, "$", &mut deviations);
+
+    (serde_json::from_value(value).map_err(Into::into), deviations)
+}
+
+/// Rename the non-camelCase fields of `value`, found at the JSON `path`, and remove its `null` fields, recursively.
+fn apply_fallbacks(value: Value, path: &str, deviations: &mut Vec<(String, String)>) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut fixed = Map::new();
+
+            for (key, value) in object {
+                let field = format!("{path}.{key}");
+
+                if value.is_null() {
+                    deviations.push((field, "removed null value".to_string()));
+                    continue;
+                }
+
+                let camel_case_key = to_camel_case(&key);
+                let key = if camel_case_key != key && !fixed.contains_key(&camel_case_key) {
+                    deviations.push((field.clone(), format!("renamed to `{camel_case_key}`")));
+                    camel_case_key
+                } else {
+                    key
+                };
+
+                fixed.insert(key, apply_fallbacks(value, &field, deviations));
+            }
+
+            Value::Object(fixed)
+        }
+        Value::Array(array) => Value::Array(
+            array
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| apply_fallbacks(value, &format!("{path}[{index}]"), deviations))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Convert a snake_case, kebab-case or PascalCase `key` to camelCase.
+fn to_camel_case(key: &str) -> String {
+    let mut camel_case = String::with_capacity(key.len());
+
+    for (index, word) in key
+        .split(['_', '-'])
+        .filter(|word| !word.is_empty())
+        .enumerate()
+    {
+        let mut chars = word.chars();
+
+        if let Some(first) = chars.next() {
+            if index == 0 {
+                camel_case.extend(first.to_lowercase());
+            } else {
+                camel_case.extend(first.to_uppercase());
+            }
+            camel_case.extend(chars);
+        }
+    }
+
+    camel_case
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Response {
+        network_id: String,
+        #[serde(default)]
+        confirmed: bool,
+        items: Vec<Item>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct Item {
+        output_id: String,
+    }
+
+    #[test]
+    fn camel_case() {
+        assert_eq!(to_camel_case("networkId"), "networkId");
+        assert_eq!(to_camel_case("network_id"), "networkId");
+        assert_eq!(to_camel_case("network-id"), "networkId");
+        assert_eq!(to_camel_case("NetworkId"), "networkId");
+    }
+
+    #[test]
+    fn lenient_parsing() {
+        let conforming = serde_json::json!({ "networkId": "1", "confirmed": true, "items": [], "extra": 0 });
+        let (parsed, deviations) = parse_lenient::<Response>(conforming);
+        assert!(parsed.is_ok());
+        assert!(deviations.is_empty());
+
+        let non_conforming = serde_json::json!({
+            "network_id": "1",
+            "confirmed": null,
+            "items": [{ "OutputId": "0x00" }],
+        });
+        let (parsed, mut deviations) = parse_lenient::<Response>(non_conforming);
+        deviations.sort();
+        assert_eq!(
+            parsed.unwrap(),
+            Response {
+                network_id: "1".to_string(),
+                confirmed: false,
+                items: vec![Item {
+                    output_id: "0x00".to_string()
+                }],
+            }
+        );
+        assert_eq!(
+            deviations,
+            vec![
+                ("$.confirmed".to_string(), "removed null value".to_string()),
+                ("$.items[0].OutputId".to_string(), "renamed to `outputId`".to_string()),
+                ("$.network_id".to_string(), "renamed to `networkId`".to_string()),
+            ]
+        );
+    }
+}
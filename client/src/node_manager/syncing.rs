@@ -14,7 +14,9 @@ use {
     tokio::{runtime::Runtime, time::sleep},
 };
 
-use super::Node;
+use super::{node_health::NodeStats, Node};
+#[cfg(not(target_family = "wasm"))]
+use crate::node_manager::node_health::NodeHealthScores;
 use crate::{Client, Error, Result};
 
 impl Client {
@@ -44,22 +46,39 @@ impl Client {
             })
     }
 
-    /// Sync the node lists per node_sync_interval milliseconds
+    /// Returns the health scores and average latencies of the nodes that were sent requests, by URL.
+    pub fn node_stats(&self) -> Result<Vec<NodeStats>> {
+        self.node_manager.node_health.stats()
+    }
+
+    /// Sync the node lists per node_sync_interval milliseconds, and right away first with `sync_first`, e.g. for a warm
+    /// node pool.
     #[cfg(not(target_family = "wasm"))]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn start_sync_process(
         runtime: &Runtime,
         sync: Arc<RwLock<HashMap<Node, InfoResponse>>>,
+        node_health: NodeHealthScores,
         nodes: HashSet<Node>,
         node_sync_interval: Duration,
         network_info: Arc<RwLock<NetworkInfo>>,
         ignore_node_health: bool,
+        sync_first: bool,
     ) -> tokio::task::JoinHandle<()> {
         runtime.spawn(async move {
+            if sync_first {
+                if let Err(e) = Client::sync_nodes(&sync, &node_health, &nodes, &network_info, ignore_node_health).await
+                {
+                    log::warn!("Syncing nodes failed: {e}");
+                }
+            }
+
             loop {
                 // Delay first since the first `sync_nodes` call is made by the builder to ensure the node list is
                 // filled before the client is used.
                 sleep(node_sync_interval).await;
-                if let Err(e) = Client::sync_nodes(&sync, &nodes, &network_info, ignore_node_health).await {
+                if let Err(e) = Client::sync_nodes(&sync, &node_health, &nodes, &network_info, ignore_node_health).await
+                {
                     log::warn!("Syncing nodes failed: {e}");
                 }
             }
@@ -69,6 +88,7 @@ impl Client {
     #[cfg(not(target_family = "wasm"))]
     pub(crate) async fn sync_nodes(
         sync: &Arc<RwLock<HashMap<Node, InfoResponse>>>,
+        node_health: &NodeHealthScores,
         nodes: &HashSet<Node>,
         network_info: &Arc<RwLock<NetworkInfo>>,
         ignore_node_health: bool,
@@ -78,8 +98,16 @@ impl Client {
         let mut network_nodes: HashMap<String, Vec<(InfoResponse, Node)>> = HashMap::new();
 
         for node in nodes {
-            // Put the healthy node url into the network_nodes
-            if let Ok(info) = Client::get_node_info(node.url.as_ref(), None).await {
+            // Put the healthy node url into the network_nodes, measuring the latency of the node on the way
+            let start_time = instant::Instant::now();
+            let info = Client::get_node_info(node.url.as_ref(), None).await;
+
+            match &info {
+                Ok(_) => node_health.record_success(node, Some(start_time.elapsed()))?,
+                Err(_) => node_health.record_failure(node)?,
+            }
+
+            if let Ok(info) = info {
                 if info.status.is_healthy || ignore_node_health {
                     match network_nodes.get_mut(&info.protocol.network_name) {
                         Some(network_node_entry) => {
@@ -0,0 +1,499 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! End-to-end scenarios running multi-step flows against a network, as living documentation of the output
+//! transitions and as a conformance suite, e.g. for forks.
+//!
+//! Each scenario uses a [`FundedSecretManager`], as provided by the [`DevnetHarness`](crate::devnet::DevnetHarness),
+//! checks the ledger state after each step, failing with [`Error::ScenarioAssertion`] on a mismatch, and cleans up
+//! after itself, so that the funds it used are returned to the funded address, even when a step failed.
+//!
+//! ```no_run
+//! # use iota_client::{devnet::{DevnetHarness, DevnetPreset}, scenarios, Result};
+//! # #[tokio::main]
+//! # async fn main() -> Result<()> {
+//! let harness = DevnetHarness::new(DevnetPreset::from_env())?;
+//! let funded = harness.funded().await?;
+//!
+//! for report in [
+//!     scenarios::nft_lifecycle(&funded).await?,
+//!     scenarios::native_token_lifecycle(&funded).await?,
+//! ] {
+//!     println!("{}: {} blocks", report.name, report.steps.len());
+//! }
+//! # Ok(())}
+//! ```
+
+use iota_types::block::{
+    address::{Address, AliasAddress},
+    output::{
+        unlock_condition::{
+            AddressUnlockCondition, GovernorAddressUnlockCondition, ImmutableAliasAddressUnlockCondition,
+            StateControllerAddressUnlockCondition, UnlockCondition,
+        },
+        AliasId, AliasOutput, AliasOutputBuilder, BasicOutputBuilder, FoundryId, FoundryOutput, FoundryOutputBuilder,
+        NativeToken, NftId, NftOutputBuilder, Output, OutputId, SimpleTokenScheme, TokenId, TokenScheme,
+    },
+    payload::{transaction::TransactionEssence, Payload},
+    Block, BlockId,
+};
+use primitive_types::U256;
+
+use crate::{devnet::FundedSecretManager, Client, Error, Result};
+
+/// The amount of each output created by the scenarios, covering their storage deposit.
+const OUTPUT_AMOUNT: u64 = 1_000_000;
+
+/// The maximum supply of the native token minted by [`native_token_lifecycle()`], all of it being minted.
+const TOKEN_SUPPLY: u64 = 1_000;
+
+/// The outcome of a scenario.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioReport {
+    /// The name of the scenario.
+    pub name: &'static str,
+    /// The steps of the scenario, including the cleanup ones, in order.
+    pub steps: Vec<ScenarioStep>,
+}
+
+/// A step of a scenario, i.e. a transaction.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioStep {
+    /// What the step does.
+    pub description: &'static str,
+    /// The block holding the transaction of the step.
+    pub block_id: BlockId,
+}
+
+/// Mint an NFT, transfer it to another address of the funded secret manager, then burn it.
+pub async fn nft_lifecycle(funded: &FundedSecretManager) -> Result<ScenarioReport> {
+    let mut scenario = Scenario::new("NFT lifecycle", funded).await?;
+    let owner = scenario.address(0).await?;
+    let recipient = scenario.address(1).await?;
+
+    let outputs = scenario
+        .step(
+            "mint an NFT",
+            Vec::new(),
+            vec![NftOutputBuilder::new_with_amount(OUTPUT_AMOUNT, NftId::null())?
+                .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(owner)))
+                .finish_output(scenario.token_supply)?],
+        )
+        .await?;
+    let nft_id = NftId::from_output_id(&find_output(&outputs, Output::is_nft, "NFT")?);
+
+    if let Err(err) = transfer_nft(&mut scenario, nft_id, recipient).await {
+        cleanup(burn_nft(&mut scenario, nft_id, owner).await);
+        return Err(err);
+    }
+
+    burn_nft(&mut scenario, nft_id, owner).await?;
+
+    Ok(scenario.report)
+}
+
+/// Create an alias, create a foundry controlled by it and mint native tokens, then melt them and destroy the foundry
+/// and the alias.
+pub async fn native_token_lifecycle(funded: &FundedSecretManager) -> Result<ScenarioReport> {
+    let mut scenario = Scenario::new("native token lifecycle", funded).await?;
+    let owner = scenario.address(0).await?;
+
+    let outputs = scenario
+        .step(
+            "create an alias",
+            Vec::new(),
+            vec![AliasOutputBuilder::new_with_amount(3 * OUTPUT_AMOUNT, AliasId::null())?
+                .add_unlock_condition(UnlockCondition::StateControllerAddress(
+                    StateControllerAddressUnlockCondition::new(owner),
+                ))
+                .add_unlock_condition(UnlockCondition::GovernorAddress(GovernorAddressUnlockCondition::new(
+                    owner,
+                )))
+                .finish_output(scenario.token_supply)?],
+        )
+        .await?;
+    let alias_output_id = find_output(&outputs, Output::is_alias, "alias")?;
+
+    let mut chain = TokenChain {
+        alias_id: AliasId::from_output_id(&alias_output_id),
+        alias_output_id,
+        foundry_output_id: None,
+        tokens_output_id: None,
+    };
+
+    if let Err(err) = create_foundry_and_mint(&mut scenario, &mut chain, owner).await {
+        cleanup(destroy_token_chain(&mut scenario, &mut chain, owner).await);
+        return Err(err);
+    }
+
+    destroy_token_chain(&mut scenario, &mut chain, owner).await?;
+
+    Ok(scenario.report)
+}
+
+/// A scenario being run.
+struct Scenario<'a> {
+    funded: &'a FundedSecretManager,
+    token_supply: u64,
+    report: ScenarioReport,
+}
+
+impl<'a> Scenario<'a> {
+    async fn new(name: &'static str, funded: &'a FundedSecretManager) -> Result<Scenario<'a>> {
+        Ok(Self {
+            funded,
+            token_supply: funded.client.get_token_supply().await?,
+            report: ScenarioReport {
+                name,
+                steps: Vec::new(),
+            },
+        })
+    }
+
+    fn client(&self) -> &Client {
+        &self.funded.client
+    }
+
+    /// Get the public address with the given index of account 0 of the funded secret manager.
+    async fn address(&self, index: u32) -> Result<Address> {
+        Ok(self
+            .client()
+            .get_addresses(&self.funded.secret_manager)
+            .with_range(index..index + 1)
+            .get_raw()
+            .await?[0])
+    }
+
+    /// Get the unspent output with the given id.
+    async fn output(&self, output_id: &OutputId) -> Result<Output> {
+        let output_response = self.client().get_output(output_id).await?;
+
+        ensure(!output_response.metadata.is_spent, || {
+            format!("the output {output_id} has already been spent")
+        })?;
+
+        Ok(Output::try_from_dto(&output_response.output, self.token_supply)?)
+    }
+
+    /// Send a transaction with `outputs`, consuming `inputs` or automatically selected inputs if empty, and wait for
+    /// its inclusion. Returns the created outputs with their ids.
+    async fn step(
+        &mut self,
+        description: &'static str,
+        inputs: Vec<OutputId>,
+        outputs: Vec<Output>,
+    ) -> Result<Vec<(OutputId, Output)>> {
+        let mut block_builder = self.client().block().with_secret_manager(&self.funded.secret_manager);
+
+        for input in inputs {
+            block_builder = block_builder.with_input(input.into())?;
+        }
+
+        let block = block_builder.with_outputs(outputs)?.finish().await?;
+        log::debug!(
+            "[scenarios] {}: {description} in block {}",
+            self.report.name,
+            block.id()
+        );
+
+        let _ = self.client().retry_until_included(&block.id(), None, None).await?;
+
+        self.report.steps.push(ScenarioStep {
+            description,
+            block_id: block.id(),
+        });
+
+        transaction_outputs(&block)
+    }
+}
+
+async fn transfer_nft(scenario: &mut Scenario<'_>, nft_id: NftId, recipient: Address) -> Result<()> {
+    // The current NFT output is selected as input automatically.
+    scenario
+        .step(
+            "transfer the NFT",
+            Vec::new(),
+            vec![NftOutputBuilder::new_with_amount(OUTPUT_AMOUNT, nft_id)?
+                .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(recipient)))
+                .finish_output(scenario.token_supply)?],
+        )
+        .await?;
+
+    let nft_output_id = scenario.client().nft_output_id(nft_id).await?;
+    let output = scenario.output(&nft_output_id).await?;
+
+    ensure(output.is_nft() && *output.as_nft().address() == recipient, || {
+        format!("the NFT {nft_id} hasn't been transferred to the recipient")
+    })
+}
+
+async fn burn_nft(scenario: &mut Scenario<'_>, nft_id: NftId, owner: Address) -> Result<()> {
+    let nft_output_id = scenario.client().nft_output_id(nft_id).await?;
+    let amount = scenario.output(&nft_output_id).await?.amount();
+
+    // An NFT is burnt by not having it in the outputs.
+    scenario
+        .step(
+            "burn the NFT",
+            vec![nft_output_id],
+            vec![BasicOutputBuilder::new_with_amount(amount)?
+                .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(owner)))
+                .finish_output(scenario.token_supply)?],
+        )
+        .await?;
+
+    ensure(scenario.client().nft_output_id(nft_id).await.is_err(), || {
+        format!("the NFT {nft_id} still exists after being burnt")
+    })
+}
+
+/// The outputs of the alias and foundry of [`native_token_lifecycle()`], as far as they have been created.
+struct TokenChain {
+    alias_id: AliasId,
+    alias_output_id: OutputId,
+    foundry_output_id: Option<OutputId>,
+    tokens_output_id: Option<OutputId>,
+}
+
+impl TokenChain {
+    fn foundry_id(&self) -> FoundryId {
+        FoundryId::build(&AliasAddress::new(self.alias_id), 1, SimpleTokenScheme::KIND)
+    }
+
+    fn alias_address_unlock_condition(&self) -> UnlockCondition {
+        UnlockCondition::ImmutableAliasAddress(ImmutableAliasAddressUnlockCondition::new(AliasAddress::new(
+            self.alias_id,
+        )))
+    }
+
+    /// Update the output ids of the chain from the outputs created by a step.
+    fn update(&mut self, outputs: &[(OutputId, Output)]) {
+        for (output_id, output) in outputs {
+            match output {
+                Output::Alias(_) => self.alias_output_id = *output_id,
+                Output::Foundry(_) => self.foundry_output_id = Some(*output_id),
+                Output::Basic(basic) if !basic.native_tokens().is_empty() => self.tokens_output_id = Some(*output_id),
+                _ => {}
+            }
+        }
+    }
+
+    async fn alias(&self, scenario: &Scenario<'_>) -> Result<AliasOutput> {
+        Ok(scenario.output(&self.alias_output_id).await?.as_alias().clone())
+    }
+
+    async fn foundry(&self, scenario: &Scenario<'_>, foundry_output_id: &OutputId) -> Result<FoundryOutput> {
+        Ok(scenario.output(foundry_output_id).await?.as_foundry().clone())
+    }
+
+    /// The next state of the alias, holding `amount`.
+    async fn next_alias_state(&self, scenario: &Scenario<'_>, amount: u64, foundry_counter: u32) -> Result<Output> {
+        let alias = self.alias(scenario).await?;
+
+        Ok(AliasOutputBuilder::from(&alias)
+            .with_alias_id(self.alias_id)
+            .with_amount(amount)?
+            .with_state_index(alias.state_index() + 1)
+            .with_foundry_counter(foundry_counter)
+            .finish_output(scenario.token_supply)?)
+    }
+}
+
+async fn create_foundry_and_mint(scenario: &mut Scenario<'_>, chain: &mut TokenChain, owner: Address) -> Result<()> {
+    let foundry_id = chain.foundry_id();
+    let token_id = TokenId::from(foundry_id);
+
+    // A foundry is created by a state transition of its alias, incrementing the foundry counter, which is the serial
+    // number of the new foundry.
+    let outputs = vec![
+        chain.next_alias_state(scenario, 2 * OUTPUT_AMOUNT, 1).await?,
+        FoundryOutputBuilder::new_with_amount(
+            OUTPUT_AMOUNT,
+            1,
+            TokenScheme::Simple(SimpleTokenScheme::new(
+                U256::from(0u8),
+                U256::from(0u8),
+                U256::from(TOKEN_SUPPLY),
+            )?),
+        )?
+        .add_unlock_condition(chain.alias_address_unlock_condition())
+        .finish_output(scenario.token_supply)?,
+    ];
+    let outputs = scenario
+        .step("create a foundry", vec![chain.alias_output_id], outputs)
+        .await?;
+    chain.update(&outputs);
+
+    let foundry_output_id = scenario.client().foundry_output_id(foundry_id).await?;
+    ensure(chain.foundry_output_id == Some(foundry_output_id), || {
+        format!("the foundry {foundry_id} hasn't been created")
+    })?;
+
+    // Tokens are minted by a state transition of the alias too, increasing the minted tokens of the foundry.
+    let foundry = chain.foundry(scenario, &foundry_output_id).await?;
+    let outputs = vec![
+        chain.next_alias_state(scenario, OUTPUT_AMOUNT, 1).await?,
+        FoundryOutputBuilder::from(&foundry)
+            .with_token_scheme(TokenScheme::Simple(SimpleTokenScheme::new(
+                U256::from(TOKEN_SUPPLY),
+                U256::from(0u8),
+                U256::from(TOKEN_SUPPLY),
+            )?))
+            .finish_output(scenario.token_supply)?,
+        BasicOutputBuilder::new_with_amount(OUTPUT_AMOUNT)?
+            .add_native_token(NativeToken::new(token_id, U256::from(TOKEN_SUPPLY))?)
+            .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(owner)))
+            .finish_output(scenario.token_supply)?,
+    ];
+    let outputs = scenario
+        .step(
+            "mint native tokens",
+            vec![chain.alias_output_id, foundry_output_id],
+            outputs,
+        )
+        .await?;
+    chain.update(&outputs);
+
+    let tokens_output_id = chain
+        .tokens_output_id
+        .ok_or_else(|| Error::ScenarioAssertion("no output holds the minted tokens".to_string()))?;
+    let minted = scenario
+        .output(&tokens_output_id)
+        .await?
+        .native_tokens()
+        .into_iter()
+        .flat_map(|native_tokens| native_tokens.iter())
+        .find(|native_token| *native_token.token_id() == token_id)
+        .map(NativeToken::amount);
+
+    ensure(minted == Some(U256::from(TOKEN_SUPPLY)), || {
+        format!("{minted:?} instead of {TOKEN_SUPPLY} tokens {token_id} have been minted")
+    })
+}
+
+/// Melt the circulating tokens, then destroy the foundry and the alias, from whichever state the chain is in.
+async fn destroy_token_chain(scenario: &mut Scenario<'_>, chain: &mut TokenChain, owner: Address) -> Result<()> {
+    if let Some(foundry_output_id) = chain.foundry_output_id {
+        let foundry = chain.foundry(scenario, &foundry_output_id).await?;
+        let TokenScheme::Simple(token_scheme) = foundry.token_scheme();
+
+        // A foundry can only be destroyed without circulating supply.
+        if token_scheme.circulating_supply() != U256::from(0u8) {
+            let tokens_output_id = chain
+                .tokens_output_id
+                .ok_or_else(|| Error::ScenarioAssertion("no output holds the minted tokens".to_string()))?;
+            let tokens_amount = scenario.output(&tokens_output_id).await?.amount();
+            let alias_amount = chain.alias(scenario).await?.amount();
+
+            let outputs = vec![
+                chain.next_alias_state(scenario, alias_amount, 1).await?,
+                FoundryOutputBuilder::from(&foundry)
+                    .with_token_scheme(TokenScheme::Simple(SimpleTokenScheme::new(
+                        token_scheme.minted_tokens(),
+                        token_scheme.minted_tokens(),
+                        token_scheme.maximum_supply(),
+                    )?))
+                    .finish_output(scenario.token_supply)?,
+                BasicOutputBuilder::new_with_amount(tokens_amount)?
+                    .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(owner)))
+                    .finish_output(scenario.token_supply)?,
+            ];
+            let outputs = scenario
+                .step(
+                    "melt the native tokens",
+                    vec![chain.alias_output_id, foundry_output_id, tokens_output_id],
+                    outputs,
+                )
+                .await?;
+            chain.tokens_output_id = None;
+            chain.update(&outputs);
+        }
+
+        // The foundry is destroyed by a state transition of the alias without it in the outputs.
+        let foundry_output_id = chain.foundry_output_id.unwrap_or(foundry_output_id);
+        let foundry_amount = chain.foundry(scenario, &foundry_output_id).await?.amount();
+        let alias = chain.alias(scenario).await?;
+        let outputs = vec![
+            chain
+                .next_alias_state(scenario, alias.amount() + foundry_amount, alias.foundry_counter())
+                .await?,
+        ];
+        let outputs = scenario
+            .step(
+                "destroy the foundry",
+                vec![chain.alias_output_id, foundry_output_id],
+                outputs,
+            )
+            .await?;
+        chain.foundry_output_id = None;
+        chain.update(&outputs);
+
+        let foundry_id = chain.foundry_id();
+        ensure(scenario.client().foundry_output_id(foundry_id).await.is_err(), || {
+            format!("the foundry {foundry_id} still exists after being destroyed")
+        })?;
+    }
+
+    // The alias is destroyed by a governance transition without it in the outputs.
+    let alias_amount = chain.alias(scenario).await?.amount();
+    scenario
+        .step(
+            "destroy the alias",
+            vec![chain.alias_output_id],
+            vec![BasicOutputBuilder::new_with_amount(alias_amount)?
+                .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(owner)))
+                .finish_output(scenario.token_supply)?],
+        )
+        .await?;
+
+    let alias_id = chain.alias_id;
+    ensure(scenario.client().alias_output_id(alias_id).await.is_err(), || {
+        format!("the alias {alias_id} still exists after being destroyed")
+    })
+}
+
+/// Get the outputs created by the transaction of `block`, with their ids.
+fn transaction_outputs(block: &Block) -> Result<Vec<(OutputId, Output)>> {
+    match block.payload() {
+        Some(Payload::Transaction(transaction_payload)) => {
+            let TransactionEssence::Regular(essence) = transaction_payload.essence();
+
+            essence
+                .outputs()
+                .iter()
+                .enumerate()
+                .map(|(index, output)| Ok((OutputId::new(transaction_payload.id(), index as u16)?, output.clone())))
+                .collect()
+        }
+        _ => Err(Error::ScenarioAssertion(format!(
+            "the block {} doesn't hold a transaction",
+            block.id()
+        ))),
+    }
+}
+
+/// Find the id of the output of `kind` among `outputs`.
+fn find_output(outputs: &[(OutputId, Output)], is_kind: fn(&Output) -> bool, kind: &str) -> Result<OutputId> {
+    outputs
+        .iter()
+        .find(|(_, output)| is_kind(output))
+        .map(|(output_id, _)| *output_id)
+        .ok_or_else(|| Error::ScenarioAssertion(format!("no {kind} output has been created")))
+}
+
+fn ensure(condition: bool, message: impl FnOnce() -> String) -> Result<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Error::ScenarioAssertion(message()))
+    }
+}
+
+/// Log a failed cleanup, as the error of the failed step is the one returned.
+fn cleanup(result: Result<()>) {
+    if let Err(err) = result {
+        log::warn!("[scenarios] cleanup failed: {err}");
+    }
+}
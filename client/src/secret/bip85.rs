@@ -0,0 +1,99 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! BIP-85 deterministic entropy, deriving the mnemonics and seeds of secondary wallets from a seed.
+//!
+//! The entropy is the HMAC-SHA512 of a secp256k1 private key derived with BIP-32 at
+//! `m/83696968'/{application}'/...`, so that the same seed gives the same child mnemonics as other BIP-85 wallets.
+//!
+//! For more information, see <https://github.com/bitcoin/bips/blob/master/bip-0085.mediawiki>.
+
+use crypto::macs::hmac::HMAC_SHA512;
+use zeroize::Zeroizing;
+
+use super::secp256k1::derive_signing_key;
+use crate::{utils::MnemonicLanguage, Error, Result};
+
+/// The purpose of BIP-85 derivation paths.
+pub const BIP85_PURPOSE: u32 = 83696968;
+/// The application number of BIP-39 mnemonics.
+const BIP39_APPLICATION: u32 = 39;
+/// The application number of raw hex entropy.
+const HEX_APPLICATION: u32 = 128169;
+/// The HMAC key of the entropy.
+const ENTROPY_HMAC_KEY: &[u8] = b"bip-entropy-from-k";
+/// The range of the lengths of raw hex entropy.
+const HEX_LENGTHS: std::ops::RangeInclusive<usize> = 16..=64;
+
+const HARDENED: u32 = 1 << 31;
+
+/// The BIP-32 chain of the child mnemonic `index` of `word_count` words of the wordlist of `language`.
+pub fn bip39_chain(language: MnemonicLanguage, word_count: usize, index: u32) -> [u32; 5] {
+    // The language codes of BIP-85
+    let language = match language {
+        MnemonicLanguage::English => 0,
+        MnemonicLanguage::Japanese => 1,
+    };
+
+    [
+        BIP85_PURPOSE | HARDENED,
+        BIP39_APPLICATION | HARDENED,
+        language | HARDENED,
+        word_count as u32 | HARDENED,
+        index | HARDENED,
+    ]
+}
+
+/// The BIP-32 chain of the raw entropy `index` of `length` bytes.
+pub fn hex_chain(length: usize, index: u32) -> [u32; 4] {
+    [
+        BIP85_PURPOSE | HARDENED,
+        HEX_APPLICATION | HARDENED,
+        length as u32 | HARDENED,
+        index | HARDENED,
+    ]
+}
+
+/// Derives the 64 bytes of entropy of `chain` from `seed`.
+pub(crate) fn derive_entropy(seed: &[u8], chain: &[u32]) -> Result<Zeroizing<[u8; 64]>> {
+    let signing_key = derive_signing_key(seed, chain)?;
+    let mut entropy = Zeroizing::new([0u8; 64]);
+
+    HMAC_SHA512(&signing_key.to_bytes(), ENTROPY_HMAC_KEY, &mut entropy);
+
+    Ok(entropy)
+}
+
+/// Derives the child mnemonic `index` of `word_count` words, 12, 18 or 24, of the wordlist of `language` from `seed`.
+pub(crate) fn derive_mnemonic(
+    seed: &[u8],
+    language: MnemonicLanguage,
+    word_count: usize,
+    index: u32,
+) -> Result<Zeroizing<String>> {
+    // Each word encodes 11 bits, of which 32 bits of entropy per 3 words
+    let length = match word_count {
+        12 | 18 | 24 => word_count / 3 * 4,
+        _ => {
+            return Err(Error::InvalidMnemonic(format!(
+                "unsupported word count {word_count}, expected 12, 18 or 24"
+            )));
+        }
+    };
+    let entropy = derive_entropy(seed, &bip39_chain(language, word_count, index))?;
+
+    crypto::keys::bip39::wordlist::encode(&entropy[..length], language.wordlist())
+        .map(Zeroizing::new)
+        .map_err(|e| Error::InvalidMnemonic(format!("{e:?}")))
+}
+
+/// Derives the raw entropy `index` of `length` bytes, from 16 to 64, from `seed`.
+pub(crate) fn derive_hex(seed: &[u8], length: usize, index: u32) -> Result<Zeroizing<Vec<u8>>> {
+    if !HEX_LENGTHS.contains(&length) {
+        return Err(Error::InvalidBIP85EntropyLength(length));
+    }
+
+    let entropy = derive_entropy(seed, &hex_chain(length, index))?;
+
+    Ok(Zeroizing::new(entropy[..length].to_vec()))
+}
@@ -0,0 +1,246 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of [`CloudKmsSecretManager`].
+//!
+//! The Ed25519 key is held by Google Cloud KMS and never leaves it: the transaction essences are signed with the
+//! `asymmetricSign` method of its REST API. The key has to be an asymmetric signing key with the `EC_SIGN_ED25519`
+//! algorithm, e.g. created with
+//! `gcloud kms keys create <key> --keyring <keyring> --location <location> --purpose asymmetric-signing
+//! --default-algorithm ec-sign-ed25519`.
+//!
+//! The public key is fetched on first use, so that the secret manager can be created without a request.
+//!
+//! As KMS holds a single key rather than a seed, there is no key derivation: all accounts and address indexes map to
+//! the one address of the key.
+
+use std::{ops::Range, sync::RwLock};
+
+use async_trait::async_trait;
+use crypto::{
+    hashes::{blake2b::Blake2b256, Digest},
+    keys::slip10::Chain,
+};
+use iota_types::block::{
+    address::{Address, Ed25519Address},
+    signature::{Ed25519Signature, Signature},
+    unlock::{SignatureUnlock, Unlock},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{types::InputSigningData, GenerateAddressOptions, SecretManage};
+use crate::{secret::RemainderData, Error, Result};
+
+/// The endpoint of the Cloud KMS REST API.
+const CLOUD_KMS_ENDPOINT: &str = "https://cloudkms.googleapis.com/v1/";
+/// The DER encoding of the SubjectPublicKeyInfo of an Ed25519 public key, up to the key itself.
+const ED25519_SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+/// The length of an Ed25519 public key.
+const PUBLIC_KEY_LENGTH: usize = 32;
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+}
+
+#[derive(Serialize)]
+struct AsymmetricSignRequest {
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+/// Secret manager that signs with an Ed25519 key held by Google Cloud KMS.
+pub struct CloudKmsSecretManager {
+    client: reqwest::Client,
+    /// The resource name of the key version.
+    key_version_name: String,
+    /// The URL of the key version, e.g.
+    /// `.../projects/<project>/locations/<location>/keyRings/<keyring>/cryptoKeys/<key>/cryptoKeyVersions/1`.
+    key_version_url: Url,
+    /// The OAuth 2.0 access token authorizing the requests.
+    access_token: RwLock<String>,
+    /// The public key, fetched on first use.
+    public_key: RwLock<Option<[u8; PUBLIC_KEY_LENGTH]>>,
+}
+
+impl CloudKmsSecretManager {
+    /// Sign with the key version `key_version_name`, i.e.
+    /// `projects/<project>/locations/<location>/keyRings/<keyring>/cryptoKeys/<key>/cryptoKeyVersions/<version>`,
+    /// authorized by `access_token`, e.g. from `gcloud auth print-access-token` or the metadata server.
+    pub fn new(key_version_name: &str, access_token: String) -> Result<Self> {
+        Self::with_endpoint(CLOUD_KMS_ENDPOINT, key_version_name, access_token)
+    }
+
+    /// Like [`new()`](Self::new()), with another endpoint of the API, e.g. a regional endpoint or an emulator.
+    pub fn with_endpoint(endpoint: &str, key_version_name: &str, access_token: String) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            key_version_name: key_version_name.to_string(),
+            key_version_url: Url::parse(endpoint)?.join(key_version_name)?,
+            access_token: RwLock::new(access_token),
+            public_key: RwLock::new(None),
+        })
+    }
+
+    /// Replace the access token, as they expire after an hour.
+    pub fn set_access_token(&self, access_token: String) -> Result<()> {
+        *self.access_token.write().map_err(|_| Error::PoisonError)? = access_token;
+        Ok(())
+    }
+
+    /// The resource name of the key version.
+    pub fn key_version_name(&self) -> &str {
+        &self.key_version_name
+    }
+
+    /// The public key of the key version.
+    pub async fn public_key(&self) -> Result<[u8; PUBLIC_KEY_LENGTH]> {
+        if let Some(public_key) = *self.public_key.read().map_err(|_| Error::PoisonError)? {
+            return Ok(public_key);
+        }
+
+        let request = self
+            .client
+            .get(self.method_url("/publicKey"))
+            .bearer_auth(self.access_token()?);
+        let response = parse_response(request.send().await?).await?;
+        let public_key = decode_pem(&response.json::<PublicKeyResponse>().await?.pem)?;
+
+        *self.public_key.write().map_err(|_| Error::PoisonError)? = Some(public_key);
+
+        Ok(public_key)
+    }
+
+    /// The address of the key.
+    pub async fn address(&self) -> Result<Address> {
+        // Hash the public key to get the address
+        let address = Blake2b256::digest(self.public_key().await?).try_into().map_err(|_e| {
+            crate::Error::Blake2b256Error("hashing the public key while generating the address failed.")
+        })?;
+
+        Ok(Address::Ed25519(Ed25519Address::new(address)))
+    }
+
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 64]> {
+        let request = self
+            .client
+            .post(self.method_url(":asymmetricSign"))
+            .bearer_auth(self.access_token()?)
+            .json(&AsymmetricSignRequest {
+                data: base64::encode(message),
+            });
+        let response = parse_response(request.send().await?).await?;
+        let signature = response.json::<AsymmetricSignResponse>().await?.signature;
+
+        base64::decode(signature)
+            .ok()
+            .and_then(|signature| signature.try_into().ok())
+            .ok_or(Error::CloudKmsInvalidSignature)
+    }
+
+    fn access_token(&self) -> Result<String> {
+        Ok(self.access_token.read().map_err(|_| Error::PoisonError)?.clone())
+    }
+
+    fn method_url(&self, method: &str) -> Url {
+        let mut url = self.key_version_url.clone();
+        url.set_path(&format!("{}{method}", self.key_version_url.path()));
+        url
+    }
+}
+
+async fn parse_response(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+
+    if status.is_success() {
+        Ok(response)
+    } else {
+        Err(Error::ResponseError {
+            code: status.as_u16(),
+            url: response.url().to_string(),
+            text: response.text().await?,
+        })
+    }
+}
+
+/// Decode the PEM-encoded SubjectPublicKeyInfo of an Ed25519 public key.
+fn decode_pem(pem: &str) -> Result<[u8; PUBLIC_KEY_LENGTH]> {
+    let base64 = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect::<String>();
+    let der = base64::decode(base64.trim()).map_err(|_| Error::CloudKmsInvalidPublicKey)?;
+
+    der.strip_prefix(&ED25519_SPKI_PREFIX)
+        .and_then(|public_key| public_key.try_into().ok())
+        .ok_or(Error::CloudKmsInvalidPublicKey)
+}
+
+#[async_trait]
+impl SecretManage for CloudKmsSecretManager {
+    async fn generate_addresses(
+        &self,
+        _coin_type: u32,
+        _account_index: u32,
+        address_indexes: Range<u32>,
+        _internal: bool,
+        _: Option<GenerateAddressOptions>,
+    ) -> crate::Result<Vec<Address>> {
+        let address = self.address().await?;
+
+        Ok(address_indexes.map(|_| address).collect())
+    }
+
+    async fn signature_unlock(
+        &self,
+        input: &InputSigningData,
+        essence_hash: &[u8; 32],
+        _: &Option<RemainderData>,
+    ) -> crate::Result<Unlock> {
+        let (_, input_address) = Address::try_from_bech32(&input.bech32_address)?;
+
+        if input_address != self.address().await? {
+            return Err(Error::CloudKmsAddressMismatch(input.bech32_address.clone()));
+        }
+
+        // The signature unlock block needs to sign the hash of the entire transaction essence of the
+        // transaction payload
+        let signature = self.sign(essence_hash).await?;
+
+        Ok(Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(
+            Ed25519Signature::new(self.public_key().await?, signature),
+        ))))
+    }
+
+    // The KMS key version is a single key, the chain is irrelevant
+    async fn sign_ed25519(&self, message: &[u8], _: &Chain) -> crate::Result<Ed25519Signature> {
+        let signature = self.sign(message).await?;
+
+        Ok(Ed25519Signature::new(self.public_key().await?, signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pem() {
+        let public_key = [42; PUBLIC_KEY_LENGTH];
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            base64::encode([&ED25519_SPKI_PREFIX[..], &public_key].concat())
+        );
+
+        assert_eq!(decode_pem(&pem).unwrap(), public_key);
+        assert!(matches!(
+            decode_pem("-----BEGIN PUBLIC KEY-----\nKg==\n-----END PUBLIC KEY-----\n"),
+            Err(Error::CloudKmsInvalidPublicKey)
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signing of a transaction by several independent signers, e.g. when its inputs belong to addresses of different
+//! parties or devices.
+//!
+//! Each [`SignerHandle`] only signs the inputs of the addresses it controls, returning [`PartialUnlocks`] which can be
+//! exchanged between the parties, then merged into the unlocks of the transaction with [`merge_unlocks()`]. When all
+//! signers are at hand, [`sign_transaction_essence_with_signers()`] does both.
+
+use std::collections::{BTreeMap, HashSet};
+
+use futures::future::try_join_all;
+use iota_types::block::{
+    address::Address,
+    unlock::{Unlock, Unlocks},
+};
+
+use super::{unlock_plan, SecretManage};
+use crate::api::PreparedTransactionData;
+
+/// The signature unlocks of some of the inputs of a transaction, by index of the input.
+pub type PartialUnlocks = BTreeMap<usize, Unlock>;
+
+/// A secret manager taking part in the signing of a transaction, signing the inputs of the addresses it controls.
+pub struct SignerHandle<'a> {
+    secret_manager: &'a dyn SecretManage,
+    addresses: HashSet<Address>,
+}
+
+impl<'a> SignerHandle<'a> {
+    /// A signer signing with `secret_manager` the inputs of `addresses`.
+    pub fn new(secret_manager: &'a dyn SecretManage, addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            secret_manager,
+            addresses: addresses.into_iter().collect(),
+        }
+    }
+
+    /// Whether the signer signs the inputs of `address`.
+    pub fn controls(&self, address: &Address) -> bool {
+        self.addresses.contains(address)
+    }
+
+    /// Sign the inputs of the addresses the signer controls, i.e. the first input of each of them, as the other ones
+    /// are unlocked by reference.
+    pub async fn sign(&self, prepared_transaction_data: &PreparedTransactionData) -> crate::Result<PartialUnlocks> {
+        // The hashed_essence gets signed
+        let hashed_essence = prepared_transaction_data.essence.hash();
+        let mut unlocks = PartialUnlocks::new();
+
+        for (index, (input, unlock)) in prepared_transaction_data
+            .inputs_data
+            .iter()
+            .zip(unlock_plan(&prepared_transaction_data.inputs_data)?)
+            .enumerate()
+        {
+            if unlock.is_some() {
+                continue;
+            }
+
+            let (_, input_address) = Address::try_from_bech32(&input.bech32_address)?;
+
+            if self.controls(&input_address) {
+                let unlock = self
+                    .secret_manager
+                    .signature_unlock(input, &hashed_essence, &prepared_transaction_data.remainder)
+                    .await?;
+                unlocks.insert(index, unlock);
+            }
+        }
+
+        Ok(unlocks)
+    }
+}
+
+/// Merge the [`PartialUnlocks`] of the signers of a transaction into its unlocks, referencing the signature unlocks
+/// for the other inputs of the same addresses.
+///
+/// Fails with [`Error::MissingSignatureUnlock`](crate::Error::MissingSignatureUnlock) if an input needing a signature
+/// hasn't been signed by any signer.
+pub fn merge_unlocks(
+    prepared_transaction_data: &PreparedTransactionData,
+    partial_unlocks: impl IntoIterator<Item = PartialUnlocks>,
+) -> crate::Result<Unlocks> {
+    let mut signature_unlocks = PartialUnlocks::new();
+
+    for partial_unlocks in partial_unlocks {
+        signature_unlocks.extend(partial_unlocks);
+    }
+
+    let blocks = unlock_plan(&prepared_transaction_data.inputs_data)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, unlock)| match unlock {
+            Some(unlock) => Ok(unlock),
+            None => signature_unlocks
+                .remove(&index)
+                .ok_or(crate::Error::MissingSignatureUnlock(index)),
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    Ok(Unlocks::new(blocks)?)
+}
+
+/// Sign a transaction with all its `signers` and merge their unlocks, see [`merge_unlocks()`].
+pub async fn sign_transaction_essence_with_signers(
+    prepared_transaction_data: &PreparedTransactionData,
+    signers: &[SignerHandle<'_>],
+) -> crate::Result<Unlocks> {
+    let partial_unlocks = try_join_all(signers.iter().map(|signer| signer.sign(prepared_transaction_data))).await?;
+
+    merge_unlocks(prepared_transaction_data, partial_unlocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::keys::slip10::Chain;
+    use iota_types::block::{
+        input::{Input, UtxoInput},
+        output::{
+            unlock_condition::AddressUnlockCondition, BasicOutputBuilder, InputsCommitment, OutputId, UnlockCondition,
+        },
+        payload::transaction::{RegularTransactionEssence, TransactionEssence},
+        protocol::ProtocolParameters,
+        rand::{block::rand_block_id, transaction::rand_transaction_id},
+    };
+
+    use super::*;
+    use crate::{
+        constants::{HD_WALLET_TYPE, IOTA_COIN_TYPE},
+        secret::{
+            mnemonic::MnemonicSecretManager,
+            types::{InputSigningData, OutputMetadata},
+            SecretManageExt, SecretManager,
+        },
+    };
+
+    async fn address(secret_manager: &MnemonicSecretManager) -> Address {
+        secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap()[0]
+    }
+
+    fn input(address: Address, amount: u64) -> InputSigningData {
+        InputSigningData {
+            output: BasicOutputBuilder::new_with_amount(amount)
+                .unwrap()
+                .add_unlock_condition(UnlockCondition::Address(AddressUnlockCondition::new(address)))
+                .finish_output(ProtocolParameters::default().token_supply())
+                .unwrap(),
+            output_metadata: OutputMetadata::new(
+                rand_block_id(),
+                OutputId::new(rand_transaction_id(), 0).unwrap(),
+                false,
+                None,
+                None,
+                None,
+                0,
+                0,
+                0,
+            ),
+            chain: Some(Chain::from_u32_hardened(vec![HD_WALLET_TYPE, IOTA_COIN_TYPE, 0, 0, 0])),
+            bech32_address: address.to_bech32("smr"),
+        }
+    }
+
+    fn prepared_transaction_data(inputs_data: Vec<InputSigningData>, address: Address) -> PreparedTransactionData {
+        let amount = inputs_data.iter().map(|input| input.output.amount()).sum();
+        let protocol_parameters = ProtocolParameters::default();
+        let essence = RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new(inputs_data.iter().map(|input| &input.output)),
+        )
+        .with_inputs(
+            inputs_data
+                .iter()
+                .map(|input| {
+                    Input::Utxo(
+                        UtxoInput::new(
+                            *input.output_metadata.transaction_id(),
+                            input.output_metadata.output_index(),
+                        )
+                        .unwrap(),
+                    )
+                })
+                .collect(),
+        )
+        .with_outputs(vec![input(address, amount).output])
+        .finish(&protocol_parameters)
+        .unwrap();
+
+        PreparedTransactionData {
+            essence: TransactionEssence::Regular(essence),
+            inputs_data,
+            remainder: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn sign_with_signers() {
+        let alice = MnemonicSecretManager::try_from_hex_seed(
+            "0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2",
+        )
+        .unwrap();
+        let bob = MnemonicSecretManager::try_from_hex_seed(
+            "0x4e4f4d4f4e4f4d4f4e4f4d4f4e4f4d4f4e4f4d4f4e4f4d4f4e4f4d4f4e4f4d4f",
+        )
+        .unwrap();
+        let (alice_address, bob_address) = (address(&alice).await, address(&bob).await);
+
+        let prepared_transaction_data = prepared_transaction_data(
+            vec![
+                input(alice_address, 1_000_000),
+                input(bob_address, 2_000_000),
+                input(alice_address, 3_000_000),
+            ],
+            bob_address,
+        );
+        let signers = [
+            SignerHandle::new(&alice, [alice_address]),
+            SignerHandle::new(&bob, [bob_address]),
+        ];
+
+        let unlocks = sign_transaction_essence_with_signers(&prepared_transaction_data, &signers)
+            .await
+            .unwrap();
+        assert!(matches!(unlocks.get(0), Some(Unlock::Signature(_))));
+        assert!(matches!(unlocks.get(1), Some(Unlock::Signature(_))));
+        assert!(matches!(&unlocks[2], Unlock::Reference(reference) if reference.index() == 0));
+
+        // Each signer only signs its inputs
+        let bob_unlocks = signers[1].sign(&prepared_transaction_data).await.unwrap();
+        assert_eq!(bob_unlocks.keys().collect::<Vec<_>>(), vec![&1]);
+        assert!(matches!(
+            merge_unlocks(&prepared_transaction_data, [bob_unlocks]),
+            Err(crate::Error::MissingSignatureUnlock(0))
+        ));
+    }
+
+    #[tokio::test]
+    async fn sign_essence_hash() {
+        let mnemonic_secret_manager = MnemonicSecretManager::try_from_hex_seed(
+            "0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2",
+        )
+        .unwrap();
+        let address = address(&mnemonic_secret_manager).await;
+        let secret_manager = SecretManager::Mnemonic(mnemonic_secret_manager);
+        let prepared_transaction_data =
+            prepared_transaction_data(vec![input(address, 1_000_000), input(address, 2_000_000)], address);
+
+        // Only the hash and the inputs are needed, with the same unlocks as when signing the whole essence
+        let unlocks = secret_manager
+            .sign_essence_hash(
+                &prepared_transaction_data.essence.hash(),
+                &prepared_transaction_data.inputs_data,
+                &None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            unlocks,
+            secret_manager
+                .sign_transaction_essence(&prepared_transaction_data)
+                .await
+                .unwrap()
+        );
+        assert!(matches!(&unlocks[1], Unlock::Reference(reference) if reference.index() == 0));
+    }
+}
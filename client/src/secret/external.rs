@@ -0,0 +1,236 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of [`ExternalSigner`].
+//!
+//! The keys are held by the application, e.g. in a custom HSM or a remote signing service: the secret manager derives
+//! the BIP-32 chains of the addresses and inputs like the other secret managers, and asks the application for the
+//! public keys and the signatures of these chains, through an [`ExternalSign`] implementation or closures.
+
+use std::ops::Range;
+
+use async_trait::async_trait;
+use crypto::{
+    hashes::{blake2b::Blake2b256, Digest},
+    keys::slip10::Chain,
+    signatures::ed25519,
+};
+use futures::future::BoxFuture;
+use iota_types::block::{
+    address::{Address, Ed25519Address},
+    signature::{Ed25519Signature, Signature},
+    unlock::{SignatureUnlock, Unlock},
+};
+
+use super::{types::InputSigningData, GenerateAddressOptions, SecretManage};
+use crate::{secret::RemainderData, Error, Result};
+
+/// The signing backend of an [`ExternalSigner`], implemented by the application.
+#[async_trait]
+pub trait ExternalSign: Send + Sync {
+    /// The Ed25519 public key of `chain`.
+    async fn public_key(&self, chain: &Chain) -> Result<[u8; 32]>;
+
+    /// Sign `message` with the Ed25519 private key of `chain`.
+    async fn sign_ed25519(&self, message: &[u8], chain: &Chain) -> Result<[u8; 64]>;
+}
+
+/// An [`ExternalSign`] implementation from closures, see [`ExternalSigner::from_fns()`].
+struct FnSigner<P, S> {
+    public_key: P,
+    sign_ed25519: S,
+}
+
+#[async_trait]
+impl<P, S> ExternalSign for FnSigner<P, S>
+where
+    P: Fn(Chain) -> BoxFuture<'static, Result<[u8; 32]>> + Send + Sync,
+    S: Fn(Vec<u8>, Chain) -> BoxFuture<'static, Result<[u8; 64]>> + Send + Sync,
+{
+    async fn public_key(&self, chain: &Chain) -> Result<[u8; 32]> {
+        (self.public_key)(chain.clone()).await
+    }
+
+    async fn sign_ed25519(&self, message: &[u8], chain: &Chain) -> Result<[u8; 64]> {
+        (self.sign_ed25519)(message.to_vec(), chain.clone()).await
+    }
+}
+
+/// Secret manager that delegates the derivation of public keys and the signatures to the application.
+pub struct ExternalSigner(Box<dyn ExternalSign>);
+
+impl ExternalSigner {
+    /// Create a new [`ExternalSigner`] delegating to `signer`.
+    pub fn new(signer: impl ExternalSign + 'static) -> Self {
+        Self(Box::new(signer))
+    }
+
+    /// Create a new [`ExternalSigner`] delegating to the closures `public_key`, returning the public key of a chain,
+    /// and `sign_ed25519`, signing a message with the private key of a chain.
+    pub fn from_fns<P, S>(public_key: P, sign_ed25519: S) -> Self
+    where
+        P: Fn(Chain) -> BoxFuture<'static, Result<[u8; 32]>> + Send + Sync + 'static,
+        S: Fn(Vec<u8>, Chain) -> BoxFuture<'static, Result<[u8; 64]>> + Send + Sync + 'static,
+    {
+        Self::new(FnSigner {
+            public_key,
+            sign_ed25519,
+        })
+    }
+}
+
+#[async_trait]
+impl SecretManage for ExternalSigner {
+    async fn generate_addresses(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        address_indexes: Range<u32>,
+        internal: bool,
+        options: Option<GenerateAddressOptions>,
+    ) -> crate::Result<Vec<Address>> {
+        let derivation_path = GenerateAddressOptions::derivation_path(&options);
+        let mut addresses = Vec::new();
+
+        for address_index in address_indexes {
+            let chain = derivation_path.chain(coin_type, account_index, internal, address_index);
+
+            let public_key = self.0.public_key(&chain).await?;
+
+            // Hash the public key to get the address
+            let result = Blake2b256::digest(public_key).try_into().map_err(|_e| {
+                crate::Error::Blake2b256Error("hashing the public key while generating the address failed.")
+            });
+
+            addresses.push(Address::Ed25519(Ed25519Address::new(result?)));
+        }
+
+        Ok(addresses)
+    }
+
+    async fn signature_unlock(
+        &self,
+        input: &InputSigningData,
+        essence_hash: &[u8; 32],
+        _: &Option<RemainderData>,
+    ) -> crate::Result<Unlock> {
+        let chain = input.chain.as_ref().ok_or(Error::MissingParameter("chain"))?;
+
+        // The signature unlock block needs to sign the hash of the entire transaction essence of the
+        // transaction payload
+        let signature = self.sign_ed25519(essence_hash, chain).await?;
+
+        Ok(Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(signature))))
+    }
+
+    async fn sign_ed25519(&self, message: &[u8], chain: &Chain) -> crate::Result<Ed25519Signature> {
+        let public_key = self.0.public_key(chain).await?;
+        let signature = self.0.sign_ed25519(message, chain).await?;
+
+        // A wrong signature would only be noticed by the node
+        let valid = ed25519::PublicKey::try_from_bytes(public_key)
+            .map(|key| key.verify(&ed25519::Signature::from_bytes(signature), message))
+            .unwrap_or(false);
+        if !valid {
+            return Err(Error::ExternalSignerInvalidSignature);
+        }
+
+        Ok(Ed25519Signature::new(public_key, signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crypto::keys::slip10::{Curve, Seed};
+    use futures::FutureExt;
+    use iota_types::block::{
+        protocol::ProtocolParameters,
+        rand::{
+            block::rand_block_id,
+            output::{rand_basic_output, rand_output_id},
+        },
+    };
+
+    use super::*;
+    use crate::{
+        constants::{HD_WALLET_TYPE, IOTA_COIN_TYPE},
+        secret::{mnemonic::MnemonicSecretManager, types::OutputMetadata},
+    };
+
+    const SEED: [u8; 32] = [42; 32];
+
+    fn secret_key(seed: &Seed, chain: &Chain) -> ed25519::SecretKey {
+        seed.derive(Curve::Ed25519, chain).unwrap().secret_key()
+    }
+
+    fn input(address_index: u32, bech32_address: String) -> InputSigningData {
+        InputSigningData {
+            output: rand_basic_output(ProtocolParameters::default().token_supply()).into(),
+            output_metadata: OutputMetadata::new(rand_block_id(), rand_output_id(), false, None, None, None, 0, 0, 0),
+            chain: Some(Chain::from_u32_hardened(vec![
+                HD_WALLET_TYPE,
+                IOTA_COIN_TYPE,
+                0,
+                0,
+                address_index,
+            ])),
+            bech32_address,
+        }
+    }
+
+    #[tokio::test]
+    async fn external_signer() {
+        let seed = Arc::new(Seed::from_bytes(&SEED));
+        let signing_seed = seed.clone();
+        let signer = ExternalSigner::from_fns(
+            move |chain| {
+                let public_key = secret_key(&seed, &chain).public_key().to_bytes();
+                async move { Ok(public_key) }.boxed()
+            },
+            move |message, chain| {
+                let signature = secret_key(&signing_seed, &chain).sign(&message).to_bytes();
+                async move { Ok(signature) }.boxed()
+            },
+        );
+
+        // The same keys as a mnemonic secret manager with the same seed
+        let addresses = signer
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..2, false, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            addresses,
+            MnemonicSecretManager::try_from_seed_bytes(&SEED)
+                .unwrap()
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..2, false, None)
+                .await
+                .unwrap()
+        );
+
+        let input = input(1, addresses[1].to_bech32("smr"));
+        assert!(matches!(
+            signer.signature_unlock(&input, &[0; 32], &None).await.unwrap(),
+            Unlock::Signature(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn external_signer_invalid_signature() {
+        let seed = Arc::new(Seed::from_bytes(&SEED));
+        let signer = ExternalSigner::from_fns(
+            move |chain| {
+                let public_key = secret_key(&seed, &chain).public_key().to_bytes();
+                async move { Ok(public_key) }.boxed()
+            },
+            |_, _| async move { Ok([0; 64]) }.boxed(),
+        );
+
+        let input = input(0, String::new());
+        assert!(matches!(
+            signer.signature_unlock(&input, &[0; 32], &None).await,
+            Err(Error::ExternalSignerInvalidSignature)
+        ));
+    }
+}
@@ -20,12 +20,13 @@ use iota_types::block::{
     unlock::{AliasUnlock, NftUnlock, ReferenceUnlock, Unlock, Unlocks},
 };
 use packable::{unpacker::SliceUnpacker, Packable, PackableExt};
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
 use super::{types::InputSigningData, GenerateAddressOptions, SecretManage, SecretManageExt};
 use crate::{
     secret::{
-        types::{LedgerApp, LedgerDeviceType},
+        types::{DerivationPath, LedgerApp, LedgerDeviceType},
         LedgerNanoStatus, PreparedTransactionData, RemainderData,
     },
     Error, Result,
@@ -70,6 +71,11 @@ impl SecretManage for LedgerSecretManager {
         internal: bool,
         options: Option<GenerateAddressOptions>,
     ) -> crate::Result<Vec<Address>> {
+        // The ledger app only derives addresses with the default path
+        if GenerateAddressOptions::derivation_path(&options) != DerivationPath::default() {
+            return Err(Error::LedgerDerivationPathUnsupported);
+        }
+
         // lock the mutex to prevent multiple simultaneous requests to a ledger
         let _lock = self.mutex.lock().await;
 
@@ -136,6 +142,171 @@ pub fn needs_blind_signing(prepared_transaction: &PreparedTransactionData, buffe
     total_size > buffer_size
 }
 
+/// Find the index of the remainder output in the essence.
+///
+/// This has to be done because outputs in essences are sorted lexically and therefore the remainder is not always the
+/// last output. The index within the essence and the bip32 index will be validated by the hardware wallet. The outputs
+/// in the essence already are sorted at this place, so we can rely on their order and don't have to sort it again.
+fn remainder_output_index(essence: &TransactionEssence, remainder_address: &Address) -> Result<u16> {
+    match essence {
+        TransactionEssence::Regular(essence) => {
+            for (index, output) in essence.outputs().iter().enumerate() {
+                if let Output::Basic(s) = output {
+                    if let Some(address) = s.unlock_conditions().address() {
+                        if *remainder_address == *address.address() {
+                            return Ok(index as u16);
+                        }
+                    }
+                } else {
+                    log::debug!("[LEDGER] unsupported output");
+                    return Err(crate::Error::LedgerMiscError);
+                }
+            }
+
+            log::debug!("[LEDGER] remainder_index not found");
+            Err(crate::Error::LedgerMiscError)
+        }
+    }
+}
+
+/// The BIP-32 path of the remainder address, from which the Ledger Nano derives it to recognize the remainder output.
+fn remainder_bip32(remainder: &RemainderData) -> Result<LedgerBIP32Index> {
+    let remainder_bip32_indices: Vec<u32> = match &remainder.chain {
+        Some(chain) => {
+            chain
+                .segments()
+                .iter()
+                // XXX: "ser32(i)". RTFSC: [crypto::keys::slip10::Segment::from_u32()]
+                .map(|seg| u32::from_be_bytes(seg.bs()))
+                .collect()
+        }
+        None => return Err(crate::Error::InvalidBIP32ChainData),
+    };
+
+    Ok(LedgerBIP32Index {
+        bip32_change: remainder_bip32_indices[3] | HARDENED,
+        bip32_index: remainder_bip32_indices[4] | HARDENED,
+    })
+}
+
+/// An output as presented by the Ledger Nano, see [`SigningPreview`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputPreview {
+    /// The index of the output in the essence.
+    pub index: u16,
+    /// The bech32 encoded address the output is sent to.
+    pub address: String,
+    /// The amount of the output.
+    pub amount: u64,
+    /// Whether the output is the remainder, which the device recognizes from its BIP-32 path and doesn't display as
+    /// a recipient.
+    pub remainder: bool,
+    /// The range of the output in the packed essence sent to the device.
+    pub byte_range: Range<usize>,
+}
+
+/// What a Ledger Nano displays to verify a transaction essence before signing it, see [`signing_preview()`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningPreview {
+    /// Whether the essence has to be blind signed, in which case only its hash is displayed.
+    pub blind_signing: bool,
+    /// The hash of the essence.
+    pub essence_hash: String,
+    /// The outputs of the essence. Empty with blind signing, as they aren't displayed.
+    pub outputs: Vec<OutputPreview>,
+    /// The lines displayed by the device, in order.
+    pub summary: Vec<String>,
+}
+
+/// Produce what a Ledger Nano with a buffer of `buffer_size` bytes, see [`LedgerNanoStatus::buffer_size()`], will
+/// display for `prepared_transaction`, so that a frontend can pre-render consistent verification screens.
+///
+/// The remainder is detected like the device does, from the BIP-32 path of the remainder address; addresses are
+/// encoded with `bech32_hrp`. No device is needed.
+pub fn signing_preview(
+    prepared_transaction: &PreparedTransactionData,
+    buffer_size: usize,
+    bech32_hrp: &str,
+) -> Result<SigningPreview> {
+    let essence_hash = prefix_hex::encode(prepared_transaction.essence.hash());
+
+    if needs_blind_signing(prepared_transaction, buffer_size) {
+        return Ok(SigningPreview {
+            blind_signing: true,
+            summary: vec![format!("Blind signing essence hash {essence_hash}")],
+            essence_hash,
+            outputs: Vec::new(),
+        });
+    }
+
+    let remainder = match &prepared_transaction.remainder {
+        Some(remainder) => Some((
+            remainder_output_index(&prepared_transaction.essence, &remainder.address)?,
+            remainder_bip32(remainder)?,
+        )),
+        None => None,
+    };
+
+    let TransactionEssence::Regular(essence) = &prepared_transaction.essence;
+
+    // The outputs follow the essence kind, the network id, the inputs, the inputs commitment and the outputs count.
+    let mut offset = std::mem::size_of::<u8>()
+        + std::mem::size_of::<u64>()
+        + std::mem::size_of::<u16>()
+        + essence.inputs().iter().map(Packable::packed_len).sum::<usize>()
+        + essence.inputs_commitment().packed_len()
+        + std::mem::size_of::<u16>();
+    let mut outputs = Vec::new();
+    let mut summary = Vec::new();
+
+    for (index, output) in essence.outputs().iter().enumerate() {
+        let index = index as u16;
+        let byte_range = offset..offset + output.packed_len();
+        offset = byte_range.end;
+
+        // Without blind signing, all outputs are basic outputs with only an address unlock condition.
+        let address = match output
+            .unlock_conditions()
+            .and_then(|unlock_conditions| unlock_conditions.address())
+        {
+            Some(address) => address.address().to_bech32(bech32_hrp),
+            None => return Err(crate::Error::LedgerMiscError),
+        };
+        let is_remainder = match &remainder {
+            Some((remainder_index, remainder_bip32)) if *remainder_index == index => {
+                summary.push(format!(
+                    "Remainder {} to {address} (change {}, index {})",
+                    output.amount(),
+                    remainder_bip32.bip32_change & !HARDENED,
+                    remainder_bip32.bip32_index & !HARDENED
+                ));
+                true
+            }
+            _ => {
+                summary.push(format!("Send {} to {address}", output.amount()));
+                false
+            }
+        };
+
+        outputs.push(OutputPreview {
+            index,
+            address,
+            amount: output.amount(),
+            remainder: is_remainder,
+            byte_range,
+        });
+    }
+
+    Ok(SigningPreview {
+        blind_signing: false,
+        essence_hash,
+        outputs,
+        summary,
+    })
+}
+
 #[async_trait]
 impl SecretManageExt for LedgerSecretManager {
     async fn sign_transaction_essence(&self, prepared_transaction: &PreparedTransactionData) -> crate::Result<Unlocks> {
@@ -201,63 +372,14 @@ impl SecretManageExt for LedgerSecretManager {
             // figure out the remainder address and bip32 index (if there is one)
             let (remainder_address, remainder_bip32): (Option<&Address>, LedgerBIP32Index) =
                 match &prepared_transaction.remainder {
-                    Some(a) => {
-                        let remainder_bip32_indices: Vec<u32> = match &a.chain {
-                            Some(chain) => {
-                                chain
-                                    .segments()
-                                    .iter()
-                                    // XXX: "ser32(i)". RTFSC: [crypto::keys::slip10::Segment::from_u32()]
-                                    .map(|seg| u32::from_be_bytes(seg.bs()))
-                                    .collect()
-                            }
-                            None => return Err(crate::Error::InvalidBIP32ChainData),
-                        };
-                        (
-                            Some(&a.address),
-                            LedgerBIP32Index {
-                                bip32_change: remainder_bip32_indices[3] | HARDENED,
-                                bip32_index: remainder_bip32_indices[4] | HARDENED,
-                            },
-                        )
-                    }
+                    Some(remainder) => (Some(&remainder.address), remainder_bip32(remainder)?),
                     None => (None, LedgerBIP32Index::default()),
                 };
 
-            let mut remainder_index = 0u16;
-            if let Some(remainder_address) = remainder_address {
-                match &prepared_transaction.essence {
-                    TransactionEssence::Regular(essence) => {
-                        // find the index of the remainder in the essence
-                        // this has to be done because outputs in essences are sorted
-                        // lexically and therefore the remainder is not always the last output.
-                        // The index within the essence and the bip32 index will be validated
-                        // by the hardware wallet.
-                        // The outputs in the essence already are sorted
-                        // at this place, so we can rely on their order and don't have to sort it again.
-                        'essence_outputs: for output in essence.outputs().iter() {
-                            if let Output::Basic(s) = output {
-                                if let Some(address) = s.unlock_conditions().address() {
-                                    if *remainder_address == *address.address() {
-                                        break 'essence_outputs;
-                                    }
-                                }
-                            } else {
-                                log::debug!("[LEDGER] unsupported output");
-                                return Err(crate::Error::LedgerMiscError);
-                            }
-
-                            remainder_index += 1;
-                        }
-
-                        // was index found?
-                        if remainder_index as usize == essence.outputs().len() {
-                            log::debug!("[LEDGER] remainder_index not found");
-                            return Err(crate::Error::LedgerMiscError);
-                        }
-                    }
-                }
-            }
+            let remainder_index = match remainder_address {
+                Some(remainder_address) => remainder_output_index(&prepared_transaction.essence, remainder_address)?,
+                None => 0,
+            };
 
             // prepare signing
             log::debug!("[LEDGER] prepare signing");
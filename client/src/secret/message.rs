@@ -0,0 +1,97 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signing and verification of arbitrary messages, proving the control of an address off-chain.
+//!
+//! The signed hash commits to [`SIGNED_MESSAGE_PREFIX`], so the signature of a message can never be used as the
+//! signature of a transaction essence.
+
+use crypto::hashes::{blake2b::Blake2b256, Digest};
+use iota_types::block::{address::Address, signature::Ed25519Signature};
+
+use super::{types::DerivationPath, SecretManage};
+use crate::Result;
+
+/// The prefix of the hash of signed messages.
+pub const SIGNED_MESSAGE_PREFIX: &[u8] = b"IOTA Signed Message:\n";
+
+/// The hash signed by [`sign_message()`], of [`SIGNED_MESSAGE_PREFIX`], the length of `message` as little-endian u64
+/// and `message`.
+pub fn message_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Blake2b256::new();
+    hasher.update(SIGNED_MESSAGE_PREFIX);
+    hasher.update((message.len() as u64).to_le_bytes());
+    hasher.update(message);
+
+    hasher.finalize().into()
+}
+
+/// Signs `message` with the key of an address, to prove the control of the address with [`verify_message()`].
+pub async fn sign_message(
+    secret_manager: &dyn SecretManage,
+    coin_type: u32,
+    account_index: u32,
+    internal: bool,
+    address_index: u32,
+    message: &[u8],
+) -> Result<Ed25519Signature> {
+    let chain = DerivationPath::default().chain(coin_type, account_index, internal, address_index);
+
+    secret_manager.sign_ed25519(&message_hash(message), &chain).await
+}
+
+/// Verifies that `signature` is a signature of `message` by the key of `bech32_address`.
+///
+/// Only fails if `bech32_address` can't be parsed, an invalid signature or a signature by another key gives `false`.
+pub fn verify_message(bech32_address: &str, message: &[u8], signature: &Ed25519Signature) -> Result<bool> {
+    let (_, address) = Address::try_from_bech32(bech32_address)?;
+
+    Ok(match address {
+        Address::Ed25519(address) => signature.is_valid(&message_hash(message), &address).is_ok(),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::IOTA_COIN_TYPE,
+        secret::{mnemonic::MnemonicSecretManager, placeholder::PlaceholderSecretManager},
+    };
+
+    #[tokio::test]
+    async fn sign_and_verify_message() {
+        let secret_manager = MnemonicSecretManager::try_from_hex_seed(
+            "0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2",
+        )
+        .unwrap();
+        let addresses = secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..2, false, None)
+            .await
+            .unwrap();
+        let (address, other_address) = (addresses[0].to_bech32("atoi"), addresses[1].to_bech32("atoi"));
+
+        let signature = sign_message(&secret_manager, IOTA_COIN_TYPE, 0, false, 0, b"I own this address")
+            .await
+            .unwrap();
+
+        assert!(verify_message(&address, b"I own this address", &signature).unwrap());
+        assert!(!verify_message(&address, b"I own another address", &signature).unwrap());
+        assert!(!verify_message(&other_address, b"I own this address", &signature).unwrap());
+        assert!(verify_message("atoi1invalid", b"I own this address", &signature).is_err());
+
+        assert!(matches!(
+            sign_message(
+                &PlaceholderSecretManager,
+                IOTA_COIN_TYPE,
+                0,
+                false,
+                0,
+                b"I own this address"
+            )
+            .await,
+            Err(crate::Error::PlaceholderSecretManager)
+        ));
+    }
+}
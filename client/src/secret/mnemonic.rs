@@ -3,7 +3,7 @@
 
 //! Implementation of [`MnemonicSecretManager`].
 
-use std::ops::Range;
+use std::{num::NonZeroUsize, ops::Range, sync::Mutex};
 
 use async_trait::async_trait;
 use crypto::{
@@ -15,14 +15,44 @@ use iota_types::block::{
     signature::{Ed25519Signature, Signature},
     unlock::{SignatureUnlock, Unlock},
 };
+use lru::LruCache;
+use zeroize::Zeroizing;
 
-use super::{types::InputSigningData, GenerateAddressOptions, SecretManage};
-use crate::{constants::HD_WALLET_TYPE, secret::RemainderData, Client, Result};
+#[cfg(feature = "secp256k1")]
+use super::{
+    bip85,
+    secp256k1::{derive_signing_key, evm_chain, EvmAddress, Secp256k1EcdsaSignature},
+};
+use super::{
+    shamir::{self, SecretShare},
+    tag::{tag_key_chain, TagKey, TAG_KEY_MESSAGE},
+    types::InputSigningData,
+    GenerateAddressOptions, SecretManage,
+};
+#[cfg(feature = "secp256k1")]
+use crate::utils::MnemonicLanguage;
+use crate::{secret::RemainderData, Error, Result};
+
+/// The supported lengths of raw seeds: 32 bytes of entropy, or the 64 bytes of a BIP-39 seed.
+const SEED_LENGTHS: [usize; 2] = [32, 64];
+
+/// The number of public keys cached by a [`MnemonicSecretManager`].
+const PUBLIC_KEY_CACHE_CAPACITY: usize = 1000;
 
 /// Secret manager that uses only a mnemonic.
 ///
 /// Computation are done in-memory. A mnemonic needs to be supplied upon the creation of [`MnemonicSecretManager`].
-pub struct MnemonicSecretManager(Seed);
+///
+/// The public keys of the most recently generated addresses are cached, by their hardened chain, so that repeated
+/// address generations, e.g. by input selection or [`search_address()`](crate::api::search_address), don't derive them
+/// again.
+pub struct MnemonicSecretManager {
+    seed: Seed,
+    public_keys: Mutex<LruCache<Vec<u32>, [u8; 32]>>,
+    /// The raw seed, secp256k1 keys being derived with BIP-32 instead of SLIP-10 and seeds being backed up as
+    /// shares.
+    raw_seed: Zeroizing<Vec<u8>>,
+}
 
 #[async_trait]
 impl SecretManage for MnemonicSecretManager {
@@ -32,25 +62,14 @@ impl SecretManage for MnemonicSecretManager {
         account_index: u32,
         address_indexes: Range<u32>,
         internal: bool,
-        _: Option<GenerateAddressOptions>,
+        options: Option<GenerateAddressOptions>,
     ) -> crate::Result<Vec<Address>> {
+        let derivation_path = GenerateAddressOptions::derivation_path(&options);
         let mut addresses = Vec::new();
 
         for address_index in address_indexes {
-            let chain = Chain::from_u32_hardened(vec![
-                HD_WALLET_TYPE,
-                coin_type,
-                account_index,
-                internal as u32,
-                address_index,
-            ]);
-
-            let public_key = self
-                .0
-                .derive(Curve::Ed25519, &chain)?
-                .secret_key()
-                .public_key()
-                .to_bytes();
+            let public_key =
+                self.public_key(&derivation_path.indexes(coin_type, account_index, internal, address_index))?;
 
             // Hash the public key to get the address
             let result = Blake2b256::digest(public_key).try_into().map_err(|_e| {
@@ -69,20 +88,22 @@ impl SecretManage for MnemonicSecretManager {
         essence_hash: &[u8; 32],
         _: &Option<RemainderData>,
     ) -> crate::Result<Unlock> {
-        // Get the private and public key for this Ed25519 address
-        let private_key = self
-            .0
-            .derive(Curve::Ed25519, &input.chain.clone().expect("no chain in ed25519 input"))?
-            .secret_key();
-        let public_key = private_key.public_key().to_bytes();
-
         // The signature unlock block needs to sign the hash of the entire transaction essence of the
         // transaction payload
-        let signature = private_key.sign(essence_hash).to_bytes();
+        let signature = self
+            .sign_ed25519(essence_hash, &input.chain.clone().expect("no chain in ed25519 input"))
+            .await?;
 
-        Ok(Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(
-            Ed25519Signature::new(public_key, signature),
-        ))))
+        Ok(Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(signature))))
+    }
+
+    async fn sign_ed25519(&self, message: &[u8], chain: &Chain) -> crate::Result<Ed25519Signature> {
+        // Get the private and public key for this Ed25519 address
+        let private_key = self.seed.derive(Curve::Ed25519, chain)?.secret_key();
+        let public_key = private_key.public_key().to_bytes();
+        let signature = private_key.sign(message).to_bytes();
+
+        Ok(Ed25519Signature::new(public_key, signature))
     }
 }
 
@@ -91,13 +112,178 @@ impl MnemonicSecretManager {
     ///
     /// For more information, see <https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki>.
     pub fn try_from_mnemonic(mnemonic: &str) -> Result<Self> {
-        Ok(Self(Client::mnemonic_to_seed(mnemonic)?))
+        Self::try_from_seed_bytes(&*crate::utils::mnemonic_to_seed_bytes(mnemonic)?)
     }
 
-    /// Create a new [`MnemonicSecretManager`] from a hex-encoded raw seed string.
+    /// Create a new [`MnemonicSecretManager`] from a hex-encoded raw seed string of 32 or 64 bytes, e.g. the entropy of
+    /// a legacy seed or the output of [`Client::mnemonic_to_hex_seed()`](crate::Client::mnemonic_to_hex_seed).
     pub fn try_from_hex_seed(hex: &str) -> Result<Self> {
-        let bytes: Vec<u8> = prefix_hex::decode(hex)?;
-        Ok(Self(Seed::from_bytes(&bytes)))
+        let bytes: Zeroizing<Vec<u8>> = Zeroizing::new(prefix_hex::decode(hex.trim())?);
+
+        Self::try_from_seed_bytes(&bytes)
+    }
+
+    /// Create a new [`MnemonicSecretManager`] from a raw seed of 32 or 64 bytes.
+    pub fn try_from_seed_bytes(bytes: &[u8]) -> Result<Self> {
+        if !SEED_LENGTHS.contains(&bytes.len()) {
+            return Err(Error::InvalidSeedLength(bytes.len()));
+        }
+
+        Ok(Self {
+            seed: Seed::from_bytes(bytes),
+            public_keys: Mutex::new(LruCache::new(
+                NonZeroUsize::new(PUBLIC_KEY_CACHE_CAPACITY).expect("the capacity isn't zero"),
+            )),
+            raw_seed: Zeroizing::new(bytes.to_vec()),
+        })
+    }
+
+    /// Create a new [`MnemonicSecretManager`] from the shares of a raw seed, see [`split_seed()`](Self::split_seed).
+    pub fn try_from_shares(shares: &[SecretShare]) -> Result<Self> {
+        Self::try_from_seed_bytes(&shamir::combine_shares(shares)?)
+    }
+
+    /// Splits the raw seed into `share_count` shares, of which any `threshold` restore the secret manager with
+    /// [`try_from_shares()`](Self::try_from_shares), see [`shamir`](super::shamir).
+    pub fn split_seed(&self, threshold: u8, share_count: u8) -> Result<Vec<SecretShare>> {
+        shamir::split_secret(&self.raw_seed, threshold, share_count)
+    }
+
+    /// Clear the cache of public keys.
+    pub fn clear_public_key_cache(&self) -> Result<()> {
+        self.public_keys.lock().map_err(|_| Error::PoisonError)?.clear();
+
+        Ok(())
+    }
+
+    /// The public key of the hardened chain of `indexes`, from the cache if it has already been derived.
+    fn public_key(&self, indexes: &[u32]) -> Result<[u8; 32]> {
+        if let Some(public_key) = self.public_keys.lock().map_err(|_| Error::PoisonError)?.get(indexes) {
+            return Ok(*public_key);
+        }
+
+        let public_key = self
+            .seed
+            .derive(Curve::Ed25519, &Chain::from_u32_hardened(indexes.to_vec()))?
+            .secret_key()
+            .public_key()
+            .to_bytes();
+
+        self.public_keys
+            .lock()
+            .map_err(|_| Error::PoisonError)?
+            .put(indexes.to_vec(), public_key);
+
+        Ok(public_key)
+    }
+
+    /// Derive the [`TagKey`] of an account.
+    pub fn derive_tag_key(&self, coin_type: u32, account_index: u32) -> Result<TagKey> {
+        let chain = Chain::from_u32_hardened(tag_key_chain(coin_type, account_index));
+        let signature = self
+            .seed
+            .derive(Curve::Ed25519, &chain)?
+            .secret_key()
+            .sign(TAG_KEY_MESSAGE)
+            .to_bytes();
+
+        Ok(TagKey::from_signature(&signature))
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl MnemonicSecretManager {
+    /// Generates the EVM addresses of secp256k1 keys derived from the seed, see [`evm_chain()`].
+    pub fn generate_evm_addresses(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        address_indexes: Range<u32>,
+        internal: bool,
+    ) -> Result<Vec<EvmAddress>> {
+        address_indexes
+            .map(|address_index| {
+                let chain = evm_chain(coin_type, account_index, internal, address_index);
+                let signing_key = derive_signing_key(&self.raw_seed, &chain)?;
+
+                Ok(EvmAddress::from_public_key(signing_key.verifying_key()))
+            })
+            .collect()
+    }
+
+    /// Derives the BIP-85 child mnemonic `index` of `word_count` words, 12, 18 or 24, of the wordlist of `language`,
+    /// e.g. to bootstrap a secondary wallet, see [`bip85`](super::bip85).
+    pub fn derive_bip85_mnemonic(
+        &self,
+        language: MnemonicLanguage,
+        word_count: usize,
+        index: u32,
+    ) -> Result<Zeroizing<String>> {
+        bip85::derive_mnemonic(&self.raw_seed, language, word_count, index)
+    }
+
+    /// Derives the BIP-85 raw entropy `index` of `length` bytes, from 16 to 64, see [`bip85`](super::bip85).
+    pub fn derive_bip85_entropy(&self, length: usize, index: u32) -> Result<Zeroizing<Vec<u8>>> {
+        bip85::derive_hex(&self.raw_seed, length, index)
+    }
+
+    /// Signs the Keccak-256 hash of `message` with the secp256k1 key of an EVM address, see [`evm_chain()`].
+    pub fn sign_secp256k1_ecdsa(
+        &self,
+        message: &[u8],
+        coin_type: u32,
+        account_index: u32,
+        internal: bool,
+        address_index: u32,
+    ) -> Result<Secp256k1EcdsaSignature> {
+        let chain = evm_chain(coin_type, account_index, internal, address_index);
+        let signing_key = derive_signing_key(&self.raw_seed, &chain)?;
+
+        Secp256k1EcdsaSignature::sign_keccak256(&signing_key, message)
+    }
+}
+
+/// An acknowledgement that an exported secret leaves the protection of the secret manager, and gives full control over
+/// the funds to whoever gets hold of it.
+#[cfg(feature = "dangerous-exports")]
+pub struct ExportAcknowledgement(());
+
+#[cfg(feature = "dangerous-exports")]
+impl ExportAcknowledgement {
+    /// The phrase acknowledging the export of a secret.
+    pub const PHRASE: &'static str = "I understand that the exported secret gives full control over the funds";
+
+    /// Acknowledge the export of a secret with [`Self::PHRASE`], e.g. typed by the user.
+    pub fn new(phrase: &str) -> Result<Self> {
+        if phrase == Self::PHRASE {
+            Ok(Self(()))
+        } else {
+            Err(Error::ExportNotAcknowledged)
+        }
+    }
+}
+
+#[cfg(feature = "dangerous-exports")]
+impl MnemonicSecretManager {
+    /// Exports the Ed25519 private key of an address, e.g. to migrate to other software.
+    pub fn export_private_key(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        internal: bool,
+        address_index: u32,
+        _acknowledgement: ExportAcknowledgement,
+    ) -> Result<Zeroizing<[u8; 32]>> {
+        let chain = super::DerivationPath::default().chain(coin_type, account_index, internal, address_index);
+
+        Ok(Zeroizing::new(
+            self.seed.derive(Curve::Ed25519, &chain)?.secret_key().to_bytes(),
+        ))
+    }
+
+    /// Exports the raw seed, e.g. to migrate to other software.
+    pub fn export_seed(&self, _acknowledgement: ExportAcknowledgement) -> Zeroizing<Vec<u8>> {
+        self.raw_seed.clone()
     }
 }
 
@@ -123,6 +309,165 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn public_key_cache() {
+        use crate::constants::IOTA_COIN_TYPE;
+
+        let secret_manager = MnemonicSecretManager::try_from_hex_seed(
+            "0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2",
+        )
+        .unwrap();
+
+        let addresses = secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..5, false, None)
+            .await
+            .unwrap();
+        assert_eq!(secret_manager.public_keys.lock().unwrap().len(), 5);
+
+        // Cached public keys give the same addresses
+        let cached_addresses = secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..5, false, None)
+            .await
+            .unwrap();
+        assert_eq!(addresses, cached_addresses);
+        assert_eq!(secret_manager.public_keys.lock().unwrap().len(), 5);
+
+        secret_manager.clear_public_key_cache().unwrap();
+        assert!(secret_manager.public_keys.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn seed_shares() {
+        use crate::constants::IOTA_COIN_TYPE;
+
+        let secret_manager = MnemonicSecretManager::try_from_hex_seed(
+            "0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2",
+        )
+        .unwrap();
+        let shares = secret_manager.split_seed(2, 3).unwrap();
+
+        let restored_secret_manager = MnemonicSecretManager::try_from_shares(&shares[1..]).unwrap();
+
+        assert_eq!(
+            restored_secret_manager
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+                .await
+                .unwrap(),
+            secret_manager
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+                .await
+                .unwrap()
+        );
+        assert!(MnemonicSecretManager::try_from_shares(&shares[..1]).is_err());
+    }
+
+    #[test]
+    fn seed_length() {
+        assert!(MnemonicSecretManager::try_from_seed_bytes(&[1; 32]).is_ok());
+        assert!(MnemonicSecretManager::try_from_seed_bytes(&[1; 64]).is_ok());
+        assert!(matches!(
+            MnemonicSecretManager::try_from_hex_seed("0x0102"),
+            Err(Error::InvalidSeedLength(2))
+        ));
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn evm_address() {
+        // The first account of the development networks of Hardhat and Foundry
+        let secret_manager =
+            MnemonicSecretManager::try_from_mnemonic("test test test test test test test test test test test junk")
+                .unwrap();
+
+        let addresses = secret_manager.generate_evm_addresses(60, 0, 0..1, false).unwrap();
+        assert_eq!(addresses[0].to_string(), "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+        let signature = secret_manager
+            .sign_secp256k1_ecdsa(b"message", 60, 0, false, 0)
+            .unwrap();
+        assert_eq!(signature.evm_address().unwrap(), addresses[0]);
+    }
+
+    #[cfg(feature = "dangerous-exports")]
+    #[tokio::test]
+    async fn export() {
+        use crypto::signatures::ed25519::SecretKey;
+
+        use crate::constants::IOTA_COIN_TYPE;
+
+        let seed = "0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2";
+        let secret_manager = MnemonicSecretManager::try_from_hex_seed(seed).unwrap();
+
+        assert!(matches!(
+            ExportAcknowledgement::new("yes"),
+            Err(Error::ExportNotAcknowledged)
+        ));
+
+        let acknowledgement = || ExportAcknowledgement::new(ExportAcknowledgement::PHRASE).unwrap();
+        let private_key = secret_manager
+            .export_private_key(IOTA_COIN_TYPE, 0, false, 0, acknowledgement())
+            .unwrap();
+        let public_key = SecretKey::from_bytes(*private_key).public_key().to_bytes();
+        let addresses = secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            addresses[0],
+            Address::Ed25519(Ed25519Address::from_public_key_bytes(public_key))
+        );
+        assert_eq!(
+            prefix_hex::encode(secret_manager.export_seed(acknowledgement()).as_slice()),
+            seed
+        );
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn bip85() {
+        let mnemonic = "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally";
+        let secret_manager = MnemonicSecretManager::try_from_mnemonic(mnemonic).unwrap();
+
+        let child_mnemonic = secret_manager
+            .derive_bip85_mnemonic(MnemonicLanguage::English, 12, 0)
+            .unwrap();
+        assert_eq!(child_mnemonic.split_whitespace().count(), 12);
+        crate::utils::verify_mnemonic(&child_mnemonic, MnemonicLanguage::English).unwrap();
+
+        // Deterministic, and different for other indexes and word counts
+        assert_eq!(
+            secret_manager
+                .derive_bip85_mnemonic(MnemonicLanguage::English, 12, 0)
+                .unwrap(),
+            child_mnemonic
+        );
+        assert_ne!(
+            secret_manager
+                .derive_bip85_mnemonic(MnemonicLanguage::English, 12, 1)
+                .unwrap(),
+            child_mnemonic
+        );
+        assert_eq!(
+            secret_manager
+                .derive_bip85_mnemonic(MnemonicLanguage::English, 24, 0)
+                .unwrap()
+                .split_whitespace()
+                .count(),
+            24
+        );
+        assert!(matches!(
+            secret_manager.derive_bip85_mnemonic(MnemonicLanguage::English, 13, 0),
+            Err(Error::InvalidMnemonic(_))
+        ));
+
+        assert_eq!(secret_manager.derive_bip85_entropy(64, 0).unwrap().len(), 64);
+        assert!(matches!(
+            secret_manager.derive_bip85_entropy(8, 0),
+            Err(Error::InvalidBIP85EntropyLength(8))
+        ));
+    }
+
     #[tokio::test]
     async fn seed_address() {
         use crate::constants::IOTA_COIN_TYPE;
@@ -0,0 +1,226 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of [`MnemonicFileSecretManager`].
+//!
+//! The mnemonic is stored in a single file, encrypted with XChaCha20-Poly1305 and a key derived from a password with
+//! PBKDF2-HMAC-SHA512, as a lightweight alternative to Stronghold snapshots, e.g. in minimal containers. The file
+//! holds [`MNEMONIC_FILE_MAGIC`], the format version, the salt and number of iterations of PBKDF2 and the encrypted
+//! mnemonic.
+//!
+//! Once opened, the seed is held in memory like by a [`MnemonicSecretManager`].
+
+use std::{
+    fs,
+    num::NonZeroU32,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use crypto::{ciphers::chacha, keys::slip10::Chain, utils::rand};
+use iota_types::block::{address::Address, signature::Ed25519Signature, unlock::Unlock};
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{
+    mnemonic::MnemonicSecretManager, tag::TagKey, types::InputSigningData, GenerateAddressOptions, SecretManage,
+};
+use crate::{secret::RemainderData, Error, Result};
+
+/// The first bytes of a mnemonic file.
+pub const MNEMONIC_FILE_MAGIC: &[u8; 8] = b"IOTAMNEM";
+/// The version of the format of the mnemonic files written.
+const MNEMONIC_FILE_VERSION: u8 = 1;
+/// The length of the PBKDF2 salt.
+const SALT_LENGTH: usize = 16;
+/// The number of PBKDF2 iterations of new mnemonic files.
+const PBKDF_ITERATIONS: u32 = 100_000;
+/// The length of the header, up to the encrypted mnemonic.
+const HEADER_LENGTH: usize = MNEMONIC_FILE_MAGIC.len() + 1 + SALT_LENGTH + 4;
+
+/// Secret manager that uses a mnemonic stored in a password-encrypted file.
+pub struct MnemonicFileSecretManager {
+    path: PathBuf,
+    secret_manager: MnemonicSecretManager,
+}
+
+impl MnemonicFileSecretManager {
+    /// Store `mnemonic` in a new file at `path`, encrypted with `password`.
+    ///
+    /// Fails with [`Error::MnemonicFileAlreadyExists`] if there is already a file at `path`, rather than losing the
+    /// mnemonic stored in it.
+    pub fn create<P: AsRef<Path>>(path: P, password: &str, mnemonic: &str) -> Result<Self> {
+        let path = path.as_ref();
+
+        if path.exists() {
+            return Err(Error::MnemonicFileAlreadyExists(path.display().to_string()));
+        }
+
+        let mnemonic = mnemonic.trim();
+        let secret_manager = MnemonicSecretManager::try_from_mnemonic(mnemonic)?;
+
+        let mut salt = [0u8; SALT_LENGTH];
+        rand::fill(&mut salt)?;
+        // PANIC: the number of iterations is not zero.
+        let iterations = NonZeroU32::new(PBKDF_ITERATIONS).unwrap();
+        let key = derive_key(password, &salt, iterations);
+
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH);
+        bytes.extend_from_slice(MNEMONIC_FILE_MAGIC);
+        bytes.push(MNEMONIC_FILE_VERSION);
+        bytes.extend_from_slice(&salt);
+        bytes.extend_from_slice(&iterations.get().to_le_bytes());
+        bytes.extend(chacha::aead_encrypt(key.as_slice(), mnemonic.as_bytes())?);
+
+        // Written to a temporary file first, so that an interrupted write doesn't leave a truncated file.
+        let temporary_path = path.with_extension("tmp");
+        fs::write(&temporary_path, bytes)?;
+        fs::rename(&temporary_path, path)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            secret_manager,
+        })
+    }
+
+    /// Open the mnemonic file at `path`, encrypted with `password`.
+    pub fn open<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+
+        if bytes.len() < HEADER_LENGTH || !bytes.starts_with(MNEMONIC_FILE_MAGIC) {
+            return Err(Error::MnemonicFileInvalid("not a mnemonic file"));
+        }
+
+        let (header, ciphertext) = bytes.split_at(HEADER_LENGTH);
+        let version = header[MNEMONIC_FILE_MAGIC.len()];
+
+        if version != MNEMONIC_FILE_VERSION {
+            return Err(Error::MnemonicFileInvalid("unsupported version"));
+        }
+
+        let (salt, iterations) = header[MNEMONIC_FILE_MAGIC.len() + 1..].split_at(SALT_LENGTH);
+        // PANIC: the iterations are the last 4 bytes of the header.
+        let iterations = NonZeroU32::new(u32::from_le_bytes(iterations.try_into().unwrap()))
+            .ok_or(Error::MnemonicFileInvalid("zero PBKDF2 iterations"))?;
+        let key = derive_key(password, salt, iterations);
+
+        // The authentication of the ciphertext fails for any other key
+        let mnemonic = Zeroizing::new(
+            chacha::aead_decrypt(key.as_slice(), ciphertext).map_err(|_| Error::MnemonicFilePasswordInvalid)?,
+        );
+        let mnemonic = std::str::from_utf8(&mnemonic).map_err(|_| Error::MnemonicFileInvalid("invalid mnemonic"))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            secret_manager: MnemonicSecretManager::try_from_mnemonic(mnemonic)?,
+        })
+    }
+
+    /// The path of the mnemonic file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Derive the [`TagKey`] of an account.
+    pub fn derive_tag_key(&self, coin_type: u32, account_index: u32) -> Result<TagKey> {
+        self.secret_manager.derive_tag_key(coin_type, account_index)
+    }
+}
+
+/// Derive the encryption key of a mnemonic file from a password.
+fn derive_key(password: &str, salt: &[u8], iterations: NonZeroU32) -> Zeroizing<[u8; 32]> {
+    let mut buffer = [0u8; 64];
+
+    // Safe to unwrap because rounds > 0.
+    crypto::keys::pbkdf::PBKDF2_HMAC_SHA512(password.as_bytes(), salt, iterations.get() as usize, buffer.as_mut())
+        .unwrap();
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&buffer[..32]);
+
+    buffer.zeroize();
+
+    key
+}
+
+#[async_trait]
+impl SecretManage for MnemonicFileSecretManager {
+    async fn generate_addresses(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        address_indexes: Range<u32>,
+        internal: bool,
+        options: Option<GenerateAddressOptions>,
+    ) -> crate::Result<Vec<Address>> {
+        self.secret_manager
+            .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+            .await
+    }
+
+    async fn signature_unlock(
+        &self,
+        input: &InputSigningData,
+        essence_hash: &[u8; 32],
+        remainder: &Option<RemainderData>,
+    ) -> crate::Result<Unlock> {
+        self.secret_manager
+            .signature_unlock(input, essence_hash, remainder)
+            .await
+    }
+
+    async fn sign_ed25519(&self, message: &[u8], chain: &Chain) -> crate::Result<Ed25519Signature> {
+        self.secret_manager.sign_ed25519(message, chain).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::IOTA_COIN_TYPE;
+
+    const MNEMONIC: &str = "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally";
+
+    #[tokio::test]
+    async fn mnemonic_file() {
+        let path = "test_mnemonic_file.mnemonic";
+        std::fs::remove_file(path).ok();
+
+        let secret_manager = MnemonicFileSecretManager::create(path, "password", MNEMONIC).unwrap();
+        let addresses = secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            addresses[0].to_bech32("atoi"),
+            "atoi1qpszqzadsym6wpppd6z037dvlejmjuke7s24hm95s9fg9vpua7vluehe53e".to_string()
+        );
+        assert!(matches!(
+            MnemonicFileSecretManager::create(path, "password", MNEMONIC),
+            Err(Error::MnemonicFileAlreadyExists(_))
+        ));
+
+        let secret_manager = MnemonicFileSecretManager::open(path, "password").unwrap();
+        assert_eq!(
+            secret_manager
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+                .await
+                .unwrap(),
+            addresses
+        );
+        assert!(matches!(
+            MnemonicFileSecretManager::open(path, "wrong password"),
+            Err(Error::MnemonicFilePasswordInvalid)
+        ));
+
+        std::fs::write(path, b"not a mnemonic file").unwrap();
+        assert!(matches!(
+            MnemonicFileSecretManager::open(path, "password"),
+            Err(Error::MnemonicFileInvalid(_))
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -3,41 +3,100 @@
 
 //! Secret manager module enabling address generation and transaction essence signing.
 
+/// Module for BIP-85 deterministic entropy derived from a seed
+#[cfg(feature = "secp256k1")]
+pub mod bip85;
+/// Module for signing with a key held by Google Cloud KMS
+#[cfg(feature = "cloud_kms")]
+pub mod cloud_kms;
+/// Module for signing a transaction with several signers
+pub mod coordinator;
+/// Module for signing with a backend supplied by the application
+pub mod external;
 #[cfg(feature = "ledger_nano")]
 pub mod ledger_nano;
+/// Module for signing and verifying messages
+pub mod message;
 /// Module for signing with a mnemonic or seed
 pub mod mnemonic;
+/// Module for signing with a mnemonic stored in a password-encrypted file
+#[cfg(not(target_family = "wasm"))]
+pub mod mnemonic_file;
+/// Module for signing with a key held by a PKCS#11 token
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
 /// Module for the PlaceholderSecretManager
 pub mod placeholder;
+/// Module for signing with a remote signing service
+#[cfg(feature = "remote_signer")]
+pub mod remote_signer;
+/// Module for secp256k1 ECDSA keys derived from a seed
+#[cfg(feature = "secp256k1")]
+pub mod secp256k1;
+/// Module for the backup of seeds as shares
+pub mod shamir;
 /// Module for signing with a Stronghold vault
 #[cfg(feature = "stronghold")]
 pub mod stronghold;
+/// Module for tags derived from the seed
+pub mod tag;
 /// Signing related types
 pub mod types;
+/// Module for the WatchOnlySecretManager
+pub mod watch_only;
+/// Module for signing with a key resident on a YubiKey
+#[cfg(feature = "yubikey")]
+pub mod yubikey;
 
 #[cfg(feature = "stronghold")]
 use std::time::Duration;
 use std::{collections::HashMap, ops::Range, str::FromStr};
 
 use async_trait::async_trait;
+use crypto::keys::slip10::Chain;
 use iota_types::block::{
-    address::Address,
+    address::{Address, Ed25519Address},
     output::Output,
+    signature::Ed25519Signature,
     unlock::{AliasUnlock, NftUnlock, ReferenceUnlock, Unlock, Unlocks},
 };
-pub use types::{GenerateAddressOptions, LedgerNanoStatus};
+pub use types::{DerivationPath, DerivationPathSegment, GenerateAddressOptions, LedgerNanoStatus};
 use zeroize::ZeroizeOnDrop;
 
+#[cfg(feature = "cloud_kms")]
+use self::cloud_kms::CloudKmsSecretManager;
 #[cfg(feature = "ledger_nano")]
 use self::ledger_nano::LedgerSecretManager;
+#[cfg(not(target_family = "wasm"))]
+use self::mnemonic_file::MnemonicFileSecretManager;
+#[cfg(feature = "pkcs11")]
+use self::pkcs11::Pkcs11SecretManager;
+#[cfg(feature = "remote_signer")]
+use self::remote_signer::RemoteSignerSecretManager;
 #[cfg(feature = "stronghold")]
 use self::stronghold::StrongholdSecretManager;
-use self::{mnemonic::MnemonicSecretManager, placeholder::PlaceholderSecretManager};
+#[cfg(feature = "yubikey")]
+use self::yubikey::YubikeySecretManager;
+use self::{
+    external::ExternalSigner, mnemonic::MnemonicSecretManager, placeholder::PlaceholderSecretManager, tag::TagKey,
+    watch_only::WatchOnlySecretManager,
+};
+#[cfg(feature = "cloud_kms")]
+use crate::secret::types::CloudKmsDto;
+#[cfg(not(target_family = "wasm"))]
+use crate::secret::types::MnemonicFileDto;
+#[cfg(feature = "pkcs11")]
+use crate::secret::types::Pkcs11Dto;
+#[cfg(feature = "remote_signer")]
+use crate::secret::types::RemoteSignerDto;
 #[cfg(feature = "stronghold")]
 use crate::secret::types::StrongholdDto;
+#[cfg(feature = "yubikey")]
+use crate::secret::types::YubikeyDto;
 use crate::{
     api::{PreparedTransactionData, RemainderData},
-    secret::types::InputSigningData,
+    client::BackgroundComponent,
+    secret::types::{InputSigningData, WatchOnlyDto},
 };
 
 /// The secret manager interface.
@@ -62,6 +121,14 @@ pub trait SecretManage: Send + Sync {
         essence_hash: &[u8; 32],
         remainder: &Option<RemainderData>,
     ) -> crate::Result<Unlock>;
+
+    /// Signs `message` with the Ed25519 key of `chain`.
+    ///
+    /// Secret managers that can only sign transactions, like Ledger Nano, keep the default implementation, which
+    /// fails with [`Error::MessageSigningUnsupported`](crate::Error::MessageSigningUnsupported).
+    async fn sign_ed25519(&self, _message: &[u8], _chain: &Chain) -> crate::Result<Ed25519Signature> {
+        Err(crate::Error::MessageSigningUnsupported)
+    }
 }
 
 /// An extension to [`SecretManager`].
@@ -98,13 +165,45 @@ pub enum SecretManager {
     #[cfg_attr(docsrs, doc(cfg(feature = "ledger_nano")))]
     LedgerNano(LedgerSecretManager),
 
+    /// Secret manager that signs with a key held by a PKCS#11 token, e.g. an HSM.
+    #[cfg(feature = "pkcs11")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pkcs11")))]
+    Pkcs11(Pkcs11SecretManager),
+
+    /// Secret manager that signs with a key held by Google Cloud KMS.
+    #[cfg(feature = "cloud_kms")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cloud_kms")))]
+    CloudKms(CloudKmsSecretManager),
+
+    /// Secret manager that signs with a key resident on a YubiKey.
+    #[cfg(feature = "yubikey")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yubikey")))]
+    Yubikey(YubikeySecretManager),
+
+    /// Secret manager that forwards the signatures to a remote signing service.
+    #[cfg(feature = "remote_signer")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "remote_signer")))]
+    RemoteSigner(RemoteSignerSecretManager),
+
+    /// Secret manager that delegates the signatures to the application.
+    External(ExternalSigner),
+
     /// Secret manager that uses a mnemonic in plain memory. It's not recommended for production use. Use
     /// LedgerNano or Stronghold instead.
     Mnemonic(MnemonicSecretManager),
 
+    /// Secret manager that uses a mnemonic stored in a password-encrypted file, a lightweight alternative to
+    /// Stronghold.
+    #[cfg(not(target_family = "wasm"))]
+    MnemonicFile(MnemonicFileSecretManager),
+
     /// Secret manager that's just a placeholder, so it can be provided to an online wallet, but can't be used for
     /// signing.
     Placeholder(PlaceholderSecretManager),
+
+    /// Secret manager that only knows the addresses of an account, so it can be used by watch-only wallets, but can't
+    /// be used for signing.
+    WatchOnly(WatchOnlySecretManager),
 }
 
 impl std::fmt::Debug for SecretManager {
@@ -114,8 +213,23 @@ impl std::fmt::Debug for SecretManager {
             Self::Stronghold(_) => f.debug_tuple("Stronghold").field(&"...").finish(),
             #[cfg(feature = "ledger_nano")]
             Self::LedgerNano(_) => f.debug_tuple("LedgerNano").field(&"...").finish(),
+            #[cfg(feature = "pkcs11")]
+            Self::Pkcs11(_) => f.debug_tuple("Pkcs11").field(&"...").finish(),
+            #[cfg(feature = "cloud_kms")]
+            Self::CloudKms(_) => f.debug_tuple("CloudKms").field(&"...").finish(),
+            #[cfg(feature = "yubikey")]
+            Self::Yubikey(_) => f.debug_tuple("Yubikey").field(&"...").finish(),
+            #[cfg(feature = "remote_signer")]
+            Self::RemoteSigner(secret_manager) => f
+                .debug_tuple("RemoteSigner")
+                .field(&secret_manager.endpoint().as_str())
+                .finish(),
+            Self::External(_) => f.debug_tuple("External").field(&"...").finish(),
             Self::Mnemonic(_) => f.debug_tuple("Mnemonic").field(&"...").finish(),
+            #[cfg(not(target_family = "wasm"))]
+            Self::MnemonicFile(secret_manager) => f.debug_tuple("MnemonicFile").field(&secret_manager.path()).finish(),
             Self::Placeholder(_) => f.debug_struct("Placeholder").finish(),
+            Self::WatchOnly(secret_manager) => f.debug_tuple("WatchOnly").field(secret_manager).finish(),
         }
     }
 }
@@ -140,15 +254,38 @@ pub enum SecretManagerDto {
     #[cfg(feature = "ledger_nano")]
     #[serde(alias = "ledgerNano")]
     LedgerNano(bool),
+    /// PKCS#11 token
+    #[cfg(feature = "pkcs11")]
+    #[serde(alias = "pkcs11")]
+    Pkcs11(Pkcs11Dto),
+    /// Google Cloud KMS key
+    #[cfg(feature = "cloud_kms")]
+    #[serde(alias = "cloudKms")]
+    CloudKms(CloudKmsDto),
+    /// YubiKey
+    #[cfg(feature = "yubikey")]
+    #[serde(alias = "yubikey")]
+    Yubikey(YubikeyDto),
+    /// Remote signing service
+    #[cfg(feature = "remote_signer")]
+    #[serde(alias = "remoteSigner")]
+    RemoteSigner(RemoteSignerDto),
     /// Mnemonic
     #[serde(alias = "mnemonic")]
     Mnemonic(String),
+    /// Mnemonic file
+    #[cfg(not(target_family = "wasm"))]
+    #[serde(alias = "mnemonicFile")]
+    MnemonicFile(MnemonicFileDto),
     /// Hex seed
     #[serde(alias = "hexSeed")]
     HexSeed(String),
     /// Placeholder
     #[serde(alias = "placeholder")]
     Placeholder,
+    /// Watch-only
+    #[serde(alias = "watchOnly")]
+    WatchOnly(WatchOnlyDto),
 }
 
 impl TryFrom<&SecretManagerDto> for SecretManager {
@@ -173,11 +310,69 @@ impl TryFrom<&SecretManagerDto> for SecretManager {
             #[cfg(feature = "ledger_nano")]
             SecretManagerDto::LedgerNano(is_simulator) => Self::LedgerNano(LedgerSecretManager::new(*is_simulator)),
 
+            #[cfg(feature = "pkcs11")]
+            SecretManagerDto::Pkcs11(pkcs11_dto) => Self::Pkcs11(Pkcs11SecretManager::new(
+                &pkcs11_dto.module_path,
+                pkcs11_dto.slot_id,
+                &pkcs11_dto.pin,
+                &pkcs11_dto.key_label,
+            )?),
+
+            #[cfg(feature = "cloud_kms")]
+            SecretManagerDto::CloudKms(cloud_kms_dto) => Self::CloudKms(CloudKmsSecretManager::new(
+                &cloud_kms_dto.key_version_name,
+                cloud_kms_dto.access_token.clone(),
+            )?),
+
+            #[cfg(feature = "yubikey")]
+            SecretManagerDto::Yubikey(yubikey_dto) => Self::Yubikey(YubikeySecretManager::with_reader(
+                yubikey_dto.reader.as_deref(),
+                &yubikey_dto.pin,
+            )?),
+
+            #[cfg(feature = "remote_signer")]
+            SecretManagerDto::RemoteSigner(remote_signer_dto) => {
+                Self::RemoteSigner(RemoteSignerSecretManager::with_tls(
+                    &remote_signer_dto.endpoint,
+                    remote_signer_dto.auth_token.clone(),
+                    remote_signer_dto.ca_certificate.as_ref().map(String::as_bytes),
+                    remote_signer_dto.client_identity.as_ref().map(String::as_bytes),
+                )?)
+            }
+
             SecretManagerDto::Mnemonic(mnemonic) => Self::Mnemonic(MnemonicSecretManager::try_from_mnemonic(mnemonic)?),
 
+            #[cfg(not(target_family = "wasm"))]
+            SecretManagerDto::MnemonicFile(mnemonic_file_dto) => {
+                Self::MnemonicFile(match &mnemonic_file_dto.mnemonic {
+                    Some(mnemonic) => MnemonicFileSecretManager::create(
+                        &mnemonic_file_dto.path,
+                        &mnemonic_file_dto.password,
+                        mnemonic,
+                    )?,
+                    None => MnemonicFileSecretManager::open(&mnemonic_file_dto.path, &mnemonic_file_dto.password)?,
+                })
+            }
+
             SecretManagerDto::HexSeed(hex_seed) => Self::Mnemonic(MnemonicSecretManager::try_from_hex_seed(hex_seed)?),
 
             SecretManagerDto::Placeholder => Self::Placeholder(PlaceholderSecretManager),
+
+            SecretManagerDto::WatchOnly(watch_only_dto) => {
+                let parse_addresses = |addresses: &[String]| {
+                    addresses
+                        .iter()
+                        .map(|address| address.parse::<Ed25519Address>())
+                        .collect::<Result<Vec<_>, _>>()
+                };
+
+                Self::WatchOnly(WatchOnlySecretManager::new(
+                    watch_only_dto.coin_type,
+                    watch_only_dto.account_index,
+                    parse_addresses(&watch_only_dto.public_addresses)?,
+                    parse_addresses(&watch_only_dto.internal_addresses)?,
+                ))
+            }
         })
     }
 }
@@ -200,11 +395,67 @@ impl From<&SecretManager> for SecretManagerDto {
             #[cfg(feature = "ledger_nano")]
             SecretManager::LedgerNano(ledger_nano) => Self::LedgerNano(ledger_nano.is_simulator),
 
+            // The PIN isn't kept, and neither is the rest of the configuration since only the type is needed
+            #[cfg(feature = "pkcs11")]
+            SecretManager::Pkcs11(_) => Self::Pkcs11(Pkcs11Dto {
+                module_path: "...".to_string(),
+                slot_id: 0,
+                pin: "...".to_string(),
+                key_label: "...".to_string(),
+            }),
+
+            // The access token isn't kept, it expires anyway
+            #[cfg(feature = "cloud_kms")]
+            SecretManager::CloudKms(cloud_kms) => Self::CloudKms(CloudKmsDto {
+                key_version_name: cloud_kms.key_version_name().to_string(),
+                access_token: "...".to_string(),
+            }),
+
+            // The PIN isn't kept, and neither is the reader since only the type is needed
+            #[cfg(feature = "yubikey")]
+            SecretManager::Yubikey(_) => Self::Yubikey(YubikeyDto {
+                reader: None,
+                pin: "...".to_string(),
+            }),
+
+            // Neither the auth token nor the TLS configuration are kept, only the endpoint
+            #[cfg(feature = "remote_signer")]
+            SecretManager::RemoteSigner(remote_signer) => Self::RemoteSigner(RemoteSignerDto {
+                endpoint: remote_signer.endpoint().to_string(),
+                auth_token: None,
+                ca_certificate: None,
+                client_identity: None,
+            }),
+
+            // The signing backend of an external signer can't be described, so it's only known to be unable to sign
+            SecretManager::External(_) => Self::Placeholder,
+
             // `MnemonicSecretManager(Seed)` doesn't have Debug or Display implemented and in the current use cases of
             // the client/wallet we also don't need to convert it in this direction with the mnemonic/seed, we only need
             // to know the type
             SecretManager::Mnemonic(_mnemonic) => Self::Mnemonic("...".to_string()),
+
+            // Neither the password nor the mnemonic are kept, only the path
+            #[cfg(not(target_family = "wasm"))]
+            SecretManager::MnemonicFile(mnemonic_file) => Self::MnemonicFile(MnemonicFileDto {
+                path: mnemonic_file.path().to_string_lossy().into(),
+                password: "...".to_string(),
+                mnemonic: None,
+            }),
+
             SecretManager::Placeholder(_) => Self::Placeholder,
+
+            SecretManager::WatchOnly(watch_only) => {
+                let to_strings =
+                    |addresses: &[Ed25519Address]| addresses.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+                Self::WatchOnly(WatchOnlyDto {
+                    coin_type: watch_only.coin_type(),
+                    account_index: watch_only.account_index(),
+                    public_addresses: to_strings(watch_only.public_addresses()),
+                    internal_addresses: to_strings(watch_only.internal_addresses()),
+                })
+            }
         }
     }
 }
@@ -219,6 +470,11 @@ impl SecretManage for SecretManager {
         internal: bool,
         options: Option<GenerateAddressOptions>,
     ) -> crate::Result<Vec<Address>> {
+        // Ignoring the prompt would let the user believe that the addresses have been confirmed on a device
+        if options.as_ref().map_or(false, |options| options.ledger_nano_prompt) && !self.can_display_addresses() {
+            return Err(crate::Error::AddressPromptUnsupported);
+        }
+
         match self {
             #[cfg(feature = "stronghold")]
             SecretManager::Stronghold(secret_manager) => {
@@ -232,16 +488,56 @@ impl SecretManage for SecretManager {
                     .generate_addresses(coin_type, account_index, address_indexes, internal, options)
                     .await
             }
+            #[cfg(feature = "pkcs11")]
+            SecretManager::Pkcs11(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
+            #[cfg(feature = "cloud_kms")]
+            SecretManager::CloudKms(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
+            #[cfg(feature = "yubikey")]
+            SecretManager::Yubikey(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
+            #[cfg(feature = "remote_signer")]
+            SecretManager::RemoteSigner(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
+            SecretManager::External(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
             SecretManager::Mnemonic(secret_manager) => {
                 secret_manager
                     .generate_addresses(coin_type, account_index, address_indexes, internal, options)
                     .await
             }
+            #[cfg(not(target_family = "wasm"))]
+            SecretManager::MnemonicFile(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
             SecretManager::Placeholder(secret_manager) => {
                 secret_manager
                     .generate_addresses(coin_type, account_index, address_indexes, internal, options)
                     .await
             }
+            SecretManager::WatchOnly(secret_manager) => {
+                secret_manager
+                    .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+                    .await
+            }
         }
     }
 
@@ -260,16 +556,78 @@ impl SecretManage for SecretManager {
             SecretManager::LedgerNano(secret_manager) => {
                 secret_manager.signature_unlock(input, essence_hash, metadata).await
             }
+            #[cfg(feature = "pkcs11")]
+            SecretManager::Pkcs11(secret_manager) => {
+                secret_manager.signature_unlock(input, essence_hash, metadata).await
+            }
+            #[cfg(feature = "cloud_kms")]
+            SecretManager::CloudKms(secret_manager) => {
+                secret_manager.signature_unlock(input, essence_hash, metadata).await
+            }
+            #[cfg(feature = "yubikey")]
+            SecretManager::Yubikey(secret_manager) => {
+                secret_manager.signature_unlock(input, essence_hash, metadata).await
+            }
+            #[cfg(feature = "remote_signer")]
+            SecretManager::RemoteSigner(secret_manager) => {
+                secret_manager.signature_unlock(input, essence_hash, metadata).await
+            }
+            SecretManager::External(secret_manager) => {
+                secret_manager.signature_unlock(input, essence_hash, metadata).await
+            }
             SecretManager::Mnemonic(secret_manager) => {
                 secret_manager.signature_unlock(input, essence_hash, metadata).await
             }
+            #[cfg(not(target_family = "wasm"))]
+            SecretManager::MnemonicFile(secret_manager) => {
+                secret_manager.signature_unlock(input, essence_hash, metadata).await
+            }
             SecretManager::Placeholder(secret_manager) => {
                 secret_manager.signature_unlock(input, essence_hash, metadata).await
             }
+            SecretManager::WatchOnly(secret_manager) => {
+                secret_manager.signature_unlock(input, essence_hash, metadata).await
+            }
+        }
+    }
+
+    async fn sign_ed25519(&self, message: &[u8], chain: &Chain) -> crate::Result<Ed25519Signature> {
+        match self {
+            #[cfg(feature = "stronghold")]
+            SecretManager::Stronghold(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            #[cfg(feature = "ledger_nano")]
+            SecretManager::LedgerNano(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            #[cfg(feature = "pkcs11")]
+            SecretManager::Pkcs11(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            #[cfg(feature = "cloud_kms")]
+            SecretManager::CloudKms(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            #[cfg(feature = "yubikey")]
+            SecretManager::Yubikey(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            #[cfg(feature = "remote_signer")]
+            SecretManager::RemoteSigner(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            SecretManager::External(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            SecretManager::Mnemonic(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            #[cfg(not(target_family = "wasm"))]
+            SecretManager::MnemonicFile(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            SecretManager::Placeholder(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
+            SecretManager::WatchOnly(secret_manager) => secret_manager.sign_ed25519(message, chain).await,
         }
     }
 }
 
+#[async_trait]
+impl BackgroundComponent for SecretManager {
+    async fn shutdown(&self) -> crate::Result<()> {
+        // Only Stronghold runs background tasks.
+        #[cfg(feature = "stronghold")]
+        if let SecretManager::Stronghold(secret_manager) = self {
+            return secret_manager.shutdown().await;
+        }
+
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl SecretManageExt for SecretManager {
     async fn sign_transaction_essence(
@@ -283,73 +641,157 @@ impl SecretManageExt for SecretManager {
             SecretManager::LedgerNano(secret_manager) => {
                 secret_manager.sign_transaction_essence(prepared_transaction_data).await
             }
+            #[cfg(feature = "pkcs11")]
+            SecretManager::Pkcs11(_) => self.default_sign_transaction_essence(prepared_transaction_data).await,
+            #[cfg(feature = "cloud_kms")]
+            SecretManager::CloudKms(_) => self.default_sign_transaction_essence(prepared_transaction_data).await,
+            #[cfg(feature = "yubikey")]
+            SecretManager::Yubikey(_) => self.default_sign_transaction_essence(prepared_transaction_data).await,
+            #[cfg(feature = "remote_signer")]
+            SecretManager::RemoteSigner(_) => self.default_sign_transaction_essence(prepared_transaction_data).await,
+            SecretManager::External(_) => self.default_sign_transaction_essence(prepared_transaction_data).await,
             SecretManager::Mnemonic(_) => self.default_sign_transaction_essence(prepared_transaction_data).await,
+            #[cfg(not(target_family = "wasm"))]
+            SecretManager::MnemonicFile(_) => self.default_sign_transaction_essence(prepared_transaction_data).await,
             SecretManager::Placeholder(_) => self.sign_transaction_essence(prepared_transaction_data).await,
+            SecretManager::WatchOnly(_) => Err(crate::Error::WatchOnlySigningUnsupported),
         }
     }
 }
 
 impl SecretManager {
+    /// Whether the secret manager can display addresses on a device for their confirmation by the user, see
+    /// [`GenerateAddressOptions::ledger_nano_prompt`].
+    pub fn can_display_addresses(&self) -> bool {
+        #[cfg(feature = "ledger_nano")]
+        if let SecretManager::LedgerNano(_) = self {
+            return true;
+        }
+
+        false
+    }
+
+    /// Derive the [`TagKey`] of an account, from which tags for application data are derived, see [`tag`].
+    ///
+    /// Only secret managers holding the seed support it.
+    pub async fn derive_tag_key(&self, coin_type: u32, account_index: u32) -> crate::Result<TagKey> {
+        match self {
+            #[cfg(feature = "stronghold")]
+            SecretManager::Stronghold(secret_manager) => secret_manager.derive_tag_key(coin_type, account_index).await,
+            #[cfg(feature = "ledger_nano")]
+            SecretManager::LedgerNano(_) => Err(crate::Error::TagKeyDerivationUnsupported),
+            #[cfg(feature = "pkcs11")]
+            SecretManager::Pkcs11(_) => Err(crate::Error::TagKeyDerivationUnsupported),
+            #[cfg(feature = "cloud_kms")]
+            SecretManager::CloudKms(_) => Err(crate::Error::TagKeyDerivationUnsupported),
+            #[cfg(feature = "yubikey")]
+            SecretManager::Yubikey(_) => Err(crate::Error::TagKeyDerivationUnsupported),
+            #[cfg(feature = "remote_signer")]
+            SecretManager::RemoteSigner(_) => Err(crate::Error::TagKeyDerivationUnsupported),
+            SecretManager::External(_) => Err(crate::Error::TagKeyDerivationUnsupported),
+            SecretManager::Mnemonic(secret_manager) => secret_manager.derive_tag_key(coin_type, account_index),
+            #[cfg(not(target_family = "wasm"))]
+            SecretManager::MnemonicFile(secret_manager) => secret_manager.derive_tag_key(coin_type, account_index),
+            SecretManager::Placeholder(_) => Err(crate::Error::PlaceholderSecretManager),
+            SecretManager::WatchOnly(_) => Err(crate::Error::TagKeyDerivationUnsupported),
+        }
+    }
+
+    /// Sign the hash of a transaction essence, built by the application, and return the unlocks of its inputs, in
+    /// the order of `inputs_data`: the signature unlock of the first input of each address, then references to it.
+    ///
+    /// Nothing is fetched from a node, so applications with their own transaction pipeline can only reuse the
+    /// signing. The Ledger Nano signs whole essences, so it isn't supported.
+    pub async fn sign_essence_hash(
+        &self,
+        essence_hash: &[u8; 32],
+        inputs_data: &[InputSigningData],
+        remainder: &Option<RemainderData>,
+    ) -> crate::Result<Unlocks> {
+        #[cfg(feature = "ledger_nano")]
+        if let SecretManager::LedgerNano(_) = self {
+            return Err(crate::Error::LedgerEssenceHashSigningUnsupported);
+        }
+
+        let mut blocks = Vec::new();
+
+        for (input, unlock) in inputs_data.iter().zip(unlock_plan(inputs_data)?) {
+            match unlock {
+                Some(unlock) => blocks.push(unlock),
+                None => blocks.push(self.signature_unlock(input, essence_hash, remainder).await?),
+            }
+        }
+
+        Ok(Unlocks::new(blocks)?)
+    }
+
     // Shared implementation for MnemonicSecretManager and StrongholdSecretManager
     async fn default_sign_transaction_essence<'a>(
         &self,
         prepared_transaction_data: &PreparedTransactionData,
     ) -> crate::Result<Unlocks> {
         // The hashed_essence gets signed
-        let hashed_essence = prepared_transaction_data.essence.hash();
-        let mut blocks = Vec::new();
-        let mut block_indexes = HashMap::<Address, usize>::new();
-
-        // Assuming inputs_data is ordered by address type
-        for (current_block_index, input) in prepared_transaction_data.inputs_data.iter().enumerate() {
-            // Get the address that is required to unlock the input
-            let (_, input_address) = Address::try_from_bech32(&input.bech32_address)?;
-
-            // Check if we already added an [Unlock] for this address
-            match block_indexes.get(&input_address) {
-                // If we already have an [Unlock] for this address, add a [Unlock] based on the address type
-                Some(block_index) => match input_address {
-                    Address::Alias(_alias) => blocks.push(Unlock::Alias(AliasUnlock::new(*block_index as u16)?)),
-                    Address::Ed25519(_ed25519) => {
-                        blocks.push(Unlock::Reference(ReferenceUnlock::new(*block_index as u16)?));
-                    }
-                    Address::Nft(_nft) => blocks.push(Unlock::Nft(NftUnlock::new(*block_index as u16)?)),
-                },
-                None => {
-                    // We can only sign ed25519 addresses and block_indexes needs to contain the alias or nft
-                    // address already at this point, because the reference index needs to be lower
-                    // than the current block index
-                    if !input_address.is_ed25519() {
-                        return Err(crate::Error::MissingInputWithEd25519Address);
-                    }
-
-                    let block = self
-                        .signature_unlock(input, &hashed_essence, &prepared_transaction_data.remainder)
-                        .await?;
-                    blocks.push(block);
-
-                    // Add the ed25519 address to the block_indexes, so it gets referenced if further inputs have
-                    // the same address in their unlock condition
-                    block_indexes.insert(input_address, current_block_index);
+        self.sign_essence_hash(
+            &prepared_transaction_data.essence.hash(),
+            &prepared_transaction_data.inputs_data,
+            &prepared_transaction_data.remainder,
+        )
+        .await
+    }
+}
+
+/// The unlocks of the inputs of a transaction which don't need a signature, i.e. the references to earlier unlocks, or
+/// `None` for the inputs needing a signature unlock, the first one of each ed25519 address.
+pub(crate) fn unlock_plan(inputs_data: &[InputSigningData]) -> crate::Result<Vec<Option<Unlock>>> {
+    let mut blocks = Vec::new();
+    let mut block_indexes = HashMap::<Address, usize>::new();
+
+    // Assuming inputs_data is ordered by address type
+    for (current_block_index, input) in inputs_data.iter().enumerate() {
+        // Get the address that is required to unlock the input
+        let (_, input_address) = Address::try_from_bech32(&input.bech32_address)?;
+
+        // Check if we already added an [Unlock] for this address
+        match block_indexes.get(&input_address) {
+            // If we already have an [Unlock] for this address, add a [Unlock] based on the address type
+            Some(block_index) => match input_address {
+                Address::Alias(_alias) => blocks.push(Some(Unlock::Alias(AliasUnlock::new(*block_index as u16)?))),
+                Address::Ed25519(_ed25519) => {
+                    blocks.push(Some(Unlock::Reference(ReferenceUnlock::new(*block_index as u16)?)));
                 }
-            }
+                Address::Nft(_nft) => blocks.push(Some(Unlock::Nft(NftUnlock::new(*block_index as u16)?))),
+            },
+            None => {
+                // We can only sign ed25519 addresses and block_indexes needs to contain the alias or nft
+                // address already at this point, because the reference index needs to be lower
+                // than the current block index
+                if !input_address.is_ed25519() {
+                    return Err(crate::Error::MissingInputWithEd25519Address);
+                }
+
+                blocks.push(None);
 
-            // When we have an alias or Nft output, we will add their alias or nft address to block_indexes,
-            // because they can be used to unlock outputs via [Unlock::Alias] or [Unlock::Nft],
-            // that have the corresponding alias or nft address in their unlock condition
-            match &input.output {
-                Output::Alias(alias_output) => block_indexes.insert(
-                    Address::Alias(alias_output.alias_address(input.output_id())),
-                    current_block_index,
-                ),
-                Output::Nft(nft_output) => block_indexes.insert(
-                    Address::Nft(nft_output.nft_address(input.output_id())),
-                    current_block_index,
-                ),
-                _ => None,
-            };
+                // Add the ed25519 address to the block_indexes, so it gets referenced if further inputs have
+                // the same address in their unlock condition
+                block_indexes.insert(input_address, current_block_index);
+            }
         }
 
-        Ok(Unlocks::new(blocks)?)
+        // When we have an alias or Nft output, we will add their alias or nft address to block_indexes,
+        // because they can be used to unlock outputs via [Unlock::Alias] or [Unlock::Nft],
+        // that have the corresponding alias or nft address in their unlock condition
+        match &input.output {
+            Output::Alias(alias_output) => block_indexes.insert(
+                Address::Alias(alias_output.alias_address(input.output_id())),
+                current_block_index,
+            ),
+            Output::Nft(nft_output) => block_indexes.insert(
+                Address::Nft(nft_output.nft_address(input.output_id())),
+                current_block_index,
+            ),
+            _ => None,
+        };
     }
+
+    Ok(blocks)
 }
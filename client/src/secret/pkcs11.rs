@@ -0,0 +1,194 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of [`Pkcs11SecretManager`].
+//!
+//! The Ed25519 key is generated and held by a PKCS#11 token, e.g. an HSM like SoftHSM or AWS CloudHSM, and never
+//! leaves it: the transaction essences are signed by the token with the `CKM_EDDSA` mechanism. The key pair is looked
+//! up by the label of its objects, which can be generated with e.g.
+//! `pkcs11-tool --module <module> --login --keypairgen --key-type EC:edwards25519 --label <label>`.
+//!
+//! As a token holds a single key rather than a seed, there is no key derivation: all accounts and address indexes
+//! map to the one address of the key.
+
+use std::{ops::Range, path::Path, sync::Mutex};
+
+use async_trait::async_trait;
+use crypto::{
+    hashes::{blake2b::Blake2b256, Digest},
+    keys::slip10::Chain,
+};
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    error::RvError,
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use iota_types::block::{
+    address::{Address, Ed25519Address},
+    signature::{Ed25519Signature, Signature},
+    unlock::{SignatureUnlock, Unlock},
+};
+
+use super::{types::InputSigningData, GenerateAddressOptions, SecretManage};
+use crate::{secret::RemainderData, Error, Result};
+
+/// The length of an Ed25519 public key.
+const PUBLIC_KEY_LENGTH: usize = 32;
+
+/// Secret manager that signs with an Ed25519 key held by a PKCS#11 token.
+pub struct Pkcs11SecretManager {
+    /// The session with the token; PKCS#11 sessions can't be used concurrently.
+    session: Mutex<Session>,
+    /// The private key object.
+    private_key: ObjectHandle,
+    /// The public key.
+    public_key: [u8; PUBLIC_KEY_LENGTH],
+    /// The address of the public key.
+    address: Address,
+}
+
+impl Pkcs11SecretManager {
+    /// Open a session with the token in the slot `slot_id` of the PKCS#11 module at `module_path`, logged in as user
+    /// with `pin`, signing with the Ed25519 key pair labelled `key_label`.
+    pub fn new<P: AsRef<Path>>(module_path: P, slot_id: u64, pin: &str, key_label: &str) -> Result<Self> {
+        let pkcs11 = Pkcs11::new(module_path)?;
+
+        // The module may already have been initialized, e.g. for another secret manager.
+        match pkcs11.initialize(CInitializeArgs::OsThreads) {
+            Ok(()) | Err(cryptoki::error::Error::Pkcs11(RvError::CryptokiAlreadyInitialized)) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let slot = pkcs11
+            .get_slots_with_token()?
+            .into_iter()
+            .find(|slot| slot.id() == slot_id)
+            .ok_or(Error::Pkcs11SlotNotFound(slot_id))?;
+
+        let session = pkcs11.open_ro_session(slot)?;
+        session.login(UserType::User, Some(&AuthPin::new(pin.to_string())))?;
+
+        let find_key = |class| -> Result<ObjectHandle> {
+            session
+                .find_objects(&[
+                    Attribute::Class(class),
+                    Attribute::KeyType(KeyType::EC_EDWARDS),
+                    Attribute::Label(key_label.as_bytes().to_vec()),
+                ])?
+                .into_iter()
+                .next()
+                .ok_or_else(|| Error::Pkcs11KeyNotFound(key_label.to_string()))
+        };
+
+        let private_key = find_key(ObjectClass::PRIVATE_KEY)?;
+        let public_key = match session
+            .get_attributes(find_key(ObjectClass::PUBLIC_KEY)?, &[AttributeType::EcPoint])?
+            .into_iter()
+            .next()
+        {
+            Some(Attribute::EcPoint(ec_point)) => decode_ec_point(&ec_point)?,
+            _ => return Err(Error::Pkcs11InvalidPublicKey),
+        };
+
+        // Hash the public key to get the address
+        let address = Blake2b256::digest(public_key).try_into().map_err(|_e| {
+            crate::Error::Blake2b256Error("hashing the public key while generating the address failed.")
+        })?;
+        let address = Address::Ed25519(Ed25519Address::new(address));
+
+        Ok(Self {
+            session: Mutex::new(session),
+            private_key,
+            public_key,
+            address,
+        })
+    }
+
+    /// The address of the key.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<[u8; 64]> {
+        let session = self.session.lock().map_err(|_| Error::PoisonError)?;
+        let signature = session.sign(&Mechanism::Eddsa, self.private_key, message)?;
+
+        signature.try_into().map_err(|_| Error::Pkcs11InvalidSignature)
+    }
+}
+
+/// Decode the `CKA_EC_POINT` of an Ed25519 public key, either a DER-encoded OCTET STRING or the raw key depending on
+/// the token.
+fn decode_ec_point(ec_point: &[u8]) -> Result<[u8; PUBLIC_KEY_LENGTH]> {
+    let public_key = match ec_point {
+        [0x04, length, public_key @ ..]
+            if *length as usize == PUBLIC_KEY_LENGTH && public_key.len() == PUBLIC_KEY_LENGTH =>
+        {
+            public_key
+        }
+        public_key => public_key,
+    };
+
+    public_key.try_into().map_err(|_| Error::Pkcs11InvalidPublicKey)
+}
+
+#[async_trait]
+impl SecretManage for Pkcs11SecretManager {
+    async fn generate_addresses(
+        &self,
+        _coin_type: u32,
+        _account_index: u32,
+        address_indexes: Range<u32>,
+        _internal: bool,
+        _: Option<GenerateAddressOptions>,
+    ) -> crate::Result<Vec<Address>> {
+        Ok(address_indexes.map(|_| self.address).collect())
+    }
+
+    async fn signature_unlock(
+        &self,
+        input: &InputSigningData,
+        essence_hash: &[u8; 32],
+        _: &Option<RemainderData>,
+    ) -> crate::Result<Unlock> {
+        let (_, input_address) = Address::try_from_bech32(&input.bech32_address)?;
+
+        if input_address != self.address {
+            return Err(Error::Pkcs11AddressMismatch(input.bech32_address.clone()));
+        }
+
+        // The signature unlock block needs to sign the hash of the entire transaction essence of the
+        // transaction payload
+        let signature = self.sign(essence_hash)?;
+
+        Ok(Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(
+            Ed25519Signature::new(self.public_key, signature),
+        ))))
+    }
+
+    // The token holds a single key, the chain is irrelevant
+    async fn sign_ed25519(&self, message: &[u8], _: &Chain) -> crate::Result<Ed25519Signature> {
+        Ok(Ed25519Signature::new(self.public_key, self.sign(message)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ec_point() {
+        let public_key = [42; PUBLIC_KEY_LENGTH];
+        let der = [&[0x04, PUBLIC_KEY_LENGTH as u8][..], &public_key].concat();
+
+        assert_eq!(decode_ec_point(&der).unwrap(), public_key);
+        assert_eq!(decode_ec_point(&public_key).unwrap(), public_key);
+        assert!(matches!(
+            decode_ec_point(&[0x04, 0x01, 0x00]),
+            Err(Error::Pkcs11InvalidPublicKey)
+        ));
+    }
+}
@@ -6,8 +6,10 @@
 use std::ops::Range;
 
 use async_trait::async_trait;
+use crypto::keys::slip10::Chain;
 use iota_types::block::{
     address::Address,
+    signature::Ed25519Signature,
     unlock::{Unlock, Unlocks},
 };
 
@@ -39,6 +41,10 @@ impl SecretManage for PlaceholderSecretManager {
     ) -> crate::Result<Unlock> {
         return Err(crate::Error::PlaceholderSecretManager);
     }
+
+    async fn sign_ed25519(&self, _message: &[u8], _chain: &Chain) -> crate::Result<Ed25519Signature> {
+        return Err(crate::Error::PlaceholderSecretManager);
+    }
 }
 
 #[async_trait]
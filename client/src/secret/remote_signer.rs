@@ -0,0 +1,308 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of [`RemoteSignerSecretManager`].
+//!
+//! The keys are held by a signing service, e.g. a dedicated signer daemon, which is asked for the public keys and the
+//! signatures with JSON requests over HTTPS, authorized with a bearer token:
+//! - `POST <endpoint>/publicKey` with `{ "path": "m/44'/4218'/0'/0'/0'" }`, answered with the public key of the path,
+//!   `{ "publicKey": "0x..." }`;
+//! - `POST <endpoint>/sign` with `{ "path": "m/44'/4218'/0'/0'/0'", "message": "0x..." }`, answered with the Ed25519
+//!   signature of the message, `{ "signature": "0x..." }`.
+//!
+//! The signatures are verified against the public keys, like the ones of an [`ExternalSigner`].
+
+use std::ops::Range;
+
+use async_trait::async_trait;
+use crypto::keys::slip10::Chain;
+use iota_types::block::{address::Address, signature::Ed25519Signature, unlock::Unlock};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    external::{ExternalSign, ExternalSigner},
+    types::InputSigningData,
+    GenerateAddressOptions, SecretManage,
+};
+use crate::{secret::RemainderData, Error, Result};
+
+/// The flag of the hardened indexes of a BIP-32 path.
+const HARDENED: u32 = 1 << 31;
+
+#[derive(Serialize)]
+struct PublicKeyRequest {
+    path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicKeyResponse {
+    public_key: String,
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    path: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+/// The HTTP client of the signing service.
+struct HttpSigner {
+    client: reqwest::Client,
+    endpoint: Url,
+    auth_token: Option<String>,
+}
+
+impl HttpSigner {
+    async fn post<T: Serialize + Sync>(&self, method: &str, body: &T) -> Result<reqwest::Response> {
+        let mut url = self.endpoint.clone();
+        url.set_path(&format!("{}/{method}", self.endpoint.path().trim_end_matches('/')));
+
+        let mut request = self.client.post(url).json(body);
+        if let Some(auth_token) = &self.auth_token {
+            request = request.bearer_auth(auth_token);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            Ok(response)
+        } else {
+            Err(Error::ResponseError {
+                code: status.as_u16(),
+                url: response.url().to_string(),
+                text: response.text().await?,
+            })
+        }
+    }
+}
+
+#[async_trait]
+impl ExternalSign for HttpSigner {
+    async fn public_key(&self, chain: &Chain) -> Result<[u8; 32]> {
+        let request = PublicKeyRequest {
+            path: bip32_path(chain),
+        };
+        let response = self.post("publicKey", &request).await?;
+
+        Ok(prefix_hex::decode(
+            &response.json::<PublicKeyResponse>().await?.public_key,
+        )?)
+    }
+
+    async fn sign_ed25519(&self, message: &[u8], chain: &Chain) -> Result<[u8; 64]> {
+        let request = SignRequest {
+            path: bip32_path(chain),
+            message: prefix_hex::encode(message),
+        };
+        let response = self.post("sign", &request).await?;
+
+        Ok(prefix_hex::decode(&response.json::<SignResponse>().await?.signature)?)
+    }
+}
+
+/// The BIP-32 notation of `chain`, e.g. `m/44'/4218'/0'/0'/0'`.
+fn bip32_path(chain: &Chain) -> String {
+    chain.segments().iter().fold("m".to_string(), |path, segment| {
+        let index = u32::from_be_bytes(segment.bs());
+
+        if segment.hardened() {
+            format!("{path}/{}'", index & !HARDENED)
+        } else {
+            format!("{path}/{index}")
+        }
+    })
+}
+
+/// Secret manager that forwards the derivation of public keys and the signatures to a remote signing service.
+pub struct RemoteSignerSecretManager {
+    endpoint: Url,
+    signer: ExternalSigner,
+}
+
+impl RemoteSignerSecretManager {
+    /// Sign with the service at `endpoint`, e.g. `https://signer.internal:8443/v1`, authorized by `auth_token`.
+    pub fn new(endpoint: &str, auth_token: Option<String>) -> Result<Self> {
+        Self::with_tls(endpoint, auth_token, None, None)
+    }
+
+    /// Like [`new()`](Self::new()), trusting the PEM-encoded CA certificate `ca_certificate`, e.g. of a private CA,
+    /// and authenticating with the PEM-encoded certificate and private key `client_identity` for mutual TLS.
+    pub fn with_tls(
+        endpoint: &str,
+        auth_token: Option<String>,
+        ca_certificate: Option<&[u8]>,
+        client_identity: Option<&[u8]>,
+    ) -> Result<Self> {
+        let endpoint = Url::parse(endpoint)?;
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(ca_certificate) = ca_certificate {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(ca_certificate)?);
+        }
+        if let Some(client_identity) = client_identity {
+            builder = builder.identity(reqwest::Identity::from_pem(client_identity)?);
+        }
+
+        Ok(Self {
+            endpoint: endpoint.clone(),
+            signer: ExternalSigner::new(HttpSigner {
+                client: builder.build()?,
+                endpoint,
+                auth_token,
+            }),
+        })
+    }
+
+    /// The endpoint of the signing service.
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+}
+
+#[async_trait]
+impl SecretManage for RemoteSignerSecretManager {
+    async fn generate_addresses(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        address_indexes: Range<u32>,
+        internal: bool,
+        options: Option<GenerateAddressOptions>,
+    ) -> crate::Result<Vec<Address>> {
+        self.signer
+            .generate_addresses(coin_type, account_index, address_indexes, internal, options)
+            .await
+    }
+
+    async fn signature_unlock(
+        &self,
+        input: &InputSigningData,
+        essence_hash: &[u8; 32],
+        remainder: &Option<RemainderData>,
+    ) -> crate::Result<Unlock> {
+        self.signer.signature_unlock(input, essence_hash, remainder).await
+    }
+
+    async fn sign_ed25519(&self, message: &[u8], chain: &Chain) -> crate::Result<Ed25519Signature> {
+        self.signer.sign_ed25519(message, chain).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+    };
+
+    use crypto::signatures::ed25519;
+
+    use super::*;
+    use crate::{constants::IOTA_COIN_TYPE, secret::DerivationPath};
+
+    /// The request lines and authorization headers of the requests received by the mock signing service.
+    type RecordedRequests = Arc<Mutex<Vec<(String, String)>>>;
+
+    /// Serve the requests of the signing service with `secret_key` for all paths, recording the request lines and
+    /// authorization headers.
+    fn mock_signer(secret_key: ed25519::SecretKey) -> (String, RecordedRequests) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/v1", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded_requests = requests.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut authorization = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let header = header.trim_end();
+                    if header.is_empty() {
+                        break;
+                    }
+                    let (name, value) = header.split_once(": ").unwrap();
+                    match name.to_ascii_lowercase().as_str() {
+                        "authorization" => authorization = value.to_string(),
+                        "content-length" => content_length = value.parse().unwrap(),
+                        _ => {}
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+                let response = if request_line.starts_with("POST /v1/publicKey ") {
+                    serde_json::json!({ "publicKey": prefix_hex::encode(secret_key.public_key().to_bytes()) })
+                } else {
+                    let message: Vec<u8> = prefix_hex::decode(body["message"].as_str().unwrap()).unwrap();
+                    serde_json::json!({ "signature": prefix_hex::encode(secret_key.sign(&message).to_bytes()) })
+                }
+                .to_string();
+                recorded_requests
+                    .lock()
+                    .unwrap()
+                    .push((request_line.trim_end().to_string(), authorization));
+
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                )
+                .unwrap();
+            }
+        });
+
+        (endpoint, requests)
+    }
+
+    #[test]
+    fn path() {
+        let chain = DerivationPath::default().chain(IOTA_COIN_TYPE, 1, true, 2);
+
+        assert_eq!(bip32_path(&chain), "m/44'/4218'/1'/1'/2'");
+    }
+
+    #[test]
+    fn invalid_endpoint() {
+        assert!(matches!(
+            RemoteSignerSecretManager::new("signer.internal", None),
+            Err(Error::UrlError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn remote_signer() {
+        let secret_key = ed25519::SecretKey::from_bytes([42; 32]);
+        let public_key = secret_key.public_key().to_bytes();
+        let (endpoint, requests) = mock_signer(secret_key);
+        let secret_manager = RemoteSignerSecretManager::new(&endpoint, Some("token".to_string())).unwrap();
+        let chain = DerivationPath::default().chain(IOTA_COIN_TYPE, 0, false, 0);
+
+        let signature = secret_manager.sign_ed25519(b"message", &chain).await.unwrap();
+        assert_eq!(signature.public_key(), &public_key);
+
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                ("POST /v1/publicKey HTTP/1.1".to_string(), "Bearer token".to_string()),
+                ("POST /v1/sign HTTP/1.1".to_string(), "Bearer token".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! secp256k1 ECDSA keys derived from a seed, for the EVM addresses and signatures of L2 chains like ShimmerEVM.
+//!
+//! SLIP-10 is only used for Ed25519 keys, secp256k1 keys are derived with BIP-32 at the BIP-44 path of EVM wallets,
+//! `m/44'/{coin_type}'/{account_index}'/{internal}/{address_index}`, so the same mnemonic gives the same addresses as
+//! in these wallets.
+
+use crypto::macs::hmac::HMAC_SHA512;
+use k256::{
+    ecdsa::{SigningKey, VerifyingKey},
+    elliptic_curve::PrimeField,
+    FieldBytes, NonZeroScalar, Scalar,
+};
+use sha3::{Digest, Keccak256};
+use zeroize::Zeroizing;
+
+use crate::{constants::HD_WALLET_TYPE, Error, Result};
+
+const HARDENED: u32 = 1 << 31;
+
+/// The BIP-32 chain of the secp256k1 key of an EVM address.
+pub fn evm_chain(coin_type: u32, account_index: u32, internal: bool, address_index: u32) -> [u32; 5] {
+    [
+        HD_WALLET_TYPE | HARDENED,
+        coin_type | HARDENED,
+        account_index | HARDENED,
+        internal as u32,
+        address_index,
+    ]
+}
+
+/// Derives the secp256k1 key of `chain` from `seed` with BIP-32, hardened indexes having their most significant bit
+/// set.
+pub(crate) fn derive_signing_key(seed: &[u8], chain: &[u32]) -> Result<SigningKey> {
+    let mut extended_key = Zeroizing::new([0u8; 64]);
+    HMAC_SHA512(seed, b"Bitcoin seed", &mut extended_key);
+
+    let mut signing_key = SigningKey::from_bytes(FieldBytes::from_slice(&extended_key[..32]))
+        .map_err(|_| Error::InvalidBIP32ChainData)?;
+
+    for index in chain {
+        let mut data = Zeroizing::new(Vec::with_capacity(37));
+        if index & HARDENED != 0 {
+            data.push(0);
+            data.extend_from_slice(&signing_key.to_bytes());
+        } else {
+            data.extend_from_slice(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let chain_code = Zeroizing::new(extended_key[32..].to_vec());
+        HMAC_SHA512(&data, &chain_code, &mut extended_key);
+
+        // The derived key is invalid if the tweak isn't a scalar or the child key is zero, which is negligibly unlikely
+        let tweak = Option::<Scalar>::from(Scalar::from_repr(*FieldBytes::from_slice(&extended_key[..32])))
+            .ok_or(Error::InvalidBIP32ChainData)?;
+        let child_key =
+            Option::<NonZeroScalar>::from(NonZeroScalar::new(tweak + signing_key.as_nonzero_scalar().as_ref()))
+                .ok_or(Error::InvalidBIP32ChainData)?;
+
+        signing_key = SigningKey::from(child_key);
+    }
+
+    Ok(signing_key)
+}
+
+/// An EVM address, the last 20 bytes of the Keccak-256 hash of an uncompressed secp256k1 public key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct EvmAddress([u8; 20]);
+
+impl EvmAddress {
+    /// The address of a public key.
+    pub fn from_public_key(public_key: &VerifyingKey) -> Self {
+        let hash = Keccak256::digest(&public_key.to_encoded_point(false).as_bytes()[1..]);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash[12..]);
+
+        Self(address)
+    }
+
+    /// The bytes of the address.
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+}
+
+/// Formats the address with the EIP-55 mixed-case checksum.
+impl core::fmt::Display for EvmAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let hex = prefix_hex::encode(self.0);
+        let hex = &hex[2..];
+        let hash = Keccak256::digest(hex.as_bytes());
+
+        write!(f, "0x")?;
+        for (i, c) in hex.chars().enumerate() {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                write!(f, "{}", c.to_ascii_uppercase())?;
+            } else {
+                write!(f, "{c}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A recoverable secp256k1 ECDSA signature, with the public key of the signer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Secp256k1EcdsaSignature {
+    public_key: [u8; 33],
+    signature: [u8; 64],
+    recovery_id: u8,
+}
+
+impl Secp256k1EcdsaSignature {
+    /// Signs the Keccak-256 hash of `message` with `signing_key`, as done for EVM transactions.
+    pub(crate) fn sign_keccak256(signing_key: &SigningKey, message: &[u8]) -> Result<Self> {
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&Keccak256::digest(message))?;
+
+        let mut public_key = [0u8; 33];
+        public_key.copy_from_slice(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        let mut signature_bytes = [0u8; 64];
+        signature_bytes.copy_from_slice(&signature.to_bytes());
+
+        Ok(Self {
+            public_key,
+            signature: signature_bytes,
+            recovery_id: recovery_id.to_byte(),
+        })
+    }
+
+    /// The compressed public key of the signer.
+    pub fn public_key(&self) -> &[u8; 33] {
+        &self.public_key
+    }
+
+    /// The `r` and `s` values of the signature.
+    pub fn signature(&self) -> &[u8; 64] {
+        &self.signature
+    }
+
+    /// The recovery id of the signature, 0 or 1, which EVM transactions encode in `v` with an offset.
+    pub fn recovery_id(&self) -> u8 {
+        self.recovery_id
+    }
+
+    /// The EVM address of the signer.
+    pub fn evm_address(&self) -> Result<EvmAddress> {
+        Ok(EvmAddress::from_public_key(&VerifyingKey::from_sec1_bytes(
+            &self.public_key,
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vector 1 of BIP-32
+    #[test]
+    fn bip32_derivation() {
+        let seed = prefix_hex::decode::<Vec<u8>>("0x000102030405060708090a0b0c0d0e0f").unwrap();
+
+        assert_eq!(
+            prefix_hex::encode(derive_signing_key(&seed, &[]).unwrap().to_bytes().to_vec()),
+            "0xe8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+        assert_eq!(
+            prefix_hex::encode(derive_signing_key(&seed, &[HARDENED, 1]).unwrap().to_bytes().to_vec()),
+            "0x3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Shamir's secret sharing of seeds, to back them up as `share_count` shares of which any `threshold` reconstruct
+//! the seed, while fewer reveal nothing about it.
+//!
+//! Like SLIP-39, each byte of the secret is shared with its own random polynomial over GF(256), here with the
+//! reducing polynomial of AES, evaluated at the indexes of the shares. The shares are written as hex strings holding
+//! the threshold, the index, the value and a checksum catching transcription errors.
+
+use std::{fmt, str::FromStr};
+
+use crypto::{
+    hashes::{blake2b::Blake2b256, Digest},
+    utils::rand,
+};
+use zeroize::Zeroizing;
+
+use crate::{Error, Result};
+
+/// The length of the checksum of an encoded share.
+const CHECKSUM_LENGTH: usize = 4;
+
+/// A share of a secret, see [`split_secret()`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretShare {
+    threshold: u8,
+    index: u8,
+    value: Zeroizing<Vec<u8>>,
+}
+
+impl SecretShare {
+    /// The number of shares needed to reconstruct the secret.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// The index of the share, from 1.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LENGTH] {
+        let mut checksum = [0; CHECKSUM_LENGTH];
+        checksum.copy_from_slice(&Blake2b256::digest(bytes)[..CHECKSUM_LENGTH]);
+        checksum
+    }
+}
+
+// The value is a part of a secret, so it isn't printed
+impl fmt::Debug for SecretShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretShare")
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl fmt::Display for SecretShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = Zeroizing::new(vec![self.threshold, self.index]);
+        bytes.extend_from_slice(&self.value);
+        let checksum = Self::checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+
+        write!(f, "{}", Zeroizing::new(prefix_hex::encode(bytes.as_slice())).as_str())
+    }
+}
+
+impl FromStr for SecretShare {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes: Zeroizing<Vec<u8>> = Zeroizing::new(prefix_hex::decode(s.trim())?);
+
+        if bytes.len() <= 2 + CHECKSUM_LENGTH {
+            return Err(Error::InvalidSecretShares("share too short".to_string()));
+        }
+
+        let (share, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LENGTH);
+
+        if checksum != Self::checksum(share) {
+            return Err(Error::InvalidSecretShares("invalid share checksum".to_string()));
+        }
+
+        Ok(Self {
+            threshold: share[0],
+            index: share[1],
+            value: Zeroizing::new(share[2..].to_vec()),
+        })
+    }
+}
+
+/// Splits `secret` into `share_count` shares, of which any `threshold` reconstruct it with [`combine_shares()`].
+pub fn split_secret(secret: &[u8], threshold: u8, share_count: u8) -> Result<Vec<SecretShare>> {
+    if threshold == 0 || threshold > share_count {
+        return Err(Error::InvalidSecretShares(format!(
+            "invalid threshold {threshold} for {share_count} shares"
+        )));
+    }
+    if secret.is_empty() {
+        return Err(Error::InvalidSecretShares("empty secret".to_string()));
+    }
+
+    // The coefficients of the polynomials of each byte, the constant terms being the secret
+    let mut coefficients = Zeroizing::new(vec![0u8; secret.len() * (threshold as usize - 1)]);
+    rand::fill(&mut coefficients)?;
+
+    Ok((1..=share_count)
+        .map(|index| SecretShare {
+            threshold,
+            index,
+            value: Zeroizing::new(
+                secret
+                    .iter()
+                    .enumerate()
+                    .map(|(byte, secret_byte)| {
+                        // Horner's method, from the highest degree
+                        let polynomial = &coefficients[byte * (threshold as usize - 1)..][..threshold as usize - 1];
+                        let value = polynomial
+                            .iter()
+                            .rev()
+                            .fold(0, |value, coefficient| gf256_mul(value, index) ^ coefficient);
+
+                        gf256_mul(value, index) ^ secret_byte
+                    })
+                    .collect(),
+            ),
+        })
+        .collect())
+}
+
+/// Reconstructs the secret of at least `threshold` of its shares, see [`split_secret()`].
+pub fn combine_shares(shares: &[SecretShare]) -> Result<Zeroizing<Vec<u8>>> {
+    let first = shares
+        .first()
+        .ok_or_else(|| Error::InvalidSecretShares("no shares".to_string()))?;
+    let mut used_shares: Vec<&SecretShare> = Vec::new();
+
+    for share in shares {
+        if share.threshold != first.threshold || share.value.len() != first.value.len() {
+            return Err(Error::InvalidSecretShares(
+                "the shares belong to different secrets".to_string(),
+            ));
+        }
+        if share.index == 0 {
+            return Err(Error::InvalidSecretShares("invalid share index 0".to_string()));
+        }
+        if !used_shares.iter().any(|used_share| used_share.index == share.index) {
+            used_shares.push(share);
+        }
+    }
+
+    let threshold = first.threshold as usize;
+
+    if used_shares.len() < threshold {
+        return Err(Error::InvalidSecretShares(format!(
+            "{} distinct shares, {threshold} needed",
+            used_shares.len()
+        )));
+    }
+
+    let used_shares = &used_shares[..threshold];
+
+    // Lagrange interpolation at 0, subtraction being addition in GF(256)
+    let basis = used_shares
+        .iter()
+        .map(|share| {
+            used_shares
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |product, other| {
+                    gf256_mul(product, gf256_mul(other.index, gf256_inv(other.index ^ share.index)))
+                })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Zeroizing::new(
+        (0..first.value.len())
+            .map(|byte| {
+                used_shares.iter().zip(&basis).fold(0, |secret_byte, (share, basis)| {
+                    secret_byte ^ gf256_mul(share.value[byte], *basis)
+                })
+            })
+            .collect(),
+    ))
+}
+
+/// Multiplication in GF(256) reduced by x^8 + x^4 + x^3 + x + 1, without branches on the operands.
+fn gf256_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = a >> 7;
+        a <<= 1;
+        a ^= 0x1b & carry.wrapping_neg();
+        b >>= 1;
+    }
+
+    product
+}
+
+/// Inverse in GF(256), a^254.
+fn gf256_inv(a: u8) -> u8 {
+    let mut inverse = 1;
+    let mut power = a;
+    let mut exponent = 254u8;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            inverse = gf256_mul(inverse, power);
+        }
+        power = gf256_mul(power, power);
+        exponent >>= 1;
+    }
+
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gf256() {
+        // The example of FIPS 197
+        assert_eq!(gf256_mul(0x57, 0x83), 0xc1);
+
+        for a in 1..=255 {
+            assert_eq!(gf256_mul(a, gf256_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn split_and_combine() {
+        let secret = [42u8; 32];
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        assert_eq!(shares.len(), 5);
+        assert_eq!(*combine_shares(&shares[..3]).unwrap(), secret);
+        assert_eq!(*combine_shares(&shares[2..]).unwrap(), secret);
+        assert_eq!(
+            *combine_shares(&[shares[4].clone(), shares[0].clone(), shares[2].clone()]).unwrap(),
+            secret
+        );
+        assert_eq!(*combine_shares(&shares).unwrap(), secret);
+
+        // Too few distinct shares
+        assert!(matches!(
+            combine_shares(&[shares[0].clone(), shares[1].clone(), shares[1].clone()]),
+            Err(Error::InvalidSecretShares(_))
+        ));
+        assert!(matches!(
+            split_secret(&secret, 6, 5),
+            Err(Error::InvalidSecretShares(_))
+        ));
+    }
+
+    #[test]
+    fn encoding() {
+        let shares = split_secret(&[7; 16], 2, 3).unwrap();
+        let encoded = shares[1].to_string();
+
+        assert_eq!(encoded.parse::<SecretShare>().unwrap(), shares[1]);
+
+        // A transcription error is caught by the checksum
+        let mut corrupted = encoded.into_bytes();
+        corrupted[10] = if corrupted[10] == b'0' { b'1' } else { b'0' };
+        assert!(matches!(
+            String::from_utf8(corrupted).unwrap().parse::<SecretShare>(),
+            Err(Error::InvalidSecretShares(_))
+        ));
+    }
+}
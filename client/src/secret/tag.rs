@@ -0,0 +1,103 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hierarchical deterministic tags for application data.
+//!
+//! A [`TagKey`] is derived once per account from the seed, by signing a fixed message with a dedicated SLIP-10 key;
+//! Ed25519 signatures being deterministic, this gives the same key with all secret managers holding the seed, without
+//! ever exposing it. Tags are then derived from the key with HMAC-SHA256 over a path of namespaces, e.g. an
+//! application, a user and a session, so that the outputs tagged with them can be found again with the `tag` query
+//! parameter of the indexer, without keeping a registry of the tags.
+
+use crypto::{
+    hashes::{blake2b::Blake2b256, Digest},
+    macs::hmac::HMAC_SHA256,
+};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::constants::HD_WALLET_TYPE;
+
+/// The segment of the SLIP-10 chain of the key signing [`TAG_KEY_MESSAGE`], in place of the internal flag of
+/// addresses, so that it's never used for an address ("tags" in ASCII).
+const TAG_KEY_CHAIN_SEGMENT: u32 = 0x7461_6773;
+
+/// The message signed to derive a [`TagKey`].
+pub(crate) const TAG_KEY_MESSAGE: &[u8] = b"iota.rs tag key";
+
+/// The SLIP-10 chain of the key signing [`TAG_KEY_MESSAGE`] for an account, to be hardened.
+pub(crate) fn tag_key_chain(coin_type: u32, account_index: u32) -> Vec<u32> {
+    vec![HD_WALLET_TYPE, coin_type, account_index, TAG_KEY_CHAIN_SEGMENT, 0]
+}
+
+/// A key of an account, from which tags are derived deterministically, see the [module-level documentation](self).
+///
+/// The key doesn't give access to any funds, but it links all the tags derived from it; it should only be shared
+/// with the applications they belong to.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct TagKey([u8; 32]);
+
+impl TagKey {
+    /// Create a key from its raw bytes, e.g. when it has been shared with an application.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Create the key from the signature of [`TAG_KEY_MESSAGE`].
+    pub(crate) fn from_signature(signature: &[u8; 64]) -> Self {
+        Self(Blake2b256::digest(signature).into())
+    }
+
+    /// The raw bytes of the key.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Derive the tag of a path of namespaces, e.g. `[b"chat", b"user-42", b"session-7"]`.
+    ///
+    /// Each path gives an unrelated tag: the tag of a path doesn't tell anything about the tags of its sub-paths.
+    pub fn derive_tag(&self, path: &[&[u8]]) -> [u8; 32] {
+        // Each namespace is prefixed with its length, so that paths can't collide.
+        let mut message = Vec::new();
+
+        for namespace in path {
+            message.extend_from_slice(&(namespace.len() as u32).to_be_bytes());
+            message.extend_from_slice(namespace);
+        }
+
+        let mut tag = [0u8; 32];
+        HMAC_SHA256(&message, &self.0, &mut tag);
+
+        tag
+    }
+}
+
+impl core::fmt::Debug for TagKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("TagKey(<omitted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derive_tag() {
+        let tag_key = TagKey::from_bytes([1; 32]);
+
+        assert_eq!(
+            tag_key.derive_tag(&[b"chat", b"user-42"]),
+            tag_key.derive_tag(&[b"chat", b"user-42"])
+        );
+        assert_ne!(
+            tag_key.derive_tag(&[b"chat", b"user-42"]),
+            tag_key.derive_tag(&[b"chat", b"user-43"])
+        );
+        // The namespaces are delimited.
+        assert_ne!(tag_key.derive_tag(&[b"ab", b"c"]), tag_key.derive_tag(&[b"a", b"bc"]));
+        assert_ne!(
+            tag_key.derive_tag(&[b"chat"]),
+            TagKey::from_bytes([2; 32]).derive_tag(&[b"chat"])
+        );
+    }
+}
@@ -16,10 +16,9 @@ use iota_types::{
     },
 };
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "stronghold")]
 use zeroize::ZeroizeOnDrop;
 
-use crate::{Error, Result};
+use crate::{constants::HD_WALLET_TYPE, Error, Result};
 
 /// Stronghold DTO to allow the creation of a Stronghold secret manager from bindings
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
@@ -33,6 +32,85 @@ pub struct StrongholdDto {
     #[serde(rename = "snapshotPath")]
     pub snapshot_path: String,
 }
+/// PKCS#11 DTO to allow the creation of a PKCS#11 secret manager from bindings
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
+#[cfg(feature = "pkcs11")]
+#[serde(rename_all = "camelCase")]
+pub struct Pkcs11Dto {
+    /// The path to the PKCS#11 module, e.g. `/usr/lib/softhsm/libsofthsm2.so`
+    pub module_path: String,
+    /// The id of the slot of the token
+    pub slot_id: u64,
+    /// The user PIN of the token
+    pub pin: String,
+    /// The label of the Ed25519 key pair
+    pub key_label: String,
+}
+
+/// Cloud KMS DTO to allow the creation of a Google Cloud KMS secret manager from bindings
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
+#[cfg(feature = "cloud_kms")]
+#[serde(rename_all = "camelCase")]
+pub struct CloudKmsDto {
+    /// The resource name of the key version, i.e.
+    /// `projects/<project>/locations/<location>/keyRings/<keyring>/cryptoKeys/<key>/cryptoKeyVersions/<version>`
+    pub key_version_name: String,
+    /// The OAuth 2.0 access token authorizing the requests
+    pub access_token: String,
+}
+
+/// YubiKey DTO to allow the creation of a YubiKey secret manager from bindings
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
+#[cfg(feature = "yubikey")]
+#[serde(rename_all = "camelCase")]
+pub struct YubikeyDto {
+    /// A part of the name of the PC/SC reader of the YubiKey, or `None` for the first YubiKey connected
+    pub reader: Option<String>,
+    /// The user PIN of the OpenPGP application
+    pub pin: String,
+}
+
+/// Remote signer DTO to allow the creation of a remote signer secret manager from bindings
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
+#[cfg(feature = "remote_signer")]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteSignerDto {
+    /// The endpoint of the signing service, e.g. `https://signer.internal:8443/v1`
+    pub endpoint: String,
+    /// The bearer token authorizing the requests
+    pub auth_token: Option<String>,
+    /// The PEM-encoded CA certificate to trust, e.g. of a private CA
+    pub ca_certificate: Option<String>,
+    /// The PEM-encoded certificate and private key authenticating the client for mutual TLS
+    pub client_identity: Option<String>,
+}
+
+/// Mnemonic file DTO to allow the creation of a mnemonic file secret manager from bindings
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
+#[cfg(not(target_family = "wasm"))]
+pub struct MnemonicFileDto {
+    /// The path of the mnemonic file
+    pub path: String,
+    /// The password the mnemonic is encrypted with
+    pub password: String,
+    /// The mnemonic to store in a new file at `path`, or `None` to open the existing one
+    pub mnemonic: Option<String>,
+}
+
+/// Watch-only DTO to allow the creation of a watch-only secret manager from bindings
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, ZeroizeOnDrop)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchOnlyDto {
+    /// The coin type of the account
+    pub coin_type: u32,
+    /// The index of the account
+    pub account_index: u32,
+    /// The hex encoded Ed25519 public addresses, ordered by address index
+    pub public_addresses: Vec<String>,
+    /// The hex encoded Ed25519 internal addresses, ordered by address index
+    pub internal_addresses: Vec<String>,
+}
+
 /// An account address.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct AccountAddress {
@@ -48,9 +126,179 @@ pub struct AccountAddress {
 /// Options provided to `generate_address()`.
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct GenerateAddressOptions {
-    /// Display the address on ledger devices.
+    /// Display the address on ledger devices, for its confirmation by the user.
+    ///
+    /// Fails with `Error::LedgerDeniedByUser` if the user rejects the address, and with
+    /// [`Error::AddressPromptUnsupported`] for secret managers that can't display addresses.
     #[serde(rename = "ledgerNanoPrompt")]
     pub ledger_nano_prompt: bool,
+    /// The derivation path of the addresses, [`DerivationPath::default()`] if not set.
+    #[serde(rename = "derivationPath", default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<DerivationPath>,
+}
+
+impl GenerateAddressOptions {
+    /// The derivation path of the addresses.
+    pub fn derivation_path(options: &Option<Self>) -> DerivationPath {
+        options
+            .as_ref()
+            .and_then(|options| options.derivation_path.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// A segment of a [`DerivationPath`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DerivationPathSegment {
+    /// A fixed index.
+    Index(u32),
+    /// The coin type, `{coin_type}`.
+    CoinType,
+    /// The account index, `{account_index}`.
+    AccountIndex,
+    /// 0 for public addresses and 1 for internal ones, `{internal}`.
+    Internal,
+    /// The address index, `{address_index}`.
+    AddressIndex,
+}
+
+/// A template of the hardened derivation path of addresses,
+/// `m/44'/{coin_type}'/{account_index}'/{internal}'/{address_index}'` by default, e.g.
+/// `m/44'/4218'/0'/{address_index}'` for wallets deriving all the addresses of an account from a single chain.
+///
+/// Ed25519 keys only support hardened derivation, so every segment is written with a `'`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DerivationPath(Vec<DerivationPathSegment>);
+
+impl Default for DerivationPath {
+    fn default() -> Self {
+        Self(vec![
+            DerivationPathSegment::Index(HD_WALLET_TYPE),
+            DerivationPathSegment::CoinType,
+            DerivationPathSegment::AccountIndex,
+            DerivationPathSegment::Internal,
+            DerivationPathSegment::AddressIndex,
+        ])
+    }
+}
+
+impl DerivationPath {
+    /// Creates a derivation path from its segments, which must contain [`DerivationPathSegment::AddressIndex`]
+    /// exactly once.
+    pub fn new(segments: Vec<DerivationPathSegment>) -> Result<Self> {
+        let address_indexes = segments
+            .iter()
+            .filter(|segment| **segment == DerivationPathSegment::AddressIndex)
+            .count();
+
+        if address_indexes != 1 {
+            return Err(Error::InvalidDerivationPath(
+                "the address index must appear exactly once".to_string(),
+            ));
+        }
+
+        if let Some(DerivationPathSegment::Index(index)) = segments
+            .iter()
+            .find(|segment| matches!(segment, DerivationPathSegment::Index(index) if *index >= 1 << 31))
+        {
+            // The hardened bit is set when deriving the chain
+            return Err(Error::InvalidDerivationPath(format!(
+                "the index {index} is out of range"
+            )));
+        }
+
+        Ok(Self(segments))
+    }
+
+    /// The segments of the derivation path.
+    pub fn segments(&self) -> &[DerivationPathSegment] {
+        &self.0
+    }
+
+    /// The indexes of the chain of an address, without the hardened bit.
+    pub fn indexes(&self, coin_type: u32, account_index: u32, internal: bool, address_index: u32) -> Vec<u32> {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                DerivationPathSegment::Index(index) => *index,
+                DerivationPathSegment::CoinType => coin_type,
+                DerivationPathSegment::AccountIndex => account_index,
+                DerivationPathSegment::Internal => internal as u32,
+                DerivationPathSegment::AddressIndex => address_index,
+            })
+            .collect()
+    }
+
+    /// The hardened chain of an address.
+    pub fn chain(&self, coin_type: u32, account_index: u32, internal: bool, address_index: u32) -> Chain {
+        Chain::from_u32_hardened(self.indexes(coin_type, account_index, internal, address_index))
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let segments = s
+            .strip_prefix("m/")
+            .ok_or_else(|| Error::InvalidDerivationPath("the path must start with `m/`".to_string()))?;
+
+        Self::new(
+            segments
+                .split('/')
+                .map(|segment| {
+                    let segment = segment
+                        .strip_suffix('\'')
+                        .ok_or_else(|| Error::InvalidDerivationPath(format!("the segment {segment} isn't hardened")))?;
+
+                    Ok(match segment {
+                        "{coin_type}" => DerivationPathSegment::CoinType,
+                        "{account_index}" => DerivationPathSegment::AccountIndex,
+                        "{internal}" => DerivationPathSegment::Internal,
+                        "{address_index}" => DerivationPathSegment::AddressIndex,
+                        index => DerivationPathSegment::Index(
+                            index
+                                .parse()
+                                .map_err(|_| Error::InvalidDerivationPath(format!("invalid segment {index}")))?,
+                        ),
+                    })
+                })
+                .collect::<Result<_>>()?,
+        )
+    }
+}
+
+impl std::fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "m")?;
+
+        for segment in &self.0 {
+            match segment {
+                DerivationPathSegment::Index(index) => write!(f, "/{index}'")?,
+                DerivationPathSegment::CoinType => write!(f, "/{{coin_type}}'")?,
+                DerivationPathSegment::AccountIndex => write!(f, "/{{account_index}}'")?,
+                DerivationPathSegment::Internal => write!(f, "/{{internal}}'")?,
+                DerivationPathSegment::AddressIndex => write!(f, "/{{address_index}}'")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for DerivationPath {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<DerivationPath> for String {
+    fn from(value: DerivationPath) -> Self {
+        value.to_string()
+    }
 }
 
 /// The Ledger device status.
@@ -273,6 +521,26 @@ impl TryFrom<&OutputMetadataResponse> for OutputMetadata {
     }
 }
 
+impl From<&OutputMetadata> for OutputMetadataResponse {
+    fn from(output_metadata: &OutputMetadata) -> Self {
+        Self {
+            block_id: output_metadata.block_id.to_string(),
+            transaction_id: output_metadata.transaction_id().to_string(),
+            output_index: output_metadata.output_index(),
+            is_spent: output_metadata.is_spent,
+            milestone_index_spent: output_metadata.milestone_index_spent,
+            milestone_timestamp_spent: output_metadata.milestone_timestamp_spent,
+            transaction_id_spent: output_metadata
+                .transaction_id_spent
+                .as_ref()
+                .map(|transaction_id| transaction_id.to_string()),
+            milestone_index_booked: output_metadata.milestone_index_booked,
+            milestone_timestamp_booked: output_metadata.milestone_timestamp_booked,
+            ledger_index: output_metadata.ledger_index,
+        }
+    }
+}
+
 /// Data for transaction inputs for signing and ordering of unlock blocks
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct InputSigningData {
@@ -0,0 +1,231 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of [`WatchOnlySecretManager`].
+//!
+//! Ed25519 keys are derived with hardened SLIP-10 chains only, so there are no extended public keys from which the
+//! addresses of an account could be derived: the secret manager is constructed from the addresses, or the public keys
+//! of the addresses, of an account instead.
+
+use std::ops::Range;
+
+use async_trait::async_trait;
+use crypto::keys::slip10::Chain;
+use iota_types::block::{
+    address::{Address, Ed25519Address},
+    signature::Ed25519Signature,
+    unlock::Unlock,
+};
+
+use super::{types::InputSigningData, GenerateAddressOptions, SecretManage};
+use crate::{secret::RemainderData, Error, Result};
+
+/// Secret manager that knows the addresses of an account, but no key, e.g. for watch-only wallets.
+///
+/// Generating addresses returns the held addresses, so balances can be synced and inputs selected, but signing fails
+/// with [`Error::WatchOnlySigningUnsupported`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WatchOnlySecretManager {
+    coin_type: u32,
+    account_index: u32,
+    public_addresses: Vec<Ed25519Address>,
+    internal_addresses: Vec<Ed25519Address>,
+}
+
+#[async_trait]
+impl SecretManage for WatchOnlySecretManager {
+    async fn generate_addresses(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        address_indexes: Range<u32>,
+        internal: bool,
+        _: Option<GenerateAddressOptions>,
+    ) -> crate::Result<Vec<Address>> {
+        address_indexes
+            .map(|address_index| {
+                self.address(coin_type, account_index, internal, address_index)
+                    .map(Address::Ed25519)
+            })
+            .collect()
+    }
+
+    async fn signature_unlock(
+        &self,
+        _input: &InputSigningData,
+        _essence_hash: &[u8; 32],
+        _: &Option<RemainderData>,
+    ) -> crate::Result<Unlock> {
+        Err(Error::WatchOnlySigningUnsupported)
+    }
+
+    async fn sign_ed25519(&self, _message: &[u8], _chain: &Chain) -> crate::Result<Ed25519Signature> {
+        Err(Error::WatchOnlySigningUnsupported)
+    }
+}
+
+impl WatchOnlySecretManager {
+    /// Create a new [`WatchOnlySecretManager`] from the public and internal addresses of an account, ordered by
+    /// address index.
+    pub fn new(
+        coin_type: u32,
+        account_index: u32,
+        public_addresses: Vec<Ed25519Address>,
+        internal_addresses: Vec<Ed25519Address>,
+    ) -> Self {
+        Self {
+            coin_type,
+            account_index,
+            public_addresses,
+            internal_addresses,
+        }
+    }
+
+    /// Create a new [`WatchOnlySecretManager`] from the Ed25519 public keys of the public and internal addresses of an
+    /// account, ordered by address index.
+    pub fn from_public_keys(
+        coin_type: u32,
+        account_index: u32,
+        public_keys: &[[u8; 32]],
+        internal_public_keys: &[[u8; 32]],
+    ) -> Self {
+        let to_addresses = |public_keys: &[[u8; 32]]| {
+            public_keys
+                .iter()
+                .map(|public_key| Ed25519Address::from_public_key_bytes(*public_key))
+                .collect()
+        };
+
+        Self::new(
+            coin_type,
+            account_index,
+            to_addresses(public_keys),
+            to_addresses(internal_public_keys),
+        )
+    }
+
+    /// The coin type of the account.
+    pub fn coin_type(&self) -> u32 {
+        self.coin_type
+    }
+
+    /// The index of the account.
+    pub fn account_index(&self) -> u32 {
+        self.account_index
+    }
+
+    /// The public addresses of the account, ordered by address index.
+    pub fn public_addresses(&self) -> &[Ed25519Address] {
+        &self.public_addresses
+    }
+
+    /// The internal addresses of the account, ordered by address index.
+    pub fn internal_addresses(&self) -> &[Ed25519Address] {
+        &self.internal_addresses
+    }
+
+    fn address(
+        &self,
+        coin_type: u32,
+        account_index: u32,
+        internal: bool,
+        address_index: u32,
+    ) -> Result<Ed25519Address> {
+        let addresses = if internal {
+            &self.internal_addresses
+        } else {
+            &self.public_addresses
+        };
+
+        addresses
+            .get(address_index as usize)
+            .filter(|_| coin_type == self.coin_type && account_index == self.account_index)
+            .copied()
+            .ok_or(Error::WatchOnlyAddressUnknown {
+                coin_type,
+                account_index,
+                internal,
+                address_index,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::IOTA_COIN_TYPE, secret::mnemonic::MnemonicSecretManager};
+
+    #[tokio::test]
+    async fn watch_only_addresses() {
+        let mnemonic_secret_manager = MnemonicSecretManager::try_from_hex_seed(
+            "0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2",
+        )
+        .unwrap();
+        let to_ed25519 = |addresses: Vec<Address>| {
+            addresses
+                .into_iter()
+                .map(|address| match address {
+                    Address::Ed25519(address) => address,
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let public_addresses = mnemonic_secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..3, false, None)
+            .await
+            .unwrap();
+        let internal_addresses = mnemonic_secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..2, true, None)
+            .await
+            .unwrap();
+
+        let secret_manager = WatchOnlySecretManager::new(
+            IOTA_COIN_TYPE,
+            0,
+            to_ed25519(public_addresses.clone()),
+            to_ed25519(internal_addresses.clone()),
+        );
+
+        assert_eq!(
+            secret_manager
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..3, false, None)
+                .await
+                .unwrap(),
+            public_addresses
+        );
+        assert_eq!(
+            secret_manager
+                .generate_addresses(IOTA_COIN_TYPE, 0, 1..2, true, None)
+                .await
+                .unwrap(),
+            internal_addresses[1..].to_vec()
+        );
+        assert!(matches!(
+            secret_manager
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..3, true, None)
+                .await,
+            Err(Error::WatchOnlyAddressUnknown { address_index: 2, .. })
+        ));
+        assert!(matches!(
+            secret_manager
+                .generate_addresses(IOTA_COIN_TYPE, 1, 0..1, false, None)
+                .await,
+            Err(Error::WatchOnlyAddressUnknown { account_index: 1, .. })
+        ));
+        assert!(matches!(
+            secret_manager
+                .sign_ed25519(b"message", &Chain::from_u32_hardened(vec![]))
+                .await,
+            Err(Error::WatchOnlySigningUnsupported)
+        ));
+    }
+
+    #[test]
+    fn from_public_keys() {
+        let public_key = [1; 32];
+        let secret_manager = WatchOnlySecretManager::from_public_keys(IOTA_COIN_TYPE, 0, &[public_key], &[]);
+
+        assert!(secret_manager.public_addresses()[0].matches_public_key(&public_key));
+        assert!(secret_manager.internal_addresses().is_empty());
+    }
+}
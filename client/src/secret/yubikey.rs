@@ -0,0 +1,284 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of [`YubikeySecretManager`].
+//!
+//! The Ed25519 key is resident in the signature slot of the OpenPGP application of a YubiKey (firmware 5.2.3 or
+//! later) and never leaves it, e.g. generated with `gpg --card-edit` or `ykman openpgp keys`. The YubiKey is driven
+//! over PC/SC, so `pcscd` has to be running on Linux.
+//!
+//! The touch policy of the signature key is read when the YubiKey is opened: if a touch is required, it has to be
+//! given for each signature, within the 15 seconds after which the YubiKey gives up and
+//! [`Error::YubikeyTouchTimeout`] is returned.
+//!
+//! As a YubiKey holds a single key rather than a seed, there is no key derivation: all accounts and address indexes
+//! map to the one address of the key.
+
+use std::{ops::Range, sync::Mutex};
+
+use async_trait::async_trait;
+use crypto::{
+    hashes::{blake2b::Blake2b256, Digest},
+    keys::slip10::Chain,
+};
+use iota_types::block::{
+    address::{Address, Ed25519Address},
+    signature::{Ed25519Signature, Signature},
+    unlock::{SignatureUnlock, Unlock},
+};
+use pcsc::{Card, Context, Protocols, Scope, ShareMode, MAX_BUFFER_SIZE};
+use zeroize::Zeroizing;
+
+use super::{types::InputSigningData, GenerateAddressOptions, SecretManage};
+use crate::{secret::RemainderData, Error, Result};
+
+/// SELECT of the OpenPGP application.
+const SELECT_OPENPGP: [u8; 11] = [0x00, 0xa4, 0x04, 0x00, 0x06, 0xd2, 0x76, 0x00, 0x01, 0x24, 0x01];
+/// GENERATE ASYMMETRIC KEY PAIR, reading the public key of the signature slot.
+const READ_SIGNATURE_PUBLIC_KEY: [u8; 8] = [0x00, 0x47, 0x81, 0x00, 0x02, 0xb6, 0x00, 0x00];
+/// GET DATA of the user interaction flag of the signature slot, i.e. its touch policy.
+const GET_SIGNATURE_TOUCH_POLICY: [u8; 5] = [0x00, 0xca, 0x00, 0xd6, 0x00];
+/// The status word of a successful command.
+const SW_SUCCESS: u16 = 0x9000;
+/// The status word of a wrong PIN.
+const SW_SECURITY_STATUS_NOT_SATISFIED: u16 = 0x6982;
+/// The status word of a signature whose touch hasn't been given in time.
+const SW_CONDITIONS_OF_USE_NOT_SATISFIED: u16 = 0x6985;
+/// The length of an Ed25519 public key.
+const PUBLIC_KEY_LENGTH: usize = 32;
+
+/// The touch policy of the signature key of a YubiKey.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TouchPolicy {
+    /// No touch is required.
+    Off,
+    /// A touch is required for each signature.
+    On,
+    /// A touch is required for each signature, and the policy can't be changed.
+    Fixed,
+    /// A touch is required, then cached for 15 seconds.
+    Cached,
+    /// A touch is required, then cached for 15 seconds, and the policy can't be changed.
+    CachedFixed,
+}
+
+impl TouchPolicy {
+    /// Whether a touch may be required to sign.
+    pub fn requires_touch(&self) -> bool {
+        *self != Self::Off
+    }
+}
+
+/// Secret manager that signs with an Ed25519 key resident on a YubiKey.
+pub struct YubikeySecretManager {
+    /// The connection with the YubiKey; commands can't be interleaved.
+    card: Mutex<Card>,
+    /// The user PIN, verified before each signature.
+    pin: Zeroizing<String>,
+    /// The touch policy of the signature key.
+    touch_policy: TouchPolicy,
+    /// Called before a signature requiring a touch, e.g. to prompt the user.
+    touch_prompt: Option<Box<dyn Fn() + Send + Sync>>,
+    /// The public key.
+    public_key: [u8; PUBLIC_KEY_LENGTH],
+    /// The address of the public key.
+    address: Address,
+}
+
+impl YubikeySecretManager {
+    /// Open the first YubiKey connected, signing after verifying `pin`.
+    pub fn new(pin: &str) -> Result<Self> {
+        Self::with_reader(None, pin)
+    }
+
+    /// Open the YubiKey in the PC/SC reader whose name contains `reader`, or the first one if `None`, signing after
+    /// verifying `pin`.
+    pub fn with_reader(reader: Option<&str>, pin: &str) -> Result<Self> {
+        let context = Context::establish(Scope::User)?;
+        let reader = context
+            .list_readers_owned()?
+            .into_iter()
+            .find(|name| {
+                let name = name.to_string_lossy();
+                match reader {
+                    Some(reader) => name.contains(reader),
+                    None => name.contains("Yubico") || name.contains("YubiKey"),
+                }
+            })
+            .ok_or(Error::YubikeyNotFound)?;
+        let card = context.connect(&reader, ShareMode::Shared, Protocols::ANY)?;
+
+        transmit(&card, &SELECT_OPENPGP)?;
+
+        let touch_policy = match transmit(&card, &GET_SIGNATURE_TOUCH_POLICY)?.first() {
+            Some(0x01) => TouchPolicy::On,
+            Some(0x02) => TouchPolicy::Fixed,
+            Some(0x03) => TouchPolicy::Cached,
+            Some(0x04) => TouchPolicy::CachedFixed,
+            _ => TouchPolicy::Off,
+        };
+        let public_key = decode_public_key(&transmit(&card, &READ_SIGNATURE_PUBLIC_KEY)?)?;
+
+        // Hash the public key to get the address
+        let address = Blake2b256::digest(public_key).try_into().map_err(|_e| {
+            crate::Error::Blake2b256Error("hashing the public key while generating the address failed.")
+        })?;
+        let address = Address::Ed25519(Ed25519Address::new(address));
+
+        Ok(Self {
+            card: Mutex::new(card),
+            pin: Zeroizing::new(pin.to_string()),
+            touch_policy,
+            touch_prompt: None,
+            public_key,
+            address,
+        })
+    }
+
+    /// Call `touch_prompt` before each signature requiring a touch, e.g. to ask the user to touch the YubiKey.
+    pub fn with_touch_prompt(mut self, touch_prompt: impl Fn() + Send + Sync + 'static) -> Self {
+        self.touch_prompt = Some(Box::new(touch_prompt));
+        self
+    }
+
+    /// The touch policy of the signature key.
+    pub fn touch_policy(&self) -> TouchPolicy {
+        self.touch_policy
+    }
+
+    /// The address of the key.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<[u8; 64]> {
+        // Checked before the VERIFY, which would otherwise be spent on a command the YubiKey can't take
+        let compute_signature = compute_signature_command(message)?;
+        let card = self.card.lock().map_err(|_| Error::PoisonError)?;
+
+        // VERIFY of PW1 for a signature, which is only valid for one signature by default
+        let verify =
+            Zeroizing::new([&[0x00, 0x20, 0x00, 0x81, self.pin.len() as u8][..], self.pin.as_bytes()].concat());
+        match transmit(&card, &verify) {
+            Err(Error::YubikeyStatus(SW_SECURITY_STATUS_NOT_SATISFIED)) => return Err(Error::YubikeyPinInvalid),
+            result => result?,
+        };
+
+        if self.touch_policy.requires_touch() {
+            log::debug!("[YubikeySecretManager] waiting for a touch");
+            if let Some(touch_prompt) = &self.touch_prompt {
+                touch_prompt();
+            }
+        }
+
+        let signature = match transmit(&card, &compute_signature) {
+            Err(Error::YubikeyStatus(SW_CONDITIONS_OF_USE_NOT_SATISFIED)) if self.touch_policy.requires_touch() => {
+                return Err(Error::YubikeyTouchTimeout);
+            }
+            result => result?,
+        };
+
+        signature.try_into().map_err(|_| Error::YubikeyInvalidSignature)
+    }
+}
+
+/// Build the PSO: COMPUTE DIGITAL SIGNATURE command for `message`, whose length has to fit in the single byte of a
+/// short APDU.
+fn compute_signature_command(message: &[u8]) -> Result<Vec<u8>> {
+    let length = u8::try_from(message.len()).map_err(|_| Error::YubikeyMessageTooLong(message.len()))?;
+
+    Ok([&[0x00, 0x2a, 0x9e, 0x9a, length][..], message, &[0x00]].concat())
+}
+
+/// Send the APDU `command` to `card`, returning the data of the response.
+fn transmit(card: &Card, command: &[u8]) -> Result<Vec<u8>> {
+    let mut buffer = [0; MAX_BUFFER_SIZE];
+    let response = card.transmit(command, &mut buffer)?;
+
+    match response {
+        [data @ .., sw1, sw2] if u16::from_be_bytes([*sw1, *sw2]) == SW_SUCCESS => Ok(data.to_vec()),
+        [.., sw1, sw2] => Err(Error::YubikeyStatus(u16::from_be_bytes([*sw1, *sw2]))),
+        _ => Err(Error::YubikeyStatus(0)),
+    }
+}
+
+/// Decode the public key template `7F49 { 86 <public key> }` of an Ed25519 key.
+fn decode_public_key(template: &[u8]) -> Result<[u8; PUBLIC_KEY_LENGTH]> {
+    match template {
+        [0x7f, 0x49, _, 0x86, length, public_key @ ..] if *length as usize == PUBLIC_KEY_LENGTH => {
+            public_key.try_into().map_err(|_| Error::YubikeyInvalidPublicKey)
+        }
+        _ => Err(Error::YubikeyInvalidPublicKey),
+    }
+}
+
+#[async_trait]
+impl SecretManage for YubikeySecretManager {
+    async fn generate_addresses(
+        &self,
+        _coin_type: u32,
+        _account_index: u32,
+        address_indexes: Range<u32>,
+        _internal: bool,
+        _: Option<GenerateAddressOptions>,
+    ) -> crate::Result<Vec<Address>> {
+        Ok(address_indexes.map(|_| self.address).collect())
+    }
+
+    async fn signature_unlock(
+        &self,
+        input: &InputSigningData,
+        essence_hash: &[u8; 32],
+        _: &Option<RemainderData>,
+    ) -> crate::Result<Unlock> {
+        let (_, input_address) = Address::try_from_bech32(&input.bech32_address)?;
+
+        if input_address != self.address {
+            return Err(Error::YubikeyAddressMismatch(input.bech32_address.clone()));
+        }
+
+        // The signature unlock block needs to sign the hash of the entire transaction essence of the
+        // transaction payload
+        let signature = self.sign(essence_hash)?;
+
+        Ok(Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(
+            Ed25519Signature::new(self.public_key, signature),
+        ))))
+    }
+
+    // The YubiKey holds a single key, the chain is irrelevant
+    async fn sign_ed25519(&self, message: &[u8], _: &Chain) -> crate::Result<Ed25519Signature> {
+        Ok(Ed25519Signature::new(self.public_key, self.sign(message)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_key_template() {
+        let public_key = [42; PUBLIC_KEY_LENGTH];
+        let template = [&[0x7f, 0x49, 0x22, 0x86, 0x20][..], &public_key].concat();
+
+        assert_eq!(decode_public_key(&template).unwrap(), public_key);
+        // An RSA key
+        assert!(matches!(
+            decode_public_key(&[0x7f, 0x49, 0x03, 0x81, 0x01, 0x00]),
+            Err(Error::YubikeyInvalidPublicKey)
+        ));
+    }
+
+    #[test]
+    fn compute_signature_command_length() {
+        let command = compute_signature_command(&[7; 255]).unwrap();
+
+        assert_eq!(&command[..5], &[0x00, 0x2a, 0x9e, 0x9a, 0xff]);
+        assert_eq!(command.len(), 5 + 255 + 1);
+        assert!(matches!(
+            compute_signature_command(&[7; 256]),
+            Err(Error::YubikeyMessageTooLong(256))
+        ));
+    }
+}
@@ -3,20 +3,26 @@
 
 //! Commonly used constants and utilities.
 
-use iota_stronghold::KeyProvider;
-use zeroize::Zeroize;
+use std::{
+    fs,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
 
-/// Stronghold vault path to secrets.
+use iota_stronghold::{KeyProvider, Location};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Stronghold vault path to secrets, by default.
 ///
 /// The value has been hard-coded historically.
 pub(super) const SECRET_VAULT_PATH: &[u8] = b"iota-wallet-secret";
 
-/// Stronghold record path to a seed.
+/// Stronghold record path to a seed, by default.
 ///
 /// The value has been hard-coded historically.
 pub(super) const SEED_RECORD_PATH: &[u8] = b"iota-wallet-seed";
 
-/// Stronghold record path to a derived SLIP-10 private key.
+/// Stronghold record path to a derived SLIP-10 private key, by default.
 ///
 /// The value has been hard-coded historically.
 pub(super) const DERIVE_OUTPUT_RECORD_PATH: &[u8] = b"iota-wallet-derived";
@@ -26,20 +32,147 @@ pub(super) const DERIVE_OUTPUT_RECORD_PATH: &[u8] = b"iota-wallet-derived";
 /// The value has been hard-coded historically.
 pub(super) const PRIVATE_DATA_CLIENT_PATH: &[u8] = b"iota_seed";
 
+/// The client path keeping track of the client paths (namespaces) created in a snapshot.
+pub(super) const CLIENT_PATH_REGISTRY_CLIENT_PATH: &[u8] = b"iota_client_paths";
+
+/// Store key of the JSON encoded list of client paths, in the [`CLIENT_PATH_REGISTRY_CLIENT_PATH`] client.
+pub(super) const CLIENT_PATH_REGISTRY_KEY: &[u8] = b"client_paths";
+
+/// Store key of the JSON encoded compressions of the stores of the client paths, in the
+/// [`CLIENT_PATH_REGISTRY_CLIENT_PATH`] client.
+pub(super) const COMPRESSION_REGISTRY_KEY: &[u8] = b"compressions";
+
+/// The PBKDF2 salt used when no other has been configured.
+///
+/// The value has been hard-coded historically.
 const PBKDF_SALT: &[u8] = b"wallet.rs";
-const PBKDF_ITER: usize = 100;
+
+/// The number of PBKDF2 iterations used when no other has been configured.
+///
+/// The value has been hard-coded historically.
+const PBKDF_ITER: u32 = 100;
+
+/// The extension appended to a snapshot path to get the path of the file recording its key derivation parameters.
+const KEY_DERIVATION_PARAMETERS_EXTENSION: &str = ".kdf";
+
+/// The PBKDF2 parameters used to derive the key of a snapshot from a password.
+///
+/// Parameters differing from the default ones are recorded in a file next to the snapshot (the snapshot path with a
+/// `.kdf` suffix) when it's created, so that it can be opened later on without configuring them again.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyDerivationParameters {
+    /// The salt.
+    pub salt: Vec<u8>,
+    /// The number of iterations.
+    pub iterations: NonZeroU32,
+}
+
+impl Default for KeyDerivationParameters {
+    fn default() -> Self {
+        Self {
+            salt: PBKDF_SALT.to_vec(),
+            // PANIC: the number of iterations is not zero.
+            iterations: NonZeroU32::new(PBKDF_ITER).unwrap(),
+        }
+    }
+}
+
+impl KeyDerivationParameters {
+    /// Read the parameters recorded for the snapshot at `snapshot_path`, if any.
+    pub(super) fn read_recorded(snapshot_path: &Path) -> crate::Result<Option<Self>> {
+        let path = recorded_parameters_path(snapshot_path);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+    }
+
+    /// Record the parameters for the snapshot at `snapshot_path`, unless they are the default ones.
+    pub(super) fn record(&self, snapshot_path: &Path) -> crate::Result<()> {
+        let path = recorded_parameters_path(snapshot_path);
+
+        if *self == Self::default() {
+            // Don't leave stale parameters behind, e.g. when exporting to a previously used path.
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        } else {
+            fs::write(path, serde_json::to_vec(self)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The vault and record paths of the secrets in a snapshot.
+///
+/// The historical paths are used by default; others can be configured to open snapshots created by other tooling.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultPaths {
+    /// The vault path to the secrets.
+    pub secret_vault_path: Vec<u8>,
+    /// The record path to the seed.
+    pub seed_record_path: Vec<u8>,
+    /// The record path to a derived SLIP-10 private key.
+    pub derive_output_record_path: Vec<u8>,
+}
+
+impl Default for VaultPaths {
+    fn default() -> Self {
+        Self {
+            secret_vault_path: SECRET_VAULT_PATH.to_vec(),
+            seed_record_path: SEED_RECORD_PATH.to_vec(),
+            derive_output_record_path: DERIVE_OUTPUT_RECORD_PATH.to_vec(),
+        }
+    }
+}
+
+impl VaultPaths {
+    /// The location of the seed.
+    pub(super) fn seed_location(&self) -> Location {
+        Location::generic(self.secret_vault_path.clone(), self.seed_record_path.clone())
+    }
+
+    /// The location of a derived SLIP-10 private key.
+    pub(super) fn derive_location(&self) -> Location {
+        Location::generic(self.secret_vault_path.clone(), self.derive_output_record_path.clone())
+    }
+}
+
+fn recorded_parameters_path(snapshot_path: &Path) -> PathBuf {
+    let mut path = snapshot_path.as_os_str().to_owned();
+    path.push(KEY_DERIVATION_PARAMETERS_EXTENSION);
+
+    PathBuf::from(path)
+}
 
 /// Hash a password, deriving a key, for accessing Stronghold.
-pub(super) fn key_provider_from_password(password: &str) -> KeyProvider {
+pub(super) fn key_provider_from_password(password: &str, parameters: &KeyDerivationParameters) -> KeyProvider {
+    // PANIC: the passphrase length is guaranteed to be 32.
+    KeyProvider::with_passphrase_truncated(derive_key(password, parameters).to_vec()).unwrap()
+}
+
+/// Derive the raw key of a password, as used by Stronghold and, historically, by wallet.rs.
+pub(super) fn derive_key(password: &str, parameters: &KeyDerivationParameters) -> Zeroizing<[u8; 32]> {
     let mut buffer = [0u8; 64];
 
     // Safe to unwrap because rounds > 0.
-    crypto::keys::pbkdf::PBKDF2_HMAC_SHA512(password.as_bytes(), PBKDF_SALT, PBKDF_ITER, buffer.as_mut()).unwrap();
+    crypto::keys::pbkdf::PBKDF2_HMAC_SHA512(
+        password.as_bytes(),
+        &parameters.salt,
+        parameters.iterations.get() as usize,
+        buffer.as_mut(),
+    )
+    .unwrap();
 
-    // PANIC: the passphrase length is guaranteed to be 32.
-    let key_provider = KeyProvider::with_passphrase_truncated(buffer[..32].to_vec()).unwrap();
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&buffer[..32]);
 
     buffer.zeroize();
 
-    key_provider
+    key
 }
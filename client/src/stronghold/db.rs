@@ -8,67 +8,216 @@ use std::ops::Deref;
 use async_trait::async_trait;
 use crypto::ciphers::chacha;
 
-use super::{common::PRIVATE_DATA_CLIENT_PATH, StrongholdAdapter};
-use crate::{db::DatabaseProvider, Error, Result};
+use super::{check_store_compression, StrongholdAdapter};
+use crate::{
+    db::{BatchOperation, DatabaseProvider},
+    Error, Result,
+};
 
 #[async_trait]
 impl DatabaseProvider for StrongholdAdapter {
-    async fn get(&mut self, k: &[u8]) -> Result<Option<Vec<u8>>> {
-        let data = match self
-            .stronghold
-            .lock()
-            .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .store()
-            .get(k)?
-        {
-            Some(data) => data,
-            None => return Ok(None),
-        };
-
-        let locked_key_provider = self.key_provider.lock().await;
-        let key_provider = if let Some(key_provider) = &*locked_key_provider {
-            key_provider
-        } else {
-            return Err(Error::StrongholdKeyCleared);
-        };
-        let buffer = key_provider.try_unlock()?;
-        let buffer_ref = buffer.borrow();
-
-        Ok(Some(chacha::aead_decrypt(buffer_ref.deref(), &data)?))
-    }
+    async fn get(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        let client_path = self.client_path.clone();
+        let k = k.to_vec();
+        let compression = self.compression;
+
+        self.run_operation(move |stronghold, key_provider| {
+            check_store_compression(stronghold, &client_path, compression)?;
 
-    async fn insert(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>> {
-        let encrypted_value = {
-            let locked_key_provider = self.key_provider.lock().await;
-            let key_provider = if let Some(key_provider) = &*locked_key_provider {
-                key_provider
-            } else {
-                return Err(Error::StrongholdKeyCleared);
+            let data = match stronghold.get_client(&client_path)?.store().get(&k)? {
+                Some(data) => data,
+                None => return Ok(None),
             };
+
+            let key_provider = key_provider.ok_or(Error::StrongholdKeyCleared)?;
             let buffer = key_provider.try_unlock()?;
             let buffer_ref = buffer.borrow();
 
-            chacha::aead_encrypt(buffer_ref.deref(), v)?
-        };
+            let value = chacha::aead_decrypt(buffer_ref.deref(), &data)?;
 
-        Ok(self
-            .stronghold
-            .lock()
-            .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .store()
-            .insert(k.to_vec(), encrypted_value, None)?)
+            Ok(Some(compression.decompress(value)?))
+        })
+        .await
+    }
+
+    async fn insert(&self, k: &[u8], v: &[u8]) -> Result<Option<Vec<u8>>> {
+        let client_path = self.client_path.clone();
+        let k = k.to_vec();
+        let compression = self.compression;
+        let v = compression.compress(v)?;
+
+        let old_value = self
+            .run_operation(move |stronghold, key_provider| {
+                check_store_compression(stronghold, &client_path, compression)?;
+
+                let encrypted_value = {
+                    let key_provider = key_provider.ok_or(Error::StrongholdKeyCleared)?;
+                    let buffer = key_provider.try_unlock()?;
+                    let buffer_ref = buffer.borrow();
+
+                    chacha::aead_encrypt(buffer_ref.deref(), &v)?
+                };
+
+                Ok(stronghold
+                    .get_client(&client_path)?
+                    .store()
+                    .insert(k, encrypted_value, None)?)
+            })
+            .await?;
+
+        self.persist_store_change().await?;
+
+        Ok(old_value)
+    }
+
+    async fn delete(&self, k: &[u8]) -> Result<Option<Vec<u8>>> {
+        let client_path = self.client_path.clone();
+        let k = k.to_vec();
+
+        let old_value = self
+            .run_operation(move |stronghold, _| Ok(stronghold.get_client(&client_path)?.store().delete(&k)?))
+            .await?;
+
+        self.persist_store_change().await?;
+
+        Ok(old_value)
+    }
+
+    async fn insert_batch(&self, records: &[(Vec<u8>, Vec<u8>)]) -> Result<()> {
+        let client_path = self.client_path.clone();
+        let records = records.to_vec();
+        let compression = self.compression;
+
+        self.run_operation(move |stronghold, key_provider| {
+            check_store_compression(stronghold, &client_path, compression)?;
+
+            let encrypted_records = {
+                let key_provider = key_provider.ok_or(Error::StrongholdKeyCleared)?;
+                let buffer = key_provider.try_unlock()?;
+                let buffer_ref = buffer.borrow();
+
+                records
+                    .into_iter()
+                    .map(|(k, v)| Ok((k, chacha::aead_encrypt(buffer_ref.deref(), &compression.compress(&v)?)?)))
+                    .collect::<Result<Vec<_>>>()?
+            };
+
+            let store = stronghold.get_client(&client_path)?.store();
+
+            for (k, encrypted_value) in encrypted_records {
+                store.insert(k, encrypted_value, None)?;
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        // The snapshot is persisted once for the whole batch.
+        self.persist_store_change().await
+    }
+
+    async fn delete_batch(&self, keys: &[Vec<u8>]) -> Result<()> {
+        let client_path = self.client_path.clone();
+        let keys = keys.to_vec();
+
+        self.run_operation(move |stronghold, _| {
+            let store = stronghold.get_client(&client_path)?.store();
+
+            for k in keys {
+                store.delete(&k)?;
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        // The snapshot is persisted once for the whole batch.
+        self.persist_store_change().await
+    }
+
+    async fn apply_batch(&self, operations: Vec<BatchOperation>) -> Result<()> {
+        let client_path = self.client_path.clone();
+        let compression = self.compression;
+
+        self.run_operation(move |stronghold, key_provider| {
+            check_store_compression(stronghold, &client_path, compression)?;
+
+            // Encrypt all values first, so that nothing is changed if that fails.
+            let operations = {
+                let key_provider = key_provider.ok_or(Error::StrongholdKeyCleared)?;
+                let buffer = key_provider.try_unlock()?;
+                let buffer_ref = buffer.borrow();
+
+                operations
+                    .into_iter()
+                    .map(|operation| match operation {
+                        BatchOperation::Put { key, value } => Ok(BatchOperation::Put {
+                            key,
+                            value: chacha::aead_encrypt(buffer_ref.deref(), &compression.compress(&value)?)?,
+                        }),
+                        operation => Ok(operation),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            };
+
+            let store = stronghold.get_client(&client_path)?.store();
+
+            for operation in operations {
+                match operation {
+                    BatchOperation::Put { key, value } => store.insert(key, value, None)?,
+                    BatchOperation::Delete { key } => store.delete(&key)?,
+                };
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        // The changes are in the snapshot as a whole, or not at all.
+        self.persist_store_change().await
     }
 
-    async fn delete(&mut self, k: &[u8]) -> Result<Option<Vec<u8>>> {
-        Ok(self
-            .stronghold
-            .lock()
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        let client_path = self.client_path.clone();
+
+        self.run_operation(move |stronghold, _| Ok(stronghold.get_client(&client_path)?.store().keys()?))
             .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .store()
-            .delete(k)?)
+    }
+
+    async fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let client_path = self.client_path.clone();
+        let prefix = prefix.to_vec();
+        let compression = self.compression;
+
+        // Read all the records in a single operation, rather than key by key, so that they're consistent with each
+        // other and the key is unlocked only once.
+        self.run_operation(move |stronghold, key_provider| {
+            check_store_compression(stronghold, &client_path, compression)?;
+
+            let store = stronghold.get_client(&client_path)?.store();
+            let mut keys = store.keys()?;
+            keys.retain(|key| key.starts_with(&prefix));
+            keys.sort();
+
+            if keys.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let key_provider = key_provider.ok_or(Error::StrongholdKeyCleared)?;
+            let buffer = key_provider.try_unlock()?;
+            let buffer_ref = buffer.borrow();
+            let mut records = Vec::with_capacity(keys.len());
+
+            for key in keys {
+                if let Some(data) = store.get(&key)? {
+                    let value = chacha::aead_decrypt(buffer_ref.deref(), &data)?;
+                    records.push((key, compression.decompress(value)?));
+                }
+            }
+
+            Ok(records)
+        })
+        .await
     }
 }
 
@@ -81,7 +230,7 @@ mod tests {
         use crate::db::DatabaseProvider;
 
         let snapshot_path = "test_stronghold_db.stronghold";
-        let mut stronghold = StrongholdAdapter::builder()
+        let stronghold = StrongholdAdapter::builder()
             .password("drowssap")
             .build(snapshot_path)
             .unwrap();
@@ -98,6 +247,15 @@ mod tests {
         assert!(matches!(stronghold.get(b"test-1").await, Ok(Some(_))));
         assert!(matches!(stronghold.get(b"test-2").await, Ok(Some(_))));
 
+        let mut keys = stronghold.keys().await.unwrap();
+        keys.sort();
+        assert_eq!(keys, vec![b"test-0".to_vec(), b"test-1".to_vec(), b"test-2".to_vec()]);
+        assert_eq!(
+            stronghold.iter_prefix(b"test-1").await.unwrap(),
+            vec![(b"test-1".to_vec(), b"test-1".to_vec())]
+        );
+        assert!(stronghold.iter_prefix(b"other").await.unwrap().is_empty());
+
         assert!(matches!(stronghold.insert(b"test-0", b"0-tset").await, Ok(Some(_))));
         assert!(matches!(stronghold.insert(b"test-1", b"1-tset").await, Ok(Some(_))));
         assert!(matches!(stronghold.insert(b"test-2", b"2-tset").await, Ok(Some(_))));
@@ -112,4 +270,39 @@ mod tests {
 
         fs::remove_file(snapshot_path).unwrap();
     }
+
+    #[tokio::test]
+    async fn test_stronghold_db_batch() {
+        use std::fs;
+
+        use super::StrongholdAdapter;
+        use crate::db::DatabaseProvider;
+
+        let snapshot_path = "test_stronghold_db_batch.stronghold";
+        let stronghold = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(snapshot_path)
+            .unwrap();
+
+        let records = (0..100)
+            .map(|i| (format!("test-{i}").into_bytes(), i.to_string().into_bytes()))
+            .collect::<Vec<_>>();
+        stronghold.insert_batch(&records).await.unwrap();
+        assert_eq!(stronghold.iter_prefix(b"test-").await.unwrap().len(), 100);
+        assert_eq!(stronghold.get(b"test-42").await.unwrap(), Some(b"42".to_vec()));
+
+        let keys = records.iter().take(50).map(|(k, _)| k.clone()).collect::<Vec<_>>();
+        stronghold.delete_batch(&keys).await.unwrap();
+        assert_eq!(stronghold.iter_prefix(b"test-").await.unwrap().len(), 50);
+        assert_eq!(stronghold.get(b"test-42").await.unwrap(), None);
+        assert_eq!(stronghold.get(b"test-99").await.unwrap(), Some(b"99".to_vec()));
+
+        let mut batch = stronghold.batch();
+        batch.put(b"test-0", b"0").delete(b"test-99");
+        batch.commit().await.unwrap();
+        assert_eq!(stronghold.get(b"test-0").await.unwrap(), Some(b"0".to_vec()));
+        assert_eq!(stronghold.get(b"test-99").await.unwrap(), None);
+
+        fs::remove_file(snapshot_path).unwrap();
+    }
 }
@@ -0,0 +1,297 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Diagnostics of a Stronghold snapshot, for support tooling.
+//!
+//! Only the format of the snapshot, the presence of records and the integrity of the file are inspected; records are
+//! never read, so no secret material can be exposed.
+
+use std::{
+    fs::File,
+    io::{ErrorKind, Read},
+    path::Path,
+};
+
+use iota_stronghold::{Location, SnapshotPath, Stronghold};
+
+use super::{common::PRIVATE_DATA_CLIENT_PATH, registered_client_paths, StrongholdAdapter};
+use crate::{Error, Result};
+
+/// The magic bytes at the start of a Stronghold snapshot file.
+const SNAPSHOT_MAGIC: &[u8] = b"PARTI";
+
+/// The version of the snapshot format written by the Stronghold version in use.
+const CURRENT_SNAPSHOT_VERSION: u16 = 2;
+
+/// The diagnostics of a Stronghold snapshot, see [`StrongholdAdapter::snapshot_diagnostics()`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiagnostics {
+    /// The format version of the snapshot file, if it exists and is a Stronghold snapshot.
+    pub snapshot_version: Option<u16>,
+    /// Whether the snapshot has been written by another Stronghold version, e.g. an older one by wallet.rs, whose
+    /// format can't be opened by the version in use.
+    pub unsupported_version: bool,
+    /// The known vault records of all client paths of the snapshot. Empty if its version is unsupported.
+    pub records: Vec<VaultRecord>,
+}
+
+/// A known vault record of a snapshot.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultRecord {
+    /// The client path (namespace) of the record.
+    pub client_path: String,
+    /// The vault path of the record.
+    pub vault_path: String,
+    /// The record path.
+    pub record_path: String,
+    /// What the record holds.
+    pub description: &'static str,
+    /// Whether the record is present.
+    pub present: bool,
+}
+
+impl StrongholdAdapter {
+    /// Inspect the snapshot at `snapshot_path`: its format version and which known vault records are present in each
+    /// of its client paths, e.g. to verify that a seed has been stored, or that a snapshot can be opened.
+    ///
+    /// The presence of records is checked without reading them; no secret material is ever exposed.
+    pub async fn snapshot_diagnostics(&self) -> Result<SnapshotDiagnostics> {
+        let snapshot_version = read_snapshot_version(&self.snapshot_path)?;
+        let unsupported_version = snapshot_version.map_or(false, |version| version != CURRENT_SNAPSHOT_VERSION);
+
+        if unsupported_version {
+            // The snapshot can't be loaded.
+            return Ok(SnapshotDiagnostics {
+                snapshot_version,
+                unsupported_version,
+                records: Vec::new(),
+            });
+        }
+
+        // The key needs to be supplied first.
+        let locked_key_provider = self.key_provider.lock().await;
+        let key_provider = if let Some(key_provider) = &*locked_key_provider {
+            key_provider
+        } else {
+            return Err(Error::StrongholdKeyCleared);
+        };
+
+        let stronghold = self.stronghold.lock().await;
+        let snapshot_path = SnapshotPath::from_path(&self.snapshot_path);
+        let mut client_paths = registered_client_paths(&stronghold, key_provider, &snapshot_path)?;
+
+        // Snapshots written before client paths were registered only have the historical one.
+        if !client_paths.iter().any(|path| path == PRIVATE_DATA_CLIENT_PATH) {
+            client_paths.push(PRIVATE_DATA_CLIENT_PATH.to_vec());
+        }
+
+        // The vault records written by this crate and, historically, by wallet.rs, at the configured paths.
+        let known_records: [(&[u8], &[u8], &str); 2] = [
+            (
+                &self.vault_paths.secret_vault_path,
+                &self.vault_paths.seed_record_path,
+                "seed",
+            ),
+            (
+                &self.vault_paths.secret_vault_path,
+                &self.vault_paths.derive_output_record_path,
+                "derived private key",
+            ),
+        ];
+        let mut records = Vec::new();
+
+        for client_path in client_paths {
+            // Load the client without creating it, so that a missing one is reported as such.
+            let client = match stronghold.load_client_from_snapshot(&client_path, key_provider, &snapshot_path) {
+                Ok(client) => Some(client),
+                Err(iota_stronghold::ClientError::ClientAlreadyLoaded(_)) => Some(stronghold.get_client(&client_path)?),
+                Err(iota_stronghold::ClientError::SnapshotFileMissing(_))
+                | Err(iota_stronghold::ClientError::ClientDataNotPresent) => None,
+                Err(err) => return Err(err.into()),
+            };
+
+            for (vault_path, record_path, description) in known_records {
+                let present = match &client {
+                    Some(client) => client.record_exists(&Location::generic(vault_path, record_path))?,
+                    None => false,
+                };
+
+                records.push(VaultRecord {
+                    client_path: String::from_utf8_lossy(&client_path).into_owned(),
+                    vault_path: String::from_utf8_lossy(vault_path).into_owned(),
+                    record_path: String::from_utf8_lossy(record_path).into_owned(),
+                    description,
+                    present,
+                });
+            }
+        }
+
+        Ok(SnapshotDiagnostics {
+            snapshot_version,
+            unsupported_version,
+            records,
+        })
+    }
+}
+
+impl StrongholdAdapter {
+    /// Verify the integrity of the snapshot at `snapshot_path` with the current key, by fully decrypting and decoding
+    /// it in a separate Stronghold instance; the state in use is left untouched.
+    ///
+    /// Fails with [`Error::StrongholdSnapshotNotFound`] if the file doesn't exist,
+    /// [`Error::StrongholdInvalidPassword`] if it can't be decrypted with the key, and
+    /// [`Error::StrongholdSnapshotCorrupted`] if it isn't a valid snapshot of the format version in use. As the content
+    /// is authenticated with the key, a tampered content can't be told apart from a wrong key and is reported as
+    /// the latter.
+    pub async fn verify_snapshot(&self) -> Result<()> {
+        // The key needs to be supplied first.
+        let locked_key_provider = self.key_provider.lock().await;
+        let key_provider = if let Some(key_provider) = &*locked_key_provider {
+            key_provider
+        } else {
+            return Err(Error::StrongholdKeyCleared);
+        };
+
+        match read_snapshot_version(&self.snapshot_path)? {
+            None if !self.snapshot_path.exists() => {
+                return Err(Error::StrongholdSnapshotNotFound(
+                    self.snapshot_path.display().to_string(),
+                ));
+            }
+            None => {
+                return Err(Error::StrongholdSnapshotCorrupted(
+                    "not a stronghold snapshot".to_string(),
+                ));
+            }
+            Some(version) if version != CURRENT_SNAPSHOT_VERSION => {
+                return Err(Error::StrongholdSnapshotCorrupted(format!(
+                    "unsupported snapshot format version {version}"
+                )));
+            }
+            Some(_) => {}
+        }
+
+        match Stronghold::default().load_snapshot(key_provider, &SnapshotPath::from_path(&self.snapshot_path)) {
+            Ok(()) => Ok(()),
+            // Matching the error string is not ideal but stronghold doesn't wrap the error types at the moment.
+            Err(iota_stronghold::ClientError::Inner(ref err_msg))
+                if err_msg.to_string().contains("XCHACHA20-POLY1305") =>
+            {
+                Err(Error::StrongholdInvalidPassword)
+            }
+            Err(err) => Err(Error::StrongholdSnapshotCorrupted(err.to_string())),
+        }
+    }
+}
+
+/// Read the format version from the header of the snapshot file at `path`, if it exists and is a Stronghold snapshot.
+fn read_snapshot_version(path: &Path) -> Result<Option<u16>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let mut header = [0u8; 7];
+
+    match file.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+
+    if &header[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+        return Ok(None);
+    }
+
+    Ok(Some(u16::from_le_bytes([header[5], header[6]])))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::stronghold::{common::key_provider_from_password, KeyDerivationParameters};
+
+    #[tokio::test]
+    async fn snapshot_diagnostics() {
+        let stronghold_path = "test_snapshot_diagnostics.stronghold";
+        let legacy_path = "test_snapshot_diagnostics_legacy.stronghold";
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+
+        let diagnostics = adapter.snapshot_diagnostics().await.unwrap();
+        assert_eq!(diagnostics.snapshot_version, Some(CURRENT_SNAPSHOT_VERSION));
+        assert!(!diagnostics.unsupported_version);
+        assert!(diagnostics.records.iter().all(|record| !record.present));
+
+        let mnemonic = String::from(
+            "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
+        );
+        adapter.store_mnemonic(mnemonic).await.unwrap();
+
+        let diagnostics = adapter.snapshot_diagnostics().await.unwrap();
+        assert!(diagnostics
+            .records
+            .iter()
+            .any(|record| record.description == "seed" && record.client_path == "iota_seed" && record.present));
+
+        // A snapshot with an older format version can't be opened.
+        let mut legacy_snapshot = fs::read(stronghold_path).unwrap();
+        legacy_snapshot[5..7].copy_from_slice(&1u16.to_le_bytes());
+        fs::write(legacy_path, legacy_snapshot).unwrap();
+        let legacy_adapter = StrongholdAdapter::builder().build(legacy_path).unwrap();
+        let diagnostics = legacy_adapter.snapshot_diagnostics().await.unwrap();
+        assert_eq!(diagnostics.snapshot_version, Some(1));
+        assert!(diagnostics.unsupported_version);
+
+        fs::remove_file(stronghold_path).unwrap();
+        fs::remove_file(legacy_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_snapshot() {
+        let stronghold_path = "test_verify_snapshot.stronghold";
+        let corrupted_path = "test_verify_snapshot_corrupted.stronghold";
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        adapter.write_stronghold_snapshot(None).await.unwrap();
+        adapter.verify_snapshot().await.unwrap();
+
+        // Another key can't decrypt the snapshot.
+        let wrong_key_adapter = StrongholdAdapter::builder().build(stronghold_path).unwrap();
+        *wrong_key_adapter.key_provider.lock().await = Some(key_provider_from_password(
+            "password",
+            &KeyDerivationParameters::default(),
+        ));
+        assert!(matches!(
+            wrong_key_adapter.verify_snapshot().await,
+            Err(Error::StrongholdInvalidPassword)
+        ));
+
+        // A file without the header of a snapshot is corrupted.
+        let mut corrupted_snapshot = fs::read(stronghold_path).unwrap();
+        corrupted_snapshot[0] = b'X';
+        fs::write(corrupted_path, corrupted_snapshot).unwrap();
+        let corrupted_adapter = StrongholdAdapter::builder().build(corrupted_path).unwrap();
+        *corrupted_adapter.key_provider.lock().await = adapter.key_provider.lock().await.take();
+        assert!(matches!(
+            corrupted_adapter.verify_snapshot().await,
+            Err(Error::StrongholdSnapshotCorrupted(_))
+        ));
+
+        fs::remove_file(corrupted_path).unwrap();
+        assert!(matches!(
+            corrupted_adapter.verify_snapshot().await,
+            Err(Error::StrongholdSnapshotNotFound(_))
+        ));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Merging of the stores of Stronghold snapshots, e.g. to consolidate the backups of several devices.
+
+use std::{ops::Deref, path::Path};
+
+use crypto::ciphers::chacha;
+use iota_stronghold::{SnapshotPath, Stronghold};
+use zeroize::Zeroizing;
+
+use super::{
+    check_or_create_snapshot, check_store_compression,
+    common::{key_provider_from_password, PRIVATE_DATA_CLIENT_PATH},
+    load_or_create_client, recorded_store_compression, registered_client_paths, KeyDerivationParameters,
+    StrongholdAdapter,
+};
+use crate::{db::Compression, Error, Result};
+
+/// How to resolve a key present with different values in the stores of both merged snapshots.
+pub enum ConflictPolicy {
+    /// Keep the value of the snapshot merged into.
+    KeepOurs,
+    /// Take the value of the merged snapshot.
+    KeepTheirs,
+    /// Call back with the key, our value and their value, decompressed, returning the value to keep.
+    Resolve(Box<dyn FnMut(&[u8], &[u8], &[u8]) -> Vec<u8> + Send>),
+}
+
+impl ConflictPolicy {
+    /// The value to write for a conflicting key, if it has to change.
+    fn resolve(&mut self, key: &[u8], ours: &[u8], theirs: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Self::KeepOurs => None,
+            Self::KeepTheirs => Some(theirs.to_vec()),
+            Self::Resolve(resolve) => Some(resolve(key, ours, theirs)),
+        }
+    }
+}
+
+impl core::fmt::Debug for ConflictPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::KeepOurs => f.write_str("KeepOurs"),
+            Self::KeepTheirs => f.write_str("KeepTheirs"),
+            Self::Resolve(_) => f.write_str("Resolve(<callback>)"),
+        }
+    }
+}
+
+/// The keys affected by a merge, per client path, see [`StrongholdAdapter::merge_snapshot()`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeReport {
+    /// The keys only present in the merged snapshot, which have been added, with their client path.
+    pub added: Vec<(Vec<u8>, Vec<u8>)>,
+    /// The keys present with different values in both snapshots, resolved with the [`ConflictPolicy`], with their
+    /// client path.
+    pub conflicting: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl StrongholdAdapter {
+    /// Merge the data saved via the [`DatabaseProvider`] interface in the snapshot at `other_snapshot_path`, in all its
+    /// client paths, into this one, then write the snapshot.
+    ///
+    /// The other snapshot is opened with `other_password` and the key derivation parameters recorded next to it, and
+    /// is left untouched. Keys present with different values in both snapshots are resolved with `conflict_policy`.
+    /// The values are compared decompressed, and the merged ones are stored with the compression of the store they're
+    /// merged into. Only the stores are merged; vault records, like a seed, aren't.
+    ///
+    /// [`DatabaseProvider`]: crate::db::DatabaseProvider
+    pub async fn merge_snapshot(
+        &self,
+        other_snapshot_path: &Path,
+        other_password: &str,
+        mut conflict_policy: ConflictPolicy,
+    ) -> Result<MergeReport> {
+        let other_key_provider = key_provider_from_password(
+            other_password,
+            &KeyDerivationParameters::read_recorded(other_snapshot_path)?.unwrap_or_default(),
+        );
+        let other_stronghold = Stronghold::default();
+        let other_snapshot_path = SnapshotPath::from_path(other_snapshot_path);
+        let mut report = MergeReport::default();
+
+        {
+            // The key needs to be supplied first.
+            let locked_key_provider = self.key_provider.lock().await;
+            let key_provider = if let Some(key_provider) = &*locked_key_provider {
+                key_provider
+            } else {
+                return Err(Error::StrongholdKeyCleared);
+            };
+
+            let stronghold = self.stronghold.lock().await;
+            let snapshot_path = SnapshotPath::from_path(&self.snapshot_path);
+            let buffer = key_provider.try_unlock()?;
+            let buffer_ref = buffer.borrow();
+            let other_buffer = other_key_provider.try_unlock()?;
+            let other_buffer_ref = other_buffer.borrow();
+
+            let mut client_paths =
+                registered_client_paths(&other_stronghold, &other_key_provider, &other_snapshot_path)?;
+
+            // Snapshots written before client paths were registered only have the historical one.
+            if !client_paths.iter().any(|path| path == PRIVATE_DATA_CLIENT_PATH) {
+                client_paths.push(PRIVATE_DATA_CLIENT_PATH.to_vec());
+            }
+
+            for client_path in client_paths {
+                load_or_create_client(
+                    &other_stronghold,
+                    &other_key_provider,
+                    &other_snapshot_path,
+                    &client_path,
+                )?;
+                check_or_create_snapshot(&stronghold, key_provider, &snapshot_path, &client_path)?;
+
+                let other_store = other_stronghold.get_client(&client_path)?.store();
+                let store = stronghold.get_client(&client_path)?.store();
+
+                // Stores without a recorded compression hold uncompressed values; an empty one of ours takes the
+                // compression of theirs.
+                let their_compression =
+                    recorded_store_compression(&other_stronghold, &client_path)?.unwrap_or_default();
+                let compression = match recorded_store_compression(&stronghold, &client_path)? {
+                    Some(compression) => compression,
+                    None if store.keys()?.is_empty() => {
+                        check_store_compression(&stronghold, &client_path, their_compression)?;
+                        their_compression
+                    }
+                    None => Compression::None,
+                };
+
+                for key in other_store.keys()? {
+                    let theirs = match other_store.get(&key)? {
+                        Some(value) => Zeroizing::new(
+                            their_compression.decompress(chacha::aead_decrypt(other_buffer_ref.deref(), &value)?)?,
+                        ),
+                        None => continue,
+                    };
+
+                    let value = match store.get(&key)? {
+                        Some(value) => {
+                            let ours = Zeroizing::new(
+                                compression.decompress(chacha::aead_decrypt(buffer_ref.deref(), &value)?)?,
+                            );
+
+                            if *ours == *theirs {
+                                continue;
+                            }
+
+                            report.conflicting.push((client_path.clone(), key.clone()));
+
+                            match conflict_policy.resolve(&key, &ours, &theirs) {
+                                Some(value) => Zeroizing::new(value),
+                                None => continue,
+                            }
+                        }
+                        None => {
+                            report.added.push((client_path.clone(), key.clone()));
+                            theirs
+                        }
+                    };
+
+                    store.insert(
+                        key,
+                        chacha::aead_encrypt(buffer_ref.deref(), &compression.compress(&value)?)?,
+                        None,
+                    )?;
+                }
+            }
+        }
+
+        self.write_stronghold_snapshot(None).await?;
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::db::DatabaseProvider;
+
+    #[tokio::test]
+    async fn merge_snapshot() {
+        let ours_path = "test_merge_snapshot_ours.stronghold";
+        let theirs_path = "test_merge_snapshot_theirs.stronghold";
+
+        let ours = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(ours_path)
+            .unwrap();
+        ours.insert(b"ours", b"ours").await.unwrap();
+        ours.insert(b"same", b"same").await.unwrap();
+        ours.insert(b"conflict", b"ours").await.unwrap();
+
+        let theirs = StrongholdAdapter::builder()
+            .password("other_password")
+            .build(theirs_path)
+            .unwrap();
+        theirs.insert(b"theirs", b"theirs").await.unwrap();
+        theirs.insert(b"same", b"same").await.unwrap();
+        theirs.insert(b"conflict", b"theirs").await.unwrap();
+        theirs.write_stronghold_snapshot(None).await.unwrap();
+
+        // A wrong password for the other snapshot fails.
+        assert!(matches!(
+            ours.merge_snapshot(Path::new(theirs_path), "drowssap", ConflictPolicy::KeepTheirs)
+                .await,
+            Err(Error::StrongholdInvalidPassword)
+        ));
+
+        let report = ours
+            .merge_snapshot(Path::new(theirs_path), "other_password", ConflictPolicy::KeepOurs)
+            .await
+            .unwrap();
+        assert_eq!(
+            report.added,
+            vec![(PRIVATE_DATA_CLIENT_PATH.to_vec(), b"theirs".to_vec())]
+        );
+        assert_eq!(
+            report.conflicting,
+            vec![(PRIVATE_DATA_CLIENT_PATH.to_vec(), b"conflict".to_vec())]
+        );
+        assert_eq!(ours.get(b"theirs").await.unwrap().as_deref(), Some(&b"theirs"[..]));
+        assert_eq!(ours.get(b"conflict").await.unwrap().as_deref(), Some(&b"ours"[..]));
+
+        let report = ours
+            .merge_snapshot(
+                Path::new(theirs_path),
+                "other_password",
+                ConflictPolicy::Resolve(Box::new(|_, ours, theirs| [ours, theirs].concat())),
+            )
+            .await
+            .unwrap();
+        assert!(report.added.is_empty());
+        assert_eq!(
+            ours.get(b"conflict").await.unwrap().as_deref(),
+            Some(&b"ourstheirs"[..])
+        );
+        assert_eq!(ours.get(b"same").await.unwrap().as_deref(), Some(&b"same"[..]));
+
+        fs::remove_file(ours_path).unwrap();
+        fs::remove_file(theirs_path).unwrap();
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn merge_compressed_snapshot() {
+        let ours_path = "test_merge_compressed_snapshot_ours.stronghold";
+        let theirs_path = "test_merge_compressed_snapshot_theirs.stronghold";
+        let value = vec![42; 1024];
+
+        let ours = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(ours_path)
+            .unwrap();
+        ours.insert(b"same", &value).await.unwrap();
+        ours.insert(b"conflict", b"ours").await.unwrap();
+
+        let theirs = StrongholdAdapter::builder()
+            .password("other_password")
+            .compression(Compression::Zstd)
+            .build(theirs_path)
+            .unwrap();
+        theirs.insert(b"same", &value).await.unwrap();
+        theirs.insert(b"conflict", &value).await.unwrap();
+        theirs.insert(b"theirs", &value).await.unwrap();
+        theirs.write_stronghold_snapshot(None).await.unwrap();
+
+        // The values are compared and resolved decompressed, and stored uncompressed in ours.
+        let report = ours
+            .merge_snapshot(
+                Path::new(theirs_path),
+                "other_password",
+                ConflictPolicy::Resolve(Box::new(|_, ours, theirs| {
+                    assert_eq!(theirs, &[42; 1024][..]);
+                    ours.to_vec()
+                })),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            report.conflicting,
+            vec![(PRIVATE_DATA_CLIENT_PATH.to_vec(), b"conflict".to_vec())]
+        );
+        assert_eq!(ours.get(b"theirs").await.unwrap(), Some(value));
+        assert_eq!(ours.get(b"conflict").await.unwrap().as_deref(), Some(&b"ours"[..]));
+
+        // A compressed store can't be read without compression.
+        drop(theirs);
+        let theirs = StrongholdAdapter::builder()
+            .password("other_password")
+            .build(theirs_path)
+            .unwrap();
+        assert!(matches!(
+            theirs.get(b"theirs").await,
+            Err(Error::CompressionMismatch { .. })
+        ));
+
+        fs::remove_file(ours_path).unwrap();
+        fs::remove_file(theirs_path).unwrap();
+    }
+}
@@ -27,7 +27,8 @@
 //! With [`set_timeout()`], an automatic task can be spawned in the background to purge the key from memory using
 //! [zeroize] after the `timeout` duration. It's used to reduce the attack vector. When the key is cleared from the
 //! memory, Stronghold will be unloaded from the memory too. If no `snapshot_path` has been set at this point, then
-//! secrets stored in Stronghold will be dropped and lost.
+//! secrets stored in Stronghold will be dropped and lost. [`on_key_cleared()`] sets a callback to be notified when it
+//! happens, e.g. to prompt for the password again.
 //!
 //! Nevertheless, Stronghold is memory-based, so it's not required to use a snapshot file on the disk. Without a
 //! snapshot path set, [`StrongholdAdapter`] will run purely in memory. If a snapshot path is set, then
@@ -35,7 +36,24 @@
 //! Subsequent actions are still performed in memory. If the snapshot file doesn't exist, these function calls will all
 //! fail. To proactively load or store the Stronghold state from or to a Stronghold snapshot on disk, use
 //! [`read_stronghold_snapshot()`] or [`write_stronghold_snapshot()`]. The latter can be used to create a snapshot file
-//! after creating a [`StrongholdAdapter`] with a non-existent snapshot path.
+//! after creating a [`StrongholdAdapter`] with a non-existent snapshot path. With [`auto_persist()`] set, the snapshot
+//! is also written after each change of the store, so that no change is lost on a crash. With [`backup_retention()`]
+//! set, the previous versions of the snapshot are kept as numbered backups next to it.
+//!
+//! Stronghold operations run on the blocking thread pool of the async runtime. With [`operation_timeout()`] set, they
+//! fail with [`Error::StrongholdTimeout`] instead of blocking forever if Stronghold hangs.
+//!
+//! A snapshot can host several logical clients, e.g. several wallets or applications, each with its own secrets and
+//! data under a _client path_ (namespace). The client path to use is set with [`client_path()`] on the builder;
+//! [`client_paths()`] lists the ones created in the snapshot and [`switch_client_path()`] switches between them. All
+//! client paths of a snapshot share the same password.
+//!
+//! Snapshots written by older Stronghold versions, e.g. by wallet.rs, can't be opened. [`snapshot_diagnostics()`] tells
+//! whether a snapshot has such an older format and which known vault records it holds, without exposing any secret,
+//! and [`verify_snapshot()`] checks the integrity of the snapshot file.
+//!
+//! The seed can be moved to another snapshot, e.g. on another device, without revealing it: [`export_seed()`] writes
+//! it to a transfer snapshot encrypted with a transfer passphrase, and [`import_seed()`] reads it from there.
 //!
 //! [Stronghold]: iota_stronghold
 //! [`DatabaseProvider`]: crate::db::DatabaseProvider
@@ -45,28 +63,65 @@
 //! [`set_timeout()`]: self::StrongholdAdapter::set_timeout()
 //! [`read_stronghold_snapshot()`]: self::StrongholdAdapter::read_stronghold_snapshot()
 //! [`write_stronghold_snapshot()`]: self::StrongholdAdapter::write_stronghold_snapshot()
+//! [`auto_persist()`]: self::StrongholdAdapterBuilder::auto_persist()
+//! [`backup_retention()`]: self::StrongholdAdapterBuilder::backup_retention()
+//! [`operation_timeout()`]: self::StrongholdAdapterBuilder::operation_timeout()
+//! [`Error::StrongholdTimeout`]: crate::Error::StrongholdTimeout
+//! [`on_key_cleared()`]: self::StrongholdAdapterBuilder::on_key_cleared()
+//! [`client_path()`]: self::StrongholdAdapterBuilder::client_path()
+//! [`client_paths()`]: self::StrongholdAdapter::client_paths()
+//! [`switch_client_path()`]: self::StrongholdAdapter::switch_client_path()
+//! [`snapshot_diagnostics()`]: self::StrongholdAdapter::snapshot_diagnostics()
+//! [`verify_snapshot()`]: self::StrongholdAdapter::verify_snapshot()
+//! [`export_seed()`]: self::StrongholdAdapter::export_seed()
+//! [`import_seed()`]: self::StrongholdAdapter::import_seed()
 
 mod common;
 mod db;
+mod diagnostics;
+mod merge;
 mod secret;
+mod transfer;
 
 use std::{
+    ops::Deref,
     path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use async_trait::async_trait;
+use crypto::ciphers::chacha;
 use derive_builder::Builder;
-use iota_stronghold::{KeyProvider, SnapshotPath, Stronghold};
+use iota_stronghold::{KeyProvider, SnapshotPath, Store, Stronghold};
 use log::{debug, error, warn};
-use tokio::{sync::Mutex, task::JoinHandle};
+use tokio::sync::Mutex;
 use zeroize::Zeroizing;
 
-use self::common::PRIVATE_DATA_CLIENT_PATH;
-use crate::{db::DatabaseProvider, Error, Result};
+use self::common::{
+    CLIENT_PATH_REGISTRY_CLIENT_PATH, CLIENT_PATH_REGISTRY_KEY, COMPRESSION_REGISTRY_KEY, PRIVATE_DATA_CLIENT_PATH,
+};
+pub use self::{
+    common::{KeyDerivationParameters, VaultPaths},
+    diagnostics::{SnapshotDiagnostics, VaultRecord},
+    merge::{ConflictPolicy, MergeReport},
+};
+use crate::{
+    async_runtime::{self, TaskHandle},
+    client::BackgroundComponent,
+    db::Compression,
+    Error, Result,
+};
+
+/// The filename of a snapshot when only its directory is given, as historically used by wallet.rs.
+pub const DEFAULT_SNAPSHOT_FILENAME: &str = "wallet.stronghold";
 
 /// A wrapper on [Stronghold].
 ///
+/// The state of the adapter lives behind internal locks, so that the database, secret manager and snapshot operations
+/// only need `&self`: an adapter can be shared, e.g. in an `Arc`, and used from concurrent tasks. Only the methods
+/// changing its configuration, like [`set_timeout()`](Self::set_timeout()), need `&mut self`.
+///
 /// See the [module-level documentation](self) for more details.
 #[derive(Builder)]
 #[builder(pattern = "owned", build_fn(skip))]
@@ -79,13 +134,30 @@ pub struct StrongholdAdapter {
     ///
     /// Note that in [`StrongholdAdapterBuilder`] there isn't a `key()` setter, because we don't want a user to
     /// directly set this field. Instead, [`password()`] is provided to hash a user-input password string and
-    /// derive a key from it.
+    /// derive a key from it, with the [`key_derivation_parameters()`], when building; [`key_bytes()`] takes a key
+    /// derived by the user instead.
     ///
     /// [`password()`]: self::StrongholdAdapterBuilder::password()
+    /// [`key_derivation_parameters()`]: self::StrongholdAdapterBuilder::key_derivation_parameters()
+    /// [`key_bytes()`]: self::StrongholdAdapterBuilder::key_bytes()
     #[builder(setter(custom))]
-    #[builder(field(type = "Option<KeyProvider>"))]
+    #[builder(field(type = "Option<KeySource>"))]
     key_provider: Arc<Mutex<Option<KeyProvider>>>,
 
+    /// The PBKDF2 parameters used to derive `key` from a password.
+    ///
+    /// The parameters recorded next to an existing snapshot take precedence over the configured ones.
+    key_derivation_parameters: KeyDerivationParameters,
+
+    /// The vault and record paths of the secrets, e.g. to open a snapshot created by other tooling.
+    vault_paths: VaultPaths,
+
+    /// How the values saved via the [`DatabaseProvider`] interface are compressed before being encrypted, see
+    /// [`Compression`].
+    ///
+    /// [`DatabaseProvider`]: crate::db::DatabaseProvider
+    compression: Compression,
+
     /// An interval of time, after which `key` will be cleared from the memory.
     ///
     /// This is an extra security measure to further prevent attacks. If a timeout is set, then upon a `key` is set, a
@@ -100,27 +172,156 @@ pub struct StrongholdAdapter {
     /// Note that this field doesn't actually have a custom setter; `setter(custom)` is only for skipping the setter
     /// generation.
     #[builder(setter(custom))]
-    timeout_task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    timeout_task: Arc<Mutex<Option<TaskHandle>>>,
+
+    /// When the running key clearing task will clear `key`.
+    ///
+    /// Note that this field doesn't actually have a custom setter; `setter(custom)` is only for skipping the setter
+    /// generation.
+    #[builder(setter(custom))]
+    key_clearing_deadline: Arc<Mutex<Option<Instant>>>,
+
+    /// A callback called when the key clearing task has cleared `key` after `timeout`, e.g. for a UI to prompt for
+    /// the password again before the next operation fails with [`Error::StrongholdKeyCleared`].
+    ///
+    /// It's called from the key clearing task, so it shouldn't block. It's not called when the key is cleared
+    /// explicitly with [`clear_key()`](StrongholdAdapter::clear_key()).
+    #[builder(setter(custom))]
+    #[builder(field(type = "Option<KeyClearedCallback>"))]
+    on_key_cleared: Option<KeyClearedCallback>,
+
+    /// A policy the passwords have to meet, e.g. a minimal entropy or a list of banned passwords.
+    ///
+    /// It's checked whenever a password is chosen, i.e. when a new snapshot is created with [`password()`] or
+    /// [`set_password()`](StrongholdAdapter::set_password()), and for the new password of
+    /// [`change_password()`](StrongholdAdapter::change_password()) and
+    /// [`export_snapshot()`](StrongholdAdapter::export_snapshot()); a password rejected by it fails with
+    /// [`Error::StrongholdPasswordRejected`]. Existing snapshots can still be opened with their password, so that
+    /// tightening the policy doesn't lock anyone out.
+    ///
+    /// [`password()`]: self::StrongholdAdapterBuilder::password()
+    #[builder(setter(custom))]
+    #[builder(field(type = "Option<PasswordPolicy>"))]
+    password_policy: Option<PasswordPolicy>,
+
+    /// Whether the snapshot is written after each change of the store via the [`DatabaseProvider`] interface, so that
+    /// no change is lost on a crash.
+    ///
+    /// [`DatabaseProvider`]: crate::db::DatabaseProvider
+    auto_persist: bool,
+
+    /// The minimal interval between two snapshot writes when `auto_persist` is enabled.
+    ///
+    /// If set, the changes made within the interval are written together once it elapses, instead of writing the
+    /// snapshot on each change.
+    #[builder(setter(strip_option))]
+    auto_persist_interval: Option<Duration>,
+
+    /// A handle to the task writing the snapshot once `auto_persist_interval` elapses.
+    ///
+    /// Note that this field doesn't actually have a custom setter; `setter(custom)` is only for skipping the setter
+    /// generation.
+    #[builder(setter(custom))]
+    auto_persist_task: Arc<Mutex<Option<TaskHandle>>>,
+
+    /// Whether the snapshot is written and the key cleared ([zeroize]) when the adapter is dropped, so that the
+    /// changes made since the last write aren't lost if the application exits without writing it.
+    ///
+    /// The snapshot is written synchronously, blocking the dropping thread until it's done; errors can only be logged.
+    /// The write is skipped, with a warning, if the adapter is still in use by another task when dropped, e.g. by a
+    /// pending operation; the snapshot can be written beforehand with
+    /// [`write_stronghold_snapshot()`](StrongholdAdapter::write_stronghold_snapshot()) to not depend on that.
+    persist_on_drop: bool,
+
+    /// The number of previous versions of the snapshot kept as backups, `0` by default.
+    ///
+    /// Before the snapshot at `snapshot_path` is written, the previous one is copied to `<snapshot_path>.1`, which is
+    /// itself rotated to `<snapshot_path>.2`, and so on, the oldest one being removed; a corrupted write then doesn't
+    /// cost the only copy of the secrets.
+    backup_retention: usize,
+
+    /// The maximal duration of an operation on Stronghold, after which it fails with [`Error::StrongholdTimeout`].
+    ///
+    /// The operations on Stronghold, i.e. reading and writing the snapshot, accessing the store and executing the
+    /// vault procedures, run on the blocking thread pool of the async runtime, so that a hanging one doesn't block
+    /// the caller forever. A timed out operation can't be interrupted though: it keeps Stronghold locked, so the
+    /// following operations time out too until it completes.
+    #[builder(setter(strip_option))]
+    operation_timeout: Option<Duration>,
+
+    /// The client path (namespace) in the snapshot holding the secrets and data of this adapter.
+    ///
+    /// Note that in [`StrongholdAdapterBuilder`] the custom [`client_path()`] setter takes a byte slice; it defaults
+    /// to the historical client path.
+    ///
+    /// [`client_path()`]: self::StrongholdAdapterBuilder::client_path()
+    #[builder(setter(custom))]
+    #[builder(field(type = "Option<Vec<u8>>"))]
+    client_path: Vec<u8>,
+
+    /// The filename of the snapshot when the path given to [`build()`] is a directory, [`DEFAULT_SNAPSHOT_FILENAME`]
+    /// if not set.
+    ///
+    /// [`build()`]: self::StrongholdAdapterBuilder::build()
+    #[builder(setter(custom))]
+    pub snapshot_filename: Option<String>,
 
     /// The path to a Stronghold snapshot file.
     #[builder(setter(skip))]
     pub snapshot_path: PathBuf,
 }
 
-fn check_or_create_snapshot(
+/// A callback called when the key has been cleared after the timeout, see
+/// [`StrongholdAdapterBuilder::on_key_cleared()`].
+pub type KeyClearedCallback = Arc<dyn Fn() + Send + Sync>;
+
+/// A policy the passwords have to meet, returning why a password is rejected, see
+/// [`StrongholdAdapterBuilder::password_policy()`].
+pub type PasswordPolicy = Arc<dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync>;
+
+/// The status of a [`StrongholdAdapter`], see [`StrongholdAdapter::status()`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrongholdStatus {
+    /// Whether the key is available, i.e. Stronghold is unlocked.
+    pub unlocked: bool,
+    /// Whether the client path of the adapter has been loaded from the snapshot, or created, in memory.
+    pub snapshot_loaded: bool,
+    /// The path to the Stronghold snapshot file.
+    pub snapshot_path: PathBuf,
+    /// The time left until the key is cleared, if a key clearing task is running.
+    pub key_timeout_remaining: Option<Duration>,
+}
+
+/// What the key of the snapshot is set from in [`StrongholdAdapterBuilder`].
+#[derive(Clone)]
+enum KeySource {
+    /// A password, from which the key is derived with the key derivation parameters.
+    Password(Zeroizing<String>),
+    /// A raw key, derived by the user.
+    Key(Zeroizing<Vec<u8>>),
+}
+
+/// Load the client at `client_path` from the snapshot, creating it if it doesn't exist yet.
+///
+/// Returns whether the client has been created, i.e. whether the snapshot needs to be committed.
+fn load_or_create_client(
     stronghold: &Stronghold,
     key_provider: &KeyProvider,
     snapshot_path: &SnapshotPath,
-) -> Result<()> {
-    let result = stronghold.load_client_from_snapshot(PRIVATE_DATA_CLIENT_PATH, key_provider, snapshot_path);
+    client_path: &[u8],
+) -> Result<bool> {
+    let result = stronghold.load_client_from_snapshot(client_path, key_provider, snapshot_path);
 
     match result {
-        Err(iota_stronghold::ClientError::SnapshotFileMissing(_)) => {
-            stronghold.create_client(PRIVATE_DATA_CLIENT_PATH)?;
-            stronghold.commit_with_keyprovider(snapshot_path, key_provider)?;
+        Err(iota_stronghold::ClientError::SnapshotFileMissing(_))
+        | Err(iota_stronghold::ClientError::ClientDataNotPresent) => {
+            stronghold.create_client(client_path)?;
+
+            return Ok(true);
         }
         Err(iota_stronghold::ClientError::ClientAlreadyLoaded(_)) => {
-            stronghold.get_client(PRIVATE_DATA_CLIENT_PATH)?;
+            stronghold.get_client(client_path)?;
         }
         Err(iota_stronghold::ClientError::Inner(ref err_msg)) => {
             // Matching the error string is not ideal but stronghold doesn't wrap the error types at the moment.
@@ -131,34 +332,260 @@ fn check_or_create_snapshot(
         _ => {}
     }
 
+    Ok(false)
+}
+
+fn check_or_create_snapshot(
+    stronghold: &Stronghold,
+    key_provider: &KeyProvider,
+    snapshot_path: &SnapshotPath,
+    client_path: &[u8],
+) -> Result<()> {
+    let mut modified = load_or_create_client(stronghold, key_provider, snapshot_path, client_path)?;
+    modified |= load_or_create_client(
+        stronghold,
+        key_provider,
+        snapshot_path,
+        CLIENT_PATH_REGISTRY_CLIENT_PATH,
+    )?;
+
+    // Keep track of the client path, so that it can be listed.
+    let registry = stronghold.get_client(CLIENT_PATH_REGISTRY_CLIENT_PATH)?.store();
+    let mut client_paths = read_client_paths(&registry)?;
+
+    if !client_paths.iter().any(|path| path == client_path) {
+        client_paths.push(client_path.to_vec());
+        registry.insert(
+            CLIENT_PATH_REGISTRY_KEY.to_vec(),
+            serde_json::to_vec(&client_paths)?,
+            None,
+        )?;
+        modified = true;
+    }
+
+    if modified {
+        stronghold.commit_with_keyprovider(snapshot_path, key_provider)?;
+    }
+
+    Ok(())
+}
+
+/// Read the client paths kept in the registry `store`.
+fn read_client_paths(store: &Store) -> Result<Vec<Vec<u8>>> {
+    match store.get(CLIENT_PATH_REGISTRY_KEY)? {
+        Some(client_paths) => Ok(serde_json::from_slice(&client_paths)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Read the compressions of the stores of the client paths, kept in the registry `store`.
+fn read_compressions(store: &Store) -> Result<Vec<(Vec<u8>, String)>> {
+    match store.get(COMPRESSION_REGISTRY_KEY)? {
+        Some(compressions) => Ok(serde_json::from_slice(&compressions)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// The compression recorded in the loaded registry for the store of the client at `client_path`, if any.
+fn recorded_store_compression(stronghold: &Stronghold, client_path: &[u8]) -> Result<Option<Compression>> {
+    let registry = stronghold.get_client(CLIENT_PATH_REGISTRY_CLIENT_PATH)?.store();
+
+    read_compressions(&registry)?
+        .into_iter()
+        .find(|(path, _)| path == client_path)
+        .map(|(_, name)| Compression::from_recorded(Some(name.as_bytes())))
+        .transpose()
+}
+
+/// Check that the values of the store of the loaded client at `client_path` are stored with `compression`, recording
+/// it in the loaded registry if they can be, i.e. if the store doesn't hold values with another one yet. The record is
+/// persisted with the next write of the snapshot.
+fn check_store_compression(stronghold: &Stronghold, client_path: &[u8], compression: Compression) -> Result<()> {
+    let recorded = recorded_store_compression(stronghold, client_path)?;
+
+    if recorded == Some(compression) {
+        return Ok(());
+    }
+
+    // Stores without a recorded compression hold uncompressed values.
+    let has_records = !stronghold.get_client(client_path)?.store().keys()?.is_empty();
+    recorded
+        .unwrap_or_default()
+        .check_switch(compression, has_records)?;
+
+    let registry = stronghold.get_client(CLIENT_PATH_REGISTRY_CLIENT_PATH)?.store();
+    let mut compressions = read_compressions(&registry)?;
+    compressions.retain(|(path, _)| path != client_path);
+    compressions.push((client_path.to_vec(), compression.name().to_string()));
+    registry.insert(
+        COMPRESSION_REGISTRY_KEY.to_vec(),
+        serde_json::to_vec(&compressions)?,
+        None,
+    )?;
+
+    Ok(())
+}
+
+/// Load the registry from the snapshot and read the client paths it keeps.
+fn registered_client_paths(
+    stronghold: &Stronghold,
+    key_provider: &KeyProvider,
+    snapshot_path: &SnapshotPath,
+) -> Result<Vec<Vec<u8>>> {
+    load_or_create_client(
+        stronghold,
+        key_provider,
+        snapshot_path,
+        CLIENT_PATH_REGISTRY_CLIENT_PATH,
+    )?;
+
+    read_client_paths(&stronghold.get_client(CLIENT_PATH_REGISTRY_CLIENT_PATH)?.store())
+}
+
+/// Re-encrypt, in memory, the data saved via the [`DatabaseProvider`] interface in all client paths of the snapshot,
+/// from `old_key_provider` to `new_key_provider`.
+///
+/// The replaced values are pushed to `original_values`, so that they can be restored with [`restore_store_values()`].
+///
+/// [`DatabaseProvider`]: crate::db::DatabaseProvider
+fn re_encrypt_client_paths(
+    stronghold: &Stronghold,
+    old_key_provider: &KeyProvider,
+    new_key_provider: &KeyProvider,
+    snapshot_path: &SnapshotPath,
+    original_values: &mut Vec<(Store, Vec<u8>, Vec<u8>)>,
+) -> Result<()> {
+    let old_buffer = old_key_provider.try_unlock()?;
+    let old_buffer_ref = old_buffer.borrow();
+    let new_buffer = new_key_provider.try_unlock()?;
+    let new_buffer_ref = new_buffer.borrow();
+
+    for client_path in registered_client_paths(stronghold, old_key_provider, snapshot_path)? {
+        load_or_create_client(stronghold, old_key_provider, snapshot_path, &client_path)?;
+
+        re_encrypt_store(
+            stronghold.get_client(&client_path)?.store(),
+            old_buffer_ref.deref(),
+            new_buffer_ref.deref(),
+            original_values,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Re-encrypt, in memory, the values of `store` from `old_key` to `new_key`, pushing the replaced values to
+/// `original_values`.
+fn re_encrypt_store(
+    store: Store,
+    old_key: &[u8],
+    new_key: &[u8],
+    original_values: &mut Vec<(Store, Vec<u8>, Vec<u8>)>,
+) -> Result<()> {
+    for key in store.keys()? {
+        if let Some(value) = store.get(&key)? {
+            let decrypted = Zeroizing::new(chacha::aead_decrypt(old_key, &value)?);
+            store.insert(key.clone(), chacha::aead_encrypt(new_key, &decrypted)?, None)?;
+            original_values.push((store.clone(), key, value));
+        }
+    }
+
+    Ok(())
+}
+
+/// Put back the values replaced by [`re_encrypt_client_paths()`].
+fn restore_store_values(original_values: Vec<(Store, Vec<u8>, Vec<u8>)>) -> Result<()> {
+    for (store, key, value) in original_values {
+        store.insert(key, value, None)?;
+    }
+
     Ok(())
 }
 
+/// The path of the snapshot file: `snapshot_path` joined with `snapshot_filename`, or [`DEFAULT_SNAPSHOT_FILENAME`]
+/// if not set, if `snapshot_path` is an existing directory, or `snapshot_path` itself otherwise.
+fn resolve_snapshot_path(snapshot_path: &Path, snapshot_filename: Option<&str>) -> PathBuf {
+    if snapshot_path.is_dir() {
+        snapshot_path.join(snapshot_filename.unwrap_or(DEFAULT_SNAPSHOT_FILENAME))
+    } else {
+        snapshot_path.to_path_buf()
+    }
+}
+
 /// Extra / custom builder method implementations.
 impl StrongholdAdapterBuilder {
     /// Use an user-input password string to derive a key to use Stronghold.
     pub fn password(mut self, password: &str) -> Self {
-        // Note that derive_builder always adds another layer of Option<T>.
-        self.key_provider = Some(self::common::key_provider_from_password(password));
+        // Note that derive_builder always adds another layer of Option<T>. The key is derived when building, once the
+        // key derivation parameters are known.
+        self.key_provider = Some(KeySource::Password(Zeroizing::new(password.to_string())));
+
+        self
+    }
+
+    /// Use a raw 32-byte key to use Stronghold, as an alternative to [`password()`](Self::password()), e.g. when the
+    /// key is derived or kept by an HSM or the keychain of the OS. The key derivation parameters aren't used then.
+    pub fn key_bytes(mut self, key: Zeroizing<Vec<u8>>) -> Self {
+        self.key_provider = Some(KeySource::Key(key));
+
+        self
+    }
+
+    /// Set a callback called when the key has been cleared after the [`timeout()`](Self::timeout()), e.g. to prompt
+    /// for the password again. It's called from the key clearing task, so it shouldn't block.
+    pub fn on_key_cleared(mut self, on_key_cleared: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_key_cleared = Some(Arc::new(on_key_cleared));
+
+        self
+    }
+
+    /// Set a policy the passwords have to meet, returning why a password is rejected, e.g. because it's too short or
+    /// too common. It's checked whenever a password is chosen, e.g. when creating a snapshot.
+    pub fn password_policy(
+        mut self,
+        password_policy: impl Fn(&str) -> std::result::Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.password_policy = Some(Arc::new(password_policy));
+
+        self
+    }
+
+    /// Set the client path (namespace) in the snapshot to use, so that several wallets or applications can share a
+    /// snapshot. The client path is created in the snapshot if it doesn't exist yet.
+    pub fn client_path(mut self, client_path: &[u8]) -> Self {
+        self.client_path = Some(client_path.to_vec());
+
+        self
+    }
+
+    /// Set the filename of the snapshot, so that several snapshots can be kept in the same directory. It's used when
+    /// the path given to [`build()`](Self::build()) is a directory; a full path to a file is used as is.
+    pub fn snapshot_filename(mut self, snapshot_filename: &str) -> Self {
+        self.snapshot_filename = Some(Some(snapshot_filename.to_string()));
 
         self
     }
 
     /// Builds a [`StrongholdAdapter`] from the configuration.
     ///
+    /// `snapshot_path` is the full path of the snapshot file, used as is. If it's an existing directory, the snapshot
+    /// is the file named after the [`snapshot_filename()`], or [`DEFAULT_SNAPSHOT_FILENAME`] if not set, in it.
+    ///
     /// If both `key` (via [`password()`]) and `timeout` (via [`timeout()`]) are set, then an asynchronous task would be
-    /// spawned in Tokio to purge ([zeroize]) `key` after `timeout`. There is a small delay (usually a few milliseconds)
-    /// from the return of this function to this task actually being spawned and set in the returned
+    /// spawned on the async runtime to purge ([zeroize]) `key` after `timeout`. There is a small delay (usually a few
+    /// milliseconds) from the return of this function to this task actually being spawned and set in the returned
     /// [`StrongholdAdapter`].
     ///
-    /// **This function must be called inside a Tokio runtime context (usually in an `async fn` invoked by a Tokio
-    /// runtime, either directly or indirectly)**, as it uses [tokio::spawn()], which requires a Tokio context.
-    /// Otherwise, the function would panic. If this is not desired, one needs to avoid calling [`password()`] and
-    /// [`timeout()`] during the building process.
+    /// With the default Tokio runtime, the task is spawned on the runtime of the caller if there is one, and on a
+    /// runtime owned by the library otherwise.
     ///
     /// [`password()`]: Self::password()
     /// [`timeout()`]: Self::timeout()
+    /// [`snapshot_filename()`]: Self::snapshot_filename()
     pub fn build<P: AsRef<Path>>(mut self, snapshot_path: P) -> Result<StrongholdAdapter> {
+        let snapshot_filename = self.snapshot_filename.unwrap_or(None);
+        let snapshot_path = resolve_snapshot_path(snapshot_path.as_ref(), snapshot_filename.as_deref());
+
         // In any case, Stronghold - as a necessary component - needs to be present at this point.
         let stronghold = if let Some(stronghold) = self.stronghold {
             stronghold
@@ -166,33 +593,73 @@ impl StrongholdAdapterBuilder {
             Stronghold::default()
         };
 
-        if let Some(key_provider) = &self.key_provider {
-            check_or_create_snapshot(&stronghold, key_provider, &SnapshotPath::from_path(&snapshot_path))?;
+        let client_path = self
+            .client_path
+            .take()
+            .unwrap_or_else(|| PRIVATE_DATA_CLIENT_PATH.to_vec());
+
+        // Snapshots created with other parameters than the default ones have them recorded.
+        let key_derivation_parameters = match KeyDerivationParameters::read_recorded(&snapshot_path)? {
+            Some(key_derivation_parameters) => key_derivation_parameters,
+            None => self.key_derivation_parameters.take().unwrap_or_default(),
+        };
+
+        let key_source = self.key_provider.take();
+        let key_provider = match &key_source {
+            Some(KeySource::Password(password)) => Some(self::common::key_provider_from_password(
+                password,
+                &key_derivation_parameters,
+            )),
+            Some(KeySource::Key(key)) => Some(KeyProvider::try_from((**key).clone())?),
+            None => None,
+        };
+
+        if let Some(key_provider) = &key_provider {
+            // The password of a new snapshot is being chosen.
+            if let Some(KeySource::Password(password)) = &key_source {
+                if !snapshot_path.exists() {
+                    check_password_policy(self.password_policy.as_ref(), password)?;
+                }
+            }
+
+            check_or_create_snapshot(
+                &stronghold,
+                key_provider,
+                &SnapshotPath::from_path(&snapshot_path),
+                &client_path,
+            )?;
+
+            // The parameters only matter for keys derived from a password.
+            if let Some(KeySource::Password(_)) = key_source {
+                key_derivation_parameters.record(&snapshot_path)?;
+            }
         }
 
-        let has_key_provider = self.key_provider.is_some();
-        let key_provider = Arc::new(Mutex::new(self.key_provider));
+        let has_key_provider = key_provider.is_some();
+        let key_provider = Arc::new(Mutex::new(key_provider));
         let stronghold = Arc::new(Mutex::new(stronghold));
 
         // If both `key` and `timeout` are set, then we spawn the task and keep its join handle.
         if let (true, Some(Some(timeout))) = (has_key_provider, self.timeout) {
             let timeout_task = Arc::new(Mutex::new(None));
+            self.key_clearing_deadline = Some(Arc::new(Mutex::new(Some(Instant::now() + timeout))));
 
             // The key clearing task, with the data it owns.
             let task_self = timeout_task.clone();
             let key_provider = key_provider.clone();
 
-            // To keep this function synchronous (`fn`), we spawn a task that spawns the key clearing task here. It'll
-            // however panic when this function is not in a Tokio runtime context (usually in an `async fn`), albeit it
-            // itself is a `fn`. There is also a small delay from the return of this function to the task actually being
-            // spawned and set in the `struct`.
+            // To keep this function synchronous (`fn`), we spawn a task that spawns the key clearing task here. There
+            // is a small delay from the return of this function to the task actually being spawned and set in the
+            // `struct`.
             let stronghold_clone = stronghold.clone();
-            tokio::spawn(async move {
-                *task_self.lock().await = Some(tokio::spawn(task_key_clear(
+            let on_key_cleared = self.on_key_cleared.clone();
+            async_runtime::spawn(async move {
+                *task_self.lock().await = Some(async_runtime::spawn(task_key_clear(
                     task_self.clone(), // LHS moves task_self
                     stronghold_clone,
                     key_provider,
                     timeout,
+                    on_key_cleared,
                 )));
             });
 
@@ -204,9 +671,23 @@ impl StrongholdAdapterBuilder {
         Ok(StrongholdAdapter {
             stronghold,
             key_provider,
+            key_derivation_parameters,
+            vault_paths: self.vault_paths.unwrap_or_default(),
+            compression: self.compression.unwrap_or_default(),
             timeout: self.timeout.unwrap_or(None),
             timeout_task: self.timeout_task.unwrap_or_else(|| Arc::new(Mutex::new(None))),
-            snapshot_path: snapshot_path.as_ref().to_path_buf(),
+            key_clearing_deadline: self.key_clearing_deadline.unwrap_or_else(|| Arc::new(Mutex::new(None))),
+            on_key_cleared: self.on_key_cleared,
+            password_policy: self.password_policy,
+            auto_persist: self.auto_persist.unwrap_or(false),
+            persist_on_drop: self.persist_on_drop.unwrap_or(false),
+            backup_retention: self.backup_retention.unwrap_or(0),
+            auto_persist_interval: self.auto_persist_interval.unwrap_or(None),
+            auto_persist_task: Arc::new(Mutex::new(None)),
+            operation_timeout: self.operation_timeout.unwrap_or(None),
+            client_path,
+            snapshot_filename,
+            snapshot_path,
         })
     }
 }
@@ -224,14 +705,19 @@ impl StrongholdAdapter {
 
     /// Use an user-input password string to derive a key to use Stronghold.
     ///
-    /// This function will also spawn an asynchronous task in Tokio to automatically purge the derived key from
-    /// `password` after `timeout` (if set).
+    /// This function will also spawn an asynchronous task on the async runtime to automatically purge the derived key
+    /// from `password` after `timeout` (if set).
     /// It will also try to load a snapshot to check if the provided password is correct, if not it's cleared and an
     /// error will be returned.
-    pub async fn set_password(&mut self, password: &str) -> Result<()> {
+    pub async fn set_password(&self, password: &str) -> Result<()> {
+        // The password of a new snapshot is being chosen.
+        if !self.snapshot_path.exists() {
+            check_password_policy(self.password_policy.as_ref(), password)?;
+        }
+
         let mut key_provider_guard = self.key_provider.lock().await;
 
-        let key_provider = self::common::key_provider_from_password(password);
+        let key_provider = self::common::key_provider_from_password(password, &self.key_derivation_parameters);
 
         if let Some(old_key_provider) = &*key_provider_guard {
             if old_key_provider.try_unlock()? != key_provider.try_unlock()? {
@@ -242,7 +728,8 @@ impl StrongholdAdapter {
         let snapshot_path = SnapshotPath::from_path(&self.snapshot_path);
         let stronghold = self.stronghold.lock().await;
 
-        check_or_create_snapshot(&stronghold, &key_provider, &snapshot_path)?;
+        check_or_create_snapshot(&stronghold, &key_provider, &snapshot_path, &self.client_path)?;
+        self.key_derivation_parameters.record(&self.snapshot_path)?;
 
         *key_provider_guard = Some(key_provider);
         drop(key_provider_guard);
@@ -258,132 +745,103 @@ impl StrongholdAdapter {
             let task_self = self.timeout_task.clone();
             let key_provider = self.key_provider.clone();
 
-            *self.timeout_task.lock().await = Some(tokio::spawn(task_key_clear(
+            *self.key_clearing_deadline.lock().await = Some(Instant::now() + timeout);
+            *self.timeout_task.lock().await = Some(async_runtime::spawn(task_key_clear(
                 task_self,
                 self.stronghold.clone(),
                 key_provider,
                 timeout,
+                self.on_key_cleared.clone(),
             )));
         }
 
         Ok(())
     }
 
-    /// Change the password of the currently loaded Stronghold.
+    /// Change the password of the Stronghold from `old_password` to `new_password`.
     ///
-    /// If a snapshot path has been set, then it'll be rewritten with the newly set password.
+    /// `old_password` is checked against the currently set key; if the key has been cleared, it's used to load the
+    /// snapshot first. The secrets (e.g. mnemonic) stored in the Stronghold vault are preserved, and the data saved
+    /// via the [`DatabaseProvider`] interface, in all client paths, are re-encrypted with the key derived from
+    /// `new_password`.
     ///
-    /// The secrets (e.g. mnemonic) stored in the Stronghold vault will be preserved, but the data saved via the
-    /// [`DatabaseProvider`] interface won't - they'll stay encrypted with the old password. To re-encrypt these
-    /// data, provide a list of keys in `keys_to_re_encrypt`, as we have no way to list and iterate over every
-    /// key-value in the Stronghold store - we'll attempt on the ones provided instead. Set it to `None` to skip
-    /// re-encryption.
-    pub async fn change_password(&mut self, new_password: &str) -> Result<()> {
+    /// The snapshot at `snapshot_path` is rewritten with the new key. It's first written to a temporary file next to
+    /// it, which then atomically replaces it, so a crash never leaves a half-written snapshot behind.
+    ///
+    /// [`DatabaseProvider`]: crate::db::DatabaseProvider
+    pub async fn change_password(&mut self, old_password: &str, new_password: &str) -> Result<()> {
+        check_password_policy(self.password_policy.as_ref(), new_password)?;
+
+        // Check the old password, or load the snapshot with it if the key has been cleared.
+        if self.is_key_available().await {
+            let old_key_provider =
+                self::common::key_provider_from_password(old_password, &self.key_derivation_parameters);
+
+            if let Some(key_provider) = &*self.key_provider.lock().await {
+                if key_provider.try_unlock()? != old_key_provider.try_unlock()? {
+                    return Err(Error::StrongholdInvalidPassword);
+                }
+            }
+        } else {
+            self.set_password(old_password).await?;
+        }
+
         // Stop the key clearing task to prevent the key from being abruptly cleared (largely).
         if let Some(timeout_task) = self.timeout_task.lock().await.take() {
             timeout_task.abort();
         }
 
+        // A pending auto persist write could write the snapshot half re-encrypted; the snapshot is rewritten below
+        // anyways.
+        if let Some(auto_persist_task) = self.auto_persist_task.lock().await.take() {
+            auto_persist_task.abort();
+        }
+
         // In case something goes wrong we can recover from the snapshot.
         self.write_stronghold_snapshot(None).await?;
 
-        // If there are keys to re-encrypt, we iterate over the requested keys and attempt to re-encrypt the
-        // corresponding values.
-        //
-        // Note that [`DatabaseProvider`] methods will do encryption / decryption automatically, so we collect values
-        // to the memory first (decrypted with the old key), then change `self.key`, then store them back (encrypted
-        // with the new key).
-        let mut values = Vec::new();
-        let keys_to_re_encrypt = self
-            .stronghold
-            .lock()
-            .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .store()
-            .keys()?;
-
-        for key in keys_to_re_encrypt {
-            let value = match self.get(&key).await {
-                Err(err) => {
-                    error!("an error occurred during the re-encryption of Stronghold Store: {err}");
-
-                    // Recover: restart the key clearing task
-                    if let Some(timeout) = self.timeout {
-                        // The key clearing task, with the data it owns.
-                        let task_self = self.timeout_task.clone();
-                        let key_provider = self.key_provider.clone();
-
-                        *self.timeout_task.lock().await = Some(tokio::spawn(task_key_clear(
-                            task_self,
-                            self.stronghold.clone(),
-                            key_provider,
-                            timeout,
-                        )));
-                    }
-
-                    return Err(err);
-                }
-                Ok(None) => continue,
-                Ok(Some(value)) => Zeroizing::new(value),
+        let new_key_provider = self::common::key_provider_from_password(new_password, &self.key_derivation_parameters);
+
+        // All client paths of the snapshot share the key, so the data of all of them are re-encrypted in memory. The
+        // original values are kept to recover from a failure.
+        let mut original_values = Vec::new();
+        let re_encryption = {
+            let locked_key_provider = self.key_provider.lock().await;
+            let key_provider = if let Some(key_provider) = &*locked_key_provider {
+                key_provider
+            } else {
+                return Err(Error::StrongholdKeyCleared);
             };
 
-            values.push((key, value));
-        }
-
-        // Now we put the new key in, enabling encryption with the new key. Also, take the old key out to prevent
-        // disasters.
-        let old_key_provider = {
-            let mut lock = self.key_provider.lock().await;
-            let old_key_provider = lock.take();
-            *lock = Some(self::common::key_provider_from_password(new_password));
-
-            old_key_provider
+            re_encrypt_client_paths(
+                &*self.stronghold.lock().await,
+                key_provider,
+                &new_key_provider,
+                &SnapshotPath::from_path(&self.snapshot_path),
+                &mut original_values,
+            )
         };
 
-        for (key, value) in values {
-            if let Err(err) = self.insert(&key, &value).await {
-                error!("an error occurred during the re-encryption of Stronghold store: {err}");
-
-                // Recover: put the old key back
-                *self.key_provider.lock().await = old_key_provider;
+        if let Err(err) = re_encryption {
+            error!("an error occurred during the re-encryption of Stronghold store: {err}");
 
-                // Recover: forcefully reload Stronghold
-                self.read_stronghold_snapshot().await?;
+            // Recover: put the original values back
+            restore_store_values(original_values)?;
 
-                // Recover: restart key clearing task
-                if let Some(timeout) = self.timeout {
-                    // The key clearing task, with the data it owns.
-                    let task_self = self.timeout_task.clone();
-                    let key_provider = self.key_provider.clone();
+            // Recover: restart the key clearing task
+            self.restart_key_clearing_task().await;
 
-                    *self.timeout_task.lock().await = Some(tokio::spawn(task_key_clear(
-                        task_self,
-                        self.stronghold.clone(),
-                        key_provider,
-                        timeout,
-                    )));
-                }
-
-                return Err(err);
-            }
+            return Err(err);
         }
 
+        // Now we put the new key in. The old key is dropped to prevent disasters.
+        *self.key_provider.lock().await = Some(new_key_provider);
+
         // Rewrite the snapshot to finish the password changing process.
-        self.write_stronghold_snapshot(None).await?;
+        self.write_stronghold_snapshot_atomically().await?;
 
         // Restart the key clearing task.
-        if let Some(timeout) = self.timeout {
-            // The key clearing task, with the data it owns.
-            let task_self = self.timeout_task.clone();
-            let key_provider = self.key_provider.clone();
-
-            *self.timeout_task.lock().await = Some(tokio::spawn(task_key_clear(
-                task_self,
-                self.stronghold.clone(),
-                key_provider,
-                timeout,
-            )));
-        }
+        self.restart_key_clearing_task().await;
 
         Ok(())
     }
@@ -391,12 +849,17 @@ impl StrongholdAdapter {
     /// Immediately clear ([zeroize]) the stored key.
     ///
     /// If a key clearing thread has been spawned, then it'll be stopped too.
-    pub async fn clear_key(&mut self) {
+    pub async fn clear_key(&self) {
         // Stop a spawned task and setting it to None first.
         if let Some(timeout_task) = self.timeout_task.lock().await.take() {
             timeout_task.abort();
         }
 
+        // Unloading writes the snapshot, so a pending auto persist write isn't needed anymore.
+        if let Some(auto_persist_task) = self.auto_persist_task.lock().await.take() {
+            auto_persist_task.abort();
+        }
+
         // Unloading the snapshot requires the key
         if self.is_key_available().await {
             // Unload Stronghold first, but we can't do much about the errors.
@@ -409,6 +872,76 @@ impl StrongholdAdapter {
         self.key_provider.lock().await.take();
     }
 
+    /// Lock the adapter: write the snapshot, unload Stronghold and clear ([zeroize]) the key, stopping the key
+    /// clearing task, as one operation.
+    ///
+    /// Unlike [`clear_key()`](Self::clear_key()), a failure to write the snapshot isn't ignored: it's returned as
+    /// [`Error::StrongholdSnapshotWrite`] and the adapter is left unlocked, so that no change is lost. Locking an
+    /// adapter without a key does nothing.
+    pub async fn lock(&self) -> Result<()> {
+        if let Some(timeout_task) = self.timeout_task.lock().await.take() {
+            timeout_task.abort();
+        }
+
+        // The pending changes of the store are written below.
+        if let Some(auto_persist_task) = self.auto_persist_task.lock().await.take() {
+            auto_persist_task.abort();
+        }
+
+        // Both are held until the key is cleared, so that no other operation can run in between.
+        let mut key_provider = self.key_provider.lock().await;
+        let stronghold = self.stronghold.lock().await;
+
+        write_snapshot_and_clear_key(
+            &stronghold,
+            &mut key_provider,
+            &self.snapshot_path,
+            self.backup_retention,
+        )
+    }
+
+    /// Stop the background tasks of the adapter, writing the changes of the store pending with `auto_persist_interval`
+    /// to the snapshot first, e.g. before the application terminates.
+    ///
+    /// The key clearing task is stopped without clearing the key; see [`clear_key()`](Self::clear_key()) for that.
+    pub async fn shutdown(&self) -> Result<()> {
+        if let Some(timeout_task) = self.timeout_task.lock().await.take() {
+            timeout_task.abort();
+        }
+
+        // Write the pending changes right away instead.
+        if let Some(auto_persist_task) = self.auto_persist_task.lock().await.take() {
+            auto_persist_task.abort();
+
+            self.write_stronghold_snapshot(None).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the status of the adapter, e.g. to render a lock indicator.
+    pub async fn status(&self) -> StrongholdStatus {
+        let unlocked = self.is_key_available().await;
+        let snapshot_loaded = self.stronghold.lock().await.get_client(&self.client_path).is_ok();
+
+        // The deadline is only meaningful while the key clearing task is running.
+        let key_timeout_remaining = if self.timeout_task.lock().await.is_some() {
+            self.key_clearing_deadline
+                .lock()
+                .await
+                .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+        } else {
+            None
+        };
+
+        StrongholdStatus {
+            unlocked,
+            snapshot_loaded,
+            snapshot_path: self.snapshot_path.clone(),
+            key_timeout_remaining,
+        }
+    }
+
     /// Get timeout for the key clearing task.
     pub fn get_timeout(&self) -> Option<Duration> {
         self.timeout
@@ -436,11 +969,13 @@ impl StrongholdAdapter {
             let task_self = self.timeout_task.clone();
             let key_provider = self.key_provider.clone();
 
-            *self.timeout_task.lock().await = Some(tokio::spawn(task_key_clear(
+            *self.key_clearing_deadline.lock().await = Some(Instant::now() + timeout);
+            *self.timeout_task.lock().await = Some(async_runtime::spawn(task_key_clear(
                 task_self,
                 self.stronghold.clone(),
                 key_provider,
                 timeout,
+                self.on_key_cleared.clone(),
             )));
         }
     }
@@ -452,8 +987,13 @@ impl StrongholdAdapter {
         self.set_timeout(self.get_timeout()).await;
     }
 
-    /// Load Stronghold from a snapshot at `snapshot_path`, if it hasn't been loaded yet.
-    pub async fn read_stronghold_snapshot(&mut self) -> Result<()> {
+    /// Get the client path (namespace) currently in use.
+    pub fn client_path(&self) -> &[u8] {
+        &self.client_path
+    }
+
+    /// List the client paths (namespaces) created in the snapshot.
+    pub async fn client_paths(&self) -> Result<Vec<Vec<u8>>> {
         // The key needs to be supplied first.
         let locked_key_provider = self.key_provider.lock().await;
         let key_provider = if let Some(key_provider) = &*locked_key_provider {
@@ -462,49 +1002,205 @@ impl StrongholdAdapter {
             return Err(Error::StrongholdKeyCleared);
         };
 
-        self.stronghold.lock().await.load_client_from_snapshot(
-            PRIVATE_DATA_CLIENT_PATH,
+        registered_client_paths(
+            &*self.stronghold.lock().await,
             key_provider,
             &SnapshotPath::from_path(&self.snapshot_path),
-        )?;
+        )
+    }
+
+    /// Switch to the client path (namespace) `client_path`, creating it in the snapshot if it doesn't exist yet.
+    ///
+    /// Subsequent database and secret manager operations act on the data of `client_path`; the data of the other
+    /// client paths are left untouched.
+    pub async fn switch_client_path(&mut self, client_path: &[u8]) -> Result<()> {
+        {
+            // The key needs to be supplied first.
+            let locked_key_provider = self.key_provider.lock().await;
+            let key_provider = if let Some(key_provider) = &*locked_key_provider {
+                key_provider
+            } else {
+                return Err(Error::StrongholdKeyCleared);
+            };
+
+            check_or_create_snapshot(
+                &*self.stronghold.lock().await,
+                key_provider,
+                &SnapshotPath::from_path(&self.snapshot_path),
+                client_path,
+            )?;
+        }
+
+        self.client_path = client_path.to_vec();
 
         Ok(())
     }
 
+    /// Load Stronghold from a snapshot at `snapshot_path`, if it hasn't been loaded yet.
+    pub async fn read_stronghold_snapshot(&self) -> Result<()> {
+        let client_path = self.client_path.clone();
+        let snapshot_path = SnapshotPath::from_path(&self.snapshot_path);
+
+        self.run_operation(move |stronghold, key_provider| {
+            // The key needs to be supplied first.
+            let key_provider = key_provider.ok_or(Error::StrongholdKeyCleared)?;
+
+            stronghold.load_client_from_snapshot(&client_path, key_provider, &snapshot_path)?;
+
+            Ok(())
+        })
+        .await
+    }
+
     /// Persist Stronghold to a snapshot at a provided `snapshot_path` or at the Stronghold's own `snapshot_path` if
     /// None.
     ///
     /// It doesn't unload the snapshot; see also [`unload_stronghold_snapshot()`].
     ///
     /// [`unload_stronghold_snapshot()`]: Self::unload_stronghold_snapshot()
-    pub async fn write_stronghold_snapshot(&mut self, snapshot_path: Option<&Path>) -> Result<()> {
-        // The key needs to be supplied first.
-        let locked_key_provider = self.key_provider.lock().await;
-        let key_provider = if let Some(key_provider) = &*locked_key_provider {
-            key_provider
-        } else {
-            return Err(Error::StrongholdKeyCleared);
-        };
+    pub async fn write_stronghold_snapshot(&self, snapshot_path: Option<&Path>) -> Result<()> {
+        let snapshot_path = snapshot_path.unwrap_or(&self.snapshot_path);
 
-        self.stronghold.lock().await.commit_with_keyprovider(
-            &SnapshotPath::from_path(snapshot_path.unwrap_or(&self.snapshot_path)),
-            key_provider,
-        )?;
+        if snapshot_path == self.snapshot_path {
+            rotate_snapshot_backups(snapshot_path, self.backup_retention)?;
+        }
 
-        Ok(())
+        commit_snapshot(
+            &self.stronghold,
+            &self.key_provider,
+            snapshot_path,
+            self.operation_timeout,
+        )
+        .await
     }
 
-    /// Unload Stronghold from memory.
-    ///
-    /// It writes Stronghold snapshot to disk. All secrets will be purged from the
-    /// memory, so if secrets aren't written to disk (for example, no snapshot path has been provided, i.e. running
-    /// Stronghold purely in memory) then secrets stored in Stronghold will be lost.
+    /// Run the blocking Stronghold `operation` with the key, if it's set, failing with [`Error::StrongholdTimeout`]
+    /// after `operation_timeout`.
+    async fn run_operation<T, F>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce(&Stronghold, Option<&KeyProvider>) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        run_stronghold_operation(&self.stronghold, &self.key_provider, self.operation_timeout, operation).await
+    }
+
+    /// Write the snapshot after a change of the store, if `auto_persist` is enabled.
+    ///
+    /// With an `auto_persist_interval`, the write is deferred until the interval elapses, and covers all the changes
+    /// made in the meantime.
+    async fn persist_store_change(&self) -> Result<()> {
+        if !self.auto_persist {
+            return Ok(());
+        }
+
+        let interval = if let Some(interval) = self.auto_persist_interval {
+            interval
+        } else {
+            return self.write_stronghold_snapshot(None).await;
+        };
+
+        let mut auto_persist_task = self.auto_persist_task.lock().await;
+
+        // A pending write will include this change.
+        if auto_persist_task.is_some() {
+            return Ok(());
+        }
+
+        // The snapshot writing task, with the data it owns.
+        let task_self = self.auto_persist_task.clone();
+        let stronghold = self.stronghold.clone();
+        let key_provider = self.key_provider.clone();
+        let snapshot_path = self.snapshot_path.clone();
+        let operation_timeout = self.operation_timeout;
+        let backup_retention = self.backup_retention;
+
+        *auto_persist_task = Some(async_runtime::spawn(async move {
+            async_runtime::sleep(interval).await;
+
+            // Changes made from now on need another write.
+            task_self.lock().await.take();
+
+            let result = match rotate_snapshot_backups(&snapshot_path, backup_retention) {
+                Ok(()) => commit_snapshot(&stronghold, &key_provider, &snapshot_path, operation_timeout).await,
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = result {
+                error!("failed to write the Stronghold snapshot after a store change: {err}");
+            }
+        }));
+
+        Ok(())
+    }
+
+    /// Export the Stronghold state to a snapshot at `path`, encrypted with a key derived from `new_password`.
+    ///
+    /// This creates a backup of the vault and of the data saved via the [`DatabaseProvider`] interface, which is
+    /// re-encrypted for the new password, without touching the snapshot at `snapshot_path`. The backup can be opened
+    /// by building a [`StrongholdAdapter`] with `path` and `new_password`.
+    ///
+    /// [`DatabaseProvider`]: crate::db::DatabaseProvider
+    pub async fn export_snapshot(&self, path: &Path, new_password: &str) -> Result<()> {
+        check_password_policy(self.password_policy.as_ref(), new_password)?;
+
+        let new_key_provider = self::common::key_provider_from_password(new_password, &self.key_derivation_parameters);
+
+        // The key needs to be supplied first.
+        let locked_key_provider = self.key_provider.lock().await;
+        let key_provider = if let Some(key_provider) = &*locked_key_provider {
+            key_provider
+        } else {
+            return Err(Error::StrongholdKeyCleared);
+        };
+
+        let stronghold = self.stronghold.lock().await;
+
+        // Re-encrypt the data of all client paths with the new key in memory, keeping the original values to restore
+        // them once the backup has been written.
+        let mut original_values = Vec::new();
+        let result = re_encrypt_client_paths(
+            &stronghold,
+            key_provider,
+            &new_key_provider,
+            &SnapshotPath::from_path(&self.snapshot_path),
+            &mut original_values,
+        )
+        .and_then(|_| Ok(stronghold.commit_with_keyprovider(&SnapshotPath::from_path(path), &new_key_provider)?));
+
+        restore_store_values(original_values)?;
+
+        result?;
+
+        // The backup is encrypted with the same key derivation parameters.
+        self.key_derivation_parameters.record(path)
+    }
+
+    /// Persist Stronghold to a temporary file next to `snapshot_path`, then rename it to `snapshot_path`.
+    ///
+    /// The rename is atomic on the same file system, so the snapshot on disk is either the old or the new one.
+    async fn write_stronghold_snapshot_atomically(&self) -> Result<()> {
+        let mut temporary_path = self.snapshot_path.clone().into_os_string();
+        temporary_path.push(".tmp");
+        let temporary_path = PathBuf::from(temporary_path);
+
+        self.write_stronghold_snapshot(Some(&temporary_path)).await?;
+        rotate_snapshot_backups(&self.snapshot_path, self.backup_retention)?;
+        std::fs::rename(&temporary_path, &self.snapshot_path)?;
+
+        Ok(())
+    }
+
+    /// Unload Stronghold from memory.
+    ///
+    /// It writes Stronghold snapshot to disk. All secrets will be purged from the
+    /// memory, so if secrets aren't written to disk (for example, no snapshot path has been provided, i.e. running
+    /// Stronghold purely in memory) then secrets stored in Stronghold will be lost.
     ///
     /// To further prevent Stronghold methods to be invoked without valid key, this method will be invoked every time
     /// the cached key is cleared from the memory. In other words, if a `timeout` is set and a `snapshot_path` is not
     /// set for a [`StrongholdAdapter`], then after `timeout` Stronghold will be purged. See the [module-level
     /// documentation](self) for more details.
-    pub async fn unload_stronghold_snapshot(&mut self) -> Result<()> {
+    pub async fn unload_stronghold_snapshot(&self) -> Result<()> {
         // Flush Stronghold.
         self.write_stronghold_snapshot(None).await?;
 
@@ -514,14 +1210,187 @@ impl StrongholdAdapter {
     }
 }
 
-/// The asynchronous key clearing task purging `key` after `timeout` spent in Tokio.
+impl Drop for StrongholdAdapter {
+    /// Write the snapshot and clear the key if `persist_on_drop` is set.
+    fn drop(&mut self) {
+        if !self.persist_on_drop {
+            return;
+        }
+
+        // The adapter may be dropped on the async runtime, even a current-thread one, where waiting for a lock held
+        // by a task of the runtime would never end, so the write is skipped if any lock is held.
+        let (mut timeout_task, mut auto_persist_task, mut key_provider, stronghold) = match (
+            self.timeout_task.try_lock(),
+            self.auto_persist_task.try_lock(),
+            self.key_provider.try_lock(),
+            self.stronghold.try_lock(),
+        ) {
+            (Ok(timeout_task), Ok(auto_persist_task), Ok(key_provider), Ok(stronghold)) => {
+                (timeout_task, auto_persist_task, key_provider, stronghold)
+            }
+            _ => {
+                warn!("skipped writing the Stronghold snapshot when dropping the adapter: it's in use by another task");
+                return;
+            }
+        };
+
+        if let Some(timeout_task) = timeout_task.take() {
+            timeout_task.abort();
+        }
+
+        // The pending changes of the store are written below.
+        if let Some(auto_persist_task) = auto_persist_task.take() {
+            auto_persist_task.abort();
+        }
+
+        if let Err(err) = write_snapshot_and_clear_key(
+            &stronghold,
+            &mut key_provider,
+            &self.snapshot_path,
+            self.backup_retention,
+        ) {
+            error!("failed to write the Stronghold snapshot when dropping the adapter: {err}");
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundComponent for StrongholdAdapter {
+    async fn shutdown(&self) -> Result<()> {
+        StrongholdAdapter::shutdown(self).await
+    }
+}
+
+/// Write the snapshot at `snapshot_path` with the key in `key_provider`, if any, then unload Stronghold and clear the
+/// key. On a failure to write the snapshot, nothing is cleared.
+fn write_snapshot_and_clear_key(
+    stronghold: &Stronghold,
+    key_provider: &mut Option<KeyProvider>,
+    snapshot_path: &Path,
+    backup_retention: usize,
+) -> Result<()> {
+    if let Some(key_provider) = &*key_provider {
+        rotate_snapshot_backups(snapshot_path, backup_retention)?;
+        stronghold
+            .commit_with_keyprovider(&SnapshotPath::from_path(snapshot_path), key_provider)
+            .map_err(Error::StrongholdSnapshotWrite)?;
+        stronghold.clear()?;
+    }
+
+    // Zeroized when dropped.
+    key_provider.take();
+
+    Ok(())
+}
+
+/// Check `password` against `password_policy`, if any.
+fn check_password_policy(password_policy: Option<&PasswordPolicy>, password: &str) -> Result<()> {
+    match password_policy {
+        Some(password_policy) => password_policy(password).map_err(Error::StrongholdPasswordRejected),
+        None => Ok(()),
+    }
+}
+
+/// Copy the snapshot at `snapshot_path`, if any, to the first of `backup_retention` backups, shifting the previous
+/// ones and removing the oldest one.
+fn rotate_snapshot_backups(snapshot_path: &Path, backup_retention: usize) -> Result<()> {
+    if backup_retention == 0 || !snapshot_path.exists() {
+        return Ok(());
+    }
+
+    let oldest_backup_path = snapshot_backup_path(snapshot_path, backup_retention);
+    if oldest_backup_path.exists() {
+        std::fs::remove_file(oldest_backup_path)?;
+    }
+
+    for index in (1..backup_retention).rev() {
+        let backup_path = snapshot_backup_path(snapshot_path, index);
+        if backup_path.exists() {
+            std::fs::rename(backup_path, snapshot_backup_path(snapshot_path, index + 1))?;
+        }
+    }
+
+    // Copied rather than moved, so that the snapshot is still there if the write fails.
+    std::fs::copy(snapshot_path, snapshot_backup_path(snapshot_path, 1))?;
+
+    Ok(())
+}
+
+/// The path of the backup of the snapshot at `snapshot_path` with the given index, the most recent one being `1`.
+fn snapshot_backup_path(snapshot_path: &Path, index: usize) -> PathBuf {
+    let mut backup_path = snapshot_path.as_os_str().to_owned();
+    backup_path.push(format!(".{index}"));
+
+    PathBuf::from(backup_path)
+}
+
+/// Persist Stronghold to a snapshot at `snapshot_path`, with the key in `key_provider`.
+async fn commit_snapshot(
+    stronghold: &Arc<Mutex<Stronghold>>,
+    key_provider: &Arc<Mutex<Option<KeyProvider>>>,
+    snapshot_path: &Path,
+    operation_timeout: Option<Duration>,
+) -> Result<()> {
+    let snapshot_path = SnapshotPath::from_path(snapshot_path);
+
+    run_stronghold_operation(
+        stronghold,
+        key_provider,
+        operation_timeout,
+        move |stronghold, key_provider| {
+            // The key needs to be supplied first.
+            let key_provider = key_provider.ok_or(Error::StrongholdKeyCleared)?;
+
+            stronghold.commit_with_keyprovider(&snapshot_path, key_provider)?;
+
+            Ok(())
+        },
+    )
+    .await
+}
+
+/// Run the blocking Stronghold `operation` on the blocking thread pool, with the key in `key_provider` if it's set.
+///
+/// Stronghold has a synchronous API, so the operation is moved off the async runtime to be able to give up on it
+/// after `operation_timeout`, with [`Error::StrongholdTimeout`].
+async fn run_stronghold_operation<T, F>(
+    stronghold: &Arc<Mutex<Stronghold>>,
+    key_provider: &Arc<Mutex<Option<KeyProvider>>>,
+    operation_timeout: Option<Duration>,
+    operation: F,
+) -> Result<T>
+where
+    F: FnOnce(&Stronghold, Option<&KeyProvider>) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let stronghold = stronghold.clone();
+    let key_provider = key_provider.clone();
+
+    let operation = async_runtime::spawn_blocking(move || {
+        // Locked in the same order as everywhere else, the key first.
+        let key_provider = key_provider.blocking_lock();
+        let stronghold = stronghold.blocking_lock();
+
+        operation(&stronghold, key_provider.as_ref())
+    });
+
+    match operation_timeout {
+        Some(operation_timeout) => async_runtime::timeout(operation_timeout, operation)
+            .await
+            .unwrap_or(Err(Error::StrongholdTimeout(operation_timeout))),
+        None => operation.await,
+    }
+}
+
+/// The asynchronous key clearing task purging `key` after `timeout` spent on the async runtime.
 async fn task_key_clear(
-    task_self: Arc<Mutex<Option<JoinHandle<()>>>>,
+    task_self: Arc<Mutex<Option<TaskHandle>>>,
     stronghold: Arc<Mutex<Stronghold>>,
     key_provider: Arc<Mutex<Option<KeyProvider>>>,
     timeout: Duration,
+    on_key_cleared: Option<KeyClearedCallback>,
 ) {
-    tokio::time::sleep(timeout).await;
+    async_runtime::sleep(timeout).await;
 
     debug!("StrongholdAdapter is purging the key");
     key_provider.lock().await.take();
@@ -531,6 +1400,10 @@ async fn task_key_clear(
 
     // Take self, but do nothing (we're exiting anyways).
     task_self.lock().await.take();
+
+    if let Some(on_key_cleared) = on_key_cleared {
+        on_key_cleared();
+    }
 }
 
 #[cfg(test)]
@@ -538,6 +1411,78 @@ mod tests {
     use std::fs;
 
     use super::*;
+    use crate::db::DatabaseProvider;
+
+    #[tokio::test]
+    async fn key_bytes() {
+        let stronghold_path = "test_key_bytes.stronghold";
+        let key = super::common::derive_key("drowssap", &KeyDerivationParameters::default());
+
+        // Only 32-byte keys are accepted.
+        assert!(StrongholdAdapter::builder()
+            .key_bytes(Zeroizing::new(vec![0; 16]))
+            .build(stronghold_path)
+            .is_err());
+
+        let adapter = StrongholdAdapter::builder()
+            .key_bytes(Zeroizing::new(key.to_vec()))
+            .build(stronghold_path)
+            .unwrap();
+        adapter.insert(b"test-0", b"0").await.unwrap();
+        adapter.write_stronghold_snapshot(None).await.unwrap();
+
+        // The same key reopens the snapshot.
+        let adapter = StrongholdAdapter::builder()
+            .key_bytes(Zeroizing::new(key.to_vec()))
+            .build(stronghold_path)
+            .unwrap();
+        assert_eq!(adapter.get(b"test-0").await.unwrap().as_deref(), Some(&b"0"[..]));
+
+        // The key derived from the password opens the snapshot.
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        assert_eq!(adapter.get(b"test-0").await.unwrap().as_deref(), Some(&b"0"[..]));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn snapshot_filename() {
+        let directory = "test_snapshot_filename";
+        fs::create_dir_all(directory).unwrap();
+
+        // A directory gets the default filename.
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(directory)
+            .unwrap();
+        assert_eq!(
+            adapter.snapshot_path,
+            Path::new(directory).join(DEFAULT_SNAPSHOT_FILENAME)
+        );
+
+        // Several snapshots can be kept in the same directory.
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .snapshot_filename("other.stronghold")
+            .build(directory)
+            .unwrap();
+        assert_eq!(adapter.snapshot_path, Path::new(directory).join("other.stronghold"));
+        assert!(adapter.snapshot_path.exists());
+
+        // A full path is used as is, even with a filename set.
+        let snapshot_path = Path::new(directory).join("file.stronghold");
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .snapshot_filename("other.stronghold")
+            .build(&snapshot_path)
+            .unwrap();
+        assert_eq!(adapter.snapshot_path, snapshot_path);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
 
     #[tokio::test]
     async fn test_clear_key() {
@@ -584,10 +1529,477 @@ mod tests {
         fs::remove_file(stronghold_path).unwrap();
     }
 
+    #[tokio::test]
+    async fn on_key_cleared() {
+        let stronghold_path = "test_on_key_cleared.stronghold";
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .timeout(Duration::from_millis(100))
+            .on_key_cleared(move || sender.send(()).unwrap())
+            .build(stronghold_path)
+            .unwrap();
+
+        // The callback is called once the key has been cleared by the key clearing task.
+        tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!adapter.is_key_available().await);
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn status() {
+        let stronghold_path = "test_status.stronghold";
+        let timeout = Duration::from_secs(60);
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .timeout(timeout)
+            .build(stronghold_path)
+            .unwrap();
+
+        // Let the key clearing task be spawned.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let status = adapter.status().await;
+        assert!(status.unlocked);
+        assert!(status.snapshot_loaded);
+        assert_eq!(status.snapshot_path, PathBuf::from(stronghold_path));
+        assert!(matches!(status.key_timeout_remaining, Some(remaining) if remaining <= timeout));
+
+        adapter.clear_key().await;
+
+        let status = adapter.status().await;
+        assert!(!status.unlocked);
+        assert!(!status.snapshot_loaded);
+        assert_eq!(status.key_timeout_remaining, None);
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn operation_timeout() {
+        let stronghold_path = "test_operation_timeout.stronghold";
+        let operation_timeout = Duration::from_millis(100);
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .operation_timeout(operation_timeout)
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+
+        // An operation hanging while holding Stronghold makes the following ones time out.
+        let stronghold = adapter.stronghold.lock().await;
+        assert!(matches!(
+            adapter.get(b"key").await,
+            Err(Error::StrongholdTimeout(timeout)) if timeout == operation_timeout
+        ));
+        drop(stronghold);
+
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn change_password() {
+        let stronghold_path = "test_change_password.stronghold";
+        let mut adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+
+        // A wrong old password is rejected.
+        assert!(matches!(
+            adapter.change_password("wrong", "new_password").await,
+            Err(Error::StrongholdInvalidPassword)
+        ));
+
+        adapter.change_password("drowssap", "new_password").await.unwrap();
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        // The snapshot on disk can only be opened with the new password.
+        adapter.clear_key().await;
+        assert!(adapter.set_password("drowssap").await.is_err());
+        assert!(adapter.set_password("new_password").await.is_ok());
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn export_snapshot() {
+        let stronghold_path = "test_export_snapshot.stronghold";
+        let backup_path = "test_export_snapshot_backup.stronghold";
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+        adapter
+            .export_snapshot(Path::new(backup_path), "backup_password")
+            .await
+            .unwrap();
+
+        // The primary Stronghold is untouched.
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        // The backup can only be opened with the new password, and holds re-encrypted data.
+        assert!(StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(backup_path)
+            .is_err());
+        let backup = StrongholdAdapter::builder()
+            .password("backup_password")
+            .build(backup_path)
+            .unwrap();
+        assert_eq!(backup.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        fs::remove_file(stronghold_path).unwrap();
+        fs::remove_file(backup_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn client_paths() {
+        let stronghold_path = "test_client_paths.stronghold";
+        let mut adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .client_path(b"wallet")
+            .build(stronghold_path)
+            .unwrap();
+
+        assert_eq!(adapter.client_path(), b"wallet");
+        adapter.insert(b"key", b"wallet value").await.unwrap();
+
+        adapter.switch_client_path(b"app").await.unwrap();
+        assert_eq!(adapter.get(b"key").await.unwrap(), None);
+        adapter.insert(b"key", b"app value").await.unwrap();
+        assert_eq!(
+            adapter.client_paths().await.unwrap(),
+            vec![b"wallet".to_vec(), b"app".to_vec()]
+        );
+
+        // Both client paths are re-encrypted when changing the password.
+        adapter.change_password("drowssap", "new_password").await.unwrap();
+        adapter.clear_key().await;
+
+        let mut adapter = StrongholdAdapter::builder()
+            .password("new_password")
+            .client_path(b"wallet")
+            .build(stronghold_path)
+            .unwrap();
+        assert_eq!(
+            adapter.get(b"key").await.unwrap().as_deref(),
+            Some(&b"wallet value"[..])
+        );
+
+        adapter.switch_client_path(b"app").await.unwrap();
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"app value"[..]));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn shared_adapter() {
+        let stronghold_path = "test_shared_adapter.stronghold";
+        let adapter = Arc::new(
+            StrongholdAdapter::builder()
+                .password("drowssap")
+                .build(stronghold_path)
+                .unwrap(),
+        );
+
+        let tasks = (0..10u8)
+            .map(|i| {
+                let adapter = adapter.clone();
+                tokio::spawn(async move { adapter.insert(&[i], &[i]).await.unwrap() })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(adapter.keys().await.unwrap().len(), 10);
+        assert_eq!(adapter.get(&[5]).await.unwrap(), Some(vec![5]));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn auto_persist() {
+        let stronghold_path = "test_auto_persist.stronghold";
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .auto_persist(true)
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+
+        // A new Stronghold instance reads the change from the snapshot.
+        let reader = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        assert_eq!(reader.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        // With an interval, the write is deferred.
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .auto_persist(true)
+            .auto_persist_interval(Duration::from_millis(100))
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.delete(b"key").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let reader = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        assert_eq!(reader.get(b"key").await.unwrap(), None);
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn shutdown() {
+        let stronghold_path = "test_shutdown.stronghold";
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .timeout(Duration::from_secs(60))
+            .auto_persist(true)
+            .auto_persist_interval(Duration::from_secs(60))
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+        adapter.shutdown().await.unwrap();
+
+        // The tasks are stopped, the key is kept and the pending change has been written.
+        assert!(adapter.timeout_task.lock().await.is_none());
+        assert!(adapter.auto_persist_task.lock().await.is_none());
+        assert!(adapter.is_key_available().await);
+
+        let reader = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        assert_eq!(reader.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn lock() {
+        let stronghold_dir = "test_lock";
+        let stronghold_path = "test_lock/test_lock.stronghold";
+        fs::create_dir_all(stronghold_dir).unwrap();
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .timeout(Duration::from_secs(60))
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+
+        // The snapshot can't be written over a directory, so the adapter stays unlocked.
+        fs::remove_file(stronghold_path).ok();
+        fs::create_dir(stronghold_path).unwrap();
+        assert!(matches!(adapter.lock().await, Err(Error::StrongholdSnapshotWrite(_))));
+        assert!(adapter.is_key_available().await);
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        fs::remove_dir(stronghold_path).unwrap();
+        adapter.lock().await.unwrap();
+        assert!(adapter.timeout_task.lock().await.is_none());
+        assert!(!adapter.is_key_available().await);
+        assert!(!adapter.status().await.snapshot_loaded);
+
+        // Locking again does nothing.
+        adapter.lock().await.unwrap();
+
+        adapter.set_password("drowssap").await.unwrap();
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        fs::remove_dir_all(stronghold_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn persist_on_drop() {
+        let stronghold_path = "test_persist_on_drop.stronghold";
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .timeout(Duration::from_secs(60))
+            .persist_on_drop(true)
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+        let key_provider = adapter.key_provider.clone();
+        drop(adapter);
+
+        // The key has been cleared and the change written.
+        assert!(key_provider.lock().await.is_none());
+
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn persist_on_drop_in_use() {
+        let stronghold_path = "test_persist_on_drop_in_use.stronghold";
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .persist_on_drop(true)
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+        let key_provider = adapter.key_provider.clone();
+        let stronghold = adapter.stronghold.clone();
+        let stronghold_guard = stronghold.lock().await;
+        // Doesn't wait for the lock held on the runtime, skipping the write.
+        drop(adapter);
+        drop(stronghold_guard);
+
+        // The key hasn't been cleared.
+        assert!(key_provider.lock().await.is_some());
+
+        fs::remove_file(stronghold_path).ok();
+    }
+
+    #[tokio::test]
+    async fn password_policy() {
+        let stronghold_path = "test_password_policy.stronghold";
+        let password_policy = |password: &str| {
+            if password.len() < 8 {
+                Err("the password is too short".to_string())
+            } else if password == "password" {
+                Err("the password is too common".to_string())
+            } else {
+                Ok(())
+            }
+        };
+
+        for password in ["short", "password"] {
+            assert!(matches!(
+                StrongholdAdapter::builder()
+                    .password(password)
+                    .password_policy(password_policy)
+                    .build(stronghold_path),
+                Err(Error::StrongholdPasswordRejected(_))
+            ));
+            assert!(!Path::new(stronghold_path).exists());
+        }
+
+        let mut adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .password_policy(password_policy)
+            .build(stronghold_path)
+            .unwrap();
+
+        assert!(matches!(
+            adapter.change_password("drowssap", "short").await,
+            Err(Error::StrongholdPasswordRejected(_))
+        ));
+        adapter.change_password("drowssap", "new_password").await.unwrap();
+
+        // The policy doesn't apply to the password of an existing snapshot.
+        let adapter = StrongholdAdapter::builder()
+            .password("new_password")
+            .password_policy(|_| Err("rejected".to_string()))
+            .build(stronghold_path)
+            .unwrap();
+        adapter.clear_key().await;
+        adapter.set_password("new_password").await.unwrap();
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn backup_retention() {
+        let stronghold_path = "test_backup_retention.stronghold";
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .backup_retention(2)
+            .build(stronghold_path)
+            .unwrap();
+
+        for value in [b"1", b"2", b"3"] {
+            adapter.insert(b"key", value).await.unwrap();
+            adapter.write_stronghold_snapshot(None).await.unwrap();
+        }
+
+        // Only the two previous versions are kept, the most recent one first.
+        assert!(!Path::new("test_backup_retention.stronghold.3").exists());
+
+        for (backup_path, value) in [
+            ("test_backup_retention.stronghold.1", b"2"),
+            ("test_backup_retention.stronghold.2", b"1"),
+        ] {
+            let backup = StrongholdAdapter::builder()
+                .password("drowssap")
+                .build(backup_path)
+                .unwrap();
+            assert_eq!(backup.get(b"key").await.unwrap().as_deref(), Some(&value[..]));
+
+            fs::remove_file(backup_path).unwrap();
+        }
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn key_derivation_parameters() {
+        let stronghold_path = "test_key_derivation_parameters.stronghold";
+        let key_derivation_parameters = KeyDerivationParameters {
+            salt: b"salt".to_vec(),
+            iterations: std::num::NonZeroU32::new(1000).unwrap(),
+        };
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .key_derivation_parameters(key_derivation_parameters.clone())
+            .build(stronghold_path)
+            .unwrap();
+
+        adapter.insert(b"key", b"value").await.unwrap();
+        adapter.write_stronghold_snapshot(None).await.unwrap();
+
+        // The recorded parameters are used without configuring them.
+        let adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        assert_eq!(adapter.key_derivation_parameters, key_derivation_parameters);
+        assert_eq!(adapter.get(b"key").await.unwrap().as_deref(), Some(&b"value"[..]));
+
+        // Without them, the key derived from the password doesn't match.
+        fs::remove_file("test_key_derivation_parameters.stronghold.kdf").unwrap();
+        assert!(matches!(
+            StrongholdAdapter::builder().password("drowssap").build(stronghold_path),
+            Err(Error::StrongholdInvalidPassword)
+        ));
+
+        fs::remove_file(stronghold_path).unwrap();
+    }
+
     #[tokio::test]
     async fn stronghold_password_already_set() {
         let stronghold_path = "stronghold_password_already_set.stronghold";
-        let mut adapter = StrongholdAdapter::builder()
+        let adapter = StrongholdAdapter::builder()
             .password("drowssap")
             .build(stronghold_path)
             .unwrap();
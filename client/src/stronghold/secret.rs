@@ -8,7 +8,7 @@ use std::ops::Range;
 use async_trait::async_trait;
 use crypto::hashes::{blake2b::Blake2b256, Digest};
 use iota_stronghold::{
-    procedures::{self, Chain, KeyType, Slip10DeriveInput},
+    procedures::{self, Chain, KeyType, MnemonicLanguage, Slip10DeriveInput},
     Location,
 };
 use iota_types::block::{
@@ -18,13 +18,14 @@ use iota_types::block::{
 };
 use zeroize::Zeroize;
 
-use super::{
-    common::{DERIVE_OUTPUT_RECORD_PATH, PRIVATE_DATA_CLIENT_PATH, SECRET_VAULT_PATH, SEED_RECORD_PATH},
-    StrongholdAdapter,
-};
+use super::StrongholdAdapter;
 use crate::{
     api::RemainderData,
-    secret::{types::InputSigningData, GenerateAddressOptions, SecretManage},
+    secret::{
+        tag::{tag_key_chain, TagKey, TAG_KEY_MESSAGE},
+        types::InputSigningData,
+        GenerateAddressOptions, SecretManage,
+    },
     Error, Result,
 };
 
@@ -36,7 +37,7 @@ impl SecretManage for StrongholdAdapter {
         account_index: u32,
         address_indexes: Range<u32>,
         internal: bool,
-        _options: Option<GenerateAddressOptions>,
+        options: Option<GenerateAddressOptions>,
     ) -> Result<Vec<Address>> {
         // Prevent the method from being invoked when the key has been cleared from the memory. Do note that Stronghold
         // only asks for a key for reading / writing a snapshot, so without our cached key this method is invocable, but
@@ -48,33 +49,48 @@ impl SecretManage for StrongholdAdapter {
         }
 
         // Stronghold arguments.
-        let seed_location = Slip10DeriveInput::Seed(Location::generic(SECRET_VAULT_PATH, SEED_RECORD_PATH));
-        let derive_location = Location::generic(SECRET_VAULT_PATH, DERIVE_OUTPUT_RECORD_PATH);
-
-        // Addresses to return.
-        let mut addresses = Vec::new();
-
-        for address_index in address_indexes {
-            let chain = Chain::from_u32_hardened(vec![44u32, coin_type, account_index, internal as u32, address_index]);
-
-            // Derive a SLIP-10 private key in the vault.
-            self.slip10_derive(chain, seed_location.clone(), derive_location.clone())
-                .await?;
-
-            // Get the Ed25519 public key from the derived SLIP-10 private key in the vault.
-            let public_key = self.ed25519_public_key(derive_location.clone()).await?;
-
-            // Hash the public key to get the address.
-            let hash = Blake2b256::digest(public_key);
-
-            // Convert the hash into [Address].
-            let address = Address::Ed25519(Ed25519Address::new(hash.into()));
-
-            // Collect it.
-            addresses.push(address);
-        }
+        let seed_location = Slip10DeriveInput::Seed(self.vault_paths.seed_location());
+        let derive_location = self.vault_paths.derive_location();
+        let derivation_path = GenerateAddressOptions::derivation_path(&options);
+
+        let chains = address_indexes
+            .map(|address_index| {
+                Chain::from_u32_hardened(derivation_path.indexes(coin_type, account_index, internal, address_index))
+            })
+            .collect::<Vec<_>>();
+        let client_path = self.client_path.clone();
+
+        // Derive all the keys in a single operation, which is much faster for large ranges, and prevents concurrent
+        // calls from overwriting the derived key before its public key is read.
+        let public_keys = self
+            .run_operation(move |stronghold, _| {
+                let client = stronghold.get_client(&client_path)?;
+
+                chains
+                    .into_iter()
+                    .map(|chain| {
+                        // Derive a SLIP-10 private key in the vault.
+                        client.execute_procedure(procedures::Slip10Derive {
+                            chain,
+                            input: seed_location.clone(),
+                            output: derive_location.clone(),
+                        })?;
+
+                        // Get the Ed25519 public key from the derived SLIP-10 private key in the vault.
+                        Ok(client.execute_procedure(procedures::PublicKey {
+                            ty: KeyType::Ed25519,
+                            private_key: derive_location.clone(),
+                        })?)
+                    })
+                    .collect::<Result<Vec<[u8; 32]>>>()
+            })
+            .await?;
 
-        Ok(addresses)
+        // Hash the public keys to get the addresses.
+        Ok(public_keys
+            .into_iter()
+            .map(|public_key| Address::Ed25519(Ed25519Address::new(Blake2b256::digest(public_key).into())))
+            .collect())
     }
 
     async fn signature_unlock(
@@ -83,6 +99,14 @@ impl SecretManage for StrongholdAdapter {
         essence_hash: &[u8; 32],
         _: &Option<RemainderData>,
     ) -> Result<Unlock> {
+        // The signature unlock block needs to sign the hash of the entire transaction essence of the
+        // transaction payload
+        let signature = self.sign_ed25519(essence_hash, input.chain.as_ref().unwrap()).await?;
+
+        Ok(Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(signature))))
+    }
+
+    async fn sign_ed25519(&self, message: &[u8], chain: &crypto::keys::slip10::Chain) -> Result<Ed25519Signature> {
         // Prevent the method from being invoked when the key has been cleared from the memory. Do note that Stronghold
         // only asks for a key for reading / writing a snapshot, so without our cached key this method is invocable, but
         // it doesn't make sense when it comes to our user (signing transactions / generating addresses without a key).
@@ -92,16 +116,9 @@ impl SecretManage for StrongholdAdapter {
             return Err(Error::StrongholdKeyCleared);
         }
 
-        // Stronghold arguments.
-        let seed_location = Slip10DeriveInput::Seed(Location::generic(SECRET_VAULT_PATH, SEED_RECORD_PATH));
-        let derive_location = Location::generic(SECRET_VAULT_PATH, DERIVE_OUTPUT_RECORD_PATH);
-
         // Stronghold asks for an older version of [Chain], so we have to perform a conversion here.
         let chain = {
-            let raw: Vec<u32> = input
-                .chain
-                .as_ref()
-                .unwrap()
+            let raw: Vec<u32> = chain
                 .segments()
                 .iter()
                 // XXX: "ser32(i)". RTFSC: [crypto::keys::slip10::Segment::from_u32()]
@@ -111,22 +128,9 @@ impl SecretManage for StrongholdAdapter {
             Chain::from_u32_hardened(raw)
         };
 
-        // Derive a SLIP-10 private key in the vault.
-        self.slip10_derive(chain, seed_location, derive_location.clone())
-            .await?;
-
-        // Get the Ed25519 public key from the derived SLIP-10 private key in the vault.
-        let public_key = self.ed25519_public_key(derive_location.clone()).await?;
+        let (public_key, signature) = self.slip10_derive_and_sign(chain, message).await?;
 
-        // Sign the essence hash with the derived SLIP-10 private key in the vault.
-        let signature = self.ed25519_sign(derive_location, essence_hash).await?;
-
-        // Convert the raw bytes into [Unlock].
-        let unlock = Unlock::Signature(SignatureUnlock::new(Signature::Ed25519(Ed25519Signature::new(
-            public_key, signature,
-        ))));
-
-        Ok(unlock)
+        Ok(Ed25519Signature::new(public_key, signature))
     }
 }
 
@@ -134,66 +138,206 @@ impl SecretManage for StrongholdAdapter {
 impl StrongholdAdapter {
     /// Execute [Procedure::BIP39Recover] in Stronghold to put a mnemonic into the Stronghold vault.
     async fn bip39_recover(&self, mnemonic: String, passphrase: Option<String>, output: Location) -> Result<()> {
-        self.stronghold
-            .lock()
-            .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .execute_procedure(procedures::BIP39Recover {
-                mnemonic,
-                passphrase,
-                output,
+        let client_path = self.client_path.clone();
+
+        self.run_operation(move |stronghold, _| {
+            stronghold
+                .get_client(&client_path)?
+                .execute_procedure(procedures::BIP39Recover {
+                    mnemonic,
+                    passphrase,
+                    output,
+                })?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Execute [Procedure::BIP39Generate] in Stronghold to generate a mnemonic and put its seed into the Stronghold
+    /// vault.
+    async fn bip39_generate(&self, passphrase: Option<String>, output: Location) -> Result<String> {
+        let client_path = self.client_path.clone();
+
+        self.run_operation(move |stronghold, _| {
+            Ok(stronghold
+                .get_client(&client_path)?
+                .execute_procedure(procedures::BIP39Generate {
+                    passphrase,
+                    language: MnemonicLanguage::English,
+                    output,
+                })?)
+        })
+        .await
+    }
+
+    /// Derive a SLIP-10 private key in the Stronghold vault and sign `msg` with it, returning the Ed25519 public key
+    /// of the derived key and the signature.
+    ///
+    /// The derived key is stored at the single derive location of the vault, so all the steps run in one operation;
+    /// otherwise a concurrent derivation could overwrite it in between and the public key and signature would come
+    /// from different keys.
+    async fn slip10_derive_and_sign(&self, chain: Chain, msg: &[u8]) -> Result<([u8; 32], [u8; 64])> {
+        let client_path = self.client_path.clone();
+        let seed_location = Slip10DeriveInput::Seed(self.vault_paths.seed_location());
+        let derive_location = self.vault_paths.derive_location();
+        let msg = msg.to_vec();
+
+        self.run_operation(move |stronghold, _| {
+            let client = stronghold.get_client(&client_path)?;
+
+            // Derive a SLIP-10 private key in the vault.
+            client.execute_procedure(procedures::Slip10Derive {
+                chain,
+                input: seed_location,
+                output: derive_location.clone(),
             })?;
 
-        Ok(())
+            // Get the Ed25519 public key from the derived SLIP-10 private key in the vault.
+            let public_key = client.execute_procedure(procedures::PublicKey {
+                ty: KeyType::Ed25519,
+                private_key: derive_location.clone(),
+            })?;
+
+            // Sign the message with the derived SLIP-10 private key in the vault.
+            let signature = client.execute_procedure(procedures::Ed25519Sign {
+                private_key: derive_location,
+                msg,
+            })?;
+
+            Ok((public_key, signature))
+        })
+        .await
     }
 
-    /// Execute [Procedure::SLIP10Derive] in Stronghold to derive a SLIP-10 private key in the Stronghold vault.
-    async fn slip10_derive(&self, chain: Chain, input: Slip10DeriveInput, output: Location) -> Result<()> {
-        self.stronghold
-            .lock()
-            .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .execute_procedure(procedures::Slip10Derive { chain, input, output })?;
+    /// Derive the [`TagKey`] of an account, signing in the Stronghold vault.
+    pub async fn derive_tag_key(&self, coin_type: u32, account_index: u32) -> Result<TagKey> {
+        // The key needs to be supplied first.
+        if !self.is_key_available().await {
+            return Err(Error::StrongholdKeyCleared);
+        }
 
-        Ok(())
+        let chain = Chain::from_u32_hardened(tag_key_chain(coin_type, account_index));
+        let (_, signature) = self.slip10_derive_and_sign(chain, TAG_KEY_MESSAGE).await?;
+
+        Ok(TagKey::from_signature(&signature))
     }
 
-    /// Execute [Procedure::Ed25519PublicKey] in Stronghold to get an Ed25519 public key from the SLIP-10 private key
-    /// located in `private_key`.
-    async fn ed25519_public_key(&self, private_key: Location) -> Result<[u8; 32]> {
-        Ok(self
-            .stronghold
-            .lock()
-            .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .execute_procedure(procedures::PublicKey {
-                ty: KeyType::Ed25519,
-                private_key,
-            })?)
+    /// Check whether a mnemonic (seed) has been stored into the Stronghold vault, without decrypting or exposing it.
+    pub async fn is_mnemonic_stored(&self) -> Result<bool> {
+        // The key needs to be supplied first.
+        if self.key_provider.lock().await.is_none() {
+            return Err(Error::StrongholdKeyCleared);
+        };
+
+        let client_path = self.client_path.clone();
+        let seed_location = self.vault_paths.seed_location();
+
+        self.run_operation(move |stronghold, _| {
+            Ok(stronghold.get_client(&client_path)?.record_exists(&seed_location)?)
+        })
+        .await
     }
 
-    /// Execute [Procedure::Ed25519Sign] in Stronghold to sign `msg` with `private_key` stored in the Stronghold vault.
-    async fn ed25519_sign(&self, private_key: Location, msg: &[u8]) -> Result<[u8; 64]> {
-        Ok(self
-            .stronghold
-            .lock()
-            .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .execute_procedure(procedures::Ed25519Sign {
-                private_key,
-                msg: msg.to_vec(),
-            })?)
+    /// Garbage-collect the SLIP-10 private key derived last in the vault, and compact the snapshot.
+    ///
+    /// Deriving addresses or signing leaves the last derived private key in the vault, under
+    /// [`VaultPaths::derive_output_record_path`](super::VaultPaths::derive_output_record_path); it's re-derived from
+    /// the seed whenever it's needed, so it can be removed. The snapshot is then rewritten without it. Returns
+    /// whether a record has been removed.
+    pub async fn prune_derived_records(&self) -> Result<bool> {
+        // The key needs to be supplied first.
+        if self.key_provider.lock().await.is_none() {
+            return Err(Error::StrongholdKeyCleared);
+        };
+
+        let client_path = self.client_path.clone();
+        let vault_paths = self.vault_paths.clone();
+
+        let pruned = self
+            .run_operation(move |stronghold, _| {
+                let client = stronghold.get_client(&client_path)?;
+
+                if !client.record_exists(&vault_paths.derive_location())? {
+                    return Ok(false);
+                }
+
+                // Revokes the record and collects the garbage.
+                Ok(client
+                    .vault(vault_paths.secret_vault_path)
+                    .delete_secret(vault_paths.derive_output_record_path)?)
+            })
+            .await?;
+
+        // Rewrite the snapshot, which only contains the remaining records.
+        if pruned {
+            self.write_stronghold_snapshot(None).await?;
+        }
+
+        Ok(pruned)
+    }
+
+    /// Generate a mnemonic inside Stronghold and store its seed, with an optional BIP-39 passphrase, into the vault,
+    /// so that the mnemonic never has to be handled by the application.
+    ///
+    /// If `return_mnemonic` is set, the mnemonic is returned, once, e.g. to be displayed to the user for a backup;
+    /// otherwise it's zeroized right away and the seed can't be exported anymore.
+    ///
+    /// Fails with [`Error::StrongholdMnemonicAlreadyStored`] if one has already been stored, see
+    /// [`is_mnemonic_stored()`](Self::is_mnemonic_stored()).
+    pub async fn generate_mnemonic_in_vault(
+        &self,
+        passphrase: Option<String>,
+        return_mnemonic: bool,
+    ) -> Result<Option<String>> {
+        // The key needs to be supplied first.
+        if self.key_provider.lock().await.is_none() {
+            return Err(Error::StrongholdKeyCleared);
+        };
+
+        // We need to check if there has been a mnemonic stored in Stronghold or not to prevent overwriting it.
+        if self.is_mnemonic_stored().await? {
+            return Err(crate::Error::StrongholdMnemonicAlreadyStored);
+        }
+
+        // Execute the BIP-39 generation procedure to put the seed into the vault (in memory).
+        let mut mnemonic = self
+            .bip39_generate(passphrase, self.vault_paths.seed_location())
+            .await?;
+
+        // Persist Stronghold to the disk
+        self.write_stronghold_snapshot(None).await?;
+
+        if return_mnemonic {
+            Ok(Some(mnemonic))
+        } else {
+            mnemonic.zeroize();
+
+            Ok(None)
+        }
     }
 
     /// Store a mnemonic into the Stronghold vault.
-    pub async fn store_mnemonic(&mut self, mut mnemonic: String) -> Result<()> {
+    ///
+    /// Fails with [`Error::StrongholdMnemonicAlreadyStored`] if one has already been stored, see
+    /// [`is_mnemonic_stored()`](Self::is_mnemonic_stored()).
+    pub async fn store_mnemonic(&self, mnemonic: String) -> Result<()> {
+        self.store_mnemonic_with_passphrase(mnemonic, None).await
+    }
+
+    /// Store a mnemonic into the Stronghold vault, converting it to a seed with an optional BIP-39 passphrase (also
+    /// known as the 25th word), e.g. to restore a passphrase-protected wallet.
+    ///
+    /// Fails with [`Error::StrongholdMnemonicAlreadyStored`] if one has already been stored, see
+    /// [`is_mnemonic_stored()`](Self::is_mnemonic_stored()).
+    pub async fn store_mnemonic_with_passphrase(&self, mut mnemonic: String, passphrase: Option<String>) -> Result<()> {
         // The key needs to be supplied first.
         if self.key_provider.lock().await.is_none() {
             return Err(Error::StrongholdKeyCleared);
         };
 
         // Stronghold arguments.
-        let output = Location::generic(SECRET_VAULT_PATH, SEED_RECORD_PATH);
+        let output = self.vault_paths.seed_location();
 
         // Trim the mnemonic, in case it hasn't been, as otherwise the restored seed would be wrong.
         let trimmed_mnemonic = mnemonic.trim().to_string();
@@ -204,18 +348,12 @@ impl StrongholdAdapter {
             .map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))?;
 
         // We need to check if there has been a mnemonic stored in Stronghold or not to prevent overwriting it.
-        if self
-            .stronghold
-            .lock()
-            .await
-            .get_client(PRIVATE_DATA_CLIENT_PATH)?
-            .record_exists(&output)?
-        {
+        if self.is_mnemonic_stored().await? {
             return Err(crate::Error::StrongholdMnemonicAlreadyStored);
         }
 
         // Execute the BIP-39 recovery procedure to put it into the vault (in memory).
-        self.bip39_recover(trimmed_mnemonic, None, output).await?;
+        self.bip39_recover(trimmed_mnemonic, passphrase, output).await?;
 
         // Persist Stronghold to the disk
         self.write_stronghold_snapshot(None).await?;
@@ -229,7 +367,7 @@ mod tests {
     use std::path::Path;
 
     use super::*;
-    use crate::constants::IOTA_COIN_TYPE;
+    use crate::{constants::IOTA_COIN_TYPE, stronghold::VaultPaths};
 
     #[tokio::test]
     async fn test_address_generation() {
@@ -239,16 +377,24 @@ mod tests {
         let mnemonic = String::from(
             "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
         );
-        let mut stronghold_adapter = StrongholdAdapter::builder()
+        let stronghold_adapter = StrongholdAdapter::builder()
             .password("drowssap")
             .build(stronghold_path)
             .unwrap();
 
-        stronghold_adapter.store_mnemonic(mnemonic).await.unwrap();
+        assert!(!stronghold_adapter.is_mnemonic_stored().await.unwrap());
+        stronghold_adapter.store_mnemonic(mnemonic.clone()).await.unwrap();
+        assert!(stronghold_adapter.is_mnemonic_stored().await.unwrap());
 
         // The snapshot should have been on the disk now.
         assert!(Path::new(stronghold_path).exists());
 
+        // A stored mnemonic isn't overwritten.
+        assert!(matches!(
+            stronghold_adapter.store_mnemonic(mnemonic).await,
+            Err(Error::StrongholdMnemonicAlreadyStored)
+        ));
+
         let addresses = stronghold_adapter
             .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
             .await
@@ -263,6 +409,238 @@ mod tests {
         std::fs::remove_file(stronghold_path).unwrap_or(());
     }
 
+    #[tokio::test]
+    async fn test_mnemonic_passphrase() {
+        let stronghold_path = "test_mnemonic_passphrase.stronghold";
+        // Remove potential old stronghold file
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+        let mnemonic = String::from(
+            "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
+        );
+        let stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+
+        stronghold_adapter
+            .store_mnemonic_with_passphrase(mnemonic, Some(String::from("passphrase")))
+            .await
+            .unwrap();
+
+        let addresses = stronghold_adapter
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap();
+
+        // The passphrase leads to another seed than the mnemonic alone.
+        assert_ne!(
+            addresses[0].to_bech32("atoi"),
+            "atoi1qpszqzadsym6wpppd6z037dvlejmjuke7s24hm95s9fg9vpua7vluehe53e".to_string()
+        );
+
+        // Remove garbage after test, but don't care about the result
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+    }
+
+    #[tokio::test]
+    async fn test_generate_mnemonic_in_vault() {
+        let stronghold_path = "test_generate_mnemonic_in_vault.stronghold";
+        // Remove potential old stronghold file
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+        let stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+
+        let mnemonic = stronghold_adapter
+            .generate_mnemonic_in_vault(None, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stronghold_adapter.is_mnemonic_stored().await.unwrap());
+
+        // The seed in the vault is the one of the returned mnemonic.
+        let addresses = stronghold_adapter
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap();
+        let mnemonic_addresses = crate::secret::mnemonic::MnemonicSecretManager::try_from_mnemonic(&mnemonic)
+            .unwrap()
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap();
+        assert_eq!(addresses, mnemonic_addresses);
+
+        // The stored mnemonic isn't overwritten.
+        assert!(matches!(
+            stronghold_adapter.generate_mnemonic_in_vault(None, false).await,
+            Err(Error::StrongholdMnemonicAlreadyStored)
+        ));
+
+        // Remove garbage after test, but don't care about the result
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+    }
+
+    #[tokio::test]
+    async fn test_prune_derived_records() {
+        let stronghold_path = "test_prune_derived_records.stronghold";
+        // Remove potential old stronghold file
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+        let mnemonic = String::from(
+            "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
+        );
+        let stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        stronghold_adapter.store_mnemonic(mnemonic).await.unwrap();
+
+        // Nothing has been derived yet.
+        assert!(!stronghold_adapter.prune_derived_records().await.unwrap());
+
+        let addresses = stronghold_adapter
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap();
+        assert!(stronghold_adapter.prune_derived_records().await.unwrap());
+        assert!(!stronghold_adapter.prune_derived_records().await.unwrap());
+
+        // The seed is kept, so the same addresses are derived again.
+        assert!(stronghold_adapter.is_mnemonic_stored().await.unwrap());
+        assert_eq!(
+            stronghold_adapter
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+                .await
+                .unwrap(),
+            addresses
+        );
+
+        // Remove garbage after test, but don't care about the result
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+    }
+
+    #[tokio::test]
+    async fn test_vault_paths() {
+        let stronghold_path = "test_vault_paths.stronghold";
+        // Remove potential old stronghold file
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+        let mnemonic = String::from(
+            "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
+        );
+        let vault_paths = VaultPaths {
+            secret_vault_path: b"other-secret".to_vec(),
+            seed_record_path: b"other-seed".to_vec(),
+            derive_output_record_path: b"other-derived".to_vec(),
+        };
+        let stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .vault_paths(vault_paths.clone())
+            .build(stronghold_path)
+            .unwrap();
+        stronghold_adapter.store_mnemonic(mnemonic).await.unwrap();
+        let addresses = stronghold_adapter
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+            .await
+            .unwrap();
+        drop(stronghold_adapter);
+
+        // With the historical paths, the seed isn't found.
+        let stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        assert!(!stronghold_adapter.is_mnemonic_stored().await.unwrap());
+        drop(stronghold_adapter);
+
+        let stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .vault_paths(vault_paths)
+            .build(stronghold_path)
+            .unwrap();
+        assert!(stronghold_adapter.is_mnemonic_stored().await.unwrap());
+        assert_eq!(
+            stronghold_adapter
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+                .await
+                .unwrap(),
+            addresses
+        );
+
+        // Remove garbage after test, but don't care about the result
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+    }
+
+    #[tokio::test]
+    async fn test_tag_key() {
+        let stronghold_path = "test_tag_key.stronghold";
+        // Remove potential old stronghold file
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+        let mnemonic = String::from(
+            "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
+        );
+        let stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        stronghold_adapter.store_mnemonic(mnemonic.clone()).await.unwrap();
+
+        let tag_key = stronghold_adapter.derive_tag_key(IOTA_COIN_TYPE, 0).await.unwrap();
+
+        // The same key is derived from the mnemonic outside of Stronghold.
+        let mnemonic_tag_key = crate::secret::mnemonic::MnemonicSecretManager::try_from_mnemonic(&mnemonic)
+            .unwrap()
+            .derive_tag_key(IOTA_COIN_TYPE, 0)
+            .unwrap();
+        assert_eq!(tag_key.as_bytes(), mnemonic_tag_key.as_bytes());
+
+        // Remove garbage after test, but don't care about the result
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_signing() {
+        let stronghold_path = "test_concurrent_signing.stronghold";
+        // Remove potential old stronghold file
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+        let mnemonic = String::from(
+            "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
+        );
+        let stronghold_adapter = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(stronghold_path)
+            .unwrap();
+        stronghold_adapter.store_mnemonic(mnemonic).await.unwrap();
+
+        let addresses = stronghold_adapter
+            .generate_addresses(IOTA_COIN_TYPE, 0, 0..8, false, None)
+            .await
+            .unwrap();
+
+        // Each signature has to be made and paired with the key of its own chain, while the others are derived.
+        let signatures = futures::future::try_join_all((0..8).map(|address_index| {
+            let chain = crypto::keys::slip10::Chain::from_u32_hardened(vec![
+                crate::constants::HD_WALLET_TYPE,
+                IOTA_COIN_TYPE,
+                0,
+                0,
+                address_index,
+            ]);
+            let stronghold_adapter = &stronghold_adapter;
+
+            async move { stronghold_adapter.sign_ed25519(b"message", &chain).await }
+        }))
+        .await
+        .unwrap();
+
+        for (signature, address) in signatures.iter().zip(&addresses) {
+            let Address::Ed25519(address) = address else { panic!("not an Ed25519 address") };
+            signature.is_valid(b"message", address).unwrap();
+        }
+
+        // Remove garbage after test, but don't care about the result
+        std::fs::remove_file(stronghold_path).unwrap_or(());
+    }
+
     #[tokio::test]
     async fn test_key_cleared() {
         let stronghold_path = "test_key_cleared.stronghold";
@@ -271,7 +649,7 @@ mod tests {
         let mnemonic = String::from(
             "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
         );
-        let mut stronghold_adapter = StrongholdAdapter::builder()
+        let stronghold_adapter = StrongholdAdapter::builder()
             .password("drowssap")
             .build(stronghold_path)
             .unwrap();
@@ -0,0 +1,213 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transfer of the seed between Stronghold snapshots, e.g. to migrate a wallet to another device.
+//!
+//! The seed is exported into a _transfer snapshot_ holding nothing but the seed record, encrypted with a key derived
+//! from a transfer passphrase; it never leaves Stronghold in the clear.
+
+use std::path::Path;
+
+use iota_stronghold::{SnapshotPath, Stronghold};
+
+use super::{
+    common::key_provider_from_password, load_or_create_client, registered_client_paths, KeyDerivationParameters,
+    StrongholdAdapter,
+};
+use crate::{Error, Result};
+
+impl StrongholdAdapter {
+    /// Export the seed stored in the vault to a transfer snapshot at `path`, encrypted with a key derived from
+    /// `transfer_passphrase`, to be imported with [`import_seed()`](Self::import_seed()).
+    ///
+    /// The transfer snapshot only holds the seed, under the client path of this adapter; the data saved via the
+    /// [`DatabaseProvider`] interface isn't exported. Fails with [`Error::StrongholdMnemonicMissing`] if no mnemonic
+    /// has been stored.
+    ///
+    /// [`DatabaseProvider`]: crate::db::DatabaseProvider
+    pub async fn export_seed(&self, path: &Path, transfer_passphrase: &str) -> Result<()> {
+        if !self.is_mnemonic_stored().await? {
+            return Err(Error::StrongholdMnemonicMissing);
+        }
+
+        let transfer_key_provider = key_provider_from_password(transfer_passphrase, &self.key_derivation_parameters);
+
+        {
+            // The key needs to be supplied first.
+            let locked_key_provider = self.key_provider.lock().await;
+            let key_provider = if let Some(key_provider) = &*locked_key_provider {
+                key_provider
+            } else {
+                return Err(Error::StrongholdKeyCleared);
+            };
+
+            // The seed is written to the snapshot when it's stored, so the client path is loaded from there into a
+            // separate instance, to be stripped of everything else.
+            let transfer = Stronghold::default();
+            transfer.load_client_from_snapshot(
+                &self.client_path,
+                key_provider,
+                &SnapshotPath::from_path(&self.snapshot_path),
+            )?;
+            let client = transfer.get_client(&self.client_path)?;
+
+            let store = client.store();
+            for key in store.keys()? {
+                store.delete(&key)?;
+            }
+
+            if client.record_exists(&self.vault_paths.derive_location())? {
+                client
+                    .vault(self.vault_paths.secret_vault_path.clone())
+                    .delete_secret(self.vault_paths.derive_output_record_path.clone())?;
+            }
+
+            transfer.commit_with_keyprovider(&SnapshotPath::from_path(path), &transfer_key_provider)?;
+        }
+
+        // The transfer snapshot is encrypted with the same key derivation parameters.
+        self.key_derivation_parameters.record(path)
+    }
+
+    /// Import the seed from a transfer snapshot at `path` written by [`export_seed()`](Self::export_seed()),
+    /// decrypting it with `transfer_passphrase`, then write the snapshot.
+    ///
+    /// The transfer snapshot has to have been exported from the same client path as the one of this adapter. The data
+    /// saved via the [`DatabaseProvider`] interface is kept. Fails with [`Error::StrongholdMnemonicAlreadyStored`] if
+    /// a mnemonic has already been stored, and with [`Error::StrongholdMnemonicMissing`] if the transfer snapshot
+    /// doesn't hold a seed.
+    ///
+    /// [`DatabaseProvider`]: crate::db::DatabaseProvider
+    pub async fn import_seed(&self, path: &Path, transfer_passphrase: &str) -> Result<()> {
+        // We need to check if there has been a mnemonic stored in Stronghold or not to prevent overwriting it.
+        if self.is_mnemonic_stored().await? {
+            return Err(Error::StrongholdMnemonicAlreadyStored);
+        }
+
+        let transfer_key_provider = key_provider_from_password(
+            transfer_passphrase,
+            &KeyDerivationParameters::read_recorded(path)?.unwrap_or_default(),
+        );
+
+        // Everything is read back from the snapshot below, so it has to be up to date.
+        self.write_stronghold_snapshot(None).await?;
+
+        // The key needs to be supplied first.
+        let locked_key_provider = self.key_provider.lock().await;
+        let key_provider = if let Some(key_provider) = &*locked_key_provider {
+            key_provider
+        } else {
+            return Err(Error::StrongholdKeyCleared);
+        };
+
+        let mut stronghold = self.stronghold.lock().await;
+        let snapshot_path = SnapshotPath::from_path(&self.snapshot_path);
+
+        // Vault records can't be copied between Stronghold instances, so a new instance is assembled from the client
+        // path of the transfer snapshot, holding the seed, and the data of this one, then replaces it.
+        let imported = Stronghold::default();
+        load_or_create_client(
+            &imported,
+            &transfer_key_provider,
+            &SnapshotPath::from_path(path),
+            &self.client_path,
+        )?;
+
+        let client = imported.get_client(&self.client_path)?;
+        if !client.record_exists(&self.vault_paths.seed_location())? {
+            return Err(Error::StrongholdMnemonicMissing);
+        }
+
+        // The values are encrypted with the key of this adapter and copied as they are.
+        let store = stronghold.get_client(&self.client_path)?.store();
+        let imported_store = client.store();
+        for key in store.keys()? {
+            if let Some(value) = store.get(&key)? {
+                imported_store.insert(key, value, None)?;
+            }
+        }
+
+        // The other client paths, and their registry, are taken as they are.
+        for client_path in registered_client_paths(&imported, key_provider, &snapshot_path)? {
+            if client_path != self.client_path {
+                load_or_create_client(&imported, key_provider, &snapshot_path, &client_path)?;
+            }
+        }
+
+        imported.commit_with_keyprovider(&snapshot_path, key_provider)?;
+        *stronghold = imported;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{constants::IOTA_COIN_TYPE, db::DatabaseProvider, secret::SecretManage};
+
+    #[tokio::test]
+    async fn transfer_seed() {
+        let source_path = "test_transfer_seed_source.stronghold";
+        let target_path = "test_transfer_seed_target.stronghold";
+        let transfer_path = "test_transfer_seed.stronghold";
+        let mnemonic = String::from(
+            "giant dynamic museum toddler six deny defense ostrich bomb access mercy blood explain muscle shoot shallow glad autumn author calm heavy hawk abuse rally",
+        );
+
+        let source = StrongholdAdapter::builder()
+            .password("drowssap")
+            .build(source_path)
+            .unwrap();
+
+        // There is no seed to export yet.
+        assert!(matches!(
+            source.export_seed(Path::new(transfer_path), "transfer").await,
+            Err(Error::StrongholdMnemonicMissing)
+        ));
+
+        source.store_mnemonic(mnemonic).await.unwrap();
+        source.insert(b"source", b"source").await.unwrap();
+        source.export_seed(Path::new(transfer_path), "transfer").await.unwrap();
+
+        let target = StrongholdAdapter::builder()
+            .password("other_password")
+            .build(target_path)
+            .unwrap();
+        target.insert(b"target", b"target").await.unwrap();
+
+        // A wrong transfer passphrase fails.
+        assert!(matches!(
+            target.import_seed(Path::new(transfer_path), "drowssap").await,
+            Err(Error::StrongholdInvalidPassword)
+        ));
+
+        target.import_seed(Path::new(transfer_path), "transfer").await.unwrap();
+
+        // The seed has been transferred, but not the data of the source.
+        assert_eq!(
+            target
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+                .await
+                .unwrap(),
+            source
+                .generate_addresses(IOTA_COIN_TYPE, 0, 0..1, false, None)
+                .await
+                .unwrap()
+        );
+        assert_eq!(target.get(b"target").await.unwrap().as_deref(), Some(&b"target"[..]));
+        assert_eq!(target.get(b"source").await.unwrap(), None);
+
+        // The stored mnemonic isn't overwritten.
+        assert!(matches!(
+            target.import_seed(Path::new(transfer_path), "transfer").await,
+            Err(Error::StrongholdMnemonicAlreadyStored)
+        ));
+
+        fs::remove_file(source_path).unwrap();
+        fs::remove_file(target_path).unwrap();
+        fs::remove_file(transfer_path).unwrap();
+    }
+}
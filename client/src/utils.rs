@@ -13,12 +13,15 @@ use crypto::{
 use iota_types::block::{
     address::{Address, AliasAddress, Ed25519Address, NftAddress},
     output::{AliasId, NftId},
-    payload::TaggedDataPayload,
+    payload::{transaction::TransactionEssence, TaggedDataPayload},
 };
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 use super::Client;
-use crate::error::{Error, Result};
+use crate::{
+    api::PreparedTransactionData,
+    error::{Error, Result},
+};
 
 /// Transforms bech32 to hex
 pub fn bech32_to_hex(bech32: &str) -> Result<String> {
@@ -68,14 +71,53 @@ pub fn is_address_valid(address: &str) -> bool {
     Address::try_from_bech32(address).is_ok()
 }
 
+/// The language of the wordlist of a mnemonic.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MnemonicLanguage {
+    /// The English wordlist.
+    #[default]
+    English,
+    /// The Japanese wordlist, whose words are separated by ideographic spaces.
+    Japanese,
+}
+
+impl MnemonicLanguage {
+    pub(crate) fn wordlist(&self) -> &'static wordlist::Wordlist<'static> {
+        match self {
+            Self::English => &wordlist::ENGLISH,
+            Self::Japanese => &wordlist::JAPANESE,
+        }
+    }
+}
+
 /// Generates a new mnemonic.
 pub fn generate_mnemonic() -> Result<String> {
-    let mut entropy = [0u8; 32];
+    generate_mnemonic_with(24, MnemonicLanguage::English)
+}
+
+/// Generates a new mnemonic of `word_count` words, 12, 18 or 24, from the wordlist of `language`.
+pub fn generate_mnemonic_with(word_count: usize, language: MnemonicLanguage) -> Result<String> {
+    // Each word encodes 11 bits, of which 32 bits of entropy per 3 words
+    let mut entropy = match word_count {
+        12 | 18 | 24 => vec![0u8; word_count / 3 * 4],
+        _ => {
+            return Err(crate::Error::InvalidMnemonic(format!(
+                "unsupported word count {word_count}, expected 12, 18 or 24"
+            )));
+        }
+    };
     utils::rand::fill(&mut entropy)?;
-    let mnemonic = wordlist::encode(&entropy, &crypto::keys::bip39::wordlist::ENGLISH)
-        .map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))?;
+    let mnemonic =
+        wordlist::encode(&entropy, language.wordlist()).map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")));
     entropy.zeroize();
-    Ok(mnemonic)
+    mnemonic
+}
+
+/// Verifies that `mnemonic` is a valid mnemonic of the wordlist of `language`, with a valid checksum.
+pub fn verify_mnemonic(mnemonic: &str, language: MnemonicLanguage) -> Result<()> {
+    // trim because empty spaces could create a different seed https://github.com/iotaledger/crypto.rs/issues/125
+    wordlist::verify(mnemonic.trim(), language.wordlist()).map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))
 }
 
 /// Returns a hex encoded seed for a mnemonic.
@@ -92,14 +134,64 @@ pub fn mnemonic_to_hex_seed(mnemonic: &str) -> Result<String> {
 
 /// Returns a seed for a mnemonic.
 pub fn mnemonic_to_seed(mnemonic: &str) -> Result<Seed> {
+    Ok(Seed::from_bytes(&*mnemonic_to_seed_bytes(mnemonic)?))
+}
+
+/// Returns the raw BIP-39 seed of a mnemonic.
+pub(crate) fn mnemonic_to_seed_bytes(mnemonic: &str) -> Result<Zeroizing<[u8; 64]>> {
     // trim because empty spaces could create a different seed https://github.com/iotaledger/crypto.rs/issues/125
     let mnemonic = mnemonic.trim();
     // first we check if the mnemonic is valid to give meaningful errors
     crypto::keys::bip39::wordlist::verify(mnemonic, &crypto::keys::bip39::wordlist::ENGLISH)
         .map_err(|e| crate::Error::InvalidMnemonic(format!("{e:?}")))?;
-    let mut mnemonic_seed = [0u8; 64];
+    let mut mnemonic_seed = Zeroizing::new([0u8; 64]);
     crypto::keys::bip39::mnemonic_to_seed(mnemonic, "", &mut mnemonic_seed);
-    Ok(Seed::from_bytes(&mnemonic_seed))
+    Ok(mnemonic_seed)
+}
+
+/// Rewrites the HRP of a bech32 encoded address, for display purposes only.
+///
+/// The returned address is the same key formatted for another network. It must never be used as the target of a
+/// transfer: use [`verify_bech32_hrp()`] on user-provided addresses to reject addresses of other networks.
+pub fn bech32_with_display_hrp(bech32: &str, display_hrp: &str) -> Result<String> {
+    Ok(Address::try_from_bech32(bech32)?.1.to_bech32(display_hrp))
+}
+
+/// Parses a bech32 encoded address, checking that its HRP is the one of the expected network.
+pub fn verify_bech32_hrp(bech32: &str, expected_hrp: &str) -> Result<Address> {
+    let (hrp, address) = Address::try_from_bech32(bech32)?;
+
+    if hrp != expected_hrp {
+        return Err(Error::Bech32HrpMismatch {
+            expected: expected_hrp.to_string(),
+            found: hrp,
+        });
+    }
+
+    Ok(address)
+}
+
+/// Checks that a prepared transaction belongs to a single network: its essence must have the expected network id and
+/// all its inputs must be owned by addresses of the expected HRP.
+pub fn verify_prepared_transaction_network(
+    prepared_transaction_data: &PreparedTransactionData,
+    expected_network_id: u64,
+    expected_hrp: &str,
+) -> Result<()> {
+    let TransactionEssence::Regular(essence) = &prepared_transaction_data.essence;
+
+    if essence.network_id() != expected_network_id {
+        return Err(Error::NetworkIdMismatch {
+            expected: expected_network_id,
+            found: essence.network_id(),
+        });
+    }
+
+    for input in &prepared_transaction_data.inputs_data {
+        verify_bech32_hrp(&input.bech32_address, expected_hrp)?;
+    }
+
+    Ok(())
 }
 
 /// Requests funds from a faucet
@@ -161,11 +253,40 @@ impl Client {
         is_address_valid(address)
     }
 
+    /// Parses a bech32 encoded address, checking that its HRP is the one of the network of the node.
+    pub async fn verify_bech32_hrp(&self, bech32: &str) -> Result<Address> {
+        verify_bech32_hrp(bech32, &self.get_bech32_hrp().await?)
+    }
+
+    /// Checks that a prepared transaction belongs to the network of the node.
+    pub async fn verify_prepared_transaction_network(
+        &self,
+        prepared_transaction_data: &PreparedTransactionData,
+    ) -> Result<()> {
+        let network_info = self.get_network_info().await?;
+
+        verify_prepared_transaction_network(
+            prepared_transaction_data,
+            network_info.protocol_parameters.network_id(),
+            network_info.protocol_parameters.bech32_hrp(),
+        )
+    }
+
     /// Generates a new mnemonic.
     pub fn generate_mnemonic() -> Result<String> {
         generate_mnemonic()
     }
 
+    /// Generates a new mnemonic of `word_count` words, 12, 18 or 24, from the wordlist of `language`.
+    pub fn generate_mnemonic_with(word_count: usize, language: MnemonicLanguage) -> Result<String> {
+        generate_mnemonic_with(word_count, language)
+    }
+
+    /// Verifies that `mnemonic` is a valid mnemonic of the wordlist of `language`, with a valid checksum.
+    pub fn verify_mnemonic(mnemonic: &str, language: MnemonicLanguage) -> Result<()> {
+        verify_mnemonic(mnemonic, language)
+    }
+
     /// Returns a seed for a mnemonic.
     pub fn mnemonic_to_seed(mnemonic: &str) -> Result<Seed> {
         mnemonic_to_seed(mnemonic)
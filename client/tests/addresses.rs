@@ -1,6 +1,8 @@
 // Copyright 2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::str::FromStr;
+
 #[cfg(feature = "message_interface")]
 use iota_client::api::GetAddressesBuilderOptions;
 #[cfg(feature = "message_interface")]
@@ -14,10 +16,14 @@ use iota_client::secret::types::StrongholdDto;
 #[cfg(feature = "message_interface")]
 use iota_client::secret::SecretManagerDto;
 use iota_client::{
-    api::GetAddressesBuilder,
+    api::{
+        search_address, search_address_widening, search_address_with_derivation_path, search_address_with_options,
+        AddressChains, AddressSearchOptions, GetAddressesBuilder,
+    },
     constants::{IOTA_BECH32_HRP, IOTA_COIN_TYPE, IOTA_TESTNET_BECH32_HRP, SHIMMER_BECH32_HRP, SHIMMER_COIN_TYPE},
-    secret::{mnemonic::MnemonicSecretManager, SecretManager},
-    Client,
+    secret::{mnemonic::MnemonicSecretManager, DerivationPath, SecretManage, SecretManager},
+    utils::{bech32_with_display_hrp, verify_bech32_hrp},
+    Client, Error,
 };
 use iota_types::block::address::Address;
 use serde::{Deserialize, Serialize};
@@ -48,6 +54,250 @@ async fn addresses() {
     );
 }
 
+#[tokio::test]
+async fn search_address_in_widened_range() {
+    let secret_manager = SecretManager::Mnemonic(
+        MnemonicSecretManager::try_from_hex_seed("0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2")
+            .unwrap(),
+    );
+    let address = GetAddressesBuilder::new(&secret_manager)
+        .with_coin_type(IOTA_COIN_TYPE)
+        .with_account_index(0)
+        .with_range(25..26)
+        .get_all_raw()
+        .await
+        .unwrap()
+        .internal[0];
+
+    match search_address(
+        &secret_manager,
+        IOTA_TESTNET_BECH32_HRP,
+        IOTA_COIN_TYPE,
+        0,
+        0..10,
+        &address,
+    )
+    .await
+    {
+        Err(Error::InputAddressNotFound {
+            account_index,
+            range,
+            internal_addresses_searched,
+            ..
+        }) => {
+            assert_eq!(account_index, 0);
+            assert_eq!(range, 0..10);
+            assert!(internal_addresses_searched);
+        }
+        _ => panic!("expected InputAddressNotFound"),
+    }
+
+    // The following ranges are searched up to the maximum.
+    assert_eq!(
+        search_address_widening(
+            &secret_manager,
+            IOTA_TESTNET_BECH32_HRP,
+            IOTA_COIN_TYPE,
+            0,
+            0..10,
+            &address,
+            30,
+        )
+        .await
+        .unwrap(),
+        (25, true)
+    );
+    assert!(matches!(
+        search_address_widening(
+            &secret_manager,
+            IOTA_TESTNET_BECH32_HRP,
+            IOTA_COIN_TYPE,
+            0,
+            0..10,
+            &address,
+            25,
+        )
+        .await,
+        Err(Error::InputAddressNotFound { range, .. }) if range == (0..25)
+    ));
+}
+
+#[tokio::test]
+async fn search_address_in_chunks() {
+    let secret_manager = SecretManager::Mnemonic(
+        MnemonicSecretManager::try_from_hex_seed("0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2")
+            .unwrap(),
+    );
+    let address = GetAddressesBuilder::new(&secret_manager)
+        .with_coin_type(IOTA_COIN_TYPE)
+        .with_range(57..58)
+        .with_internal_addresses(true)
+        .get_raw()
+        .await
+        .unwrap()[0];
+    let options = AddressSearchOptions {
+        chunk_size: 7,
+        concurrency: 3,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        search_address_with_options(
+            &secret_manager,
+            IOTA_TESTNET_BECH32_HRP,
+            IOTA_COIN_TYPE,
+            0,
+            0..100,
+            &address,
+            options.clone(),
+        )
+        .await
+        .unwrap(),
+        (57, true)
+    );
+
+    // The internal address isn't found if only the public chain is searched
+    assert!(matches!(
+        search_address_with_options(
+            &secret_manager,
+            IOTA_TESTNET_BECH32_HRP,
+            IOTA_COIN_TYPE,
+            0,
+            0..100,
+            &address,
+            AddressSearchOptions {
+                chains: AddressChains::Public,
+                ..options
+            },
+        )
+        .await,
+        Err(Error::InputAddressNotFound {
+            range,
+            internal_addresses_searched: false,
+            ..
+        }) if range == (0..100)
+    ));
+}
+
+#[tokio::test]
+async fn custom_derivation_path() {
+    let secret_manager = SecretManager::Mnemonic(
+        MnemonicSecretManager::try_from_hex_seed("0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2")
+            .unwrap(),
+    );
+    let addresses = |derivation_path: DerivationPath| {
+        GetAddressesBuilder::new(&secret_manager)
+            .with_coin_type(IOTA_COIN_TYPE)
+            .with_account_index(0)
+            .with_range(0..5)
+            .with_derivation_path(derivation_path)
+            .get_raw()
+    };
+
+    let default_path =
+        DerivationPath::from_str("m/44'/{coin_type}'/{account_index}'/{internal}'/{address_index}'").unwrap();
+    assert_eq!(default_path, DerivationPath::default());
+    assert_eq!(
+        addresses(default_path).await.unwrap(),
+        GetAddressesBuilder::new(&secret_manager)
+            .with_coin_type(IOTA_COIN_TYPE)
+            .with_account_index(0)
+            .with_range(0..5)
+            .get_raw()
+            .await
+            .unwrap()
+    );
+
+    let derivation_path = DerivationPath::from_str("m/44'/{coin_type}'/{account_index}'/{address_index}'").unwrap();
+    assert_eq!(
+        derivation_path.to_string(),
+        "m/44'/{coin_type}'/{account_index}'/{address_index}'"
+    );
+    let address = addresses(derivation_path.clone()).await.unwrap()[3];
+    assert_eq!(
+        search_address_with_derivation_path(
+            &secret_manager,
+            IOTA_TESTNET_BECH32_HRP,
+            IOTA_COIN_TYPE,
+            0,
+            0..5,
+            &address,
+            &derivation_path,
+        )
+        .await
+        .unwrap(),
+        (3, false)
+    );
+    assert!(search_address(
+        &secret_manager,
+        IOTA_TESTNET_BECH32_HRP,
+        IOTA_COIN_TYPE,
+        0,
+        0..5,
+        &address,
+    )
+    .await
+    .is_err());
+
+    for invalid_path in [
+        "44'/{coin_type}'/{address_index}'",
+        "m/44'/{coin_type}'/{address_index}",
+        "m/44'/{coin_type}'/{account_index}'",
+        "m/44'/{address_index}'/{address_index}'",
+        "m/2147483648'/{address_index}'",
+        "m/44'/{unknown}'/{address_index}'",
+    ] {
+        assert!(matches!(
+            DerivationPath::from_str(invalid_path),
+            Err(Error::InvalidDerivationPath(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn concurrent_derivation() {
+    let secret_manager = SecretManager::Mnemonic(
+        MnemonicSecretManager::try_from_hex_seed("0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2")
+            .unwrap(),
+    );
+
+    let addresses = GetAddressesBuilder::new(&secret_manager)
+        .with_coin_type(IOTA_COIN_TYPE)
+        .with_range(5..255)
+        .with_concurrency(8)
+        .get_raw()
+        .await
+        .unwrap();
+
+    // The batches are returned in order
+    assert_eq!(
+        addresses,
+        secret_manager
+            .generate_addresses(IOTA_COIN_TYPE, 0, 5..255, false, None)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn device_prompt_unsupported() {
+    let secret_manager = SecretManager::Mnemonic(
+        MnemonicSecretManager::try_from_hex_seed("0x256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2")
+            .unwrap(),
+    );
+    assert!(!secret_manager.can_display_addresses());
+
+    // The addresses can't be confirmed without a device
+    assert!(matches!(
+        GetAddressesBuilder::new(&secret_manager)
+            .with_range(0..1)
+            .with_device_prompt(true)
+            .get_raw()
+            .await,
+        Err(Error::AddressPromptUnsupported)
+    ));
+}
+
 #[tokio::test]
 async fn public_key_to_address() {
     let client = Client::builder().finish().unwrap();
@@ -64,6 +314,23 @@ async fn public_key_to_address() {
     );
 }
 
+#[test]
+fn bech32_hrp_helpers() {
+    let atoi_address = "atoi1qzt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupx3y7x0r";
+
+    let smr_address = bech32_with_display_hrp(atoi_address, SHIMMER_BECH32_HRP).unwrap();
+    assert!(smr_address.starts_with(SHIMMER_BECH32_HRP));
+    assert_eq!(
+        verify_bech32_hrp(&smr_address, SHIMMER_BECH32_HRP).unwrap(),
+        verify_bech32_hrp(atoi_address, IOTA_TESTNET_BECH32_HRP).unwrap()
+    );
+
+    assert!(matches!(
+        verify_bech32_hrp(atoi_address, SHIMMER_BECH32_HRP),
+        Err(Error::Bech32HrpMismatch { expected, found }) if expected == SHIMMER_BECH32_HRP && found == IOTA_TESTNET_BECH32_HRP
+    ));
+}
+
 #[tokio::test]
 async fn mnemonic_address_generation_iota() {
     let mnemonic = "acoustic trophy damage hint search taste love bicycle foster cradle brown govern endless depend situate athlete pudding blame question genius transfer van random vast";
@@ -188,7 +455,7 @@ async fn address_generation() {
     #[cfg(feature = "stronghold")]
     for address in &addresses_data {
         let stronghold_filename = format!("{}.stronghold", address.bech32_address);
-        let mut stronghold_secret_manager = StrongholdSecretManager::builder()
+        let stronghold_secret_manager = StrongholdSecretManager::builder()
             .password("some_hopefully_secure_password")
             .build(&stronghold_filename)
             .unwrap();
@@ -231,6 +498,7 @@ async fn address_generation() {
                 internal: Some(address.internal),
                 bech32_hrp: Some(address.bech32_hrp.to_string()),
                 options: None,
+                concurrency: None,
             };
             let message = Message::GenerateAddresses {
                 secret_manager: SecretManagerDto::Mnemonic(address.mnemonic.clone()),
@@ -267,6 +535,7 @@ async fn address_generation() {
             let message = Message::StoreMnemonic {
                 secret_manager: SecretManagerDto::Stronghold(secret_manager_dto.clone()),
                 mnemonic: address.mnemonic,
+                passphrase: None,
             };
             let _response = message_interface::send_message(&message_handler, message).await;
 
@@ -280,6 +549,7 @@ async fn address_generation() {
                 internal: Some(address.internal),
                 bech32_hrp: Some(address.bech32_hrp.to_string()),
                 options: None,
+                concurrency: None,
             };
             let message = Message::GenerateAddresses {
                 secret_manager: SecretManagerDto::Stronghold(secret_manager_dto),
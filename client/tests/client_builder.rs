@@ -132,3 +132,19 @@ async fn client_builder() {
 
     let _client_builder = serde_json::from_str::<ClientBuilder>(client_builder_json).unwrap();
 }
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_client() {
+    let client = iota_client::blocking::Client::new(Client::builder().finish().unwrap()).unwrap();
+    let hex_public_key = "0x2baaf3bca8ace9f862e60184bd3e79df25ff230f7eaaa4c7f03daa9833ba854a";
+
+    let public_key_address = client
+        .hex_public_key_to_bech32_address(hex_public_key, Some("atoi"))
+        .unwrap();
+
+    assert_eq!(
+        public_key_address,
+        "atoi1qzt0nhsf38nh6rs4p6zs5knqp6psgha9wsv74uajqgjmwc75ugupx3y7x0r".to_string()
+    );
+}
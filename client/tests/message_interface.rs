@@ -34,6 +34,7 @@ async fn generate_addresses() {
         internal: None,
         bech32_hrp: Some("atoi".to_string()),
         options: None,
+        concurrency: None,
     };
     let message = Message::GenerateAddresses {
         secret_manager: serde_json::from_str::<SecretManagerDto>(&secret_manager).unwrap(),
@@ -81,6 +82,7 @@ async fn build_and_post_block() {
         internal: None,
         bech32_hrp: Some("atoi".to_string()),
         options: None,
+        concurrency: None,
     };
 
     let generate_addresses_message = Message::GenerateAddresses {
@@ -184,6 +186,7 @@ async fn stronghold() {
     let message = Message::StoreMnemonic {
         secret_manager: serde_json::from_str(secret_manager_dto).unwrap(),
         mnemonic,
+        passphrase: None,
     };
     let _response = message_interface::send_message(&message_handler, message).await;
 
@@ -195,6 +198,7 @@ async fn stronghold() {
         internal: None,
         bech32_hrp: Some("rms".to_string()),
         options: None,
+        concurrency: None,
     };
     let message = Message::GenerateAddresses {
         secret_manager: serde_json::from_str(secret_manager_dto).unwrap(),
@@ -1,7 +1,7 @@
 // Copyright 2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota_client::{Client, Result};
+use iota_client::{utils::MnemonicLanguage, Client, Result};
 
 #[tokio::test]
 async fn mnemonic() -> Result<()> {
@@ -19,3 +19,20 @@ async fn mnemonic() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn mnemonic_with() -> Result<()> {
+    for word_count in [12, 18, 24] {
+        for language in [MnemonicLanguage::English, MnemonicLanguage::Japanese] {
+            let mnemonic = Client::generate_mnemonic_with(word_count, language)?;
+            assert!(Client::verify_mnemonic(&mnemonic, language).is_ok());
+        }
+    }
+
+    let mnemonic = Client::generate_mnemonic_with(12, MnemonicLanguage::English)?;
+    assert_eq!(mnemonic.split_whitespace().count(), 12);
+    assert!(Client::verify_mnemonic(&mnemonic, MnemonicLanguage::Japanese).is_err());
+    assert!(Client::generate_mnemonic_with(15, MnemonicLanguage::English).is_err());
+
+    Ok(())
+}
@@ -339,3 +339,33 @@ async fn test_get_included_block() {
 
     println!("{r:#?}");
 }
+
+#[ignore]
+#[tokio::test]
+async fn test_ledger_state() {
+    let (_block_id, _transaction_id) = setup_transaction_block().await;
+    let client = setup_client_with_node_health_ignored();
+    let addresses = client
+        .get_addresses(&setup_secret_manager())
+        .with_range(0..2)
+        .finish()
+        .await
+        .unwrap();
+    let confirmed_milestone_index = client
+        .get_info()
+        .await
+        .unwrap()
+        .node_info
+        .status
+        .confirmed_milestone
+        .index;
+
+    let r = client
+        .ledger_state(confirmed_milestone_index - 1)
+        .with_addresses(addresses)
+        .finish()
+        .await
+        .unwrap();
+
+    println!("{r:#?}");
+}
@@ -0,0 +1,91 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use iota_client::api::{
+    MaxOutputsPerRecipient, MinimumOutputAmount, OutputPolicy, PolicyViolation, PreparedTransactionData,
+};
+use iota_types::block::{
+    address::{Address, Ed25519Address},
+    input::{Input, UtxoInput},
+    output::{unlock_condition::AddressUnlockCondition, BasicOutput, Output},
+    payload::transaction::{RegularTransactionEssence, TransactionEssence, TransactionId},
+    protocol::protocol_parameters,
+    rand::output::rand_inputs_commitment,
+};
+
+const TRANSACTION_ID: &str = "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+const ED25519_ADDRESS: &str = "0xd56da1eb7726ed482dfe9d457cf548c2ae2a6ce3e053dbf82f11223be476adb9";
+
+fn prepared_transaction(amounts: &[u64]) -> (PreparedTransactionData, Address) {
+    let protocol_parameters = protocol_parameters();
+    let transaction_id = TransactionId::new(prefix_hex::decode(TRANSACTION_ID).unwrap());
+    let address = Address::from(Ed25519Address::new(prefix_hex::decode(ED25519_ADDRESS).unwrap()));
+    let outputs = amounts
+        .iter()
+        .map(|amount| {
+            Output::Basic(
+                BasicOutput::build_with_amount(*amount)
+                    .unwrap()
+                    .add_unlock_condition(AddressUnlockCondition::new(address).into())
+                    .finish(protocol_parameters.token_supply())
+                    .unwrap(),
+            )
+        })
+        .collect();
+
+    let essence = RegularTransactionEssence::builder(protocol_parameters.network_id(), rand_inputs_commitment())
+        .with_inputs(vec![Input::Utxo(UtxoInput::new(transaction_id, 0).unwrap())])
+        .with_outputs(outputs)
+        .finish(&protocol_parameters)
+        .unwrap();
+
+    (
+        PreparedTransactionData {
+            essence: TransactionEssence::Regular(essence),
+            inputs_data: Vec::new(),
+            remainder: None,
+        },
+        address,
+    )
+}
+
+#[test]
+fn minimum_output_amount() {
+    let (prepared_transaction, _) = prepared_transaction(&[1_000_000, 500_000]);
+
+    assert!(MinimumOutputAmount(500_000).check(&prepared_transaction).is_empty());
+    assert_eq!(
+        MinimumOutputAmount(1_000_000).check(&prepared_transaction),
+        vec![PolicyViolation::AmountBelowMinimum {
+            output_index: 1,
+            amount: 500_000,
+            minimum: 1_000_000,
+        }]
+    );
+}
+
+#[test]
+fn max_outputs_per_recipient() {
+    let policy = MaxOutputsPerRecipient::new(3, Duration::from_secs(86400));
+    let (prepared_transaction, address) = prepared_transaction(&[1_000_000, 1_000_000]);
+
+    assert!(policy.check(&prepared_transaction).is_empty());
+    policy.record(&prepared_transaction);
+
+    // The outputs of the recorded transaction count towards the maximum.
+    assert_eq!(
+        policy.check(&prepared_transaction),
+        vec![PolicyViolation::TooManyOutputsForRecipient {
+            address,
+            count: 4,
+            maximum: 3,
+        }]
+    );
+
+    // Outputs sent before the period are forgotten.
+    let policy = MaxOutputsPerRecipient::new(3, Duration::ZERO);
+    policy.record(&prepared_transaction);
+    assert!(policy.check(&prepared_transaction).is_empty());
+}
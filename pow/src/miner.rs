@@ -9,6 +9,7 @@ use std::{
         Arc,
     },
     thread,
+    time::{Duration, Instant},
 };
 
 use crypto::{
@@ -23,7 +24,10 @@ use crypto::{
     },
 };
 
-use crate::{score::count_trailing_zeros, Error, LN_3};
+use crate::{
+    score::{count_trailing_zeros, target_zeros},
+    Error,
+};
 
 const DEFAULT_NUM_WORKERS: usize = 1;
 
@@ -137,8 +141,7 @@ impl Miner {
 
         let mut nonce = 0;
         let mut pow_digest = TritBuf::<T1B1Buf>::new();
-        let target_zeros =
-            (((bytes.len() + std::mem::size_of::<u64>()) as f64 * target_score as f64).ln() / LN_3).ceil() as usize;
+        let target_zeros = target_zeros(bytes.len() + std::mem::size_of::<u64>(), target_score);
 
         if target_zeros > HASH_LENGTH {
             return Err(Error::InvalidPowScore(target_score, target_zeros));
@@ -171,6 +174,28 @@ impl Miner {
     }
 }
 
+/// Measures the number of Proof of Work hashes a single worker computes per second on this machine, by hashing for
+/// `duration`.
+pub fn hash_rate_per_worker(duration: Duration) -> f64 {
+    let mut hasher = CurlPBatchHasher::<T1B1Buf>::new(HASH_LENGTH);
+    let buffer = TritBuf::<T1B1Buf>::zeros(HASH_LENGTH);
+    let start = Instant::now();
+    let mut hashes = 0;
+
+    loop {
+        for _ in 0..BATCH_SIZE {
+            hasher.add(buffer.clone());
+        }
+        // The hashes are computed lazily, they have to be consumed.
+        hashes += hasher.hash().count();
+
+        let elapsed = start.elapsed();
+        if elapsed >= duration {
+            return hashes as f64 / elapsed.as_secs_f64();
+        }
+    }
+}
+
 fn _get_miner(bytes: &[u8], min_pow_score: u32, num_workers: usize) -> Result<u64, Error> {
     MinerBuilder::new()
         .with_num_workers(num_workers)
@@ -12,6 +12,8 @@ use crypto::{
     },
 };
 
+use crate::LN_3;
+
 /// Encapsulates the different steps that are used for scoring Proof of Work.
 pub struct PowScorer {
     blake2b: Blake2b256,
@@ -75,3 +77,8 @@ pub fn count_trailing_zeros(pow_hash: &Trits<T1B1>) -> usize {
 pub fn pow_score_for_hash(pow_hash: &Trits<T1B1>, len: usize) -> f64 {
     3u128.pow(count_trailing_zeros(pow_hash) as u32) as f64 / len as f64
 }
+
+/// Returns the number of trailing zeros a Proof of Work hash needs for a block of `len` bytes to reach `target_score`.
+pub fn target_zeros(len: usize, target_score: u32) -> usize {
+    ((len as f64 * target_score as f64).ln() / LN_3).ceil() as usize
+}
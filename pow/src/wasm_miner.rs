@@ -15,7 +15,10 @@ use crypto::{
     },
 };
 
-use crate::{score::count_trailing_zeros, Error, LN_3};
+use crate::{
+    score::{count_trailing_zeros, target_zeros},
+    Error,
+};
 
 // Should take around one second to reach on an average CPU, so shouldn't cause a noticeable delay on
 // `timeout_in_seconds`.
@@ -61,8 +64,7 @@ impl SingleThreadedMiner {
     pub fn nonce(&self, bytes: &[u8], target_score: u32) -> Result<u64, Error> {
         let mut nonce = 0;
         let mut pow_digest = TritBuf::<T1B1Buf>::new();
-        let target_zeros =
-            (((bytes.len() + std::mem::size_of::<u64>()) as f64 * target_score as f64).ln() / LN_3).ceil() as usize;
+        let target_zeros = target_zeros(bytes.len() + std::mem::size_of::<u64>(), target_score);
 
         if target_zeros > HASH_LENGTH {
             return Err(Error::InvalidPowScore(target_score, target_zeros));
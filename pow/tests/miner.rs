@@ -1,8 +1,10 @@
 // Copyright 2020-2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::time::Duration;
+
 use iota_pow::{
-    miner::{MinerBuilder, MinerCancel},
+    miner::{hash_rate_per_worker, MinerBuilder, MinerCancel},
     score::PowScorer,
 };
 use iota_types::block::rand::bytes::rand_bytes;
@@ -50,3 +52,8 @@ fn miner_cancel() {
     assert!(now.elapsed().as_secs() < 2);
     assert!(matches!(handle.join(), Ok(0)));
 }
+
+#[test]
+fn miner_hash_rate() {
+    assert!(hash_rate_per_worker(Duration::from_millis(100)) > 0.0);
+}
@@ -1,7 +1,7 @@
 // Copyright 2020-2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota_pow::score::PowScorer;
+use iota_pow::score::{target_zeros, PowScorer};
 
 // Tests are from:
 // https://github.com/iotaledger/tips/blob/main/tips/TIP-0012/tip-0012.md#example
@@ -34,3 +34,12 @@ fn pow_score() {
 
     assert!((pow.score(&block) - 3u128.pow(0) as f64 / 10000_f64).abs() < f64::EPSILON);
 }
+
+#[test]
+fn pow_target_zeros() {
+    // 3^7 = 2187 < 8 * 300 = 2400 <= 3^8 = 6561.
+    assert_eq!(target_zeros(8, 300), 8);
+    assert_eq!(target_zeros(8, 1), 2);
+    // The nonce of a 32 bytes block with a score of 1000 needs 10 trailing zeros, 3^10 = 59049 >= 32000.
+    assert_eq!(target_zeros(32, 1000), 10);
+}
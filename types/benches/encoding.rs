@@ -0,0 +1,56 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use iota_types::block::{
+    address::{Address, Ed25519Address},
+    helper::{decode_prefix_hex_into, encode_prefix_hex_into},
+    BlockId,
+};
+
+const ED25519_ADDRESS: &str = "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+
+fn hex(c: &mut Criterion) {
+    let block_id = BlockId::new(prefix_hex::decode(ED25519_ADDRESS).unwrap());
+    let data = (0..=255).collect::<Vec<u8>>();
+    let encoded_data = prefix_hex::encode(data.as_slice());
+
+    c.bench_function("block id to string", |b| b.iter(|| black_box(&block_id).to_string()));
+    c.bench_function("block id from string", |b| {
+        b.iter(|| black_box(ED25519_ADDRESS).parse::<BlockId>().unwrap())
+    });
+    c.bench_function("prefix_hex encode", |b| {
+        b.iter(|| prefix_hex::encode(black_box(data.as_slice())))
+    });
+    c.bench_function("prefix_hex encode into buffer", |b| {
+        let mut buffer = String::new();
+        b.iter(|| encode_prefix_hex_into(black_box(&data), &mut buffer))
+    });
+    c.bench_function("prefix_hex decode", |b| {
+        b.iter(|| prefix_hex::decode::<Vec<u8>>(black_box(&encoded_data)).unwrap())
+    });
+    c.bench_function("prefix_hex decode into buffer", |b| {
+        let mut buffer = Vec::new();
+        b.iter(|| decode_prefix_hex_into(black_box(&encoded_data), &mut buffer).unwrap())
+    });
+}
+
+fn bech32(c: &mut Criterion) {
+    let address = Address::from(Ed25519Address::new(prefix_hex::decode(ED25519_ADDRESS).unwrap()));
+    let bech32_address = address.to_bech32("rms");
+
+    c.bench_function("address to bech32", |b| b.iter(|| black_box(&address).to_bech32("rms")));
+    c.bench_function("address write bech32 into buffer", |b| {
+        let mut buffer = String::new();
+        b.iter(|| {
+            buffer.clear();
+            black_box(&address).write_bech32("rms", &mut buffer).unwrap()
+        })
+    });
+    c.bench_function("address from bech32", |b| {
+        b.iter(|| Address::try_from_bech32(black_box(&bech32_address)).unwrap())
+    });
+}
+
+criterion_group!(benches, hex, bech32);
+criterion_main!(benches);
@@ -3,7 +3,10 @@
 
 use core::str::FromStr;
 
-use crypto::signatures::ed25519::PUBLIC_KEY_LENGTH;
+use crypto::{
+    hashes::{blake2b::Blake2b256, Digest},
+    signatures::ed25519::PUBLIC_KEY_LENGTH,
+};
 use derive_more::{AsRef, Deref, From};
 
 use crate::block::Error;
@@ -24,6 +27,16 @@ impl Ed25519Address {
     pub fn new(address: [u8; Self::LENGTH]) -> Self {
         Self::from(address)
     }
+
+    /// Creates the [`Ed25519Address`] of an Ed25519 public key, the BLAKE2b-256 hash of the key.
+    pub fn from_public_key_bytes(public_key: [u8; PUBLIC_KEY_LENGTH]) -> Self {
+        Self::new(Blake2b256::digest(public_key).into())
+    }
+
+    /// Checks that an Ed25519 public key hashes to the [`Ed25519Address`].
+    pub fn matches_public_key(&self, public_key: &[u8; PUBLIC_KEY_LENGTH]) -> bool {
+        *self == Self::from_public_key_bytes(*public_key)
+    }
 }
 
 #[cfg(feature = "serde")]
@@ -39,7 +52,7 @@ impl FromStr for Ed25519Address {
 
 impl core::fmt::Display for Ed25519Address {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "{}", prefix_hex::encode(self.0))
+        crate::block::helper::write_prefix_hex(&self.0, f)
     }
 }
 
@@ -7,7 +7,7 @@ mod nft;
 
 use alloc::{string::String, vec::Vec};
 
-use bech32::{self, FromBase32, ToBase32, Variant};
+use bech32::{self, u5, FromBase32, Variant};
 use derive_more::From;
 use packable::PackableExt;
 
@@ -112,8 +112,27 @@ impl Address {
     /// Encodes this address to a bech32 string with the given Human Readable Part as prefix.
     #[allow(clippy::wrong_self_convention)]
     pub fn to_bech32<T: AsRef<str>>(&self, hrp: T) -> String {
+        let mut bech32 = String::with_capacity(hrp.as_ref().len() + 1 + BECH32_DATA_LENGTH + 6);
+        // PANIC: writing to a `String` can't fail.
+        self.write_bech32(hrp, &mut bech32).unwrap();
+
+        bech32
+    }
+
+    /// Writes this address as a bech32 string with the given Human Readable Part as prefix to `writer`, e.g. a
+    /// [`Formatter`](core::fmt::Formatter) or a reused buffer, without allocating.
+    pub fn write_bech32<T: AsRef<str>, W: core::fmt::Write>(&self, hrp: T, writer: &mut W) -> core::fmt::Result {
+        // All address kinds are packed as their kind followed by 32 bytes.
+        let mut bytes = [0u8; ADDRESS_PACKED_LENGTH];
+        bytes[0] = self.kind();
+        bytes[1..].copy_from_slice(match self {
+            Self::Ed25519(address) => address.as_ref(),
+            Self::Alias(address) => address.alias_id().as_ref(),
+            Self::Nft(address) => address.nft_id().as_ref(),
+        });
+
         // PANIC: encoding can't fail as `self` has already been validated and built.
-        bech32::encode(hrp.as_ref(), self.pack_to_vec().to_base32(), Variant::Bech32).unwrap()
+        bech32::encode_to_fmt(writer, hrp.as_ref(), bytes_to_base32(&bytes), Variant::Bech32).unwrap()
     }
 
     ///
@@ -176,6 +195,40 @@ impl Address {
     }
 }
 
+/// The length of a packed [`Address`].
+const ADDRESS_PACKED_LENGTH: usize = 1 + Ed25519Address::LENGTH;
+/// The length of a packed [`Address`] converted to 5-bit groups.
+const BECH32_DATA_LENGTH: usize = (ADDRESS_PACKED_LENGTH * 8 + 4) / 5;
+
+/// Converts the bytes of a packed address to 5-bit groups, like [`ToBase32`](bech32::ToBase32) but on the stack.
+fn bytes_to_base32(bytes: &[u8; ADDRESS_PACKED_LENGTH]) -> [u5; BECH32_DATA_LENGTH] {
+    // PANIC: unwrapping is fine as the values are masked to 5 bits.
+    let mut data = [u5::try_from_u8(0).unwrap(); BECH32_DATA_LENGTH];
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    let mut index = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            data[index] = u5::try_from_u8(((buffer >> bits) & 0x1f) as u8).unwrap();
+            index += 1;
+        }
+
+        buffer &= (1 << bits) - 1;
+    }
+
+    // The remaining bits are padded with zeros.
+    if bits > 0 {
+        data[index] = u5::try_from_u8(((buffer << (5 - bits)) & 0x1f) as u8).unwrap();
+    }
+
+    data
+}
+
 #[cfg(feature = "dto")]
 #[allow(missing_docs)]
 pub mod dto {
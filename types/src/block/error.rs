@@ -47,6 +47,7 @@ pub enum Error {
     InsufficientStorageDepositAmount { amount: u64, required: u64 },
     StorageDepositReturnExceedsOutputAmount { deposit: u64, amount: u64 },
     InsufficientStorageDepositReturnAmount { deposit: u64, required: u64 },
+    InvalidBinaryParameters,
     InvalidBinaryParametersLength(<BinaryParametersLength as TryFrom<usize>>::Error),
     InvalidEssenceKind(u8),
     InvalidFeatureCount(<FeatureCount as TryFrom<usize>>::Error),
@@ -162,6 +163,7 @@ impl fmt::Display for Error {
             Error::InvalidAddressKind(k) => write!(f, "invalid address kind: {k}"),
             Error::InvalidAliasIndex(index) => write!(f, "invalid alias index: {index}"),
             Error::InvalidBech32Hrp(err) => write!(f, "invalid bech32 hrp: {err}"),
+            Error::InvalidBinaryParameters => write!(f, "invalid binary parameters"),
             Error::InvalidBinaryParametersLength(length) => {
                 write!(f, "invalid binary parameters length: {length}")
             }
@@ -1,11 +1,67 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
 use crypto::hashes::{blake2b::Blake2b256, Digest};
 
+use crate::block::Error;
+
+/// The number of bytes hex encoded at once on the stack by [`write_prefix_hex()`].
+const HEX_CHUNK_LENGTH: usize = 32;
+
 /// Hashes a string network name to a digit network ID.
 pub fn network_name_to_id(network_name: &str) -> u64 {
     // PANIC: indexing and unwrapping is fine as a Blake2b256 digest has 32 bytes, we ask for 8 of them and we convert
     // that slice to an array of 8 bytes.
     u64::from_le_bytes(Blake2b256::digest(network_name.as_bytes())[0..8].try_into().unwrap())
 }
+
+/// Writes `bytes` as a `0x` prefixed hex string to `writer`, e.g. a [`Formatter`](core::fmt::Formatter), without
+/// allocating.
+pub fn write_prefix_hex<W: Write + ?Sized>(bytes: &[u8], writer: &mut W) -> core::fmt::Result {
+    let mut buffer = [0u8; 2 * HEX_CHUNK_LENGTH];
+
+    writer.write_str("0x")?;
+
+    for chunk in bytes.chunks(HEX_CHUNK_LENGTH) {
+        let encoded = &mut buffer[..2 * chunk.len()];
+        // PANIC: unwrapping is fine as the output slice has exactly twice the length of the chunk.
+        hex::encode_to_slice(chunk, encoded).unwrap();
+        // PANIC: unwrapping is fine as hex digits are ASCII.
+        writer.write_str(core::str::from_utf8(encoded).unwrap())?;
+    }
+
+    Ok(())
+}
+
+/// Encodes `bytes` as a `0x` prefixed hex string into `buffer`, replacing its content but reusing its allocation.
+pub fn encode_prefix_hex_into(bytes: &[u8], buffer: &mut String) {
+    buffer.clear();
+    buffer.reserve(2 + 2 * bytes.len());
+    // PANIC: unwrapping is fine as writing to a `String` can't fail.
+    write_prefix_hex(bytes, buffer).unwrap();
+}
+
+/// Decodes a `0x` prefixed hex string into `buffer`, replacing its content but reusing its allocation.
+pub fn decode_prefix_hex_into(hex: &str, buffer: &mut Vec<u8>) -> Result<(), Error> {
+    buffer.clear();
+
+    if let Some(digits) = hex.strip_prefix("0x") {
+        if digits.len() % 2 == 0 {
+            buffer.resize(digits.len() / 2, 0);
+
+            if hex::decode_to_slice(digits, buffer).is_ok() {
+                return Ok(());
+            }
+
+            buffer.clear();
+        }
+    }
+
+    // Invalid strings are decoded again by `prefix_hex` to report the same errors.
+    *buffer = prefix_hex::decode(hex).map_err(Error::Hex)?;
+
+    Ok(())
+}
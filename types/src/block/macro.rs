@@ -51,7 +51,7 @@ macro_rules! impl_id {
 
         impl core::fmt::Display for $name {
             fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-                write!(f, "{}", prefix_hex::encode(self.0))
+                $crate::block::helper::write_prefix_hex(&self.0, f)
             }
         }
 
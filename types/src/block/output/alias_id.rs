@@ -3,19 +3,47 @@
 
 use crate::block::output::OutputId;
 
-impl_id!(pub AliasId, 32, "TODO.");
+impl_id!(
+    pub AliasId,
+    32,
+    "Defines the unique identifier of an alias, the BLAKE2b-256 hash of the [`OutputId`] of the output that created it."
+);
 
 #[cfg(feature = "serde")]
 string_serde_impl!(AliasId);
 
 impl From<&OutputId> for AliasId {
     fn from(output_id: &OutputId) -> Self {
-        Self::from(output_id.hash())
+        Self::from_output_id(output_id)
     }
 }
 
 impl AliasId {
+    /// Computes the [`AliasId`] of an alias created by the output with the given [`OutputId`].
+    ///
+    /// A new [`AliasOutput`](crate::block::output::AliasOutput) holds a null [`AliasId`], the actual one is only known
+    /// once the transaction creating it has been issued; it's needed e.g. to transition the output.
+    ///
+    /// ```
+    /// # use core::str::FromStr;
+    /// # use iota_types::block::output::{AliasId, OutputId};
+    /// let output_id = OutputId::from_str(
+    ///     "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c6492a00",
+    /// )
+    /// .unwrap();
+    /// let alias_id = AliasId::from_output_id(&output_id);
     ///
+    /// assert_eq!(
+    ///     alias_id.to_string(),
+    ///     "0xf29dd16310c2100fd1bf568b345fb1cc14d71caa3bd9b5ad735d2bd6d455ca3b"
+    /// );
+    /// ```
+    pub fn from_output_id(output_id: &OutputId) -> Self {
+        Self::from(output_id.hash())
+    }
+
+    /// Returns the [`AliasId`] itself or, if it's null, i.e. for an alias created by the output with the given
+    /// [`OutputId`], the [`AliasId`] computed from it.
     pub fn or_from_output_id(self, output_id: &OutputId) -> Self {
         if self.is_null() { Self::from(output_id) } else { self }
     }
@@ -52,7 +52,7 @@ impl MetadataFeature {
 
 impl core::fmt::Display for MetadataFeature {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "{}", prefix_hex::encode(self.data()))
+        crate::block::helper::write_prefix_hex(self.data(), f)
     }
 }
 
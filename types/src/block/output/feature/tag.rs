@@ -52,7 +52,7 @@ impl TagFeature {
 
 impl core::fmt::Display for TagFeature {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "{}", prefix_hex::encode(self.tag()))
+        crate::block::helper::write_prefix_hex(self.tag(), f)
     }
 }
 
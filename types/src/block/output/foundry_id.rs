@@ -20,7 +20,29 @@ impl From<TokenId> for FoundryId {
 }
 
 impl FoundryId {
-    /// Builds a new [`FoundryId`] from its components.
+    /// Builds a new [`FoundryId`] from its components: the [`AliasAddress`] of the alias controlling the foundry, the
+    /// serial number of the foundry within it and the kind of its [`TokenScheme`](crate::block::output::TokenScheme).
+    ///
+    /// The serial number of a new foundry is the foundry counter of the controlling alias output after its
+    /// transition. The [`FoundryId`] also is the [`TokenId`] of the native tokens minted by the foundry.
+    ///
+    /// ```
+    /// # use core::str::FromStr;
+    /// # use iota_types::block::{
+    /// #     address::AliasAddress,
+    /// #     output::{AliasId, FoundryId, OutputId, SimpleTokenScheme},
+    /// # };
+    /// let alias_output_id = OutputId::from_str(
+    ///     "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c6492a00",
+    /// )
+    /// .unwrap();
+    /// let alias_address = AliasAddress::new(AliasId::from_output_id(&alias_output_id));
+    /// let foundry_id = FoundryId::build(&alias_address, 1, SimpleTokenScheme::KIND);
+    ///
+    /// assert_eq!(foundry_id.alias_address(), alias_address);
+    /// assert_eq!(foundry_id.serial_number(), 1);
+    /// assert_eq!(foundry_id.token_scheme_kind(), SimpleTokenScheme::KIND);
+    /// ```
     pub fn build(alias_address: &AliasAddress, serial_number: u32, token_scheme_kind: u8) -> Self {
         let mut bytes = [0u8; FoundryId::LENGTH];
         let mut packer = SlicePacker::new(&mut bytes);
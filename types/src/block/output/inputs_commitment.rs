@@ -38,7 +38,7 @@ impl core::str::FromStr for InputsCommitment {
 
 impl core::fmt::Display for InputsCommitment {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "{}", prefix_hex::encode(self.0))
+        crate::block::helper::write_prefix_hex(&self.0, f)
     }
 }
 
@@ -3,19 +3,47 @@
 
 use crate::block::output::OutputId;
 
-impl_id!(pub NftId, 32, "TODO.");
+impl_id!(
+    pub NftId,
+    32,
+    "Defines the unique identifier of an NFT, the BLAKE2b-256 hash of the [`OutputId`] of the output that created it."
+);
 
 #[cfg(feature = "serde")]
 string_serde_impl!(NftId);
 
 impl From<&OutputId> for NftId {
     fn from(output_id: &OutputId) -> Self {
-        Self::from(output_id.hash())
+        Self::from_output_id(output_id)
     }
 }
 
 impl NftId {
+    /// Computes the [`NftId`] of an NFT created by the output with the given [`OutputId`].
+    ///
+    /// A new [`NftOutput`](crate::block::output::NftOutput) holds a null [`NftId`], the actual one is only known
+    /// once the transaction creating it has been issued; it's needed e.g. to transition the output.
+    ///
+    /// ```
+    /// # use core::str::FromStr;
+    /// # use iota_types::block::output::{NftId, OutputId};
+    /// let output_id = OutputId::from_str(
+    ///     "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c6492a00",
+    /// )
+    /// .unwrap();
+    /// let nft_id = NftId::from_output_id(&output_id);
     ///
+    /// assert_eq!(
+    ///     nft_id.to_string(),
+    ///     "0xf29dd16310c2100fd1bf568b345fb1cc14d71caa3bd9b5ad735d2bd6d455ca3b"
+    /// );
+    /// ```
+    pub fn from_output_id(output_id: &OutputId) -> Self {
+        Self::from(output_id.hash())
+    }
+
+    /// Returns the [`NftId`] itself or, if it's null, i.e. for an NFT created by the output with the given
+    /// [`OutputId`], the [`NftId`] computed from it.
     pub fn or_from_output_id(self, output_id: &OutputId) -> Self {
         if self.is_null() { Self::from(output_id) } else { self }
     }
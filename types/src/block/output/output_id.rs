@@ -98,7 +98,7 @@ impl core::fmt::Display for OutputId {
         let (transaction_id, index) = buffer.split_at_mut(TransactionId::LENGTH);
         transaction_id.copy_from_slice(self.transaction_id.as_ref());
         index.copy_from_slice(&self.index().to_le_bytes());
-        write!(f, "{}", prefix_hex::encode(buffer))
+        crate::block::helper::write_prefix_hex(&buffer, f)
     }
 }
 
@@ -33,7 +33,7 @@ impl core::ops::Deref for MerkleRoot {
 
 impl core::fmt::Display for MerkleRoot {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(f, "{}", prefix_hex::encode(self.0))
+        crate::block::helper::write_prefix_hex(&self.0, f)
     }
 }
 
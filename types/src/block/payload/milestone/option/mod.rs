@@ -12,7 +12,10 @@ use packable::{bounded::BoundedU8, prefix::BoxedSlicePrefix, Packable};
 
 pub(crate) use self::{parameters::BinaryParametersLength, receipt::ReceiptFundsCount};
 pub use self::{
-    parameters::ParametersMilestoneOption,
+    parameters::{
+        decode_protocol_parameters, BinaryParametersDecoder, BinaryParametersRegistry, DecodedBinaryParameters,
+        ParametersMilestoneOption,
+    },
     receipt::{MigratedFundsEntry, ReceiptMilestoneOption, TailTransactionHash},
 };
 use crate::block::{protocol::ProtocolParameters, Error};
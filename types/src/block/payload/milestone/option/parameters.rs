@@ -3,12 +3,12 @@
 
 //! Module describing the parameters milestone option.
 
-use alloc::vec::Vec;
-use core::ops::RangeInclusive;
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use core::{any::Any, ops::RangeInclusive};
 
-use packable::{bounded::BoundedU16, prefix::BoxedSlicePrefix, Packable};
+use packable::{bounded::BoundedU16, prefix::BoxedSlicePrefix, Packable, PackableExt};
 
-use crate::block::{payload::milestone::MilestoneIndex, Error};
+use crate::block::{payload::milestone::MilestoneIndex, protocol::ProtocolParameters, Error, PROTOCOL_VERSION};
 
 pub(crate) type BinaryParametersLength = BoundedU16<
     { *ParametersMilestoneOption::BINARY_PARAMETERS_LENGTH_RANGE.start() },
@@ -67,6 +67,96 @@ impl ParametersMilestoneOption {
     }
 }
 
+/// The binary parameters of a [`ParametersMilestoneOption`], decoded by a [`BinaryParametersRegistry`].
+#[derive(Debug)]
+pub enum DecodedBinaryParameters {
+    /// Protocol parameters, the format of the binary parameters of the known protocol versions.
+    Protocol(ProtocolParameters),
+    /// Parameters decoded by a decoder registered by the application, to be downcast to their type.
+    Custom(Box<dyn Any + Send + Sync>),
+    /// Parameters of a protocol version without a registered decoder, left as bytes.
+    Unknown(Box<[u8]>),
+}
+
+/// A function decoding the binary parameters of a protocol version, see [`BinaryParametersRegistry::register()`].
+pub type BinaryParametersDecoder = fn(&[u8]) -> Result<DecodedBinaryParameters, Error>;
+
+/// A registry of the formats of binary parameters per protocol version, decoding [`ParametersMilestoneOption`]s, e.g.
+/// for monitoring tools to be alerted of upcoming protocol changes.
+///
+/// The [`Default`] registry decodes the binary parameters of the current protocol version as [`ProtocolParameters`];
+/// decoders for other protocol versions can be registered.
+#[derive(Clone)]
+pub struct BinaryParametersRegistry {
+    decoders: BTreeMap<u8, BinaryParametersDecoder>,
+}
+
+impl Default for BinaryParametersRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register(PROTOCOL_VERSION, decode_protocol_parameters);
+
+        registry
+    }
+}
+
+impl BinaryParametersRegistry {
+    /// Creates a registry without any decoder.
+    pub fn empty() -> Self {
+        Self {
+            decoders: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the decoder of the binary parameters of a protocol version, returning the one it replaces, if any.
+    pub fn register(
+        &mut self,
+        protocol_version: u8,
+        decoder: BinaryParametersDecoder,
+    ) -> Option<BinaryParametersDecoder> {
+        self.decoders.insert(protocol_version, decoder)
+    }
+
+    /// Decodes the binary parameters of a [`ParametersMilestoneOption`] with the decoder of its protocol version, or
+    /// returns them as [`DecodedBinaryParameters::Unknown`] if none has been registered.
+    pub fn decode(&self, option: &ParametersMilestoneOption) -> Result<DecodedBinaryParameters, Error> {
+        match self.decoders.get(&option.protocol_version()) {
+            Some(decoder) => {
+                let decoded = decoder(option.binary_parameters())?;
+
+                // Protocol parameters also carry their version, which has to be the one of the option.
+                if let DecodedBinaryParameters::Protocol(protocol_parameters) = &decoded {
+                    if protocol_parameters.protocol_version() != option.protocol_version() {
+                        return Err(Error::ProtocolVersionMismatch {
+                            expected: option.protocol_version(),
+                            actual: protocol_parameters.protocol_version(),
+                        });
+                    }
+                }
+
+                Ok(decoded)
+            }
+            None => Ok(DecodedBinaryParameters::Unknown(option.binary_parameters().into())),
+        }
+    }
+}
+
+impl core::fmt::Debug for BinaryParametersRegistry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BinaryParametersRegistry")
+            .field("protocol_versions", &self.decoders.keys())
+            .finish()
+    }
+}
+
+/// Decodes binary parameters as packed [`ProtocolParameters`].
+pub fn decode_protocol_parameters(binary_parameters: &[u8]) -> Result<DecodedBinaryParameters, Error> {
+    ProtocolParameters::unpack_verified(binary_parameters, &())
+        .map(DecodedBinaryParameters::Protocol)
+        .map_err(|_| Error::InvalidBinaryParameters)
+}
+
 #[cfg(feature = "dto")]
 #[allow(missing_docs)]
 pub mod dto {
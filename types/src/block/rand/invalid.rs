@@ -0,0 +1,119 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generators of near-valid but invalid structures.
+//!
+//! Constructors reject or normalise invalid data, so most generators return the packed representation of the
+//! structure, which is what a validator receives from the network. Each generator documents the [`Error`] variant
+//! that verification is expected to fail with.
+//!
+//! [`Error`]: crate::block::Error
+
+use packable::PackableExt;
+
+use crate::block::{
+    input::Input,
+    output::{feature::Feature, BasicOutput, Output},
+    parent::Parents,
+    rand::{
+        block::rand_block_ids,
+        input::rand_utxo_input,
+        number::rand_number_range,
+        output::{
+            feature::{rand_issuer_feature, rand_sender_feature, rand_tag_feature},
+            unlock_condition::rand_address_unlock_condition,
+        },
+    },
+};
+
+fn pack_prefixed<T: PackableExt>(items: &[T]) -> Vec<u8> {
+    let mut bytes = vec![items.len() as u8];
+
+    for item in items {
+        bytes.extend(item.pack_to_vec());
+    }
+
+    bytes
+}
+
+/// Generates packed [`Parents`] containing the same block id twice.
+///
+/// Unpacking fails with [`Error::ParentsNotUniqueSorted`](crate::block::Error::ParentsNotUniqueSorted).
+pub fn rand_packed_duplicate_parents() -> Vec<u8> {
+    let mut block_ids = rand_block_ids(rand_number_range(1..*Parents::COUNT_RANGE.end()) as usize);
+    block_ids.push(*block_ids.last().unwrap());
+
+    pack_prefixed(&block_ids)
+}
+
+/// Generates packed [`Parents`] that are not lexicographically sorted.
+///
+/// Unpacking fails with [`Error::ParentsNotUniqueSorted`](crate::block::Error::ParentsNotUniqueSorted).
+pub fn rand_packed_unsorted_parents() -> Vec<u8> {
+    let mut block_ids = rand_block_ids(rand_number_range(2..=*Parents::COUNT_RANGE.end()) as usize);
+    block_ids.reverse();
+
+    pack_prefixed(&block_ids)
+}
+
+/// Generates packed [`Parents`] with one more block id than allowed.
+///
+/// Unpacking fails with [`Error::InvalidParentCount`](crate::block::Error::InvalidParentCount).
+pub fn rand_packed_too_many_parents() -> Vec<u8> {
+    pack_prefixed(&rand_block_ids(*Parents::COUNT_RANGE.end() as usize + 1))
+}
+
+/// Generates packed [`Features`](crate::block::output::feature::Features) that are not sorted by kind.
+///
+/// Unpacking fails with [`Error::FeaturesNotUniqueSorted`](crate::block::Error::FeaturesNotUniqueSorted).
+pub fn rand_packed_unsorted_features() -> Vec<u8> {
+    pack_prefixed(&[
+        Feature::Tag(rand_tag_feature()),
+        Feature::Issuer(rand_issuer_feature()),
+        Feature::Sender(rand_sender_feature()),
+    ])
+}
+
+/// Generates packed [`Features`](crate::block::output::feature::Features) containing the same feature kind twice.
+///
+/// Unpacking fails with [`Error::FeaturesNotUniqueSorted`](crate::block::Error::FeaturesNotUniqueSorted).
+pub fn rand_packed_duplicate_features() -> Vec<u8> {
+    pack_prefixed(&[
+        Feature::Sender(rand_sender_feature()),
+        Feature::Sender(rand_sender_feature()),
+    ])
+}
+
+/// Generates an output amount exceeding `token_supply`.
+///
+/// Building an output with it fails with [`Error::InvalidOutputAmount`](crate::block::Error::InvalidOutputAmount).
+pub fn rand_overflowing_amount(token_supply: u64) -> u64 {
+    rand_number_range(token_supply + 1..=u64::MAX)
+}
+
+/// Generates [`Output`]s whose amounts are individually valid but exceed `token_supply` once summed.
+///
+/// Building a transaction essence with them fails with
+/// [`Error::InvalidTransactionAmountSum`](crate::block::Error::InvalidTransactionAmountSum).
+pub fn rand_outputs_overflowing_amount_sum(token_supply: u64) -> Vec<Output> {
+    (0..2)
+        .map(|_| {
+            Output::Basic(
+                BasicOutput::build_with_amount(token_supply)
+                    .unwrap()
+                    .add_unlock_condition(rand_address_unlock_condition().into())
+                    .finish(token_supply)
+                    .unwrap(),
+            )
+        })
+        .collect()
+}
+
+/// Generates [`Input`]s referencing the same [`UtxoInput`](crate::block::input::UtxoInput) twice.
+///
+/// Building a transaction essence with them fails with [`Error::DuplicateUtxo`](crate::block::Error::DuplicateUtxo).
+pub fn rand_duplicate_inputs() -> Vec<Input> {
+    let input = rand_utxo_input();
+
+    vec![Input::Utxo(input.clone()), Input::Utxo(input)]
+}
@@ -11,6 +11,8 @@ pub mod bool;
 pub mod bytes;
 /// Module providing random input generation utilities.
 pub mod input;
+/// Module providing near-valid but invalid structure generation utilities.
+pub mod invalid;
 /// Module providing random milestone generation utilities.
 pub mod milestone;
 /// Module providing random milestone option generation utilities.
@@ -1,12 +1,9 @@
 // Copyright 2020-2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use core::{fmt, ops::Deref};
+use core::fmt;
 
-use crypto::{
-    hashes::{blake2b::Blake2b256, Digest},
-    signatures::ed25519::{PublicKey, Signature, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH},
-};
+use crypto::signatures::ed25519::{PublicKey, Signature, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
 
 use crate::block::{address::Ed25519Address, Error};
 
@@ -44,15 +41,19 @@ impl Ed25519Signature {
 
     /// Verifies the [`Ed25519Signature`] for a message against an [`Ed25519Address`].
     pub fn is_valid(&self, message: &[u8], address: &Ed25519Address) -> Result<(), Error> {
-        let signature_address: [u8; PUBLIC_KEY_LENGTH] = Blake2b256::digest(self.public_key).into();
-
-        if address.deref() != &signature_address {
+        if !address.matches_public_key(&self.public_key) {
             return Err(Error::SignaturePublicKeyMismatch {
                 expected: prefix_hex::encode(address.as_ref()),
-                actual: prefix_hex::encode(signature_address),
+                actual: prefix_hex::encode(*Ed25519Address::from_public_key_bytes(self.public_key)),
             });
         }
 
+        self.verify(message)
+    }
+
+    /// Verifies the [`Ed25519Signature`] for a message, e.g. an essence hash, regardless of the address of its public
+    /// key.
+    pub fn verify(&self, message: &[u8]) -> Result<(), Error> {
         if !PublicKey::try_from_bytes(self.public_key)?.verify(&Signature::from_bytes(self.signature), message) {
             return Err(Error::InvalidSignature);
         }
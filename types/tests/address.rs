@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use iota_types::block::{
-    address::{Address, Ed25519Address},
+    address::{Address, AliasAddress, Ed25519Address, NftAddress},
+    output::{AliasId, NftId},
     Error,
 };
 use packable::PackableExt;
@@ -66,6 +67,27 @@ fn bech32_string_to_address() {
     assert_eq!(ed.to_string(), ED25519_ADDRESS);
 }
 
+#[test]
+fn bech32_string_round_trip() {
+    let bytes: [u8; 32] = prefix_hex::decode(ED25519_ADDRESS).unwrap();
+
+    for address in [
+        Address::from(Ed25519Address::new(bytes)),
+        Address::from(AliasAddress::new(AliasId::new(bytes))),
+        Address::from(NftAddress::new(NftId::new(bytes))),
+    ] {
+        let bech32_string = address.to_bech32("rms");
+        let mut buffer = String::new();
+        address.write_bech32("rms", &mut buffer).unwrap();
+
+        assert_eq!(buffer, bech32_string);
+        assert_eq!(
+            Address::try_from_bech32(&bech32_string).unwrap(),
+            (String::from("rms"), address)
+        );
+    }
+}
+
 #[test]
 fn invalid_bech32_string_to_address() {
     let address = Address::try_from_bech32(ED25519_ADDRESS_BAD);
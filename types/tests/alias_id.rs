@@ -0,0 +1,29 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use core::str::FromStr;
+
+use iota_types::block::output::{AliasId, OutputId};
+
+const OUTPUT_ID: &str = "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c6492a00";
+const ALIAS_ID: &str = "0xf29dd16310c2100fd1bf568b345fb1cc14d71caa3bd9b5ad735d2bd6d455ca3b";
+
+#[test]
+fn from_output_id() {
+    let output_id = OutputId::from_str(OUTPUT_ID).unwrap();
+
+    assert_eq!(
+        AliasId::from_output_id(&output_id),
+        AliasId::from_str(ALIAS_ID).unwrap()
+    );
+    assert_eq!(AliasId::from(&output_id), AliasId::from_output_id(&output_id));
+}
+
+#[test]
+fn or_from_output_id() {
+    let output_id = OutputId::from_str(OUTPUT_ID).unwrap();
+    let alias_id = AliasId::from_str(ALIAS_ID).unwrap();
+
+    assert_eq!(AliasId::null().or_from_output_id(&output_id), alias_id);
+    assert_eq!(alias_id.or_from_output_id(&OutputId::null()), alias_id);
+}
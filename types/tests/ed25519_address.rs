@@ -3,6 +3,7 @@
 
 use core::str::FromStr;
 
+use crypto::hashes::{blake2b::Blake2b256, Digest};
 use iota_types::block::address::{Address, Ed25519Address};
 use packable::PackableExt;
 
@@ -21,6 +22,19 @@ fn debug_impl() {
     );
 }
 
+#[test]
+fn from_public_key_bytes() {
+    let public_key: [u8; 32] =
+        prefix_hex::decode("0x1da5ddd11ba3f961acab68fafee3177d039875eaa94ac5fdbff8b53f0c50bfb9").unwrap();
+    let address = Ed25519Address::from_public_key_bytes(public_key);
+
+    assert_eq!(address, Ed25519Address::new(Blake2b256::digest(public_key).into()));
+    assert!(address.matches_public_key(&public_key));
+    assert!(!Ed25519Address::from_str(ED25519_ADDRESS)
+        .unwrap()
+        .matches_public_key(&public_key));
+}
+
 #[test]
 fn generate_address() {
     let bytes = [1; 32];
@@ -1,7 +1,8 @@
 // Copyright 2020-2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota_types::block::signature::Ed25519Signature;
+use crypto::signatures::ed25519::SecretKey;
+use iota_types::block::{address::Ed25519Address, signature::Ed25519Signature, Error};
 use packable::PackableExt;
 
 const ED25519_PUBLIC_KEY: &str = "0x1da5ddd11ba3f961acab68fafee3177d039875eaa94ac5fdbff8b53f0c50bfb9";
@@ -31,3 +32,22 @@ fn pack_unpack_valid() {
 
     assert_eq!(sig, PackableExt::unpack_verified(sig_packed.as_slice(), &()).unwrap());
 }
+
+#[test]
+fn verify() {
+    let secret_key = SecretKey::from_bytes([1; 32]);
+    let public_key = secret_key.public_key().to_bytes();
+    let essence_hash = [2; 32];
+    let sig = Ed25519Signature::new(public_key, secret_key.sign(&essence_hash).to_bytes());
+
+    assert!(sig.verify(&essence_hash).is_ok());
+    assert!(matches!(sig.verify(&[3; 32]), Err(Error::InvalidSignature)));
+
+    assert!(sig
+        .is_valid(&essence_hash, &Ed25519Address::from_public_key_bytes(public_key))
+        .is_ok());
+    assert!(matches!(
+        sig.is_valid(&essence_hash, &Ed25519Address::new([0; 32])),
+        Err(Error::SignaturePublicKeyMismatch { .. })
+    ));
+}
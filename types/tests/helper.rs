@@ -0,0 +1,40 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_types::block::{
+    helper::{decode_prefix_hex_into, encode_prefix_hex_into},
+    Error,
+};
+
+#[test]
+fn prefix_hex_into() {
+    let bytes = (0..=255).collect::<Vec<u8>>();
+    let mut encoded = String::from("garbage");
+    let mut decoded = vec![42];
+
+    encode_prefix_hex_into(&bytes, &mut encoded);
+    assert_eq!(encoded, prefix_hex::encode(bytes.as_slice()));
+
+    decode_prefix_hex_into(&encoded, &mut decoded).unwrap();
+    assert_eq!(decoded, bytes);
+
+    encode_prefix_hex_into(&[], &mut encoded);
+    assert_eq!(encoded, "0x");
+    decode_prefix_hex_into(&encoded, &mut decoded).unwrap();
+    assert!(decoded.is_empty());
+}
+
+#[test]
+fn decode_invalid_prefix_hex_into() {
+    let mut decoded = Vec::new();
+
+    assert!(matches!(
+        decode_prefix_hex_into("0x0", &mut decoded),
+        Err(Error::Hex(_))
+    ));
+    assert!(matches!(
+        decode_prefix_hex_into("0xzz", &mut decoded),
+        Err(Error::Hex(_))
+    ));
+    assert!(matches!(decode_prefix_hex_into("00", &mut decoded), Err(Error::Hex(_))));
+}
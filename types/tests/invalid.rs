@@ -0,0 +1,104 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_types::block::{
+    input::Input,
+    output::{feature::Features, BasicOutput},
+    parent::Parents,
+    payload::transaction::RegularTransactionEssence,
+    protocol::protocol_parameters,
+    rand::{
+        input::rand_utxo_input,
+        invalid::{
+            rand_duplicate_inputs, rand_outputs_overflowing_amount_sum, rand_overflowing_amount,
+            rand_packed_duplicate_features, rand_packed_duplicate_parents, rand_packed_too_many_parents,
+            rand_packed_unsorted_features, rand_packed_unsorted_parents,
+        },
+        output::{rand_basic_output, rand_inputs_commitment, unlock_condition::rand_address_unlock_condition},
+    },
+    Error,
+};
+use packable::{bounded::TryIntoBoundedU8Error, error::UnpackError, PackableExt};
+
+#[test]
+fn duplicate_parents() {
+    assert!(matches!(
+        Parents::unpack_verified(rand_packed_duplicate_parents().as_slice(), &()),
+        Err(UnpackError::Packable(Error::ParentsNotUniqueSorted))
+    ));
+}
+
+#[test]
+fn unsorted_parents() {
+    assert!(matches!(
+        Parents::unpack_verified(rand_packed_unsorted_parents().as_slice(), &()),
+        Err(UnpackError::Packable(Error::ParentsNotUniqueSorted))
+    ));
+}
+
+#[test]
+fn too_many_parents() {
+    assert!(matches!(
+        Parents::unpack_verified(rand_packed_too_many_parents().as_slice(), &()),
+        Err(UnpackError::Packable(Error::InvalidParentCount(
+            TryIntoBoundedU8Error::Invalid(9)
+        )))
+    ));
+}
+
+#[test]
+fn unsorted_features() {
+    assert!(matches!(
+        Features::unpack_verified(rand_packed_unsorted_features().as_slice(), &()),
+        Err(UnpackError::Packable(Error::FeaturesNotUniqueSorted))
+    ));
+}
+
+#[test]
+fn duplicate_features() {
+    assert!(matches!(
+        Features::unpack_verified(rand_packed_duplicate_features().as_slice(), &()),
+        Err(UnpackError::Packable(Error::FeaturesNotUniqueSorted))
+    ));
+}
+
+#[test]
+fn overflowing_amount() {
+    let token_supply = protocol_parameters().token_supply();
+    let amount = rand_overflowing_amount(token_supply);
+
+    assert!(matches!(
+        BasicOutput::build_with_amount(amount)
+            .unwrap()
+            .add_unlock_condition(rand_address_unlock_condition().into())
+            .finish(token_supply),
+        Err(Error::InvalidOutputAmount(a)) if a == amount
+    ));
+}
+
+#[test]
+fn overflowing_amount_sum() {
+    let protocol_parameters = protocol_parameters();
+    let token_supply = protocol_parameters.token_supply();
+
+    assert!(matches!(
+        RegularTransactionEssence::builder(protocol_parameters.network_id(), rand_inputs_commitment())
+            .add_input(Input::Utxo(rand_utxo_input()))
+            .with_outputs(rand_outputs_overflowing_amount_sum(token_supply))
+            .finish(&protocol_parameters),
+        Err(Error::InvalidTransactionAmountSum(sum)) if sum == 2 * token_supply as u128
+    ));
+}
+
+#[test]
+fn duplicate_inputs() {
+    let protocol_parameters = protocol_parameters();
+
+    assert!(matches!(
+        RegularTransactionEssence::builder(protocol_parameters.network_id(), rand_inputs_commitment())
+            .with_inputs(rand_duplicate_inputs())
+            .add_output(rand_basic_output(protocol_parameters.token_supply()).into())
+            .finish(&protocol_parameters),
+        Err(Error::DuplicateUtxo(_))
+    ));
+}
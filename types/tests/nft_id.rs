@@ -0,0 +1,26 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use core::str::FromStr;
+
+use iota_types::block::output::{NftId, OutputId};
+
+const OUTPUT_ID: &str = "0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c6492a00";
+const NFT_ID: &str = "0xf29dd16310c2100fd1bf568b345fb1cc14d71caa3bd9b5ad735d2bd6d455ca3b";
+
+#[test]
+fn from_output_id() {
+    let output_id = OutputId::from_str(OUTPUT_ID).unwrap();
+
+    assert_eq!(NftId::from_output_id(&output_id), NftId::from_str(NFT_ID).unwrap());
+    assert_eq!(NftId::from(&output_id), NftId::from_output_id(&output_id));
+}
+
+#[test]
+fn or_from_output_id() {
+    let output_id = OutputId::from_str(OUTPUT_ID).unwrap();
+    let nft_id = NftId::from_str(NFT_ID).unwrap();
+
+    assert_eq!(NftId::null().or_from_output_id(&output_id), nft_id);
+    assert_eq!(nft_id.or_from_output_id(&OutputId::null()), nft_id);
+}
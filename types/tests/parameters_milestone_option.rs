@@ -0,0 +1,77 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_types::block::{
+    payload::milestone::option::{
+        decode_protocol_parameters, BinaryParametersRegistry, DecodedBinaryParameters, ParametersMilestoneOption,
+    },
+    protocol::ProtocolParameters,
+    Error,
+};
+use packable::PackableExt;
+
+#[test]
+fn decode_protocol_parameters() {
+    let protocol_parameters = ProtocolParameters::default();
+    let option = ParametersMilestoneOption::new(
+        1000.into(),
+        protocol_parameters.protocol_version(),
+        protocol_parameters.pack_to_vec(),
+    )
+    .unwrap();
+
+    match BinaryParametersRegistry::default().decode(&option).unwrap() {
+        DecodedBinaryParameters::Protocol(decoded) => assert_eq!(decoded, protocol_parameters),
+        decoded => panic!("unexpected decoded binary parameters: {decoded:?}"),
+    }
+}
+
+#[test]
+fn decode_invalid_protocol_parameters() {
+    let protocol_parameters = ProtocolParameters::default();
+    let option =
+        ParametersMilestoneOption::new(1000.into(), protocol_parameters.protocol_version(), vec![1, 2, 3]).unwrap();
+
+    assert!(matches!(
+        BinaryParametersRegistry::default().decode(&option),
+        Err(Error::InvalidBinaryParameters)
+    ));
+
+    // The protocol version of the parameters has to be the one of the option.
+    let option = ParametersMilestoneOption::new(
+        1000.into(),
+        protocol_parameters.protocol_version() + 1,
+        protocol_parameters.pack_to_vec(),
+    )
+    .unwrap();
+    let mut registry = BinaryParametersRegistry::default();
+    registry.register(protocol_parameters.protocol_version() + 1, decode_protocol_parameters);
+
+    assert!(matches!(
+        registry.decode(&option),
+        Err(Error::ProtocolVersionMismatch { .. })
+    ));
+}
+
+#[test]
+fn decode_registered_binary_parameters() {
+    let option = ParametersMilestoneOption::new(1000.into(), 42, vec![1, 2, 3]).unwrap();
+    let mut registry = BinaryParametersRegistry::default();
+
+    // Without a decoder for the protocol version, the parameters are left as bytes.
+    match registry.decode(&option).unwrap() {
+        DecodedBinaryParameters::Unknown(bytes) => assert_eq!(&*bytes, &[1, 2, 3]),
+        decoded => panic!("unexpected decoded binary parameters: {decoded:?}"),
+    }
+
+    registry.register(42, |bytes| {
+        Ok(DecodedBinaryParameters::Custom(Box::new(
+            bytes.iter().map(|byte| u32::from(*byte)).sum::<u32>(),
+        )))
+    });
+
+    match registry.decode(&option).unwrap() {
+        DecodedBinaryParameters::Custom(decoded) => assert_eq!(decoded.downcast_ref::<u32>(), Some(&6)),
+        decoded => panic!("unexpected decoded binary parameters: {decoded:?}"),
+    }
+}